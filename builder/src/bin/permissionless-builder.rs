@@ -159,7 +159,8 @@ async fn run<V: Versions>(
     let builder_server_url: Url = format!("http://0.0.0.0:{}", opt.port).parse().unwrap();
 
     let instance_state =
-        build_instance_state::<V>(genesis.chain_config, l1_params, opt.state_peers);
+        build_instance_state::<V>(genesis.chain_config, l1_params, opt.state_peers)
+            .with_allowed_namespaces(genesis.allowed_namespaces.clone());
 
     let base_fee = genesis.max_base_fee();
     tracing::info!(?base_fee, "base_fee");
@@ -194,3 +195,15 @@ async fn run<V: Versions>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        NonPermissionedBuilderOptions::command().debug_assert();
+    }
+}
@@ -1,26 +1,23 @@
-use alloy::{network::Ethereum, providers::PendingTransactionBuilder, sol_types::SolInterface};
+use alloy::sol_types::SolInterface;
 
-pub trait DecodeRevert {
-    fn maybe_decode_revert<E: SolInterface + std::fmt::Debug>(
-        self,
-    ) -> anyhow::Result<PendingTransactionBuilder<Ethereum>>;
+/// Decode the revert reason of a failed contract call or transaction as one of the errors of `E`.
+///
+/// Errors that can't be decoded keep the original [`alloy::contract::Error`] as their source, so
+/// callers can still tell transport failures apart from reverts by downcasting.
+pub trait DecodeRevert<T> {
+    fn maybe_decode_revert<E: SolInterface + std::fmt::Debug>(self) -> anyhow::Result<T>;
 }
 
-impl DecodeRevert
-    for alloy::contract::Result<PendingTransactionBuilder<Ethereum>, alloy::contract::Error>
-{
-    fn maybe_decode_revert<E: SolInterface + std::fmt::Debug>(
-        self,
-    ) -> anyhow::Result<PendingTransactionBuilder<Ethereum>> {
+impl<T> DecodeRevert<T> for alloy::contract::Result<T, alloy::contract::Error> {
+    fn maybe_decode_revert<E: SolInterface + std::fmt::Debug>(self) -> anyhow::Result<T> {
         match self {
             Ok(ret) => Ok(ret),
-            Err(err) => {
-                let decoded = err.as_decoded_interface_error::<E>();
-                let msg = match decoded {
-                    Some(e) => format!("{:?}", e),
-                    None => format!("{:?}", err),
-                };
-                Err(anyhow::anyhow!(msg))
+            Err(err) => match err.as_decoded_interface_error::<E>() {
+                Some(e) => Err(anyhow::anyhow!("{:?}", e)),
+                None => {
+                    let msg = format!("{:?}", err);
+                    Err(anyhow::Error::new(err).context(msg))
+                },
             },
         }
     }
@@ -49,6 +46,15 @@ mod test {
             .unwrap_err();
         assert!(err.to_string().contains("ERC20InsufficientBalance"));
 
+        // the same works for calls
+        let err = token
+            .transfer(Address::random(), U256::MAX)
+            .call()
+            .await
+            .maybe_decode_revert::<EspTokenErrors>()
+            .unwrap_err();
+        assert!(err.to_string().contains("ERC20InsufficientBalance"));
+
         Ok(())
     }
 }
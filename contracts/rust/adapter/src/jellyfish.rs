@@ -427,6 +427,18 @@ impl PlonkProofSol {
     }
 }
 
+/// Size in bytes of an ABI-encoded `PlonkProof`: 13 G1 points of two words each and 10 field
+/// elements of one word each.
+pub const PLONK_PROOF_CALLDATA_SIZE: usize = (13 * 2 + 10) * 32;
+
+/// Return the size of `proof` when ABI-encoded as a contract call argument.
+///
+/// `PlonkProof` is a static struct, so this is always [`PLONK_PROOF_CALLDATA_SIZE`]. Useful for
+/// estimating the size of RPC requests without encoding the proof.
+pub fn plonk_proof_calldata_size(_proof: &PlonkProofSol) -> usize {
+    PLONK_PROOF_CALLDATA_SIZE
+}
+
 impl From<Challenges<Fr>> for ChallengesSol {
     fn from(c: Challenges<Fr>) -> Self {
         let alpha_2 = c.alpha * c.alpha;
@@ -476,3 +488,20 @@ impl ChallengesSol {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use alloy::sol_types::SolValue;
+    use ark_std::test_rng;
+
+    use super::*;
+
+    #[test]
+    fn test_plonk_proof_calldata_size() {
+        let proof = PlonkProofSol::dummy(&mut test_rng());
+        assert_eq!(
+            plonk_proof_calldata_size(&proof),
+            proof.abi_encode_params().len()
+        );
+    }
+}
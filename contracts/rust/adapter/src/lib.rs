@@ -24,3 +24,37 @@ pub fn u256_to_field<F: PrimeField>(x: U256) -> F {
     let bytes: [u8; 32] = x.to_le_bytes();
     F::from_le_bytes_mod_order(&bytes)
 }
+
+/// convert a fixed-size array of field elements to U256s, see [`field_to_u256()`]
+pub fn field_array_to_u256<F: PrimeField, const N: usize>(arr: [F; N]) -> [U256; N] {
+    arr.map(field_to_u256)
+}
+
+/// convert a fixed-size array of U256s to field elements (mod order), see [`u256_to_field()`]
+pub fn u256_array_to_field<F: PrimeField, const N: usize>(arr: [U256; N]) -> [F; N] {
+    arr.map(u256_to_field)
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_std::UniformRand;
+
+    use super::*;
+
+    #[test]
+    fn test_field_array_to_u256_round_trip() {
+        let mut rng = ark_std::test_rng();
+        let fields: [Fr; 4] = std::array::from_fn(|_| Fr::rand(&mut rng));
+
+        let u256s = field_array_to_u256(fields);
+        for (f, x) in fields.iter().zip(&u256s) {
+            assert_eq!(field_to_u256(*f), *x);
+        }
+        assert_eq!(u256_array_to_field::<Fr, 4>(u256s), fields);
+
+        // values beyond the modulus are reduced
+        let reduced: [Fr; 4] = u256_array_to_field([U256::MAX; 4]);
+        assert_eq!(reduced, [u256_to_field::<Fr>(U256::MAX); 4]);
+    }
+}
@@ -1,13 +1,36 @@
 //! Helpers and test mocks for Light Client logic
 
-use alloy::primitives::U256;
+use alloy::{
+    primitives::{Address, U256},
+    rpc::types::TransactionReceipt,
+    sol_types::SolEvent,
+};
+use ark_bn254::{Bn254, Fr};
 use ark_ff::PrimeField;
-use hotshot_types::light_client::{GenericLightClientState, GenericStakeTableState};
+use committable::{Commitment, Committable};
+use hotshot_types::{
+    data::Leaf2,
+    light_client::{GenericLightClientState, GenericStakeTableState},
+    stake_table::{one_honest_threshold, HSStakeTable},
+    traits::{
+        block_contents::BlockHeader, node_implementation::NodeType,
+        signature_key::StakeTableEntryType,
+    },
+    PeerConfig,
+};
+use jf_plonk::{
+    errors::PlonkError,
+    proof_system::{
+        structs::{Proof, VerifyingKey},
+        PlonkKzgSnark, UniversalSNARK,
+    },
+    transcript::SolidityTranscript,
+};
 use rand::Rng;
 
 use crate::{
     field_to_u256,
-    sol_types::{LightClient, LightClientStateSol, StakeTableStateSol},
+    sol_types::{LightClient, LightClientStateSol, ScalarFieldSol, StakeTableStateSol},
     u256_to_field,
 };
 
@@ -77,6 +100,25 @@ impl StakeTableStateSol {
         }
     }
 
+    /// Construct the genesis stake table state from the initial set of `peers`.
+    ///
+    /// The commitments are computed for a stake table of `stake_table_capacity`, and the threshold
+    /// is set to the stake that includes at least one honest node (1/3 of the total stake, plus
+    /// one), the same as the prover and the state relay server use.
+    pub fn with_threshold_from_stake<TYPES: NodeType>(
+        peers: &[PeerConfig<TYPES>],
+        stake_table_capacity: usize,
+    ) -> anyhow::Result<Self> {
+        let st = HSStakeTable::from(peers.to_vec());
+        let total_stake = st
+            .iter()
+            .map(|peer| peer.stake_table_entry.stake())
+            .sum::<U256>();
+        let mut state = Self::from(st.commitment(stake_table_capacity)?);
+        state.threshold = one_honest_threshold(total_stake);
+        Ok(state)
+    }
+
     /// Returns a random value
     pub fn rand<R: Rng>(rng: &mut R) -> Self {
         Self {
@@ -116,3 +158,299 @@ impl<F: PrimeField> From<GenericStakeTableState<F>> for StakeTableStateSol {
         }
     }
 }
+
+/// Compute the HotShot block commitment for `leaf` in the same encoding the light client contract
+/// stores in its state history, i.e. the `hotShotBlockCommRoot` returned by `getHotShotCommitment`.
+///
+/// Monitoring tools can use this to assert that the on-chain commitment matches the local one.
+pub fn expected_hotshot_commitment<TYPES: NodeType>(leaf: &Leaf2<TYPES>) -> anyhow::Result<U256> {
+    let state = leaf
+        .block_header()
+        .get_light_client_state(leaf.view_number())?;
+    Ok(field_to_u256(state.block_comm_root))
+}
+
+/// Convert a commitment, e.g. the HotShot block commitment root, into the `BN254.ScalarField` the
+/// contract bindings expect.
+///
+/// The commitment bytes are read as a little-endian integer and reduced modulo the scalar field
+/// order, the same way HotShot derives the field elements of the light client state.
+pub fn scalar_field_from_commitment<T: Committable>(c: Commitment<T>) -> ScalarFieldSol {
+    let bytes = <[u8; 32]>::from(c);
+    ScalarFieldSol::from(field_to_u256(Fr::from_le_bytes_mod_order(&bytes)))
+}
+
+/// Verify a light client state update proof off-chain, the same way `newFinalizedState` does.
+///
+/// Provers can use this to avoid submitting a proof that would revert with `InvalidProof`. Returns
+/// `false` if the proof is wrong, and an error if it could not be verified at all, e.g. because
+/// the number of public inputs doesn't match the verifying key.
+pub fn verify_light_client_proof(
+    vk: &VerifyingKey<Bn254>,
+    public_inputs: &[Fr],
+    proof: &Proof<Bn254>,
+) -> anyhow::Result<bool> {
+    match PlonkKzgSnark::<Bn254>::verify::<SolidityTranscript>(vk, public_inputs, proof, None) {
+        Ok(()) => Ok(true),
+        Err(PlonkError::WrongProof) => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// The public inputs of a proof that `new_state` was signed by the stake table committed to by
+/// `stake`, in the order `LightClient.verifyProof` passes them to the Plonk verifier.
+pub fn light_client_public_inputs(
+    new_state: &LightClientStateSol,
+    stake: &StakeTableStateSol,
+) -> Vec<U256> {
+    vec![
+        U256::from(new_state.viewNum),
+        U256::from(new_state.blockHeight),
+        new_state.blockCommRoot,
+        stake.blsKeyComm,
+        stake.schnorrKeyComm,
+        stake.amountComm,
+        stake.threshold,
+    ]
+}
+
+/// The public inputs `LightClientV2.verifyProof` expects: those of [`light_client_public_inputs`]
+/// for the voting stake table, followed by the next stake table state.
+///
+/// Outside of an epoch change, `next_stake` is the voting stake table state itself.
+pub fn light_client_v2_public_inputs(
+    new_state: &LightClientStateSol,
+    voting_stake: &StakeTableStateSol,
+    next_stake: &StakeTableStateSol,
+) -> Vec<U256> {
+    let mut inputs = light_client_public_inputs(new_state, voting_stake);
+    inputs.extend([
+        next_stake.blsKeyComm,
+        next_stake.schnorrKeyComm,
+        next_stake.amountComm,
+        next_stake.threshold,
+    ]);
+    inputs
+}
+
+/// A decoded `NewState` event emitted by the light client when a new state is finalized
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NewStateEvent {
+    pub view_num: u64,
+    pub block_height: u64,
+    pub block_comm_root: U256,
+}
+
+impl From<LightClient::NewState> for NewStateEvent {
+    fn from(v: LightClient::NewState) -> Self {
+        Self {
+            view_num: v.viewNum,
+            block_height: v.blockHeight,
+            block_comm_root: v.blockCommRoot,
+        }
+    }
+}
+
+/// Decode all the `NewState` events in the logs of a transaction receipt (e.g. of a
+/// `newFinalizedState` call), skipping logs of any other event.
+pub fn decode_new_state_events(receipt: &TransactionReceipt) -> Vec<NewStateEvent> {
+    receipt
+        .inner
+        .logs()
+        .iter()
+        .filter(|log| log.topic0() == Some(&LightClient::NewState::SIGNATURE_HASH))
+        .filter_map(|log| log.log_decode::<LightClient::NewState>().ok())
+        .map(|log| log.inner.data.into())
+        .collect()
+}
+
+/// Decode the new implementation address from the `Upgraded` event in the logs of a transaction
+/// receipt (e.g. of an `upgradeToAndCall` call), if there is one.
+pub fn decode_upgraded_event(receipt: &TransactionReceipt) -> Option<Address> {
+    receipt
+        .inner
+        .logs()
+        .iter()
+        .filter(|log| log.topic0() == Some(&LightClient::Upgraded::SIGNATURE_HASH))
+        .find_map(|log| log.log_decode::<LightClient::Upgraded>().ok())
+        .map(|log| log.inner.data.implementation)
+}
+
+#[cfg(test)]
+mod test {
+    use alloy::{
+        consensus::{Receipt, ReceiptEnvelope, ReceiptWithBloom},
+        primitives::Log as PrimitiveLog,
+        rpc::types::Log,
+    };
+    use hotshot_example_types::node_types::TestTypes;
+    use hotshot_types::{
+        light_client::{GenericPublicInput, StateKeyPair, STAKE_TABLE_CAPACITY},
+        signature_key::BLSPubKey,
+        stake_table::StakeTableEntry,
+        traits::signature_key::SignatureKey,
+    };
+
+    use super::*;
+
+    fn receipt_with_logs(logs: Vec<Log>) -> TransactionReceipt {
+        TransactionReceipt {
+            inner: ReceiptEnvelope::Legacy(ReceiptWithBloom {
+                receipt: Receipt {
+                    status: true.into(),
+                    cumulative_gas_used: 0,
+                    logs,
+                },
+                logs_bloom: Default::default(),
+            }),
+            transaction_hash: Default::default(),
+            transaction_index: None,
+            block_hash: None,
+            block_number: None,
+            gas_used: 0,
+            effective_gas_price: 0,
+            blob_gas_used: None,
+            blob_gas_price: None,
+            from: Address::ZERO,
+            to: None,
+            contract_address: None,
+        }
+    }
+
+    fn log_of(event: &impl SolEvent) -> Log {
+        Log {
+            inner: PrimitiveLog {
+                address: Address::random(),
+                data: event.encode_log_data(),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_decode_new_state_events() {
+        let new_state = LightClient::NewState {
+            viewNum: 10,
+            blockHeight: 5,
+            blockCommRoot: U256::from(42),
+        };
+        let other = LightClient::PermissionedProverRequired {
+            permissionedProver: Address::random(),
+        };
+        let receipt = receipt_with_logs(vec![log_of(&other), log_of(&new_state)]);
+
+        let events = decode_new_state_events(&receipt);
+        assert_eq!(
+            events,
+            vec![NewStateEvent {
+                view_num: 10,
+                block_height: 5,
+                block_comm_root: U256::from(42),
+            }]
+        );
+
+        // a receipt without any `NewState` log decodes to nothing
+        let receipt = receipt_with_logs(vec![log_of(&other)]);
+        assert!(decode_new_state_events(&receipt).is_empty());
+    }
+
+    #[test]
+    fn test_scalar_field_from_commitment() {
+        let mut bytes = [0xabu8; 32];
+        // clear the most significant (little-endian) byte to stay below the modulus
+        bytes[31] = 0;
+        let comm = Commitment::<Leaf2<TestTypes>>::from_raw(bytes);
+        let value = scalar_field_from_commitment(comm).into();
+        let round_trip = Commitment::<Leaf2<TestTypes>>::from_raw(value.to_le_bytes());
+        assert_eq!(round_trip, comm);
+        // the same encoding as a field element converted with `field_to_u256`
+        assert_eq!(
+            u256_to_field::<Fr>(value),
+            Fr::from_le_bytes_mod_order(&bytes)
+        );
+
+        // values above the modulus are reduced instead of rejected
+        let comm = Commitment::<Leaf2<TestTypes>>::from_raw([0xff; 32]);
+        let value: U256 = scalar_field_from_commitment(comm).into();
+        assert!(value < U256::from_limbs(Fr::MODULUS.0));
+        assert_eq!(
+            u256_to_field::<Fr>(value),
+            Fr::from_le_bytes_mod_order(&[0xff; 32])
+        );
+    }
+
+    #[test]
+    fn test_decode_upgraded_event() {
+        let implementation = Address::random();
+        let upgraded = LightClient::Upgraded { implementation };
+        let other = LightClient::Upgrade { implementation };
+        let receipt = receipt_with_logs(vec![log_of(&other), log_of(&upgraded)]);
+        assert_eq!(decode_upgraded_event(&receipt), Some(implementation));
+
+        // a receipt without any `Upgraded` log decodes to nothing
+        let receipt = receipt_with_logs(vec![log_of(&other)]);
+        assert_eq!(decode_upgraded_event(&receipt), None);
+    }
+
+    #[test]
+    fn test_with_threshold_from_stake() {
+        let peers = (0..4u64)
+            .map(|i| PeerConfig::<TestTypes> {
+                stake_table_entry: StakeTableEntry {
+                    stake_key: BLSPubKey::generated_from_seed_indexed([0u8; 32], i).0,
+                    stake_amount: U256::from(i + 1),
+                },
+                state_ver_key: StateKeyPair::generate_from_seed_indexed([0u8; 32], i).ver_key(),
+            })
+            .collect::<Vec<_>>();
+
+        let state =
+            StakeTableStateSol::with_threshold_from_stake(&peers, STAKE_TABLE_CAPACITY).unwrap();
+        // total stake is 10, so more than 1/3 of it includes at least one honest node
+        assert_eq!(state.threshold, U256::from(4));
+
+        let expected = StakeTableStateSol::from(
+            HSStakeTable::from(peers)
+                .commitment(STAKE_TABLE_CAPACITY)
+                .unwrap(),
+        );
+        assert_eq!(state.blsKeyComm, expected.blsKeyComm);
+        assert_eq!(state.schnorrKeyComm, expected.schnorrKeyComm);
+        assert_eq!(state.amountComm, expected.amountComm);
+        assert_eq!(state.threshold, expected.threshold);
+    }
+
+    #[test]
+    fn test_light_client_public_inputs() {
+        let state = LightClientStateSol {
+            viewNum: 1,
+            blockHeight: 2,
+            blockCommRoot: U256::from(3),
+        };
+        let voting_stake = StakeTableStateSol {
+            blsKeyComm: U256::from(4),
+            schnorrKeyComm: U256::from(5),
+            amountComm: U256::from(6),
+            threshold: U256::from(7),
+        };
+        let next_stake = StakeTableStateSol {
+            blsKeyComm: U256::from(8),
+            schnorrKeyComm: U256::from(9),
+            amountComm: U256::from(10),
+            threshold: U256::from(11),
+        };
+
+        assert_eq!(
+            light_client_public_inputs(&state, &voting_stake),
+            (1..=7).map(U256::from).collect::<Vec<_>>()
+        );
+        let inputs = light_client_v2_public_inputs(&state, &voting_stake, &next_stake);
+        assert_eq!(inputs, (1..=11).map(U256::from).collect::<Vec<_>>());
+
+        // the prover lays out its public input the same way
+        let pi =
+            GenericPublicInput::<Fr>::new(state.into(), voting_stake.into(), next_stake.into());
+        let prover_inputs: Vec<U256> = pi.to_vec().into_iter().map(field_to_u256).collect();
+        assert_eq!(prover_inputs, inputs);
+    }
+}
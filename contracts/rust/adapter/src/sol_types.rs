@@ -32,7 +32,7 @@ pub use crate::bindings::{
             self, LightClientErrors, LightClientInstance, LightClientState as LightClientStateSol,
             StakeTableState as StakeTableStateSol,
         },
-        BN254::G1Point as G1PointSol,
+        BN254::{G1Point as G1PointSol, ScalarField as ScalarFieldSol},
     },
     lightclientmock::{self, LightClientMock},
     lightclientv2::{self, LightClientV2},
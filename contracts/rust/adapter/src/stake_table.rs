@@ -1,5 +1,9 @@
-use ark_bn254::G2Affine;
+use alloy::primitives::U256;
+use anyhow::{bail, Result};
+use ark_bn254::{Fq, G2Affine};
 use ark_ec::AffineRepr;
+use ark_ed_on_bn254::EdwardsAffine;
+use ark_ff::PrimeField;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use hotshot_types::{light_client::StateVerKey, signature_key::BLSPubKey};
 
@@ -17,18 +21,72 @@ impl From<G2PointSol> for BLSPubKey {
     }
 }
 
+/// Convert a BLS public key read from the contract, rejecting malformed points.
+///
+/// Unlike the `From` conversion, this checks that the coordinates are canonical field elements and
+/// that the point is on the curve and in the prime order subgroup.
+pub fn sol_g2_to_bls_pubkey(p: &G2PointSol) -> Result<BLSPubKey> {
+    let modulus = U256::from_limbs(Fq::MODULUS.0);
+    for (name, coordinate) in [("x0", p.x0), ("x1", p.x1), ("y0", p.y0), ("y1", p.y1)] {
+        if coordinate >= modulus {
+            bail!("invalid BLS key: coordinate {name} = {coordinate} is not a field element");
+        }
+    }
+    let point: G2Affine = p.clone().into();
+    if !point.is_on_curve() {
+        bail!("invalid BLS key: point is not on the BN254 G2 curve");
+    }
+    if !point.is_in_correct_subgroup_assuming_on_curve() {
+        bail!("invalid BLS key: point is not in the BN254 G2 subgroup");
+    }
+    Ok(p.clone().into())
+}
+
 impl From<EdOnBN254PointSol> for StateVerKey {
     fn from(value: EdOnBN254PointSol) -> Self {
-        let point: ark_ed_on_bn254::EdwardsAffine = value.into();
+        let point: EdwardsAffine = value.into();
         Self::from(point)
     }
 }
 
+/// Convert a Schnorr state verification key to the point stored in the contract.
+pub fn state_ver_key_to_sol(k: &StateVerKey) -> EdOnBN254PointSol {
+    k.to_affine().into()
+}
+
+/// Convert a Schnorr state verification key read from the contract, rejecting malformed points.
+///
+/// Unlike the `From` conversion, this checks that the coordinates are canonical field elements and
+/// that the point is on the curve and in the prime order subgroup.
+pub fn sol_to_state_ver_key(p: &EdOnBN254PointSol) -> Result<StateVerKey> {
+    let modulus = U256::from_limbs(ark_ed_on_bn254::Fq::MODULUS.0);
+    for (name, coordinate) in [("x", p.x), ("y", p.y)] {
+        if coordinate >= modulus {
+            bail!("invalid state key: coordinate {name} = {coordinate} is not a field element");
+        }
+    }
+    let point: EdwardsAffine = p.clone().into();
+    if !point.is_on_curve() {
+        bail!("invalid state key: point is not on the Ed-on-BN254 curve");
+    }
+    if !point.is_in_correct_subgroup_assuming_on_curve() {
+        bail!("invalid state key: point is not in the Ed-on-BN254 subgroup");
+    }
+    Ok(point.into())
+}
+
 #[cfg(test)]
 mod test {
-    use hotshot_types::signature_key::{BLSPrivKey, BLSPubKey};
+    use ark_bn254::{g2, Fq2};
+    use ark_ec::{short_weierstrass::SWCurveConfig, twisted_edwards::TECurveConfig};
+    use ark_ed_on_bn254::EdwardsConfig;
+    use ark_ff::{Field, One};
+    use hotshot_types::{
+        light_client::StateKeyPair,
+        signature_key::{BLSPrivKey, BLSPubKey},
+    };
 
-    use crate::sol_types::G2PointSol;
+    use super::*;
 
     fn check_round_trip(pk: BLSPubKey) {
         let g2: G2PointSol = pk.to_affine().into();
@@ -52,4 +110,93 @@ mod test {
         let pk: BLSPubKey = s.parse().unwrap();
         check_round_trip(pk);
     }
+
+    #[test]
+    fn test_sol_g2_to_bls_pubkey() {
+        let mut rng = rand::thread_rng();
+        let pk: BLSPubKey = (&BLSPrivKey::generate(&mut rng)).into();
+        let g2: G2PointSol = pk.to_affine().into();
+        assert_eq!(sol_g2_to_bls_pubkey(&g2).unwrap(), pk);
+
+        // off the curve
+        let mut off_curve = g2.clone();
+        off_curve.y0 = if off_curve.y0 == U256::ZERO {
+            U256::from(1)
+        } else {
+            off_curve.y0 - U256::from(1)
+        };
+        let err = sol_g2_to_bls_pubkey(&off_curve).unwrap_err();
+        assert!(
+            err.to_string().contains("not on the BN254 G2 curve"),
+            "{err}"
+        );
+
+        // a coordinate that's not reduced modulo the field order
+        let mut unreduced = g2.clone();
+        unreduced.x0 += U256::from_limbs(Fq::MODULUS.0);
+        let err = sol_g2_to_bls_pubkey(&unreduced).unwrap_err();
+        assert!(err.to_string().contains("not a field element"), "{err}");
+
+        // on the curve, but (with overwhelming probability) not in the subgroup because of the
+        // large cofactor of G2
+        let point = (1u64..)
+            .find_map(|i| {
+                let x = Fq2::from(i);
+                let y = (x.square() * x + g2::Config::COEFF_B).sqrt()?;
+                Some(G2Affine::new_unchecked(x, y))
+            })
+            .unwrap();
+        assert!(point.is_on_curve());
+        assert!(!point.is_in_correct_subgroup_assuming_on_curve());
+        let err = sol_g2_to_bls_pubkey(&point.into()).unwrap_err();
+        assert!(
+            err.to_string().contains("not in the BN254 G2 subgroup"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_state_ver_key_sol_round_trip() {
+        let key = StateKeyPair::generate().ver_key();
+        let sol = state_ver_key_to_sol(&key);
+        assert_eq!(sol_to_state_ver_key(&sol).unwrap(), key);
+        assert_eq!(StateVerKey::from(sol.clone()), key);
+
+        // off the curve
+        let mut off_curve = sol.clone();
+        off_curve.y = if off_curve.y == U256::ZERO {
+            U256::from(1)
+        } else {
+            off_curve.y - U256::from(1)
+        };
+        let err = sol_to_state_ver_key(&off_curve).unwrap_err();
+        assert!(
+            err.to_string().contains("not on the Ed-on-BN254 curve"),
+            "{err}"
+        );
+
+        // a coordinate that's not reduced modulo the field order
+        let mut unreduced = sol.clone();
+        unreduced.x += U256::from_limbs(ark_ed_on_bn254::Fq::MODULUS.0);
+        let err = sol_to_state_ver_key(&unreduced).unwrap_err();
+        assert!(err.to_string().contains("not a field element"), "{err}");
+
+        // on the curve, but outside of the prime order subgroup because of the cofactor
+        let point = (2u64..)
+            .find_map(|i| {
+                // solve a * x^2 + y^2 = 1 + d * x^2 * y^2 for x
+                let y = ark_ed_on_bn254::Fq::from(i);
+                let x2 = (ark_ed_on_bn254::Fq::one() - y.square())
+                    / (EdwardsConfig::COEFF_A - EdwardsConfig::COEFF_D * y.square());
+                let point = EdwardsAffine::new_unchecked(x2.sqrt()?, y);
+                (!point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+            })
+            .unwrap();
+        assert!(point.is_on_curve());
+        let err = sol_to_state_ver_key(&point.into()).unwrap_err();
+        assert!(
+            err.to_string().contains("not in the Ed-on-BN254 subgroup"),
+            "{err}"
+        );
+    }
 }
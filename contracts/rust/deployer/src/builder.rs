@@ -55,7 +55,14 @@ impl DeployerArgs {
                 let addr = crate::deploy_fee_contract_proxy(provider, contracts, admin).await?;
 
                 if let Some(multisig) = self.multisig {
-                    crate::transfer_ownership(provider, target, addr, multisig).await?;
+                    crate::transfer_ownership(
+                        provider,
+                        target,
+                        addr,
+                        multisig,
+                        contracts.confirmations(),
+                    )
+                    .await?;
                 }
             },
             Contract::EspTokenProxy => {
@@ -64,7 +71,14 @@ impl DeployerArgs {
                     crate::deploy_token_proxy(provider, contracts, admin, token_recipient).await?;
 
                 if let Some(multisig) = self.multisig {
-                    crate::transfer_ownership(provider, target, addr, multisig).await?;
+                    crate::transfer_ownership(
+                        provider,
+                        target,
+                        addr,
+                        multisig,
+                        contracts.confirmations(),
+                    )
+                    .await?;
                 }
             },
             Contract::LightClientProxy => {
@@ -116,6 +130,7 @@ impl DeployerArgs {
                     use_mock,
                     blocks_per_epoch,
                     epoch_start_block,
+                    None,
                 )
                 .await?;
 
@@ -128,6 +143,7 @@ impl DeployerArgs {
                         Contract::LightClientProxy,
                         lc_proxy,
                         multisig,
+                        contracts.confirmations(),
                     )
                     .await?;
                 }
@@ -151,7 +167,14 @@ impl DeployerArgs {
                 .await?;
 
                 if let Some(multisig) = self.multisig {
-                    crate::transfer_ownership(provider, target, addr, multisig).await?;
+                    crate::transfer_ownership(
+                        provider,
+                        target,
+                        addr,
+                        multisig,
+                        contracts.confirmations(),
+                    )
+                    .await?;
                 }
             },
             _ => {
@@ -1,23 +1,25 @@
-use std::{collections::HashMap, io::Write};
+use std::{collections::HashMap, fs::File, io::Write, path::Path, str::FromStr, time::Duration};
 
 use alloy::{
-    contract::RawCallBuilder,
+    contract::{CallBuilder, CallDecoder, RawCallBuilder},
     hex::{FromHex, ToHexExt},
     network::{Ethereum, EthereumWallet, TransactionBuilder},
-    primitives::{Address, Bytes, U256},
+    primitives::{Address, Bytes, B256, U256},
     providers::{
         fillers::{FillProvider, JoinFill, WalletFiller},
         utils::JoinedRecommendedFillers,
-        Provider, ProviderBuilder, RootProvider,
+        Provider, ProviderBuilder, RootProvider, WalletProvider,
     },
-    rpc::types::TransactionReceipt,
+    rpc::types::{TransactionReceipt, TransactionRequest},
     signers::local::{coins_bip39::English, MnemonicBuilder, PrivateKeySigner},
+    sol,
     transports::http::reqwest::Url,
 };
-use anyhow::{anyhow, Result};
-use clap::{builder::OsStr, Parser};
+use anyhow::{anyhow, ensure, Context, Result};
+use clap::{builder::OsStr, Parser, ValueEnum};
 use derive_more::{derive::Deref, Display};
-use hotshot_contract_adapter::sol_types::*;
+use hotshot_contract_adapter::{light_client::decode_upgraded_event, sol_types::*};
+use sequencer_utils::backoff::{retry_with_backoff, BoundedBackoff};
 
 pub mod builder;
 pub mod network_config;
@@ -39,6 +41,15 @@ pub fn build_provider(mnemonic: String, account_index: u32, url: Url) -> HttpPro
     ProviderBuilder::new().wallet(wallet).on_http(url)
 }
 
+/// similar to [`build_provider()`] but with a wallet for the raw secp256k1 `private_key`
+///
+/// Fails if the key is not a valid (non-zero, less than the curve order) scalar.
+pub fn build_provider_from_key(private_key: B256, url: Url) -> Result<HttpProviderWithWallet> {
+    let signer = PrivateKeySigner::from_bytes(&private_key).context("invalid private key")?;
+    let wallet = EthereumWallet::from(signer);
+    Ok(ProviderBuilder::new().wallet(wallet).on_http(url))
+}
+
 pub fn build_signer(mnemonic: String, account_index: u32) -> PrivateKeySigner {
     MnemonicBuilder::<English>::default()
         .phrase(mnemonic)
@@ -57,6 +68,41 @@ pub fn build_random_provider(url: Url) -> HttpProviderWithWallet {
     ProviderBuilder::new().wallet(wallet).on_http(url)
 }
 
+/// Build a provider without wallet for an anvil node, on which `account` is impersonated.
+///
+/// Transactions with their `from` set to `account` are accepted by anvil without a signature,
+/// e.g. to act as some mainnet address on a forked chain. Note that `from` has to be set
+/// explicitly on every transaction, and that the account needs ETH to pay for gas.
+#[cfg(any(test, feature = "testing"))]
+pub async fn build_impersonated_provider(url: Url, account: Address) -> Result<impl Provider> {
+    use alloy::providers::ext::AnvilApi;
+
+    let provider = ProviderBuilder::new().on_http(url);
+    provider.anvil_impersonate_account(account).await?;
+    tracing::info!(%account, "impersonating account");
+    Ok(provider)
+}
+
+/// similar to [`build_random_provider()`] but the random wallet is funded with `amount` wei by
+/// `funder`, so that it can send transactions right away
+pub async fn build_funded_random_provider(
+    url: Url,
+    funder: HttpProviderWithWallet,
+    amount: U256,
+) -> Result<HttpProviderWithWallet> {
+    let provider = build_random_provider(url);
+    let address = provider.default_signer_address();
+    let tx = TransactionRequest::default()
+        .with_to(address)
+        .with_value(amount);
+    let receipt = funder.send_transaction(tx).await?.get_receipt().await?;
+    if !receipt.inner.is_success() {
+        return Err(anyhow!("failed to fund random wallet {address}"));
+    }
+    tracing::info!(%address, %amount, "funded random wallet");
+    Ok(provider)
+}
+
 // We pass this during `forge bind --libraries` as a placeholder for the actual deployed library address
 const LIBRARY_PLACEHOLDER_ADDRESS: &str = "ffffffffffffffffffffffffffffffffffffffff";
 /// `stateHistoryRetentionPeriod` in LightClient.sol as the maximum retention period in seconds
@@ -111,6 +157,26 @@ pub struct DeployedContracts {
     stake_table_proxy: Option<Address>,
 }
 
+impl DeployedContracts {
+    /// The address of the predeployed `contract`, if any.
+    pub fn address(&self, contract: Contract) -> Option<Address> {
+        match contract {
+            Contract::PlonkVerifier => self.plonk_verifier,
+            Contract::Timelock => self.timelock,
+            Contract::PlonkVerifierV2 => self.plonk_verifier_v2,
+            Contract::LightClient => self.light_client,
+            Contract::LightClientV2 => self.light_client_v2,
+            Contract::LightClientProxy => self.light_client_proxy,
+            Contract::FeeContract => self.fee_contract,
+            Contract::FeeContractProxy => self.fee_contract_proxy,
+            Contract::EspToken => self.esp_token,
+            Contract::EspTokenProxy => self.esp_token_proxy,
+            Contract::StakeTable => self.stake_table,
+            Contract::StakeTableProxy => self.stake_table_proxy,
+        }
+    }
+}
+
 /// An identifier for a particular contract.
 #[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Hash)]
 pub enum Contract {
@@ -140,6 +206,49 @@ pub enum Contract {
     StakeTableProxy,
 }
 
+impl Contract {
+    /// All contracts, in declaration order.
+    pub fn all() -> &'static [Contract] {
+        &[
+            Contract::PlonkVerifier,
+            Contract::Timelock,
+            Contract::PlonkVerifierV2,
+            Contract::LightClient,
+            Contract::LightClientV2,
+            Contract::LightClientProxy,
+            Contract::FeeContract,
+            Contract::FeeContractProxy,
+            Contract::EspToken,
+            Contract::EspTokenProxy,
+            Contract::StakeTable,
+            Contract::StakeTableProxy,
+        ]
+    }
+}
+
+/// Format in which contract addresses are written as environment variables.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum EnvFormat {
+    /// `KEY=0xaddr` lines, as read by dotenv loaders.
+    #[default]
+    Dotenv,
+    /// `export KEY=0xaddr` lines, which set the variables when sourced by a shell.
+    ShellExport,
+}
+
+/// Parse a contract from its env var name.
+impl FromStr for Contract {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|contract| contract.to_string() == s)
+            .ok_or_else(|| anyhow!("unknown contract env var {s}"))
+    }
+}
+
 impl From<Contract> for OsStr {
     fn from(c: Contract) -> OsStr {
         c.to_string().into()
@@ -148,58 +257,65 @@ impl From<Contract> for OsStr {
 
 /// Cache of contracts predeployed or deployed during this current run.
 #[derive(Deref, Debug, Clone, Default)]
-pub struct Contracts(HashMap<Contract, Address>);
+pub struct Contracts {
+    #[deref]
+    addresses: HashMap<Contract, Address>,
+    /// Number of confirmations to wait for after each deployment and follow-up transaction, zero
+    /// only waits for inclusion.
+    confirmations: u64,
+    /// Library address the bindings were generated with, [`LIBRARY_PLACEHOLDER_ADDRESS`] if unset.
+    library_placeholder: Option<String>,
+}
 
 impl From<DeployedContracts> for Contracts {
     fn from(deployed: DeployedContracts) -> Self {
-        let mut m = HashMap::new();
-        if let Some(addr) = deployed.plonk_verifier {
-            m.insert(Contract::PlonkVerifier, addr);
+        Self {
+            addresses: Contract::all()
+                .iter()
+                .filter_map(|&contract| Some((contract, deployed.address(contract)?)))
+                .collect(),
+            confirmations: 0,
+            library_placeholder: None,
         }
-        if let Some(addr) = deployed.plonk_verifier_v2 {
-            m.insert(Contract::PlonkVerifierV2, addr);
-        }
-        if let Some(addr) = deployed.timelock {
-            m.insert(Contract::Timelock, addr);
-        }
-        if let Some(addr) = deployed.light_client {
-            m.insert(Contract::LightClient, addr);
-        }
-        if let Some(addr) = deployed.light_client_v2 {
-            m.insert(Contract::LightClientV2, addr);
-        }
-        if let Some(addr) = deployed.light_client_proxy {
-            m.insert(Contract::LightClientProxy, addr);
-        }
-        if let Some(addr) = deployed.fee_contract {
-            m.insert(Contract::FeeContract, addr);
-        }
-        if let Some(addr) = deployed.fee_contract_proxy {
-            m.insert(Contract::FeeContractProxy, addr);
-        }
-        if let Some(addr) = deployed.esp_token {
-            m.insert(Contract::EspToken, addr);
-        }
-        if let Some(addr) = deployed.esp_token_proxy {
-            m.insert(Contract::EspTokenProxy, addr);
-        }
-        if let Some(addr) = deployed.stake_table {
-            m.insert(Contract::StakeTable, addr);
-        }
-        if let Some(addr) = deployed.stake_table_proxy {
-            m.insert(Contract::StakeTableProxy, addr);
-        }
-        Self(m)
     }
 }
 
 impl Contracts {
     pub fn new() -> Self {
-        Contracts(HashMap::new())
+        Self::default()
+    }
+
+    /// Wait for `confirmations` blocks after every deployment and follow-up transaction.
+    pub fn with_confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    pub fn confirmations(&self) -> u64 {
+        self.confirmations
+    }
+
+    /// Link libraries at `placeholder`, for bindings generated with a different library address
+    /// than [`LIBRARY_PLACEHOLDER_ADDRESS`].
+    pub fn with_library_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.library_placeholder = Some(placeholder.into());
+        self
+    }
+
+    pub fn library_placeholder(&self) -> &str {
+        self.library_placeholder
+            .as_deref()
+            .unwrap_or(LIBRARY_PLACEHOLDER_ADDRESS)
     }
 
     pub fn address(&self, contract: Contract) -> Option<Address> {
-        self.0.get(&contract).copied()
+        self.addresses.get(&contract).copied()
+    }
+
+    /// Use the already-deployed `address` for `contract`.
+    pub fn with_address(mut self, contract: Contract, address: Address) -> Self {
+        self.addresses.insert(contract, address);
+        self
     }
 
     /// Deploy a contract (with logging and cached deployments)
@@ -214,29 +330,147 @@ impl Contracts {
     where
         P: Provider,
     {
-        if let Some(addr) = self.0.get(&name) {
+        if let Some(addr) = self.addresses.get(&name) {
             tracing::info!("skipping deployment of {name}, already deployed at {addr:#x}");
             return Ok(*addr);
         }
         tracing::info!("deploying {name}");
-        let addr = tx.deploy().await?;
+        let addr = deploy_and_confirm(tx, self.confirmations).await?;
         tracing::info!("deployed {name} at {addr:#x}");
 
-        self.0.insert(name, addr);
+        self.addresses.insert(name, addr);
         Ok(addr)
     }
 
+    /// Like [`Contracts::deploy()`], but doesn't trust the cache blindly.
+    ///
+    /// If contract `name` has a cached address, check that there is still code at that address
+    /// on-chain (which may not be the case e.g. after a testnet reset), and send the deployment
+    /// `tx` again if the code is gone, updating the cached address.
+    pub async fn ensure_deployed<T, P>(
+        &mut self,
+        name: Contract,
+        tx: RawCallBuilder<T, P>,
+        provider: impl Provider,
+    ) -> Result<Address>
+    where
+        P: Provider,
+    {
+        if let Some(addr) = self.address(name) {
+            if is_contract(&provider, addr).await? {
+                tracing::info!("skipping deployment of {name}, already deployed at {addr:#x}");
+                return Ok(addr);
+            }
+            tracing::warn!("no code for cached {name} at {addr:#x}, redeploying");
+            self.addresses.remove(&name);
+        }
+        self.deploy(name, tx).await
+    }
+
+    /// The contracts in `required` that have no cached address, in the order given.
+    pub fn missing(&self, required: &[Contract]) -> Vec<Contract> {
+        required
+            .iter()
+            .filter(|contract| !self.addresses.contains_key(contract))
+            .copied()
+            .collect()
+    }
+
+    /// Like [`Contracts::missing()`], but also treats a cached address without code on-chain as
+    /// missing, e.g. after a testnet reset.
+    pub async fn missing_on_chain(
+        &self,
+        provider: impl Provider,
+        required: &[Contract],
+    ) -> Result<Vec<Contract>> {
+        let mut missing = vec![];
+        for &contract in required {
+            match self.address(contract) {
+                Some(addr) if is_contract(&provider, addr).await? => {},
+                _ => missing.push(contract),
+            }
+        }
+        Ok(missing)
+    }
+
     /// Write a .env file.
-    pub fn write(&self, mut w: impl Write) -> Result<()> {
-        for (contract, address) in &self.0 {
-            writeln!(w, "{contract}={address:#x}")?;
+    pub fn write(&self, w: impl Write) -> Result<()> {
+        self.write_with_format(w, EnvFormat::Dotenv)
+    }
+
+    /// Write the contract addresses as environment variables in the given format.
+    pub fn write_with_format(&self, mut w: impl Write, format: EnvFormat) -> Result<()> {
+        let prefix = match format {
+            EnvFormat::Dotenv => "",
+            EnvFormat::ShellExport => "export ",
+        };
+        for (contract, address) in &self.addresses {
+            writeln!(w, "{prefix}{contract}={address:#x}")?;
+        }
+        Ok(())
+    }
+
+    /// Write one `.env` fragment per contract into `dir`, so that each can be sourced separately.
+    ///
+    /// Each file is named after the contract's env var (e.g.
+    /// `ESPRESSO_SEQUENCER_LIGHT_CLIENT_PROXY_ADDRESS.env`) and contains just that contract's
+    /// `KEY=0xaddr` line. The directory is created if it doesn't exist yet.
+    pub fn write_fragments(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+        for (contract, address) in &self.addresses {
+            let mut file = File::create(dir.join(format!("{contract}.env")))?;
+            writeln!(file, "{contract}={address:#x}")?;
         }
         Ok(())
     }
 }
 
+/// Link library addresses into hex-encoded contract bytecode.
+///
+/// Each `(placeholder, address, occurrences)` entry replaces `placeholder` (the hex address passed
+/// to `forge bind --libraries`, without `0x`) with the actual deployed library `address`. The
+/// placeholder must occur exactly `occurrences` times: any other count means either the library
+/// is missing, or the placeholder collides with a constant in the bytecode, in which case the
+/// error suggests an unused placeholder to bind against instead.
+pub fn link_libraries(bytecode_hex: &str, libs: &[(&str, Address, usize)]) -> Result<Bytes> {
+    let mut linked = bytecode_hex.to_lowercase();
+    for (placeholder, addr, occurrences) in libs {
+        let placeholder = placeholder.trim_start_matches("0x").to_lowercase();
+        match linked.matches(&placeholder).count() {
+            0 => return Err(anyhow!("lib placeholder {placeholder} not found")),
+            n if n == *occurrences => {
+                linked = linked.replace(&placeholder, &addr.encode_hex());
+            },
+            n => {
+                return Err(anyhow!(
+                    "lib placeholder {placeholder} found {n} times, expected {occurrences}, \
+                     consider using a different value, e.g. {}",
+                    unused_library_placeholder(&linked)
+                        .unwrap_or_else(|| "<none available>".to_string())
+                ))
+            },
+        }
+    }
+    Ok(Bytes::from_hex(linked)?)
+}
+
+/// Find an address-sized placeholder made of a single repeated hex digit that does not occur in
+/// `bytecode_hex`.
+fn unused_library_placeholder(bytecode_hex: &str) -> Option<String> {
+    "123456789abcde"
+        .chars()
+        .map(|c| c.to_string().repeat(40))
+        .find(|placeholder| !bytecode_hex.contains(placeholder.as_str()))
+}
+
 /// Default deployment function `LightClient.sol` or `LightClientMock.sol` with `mock: true`.
 ///
+/// If `plonk_verifier` is given, the light client is linked against that already-deployed
+/// `PlonkVerifier` library, bypassing the `contracts` cache. Otherwise the cached `PlonkVerifier`
+/// is used, deploying one if necessary.
+///
+/// The library is linked at [`Contracts::library_placeholder`].
+///
 /// # NOTE:
 /// In most cases, you only need to use [`deploy_light_client_proxy()`]
 ///
@@ -248,16 +482,27 @@ pub(crate) async fn deploy_light_client_contract(
     provider: impl Provider,
     contracts: &mut Contracts,
     mock: bool,
+    plonk_verifier: Option<Address>,
 ) -> Result<Address> {
-    // Deploy library contracts.
-    let plonk_verifier_addr = contracts
-        .deploy(
-            Contract::PlonkVerifier,
-            PlonkVerifier::deploy_builder(&provider),
-        )
-        .await?;
-
-    assert!(is_contract(&provider, plonk_verifier_addr).await?);
+    // Deploy library contracts, unless the caller gave us one to link against.
+    let plonk_verifier_addr = match plonk_verifier {
+        Some(addr) => {
+            if !is_contract(&provider, addr).await? {
+                return Err(anyhow!("PlonkVerifier not found at {addr:#x}"));
+            }
+            addr
+        },
+        None => {
+            let addr = contracts
+                .deploy(
+                    Contract::PlonkVerifier,
+                    PlonkVerifier::deploy_builder(&provider),
+                )
+                .await?;
+            assert!(is_contract(&provider, addr).await?);
+            addr
+        },
+    };
 
     // when generate alloy's bindings, we supply a placeholder address, now we modify the actual
     // bytecode with deployed address of the library.
@@ -266,32 +511,20 @@ pub(crate) async fn deploy_light_client_contract(
     } else {
         LightClient::BYTECODE.encode_hex()
     };
-    let lc_linked_bytecode = {
-        match target_lc_bytecode
-            .matches(LIBRARY_PLACEHOLDER_ADDRESS)
-            .count()
-        {
-            0 => return Err(anyhow!("lib placeholder not found")),
-            1 => Bytes::from_hex(target_lc_bytecode.replacen(
-                LIBRARY_PLACEHOLDER_ADDRESS,
-                &plonk_verifier_addr.encode_hex(),
-                1,
-            ))?,
-            _ => {
-                return Err(anyhow!(
-                    "more than one lib placeholder found, consider using a different value"
-                ))
-            },
-        }
-    };
+    let lc_linked_bytecode = link_libraries(
+        &target_lc_bytecode,
+        &[(contracts.library_placeholder(), plonk_verifier_addr, 1)],
+    )?;
 
     // Deploy the light client
     let light_client_addr = if mock {
         // for mock, we don't populate the `contracts` since it only track production-ready deployments
-        let addr = LightClientMock::deploy_builder(&provider)
-            .map(|req| req.with_deploy_code(lc_linked_bytecode))
-            .deploy()
-            .await?;
+        let addr = deploy_and_confirm(
+            LightClientMock::deploy_builder(&provider)
+                .map(|req| req.with_deploy_code(lc_linked_bytecode)),
+            contracts.confirmations(),
+        )
+        .await?;
         tracing::info!("deployed LightClientMock at {addr:#x}");
         addr
     } else {
@@ -306,11 +539,67 @@ pub(crate) async fn deploy_light_client_contract(
     Ok(light_client_addr)
 }
 
+sol! {
+    /// The functions shared by all our upgradable contracts.
+    #[sol(rpc)]
+    interface IUpgradable {
+        function getVersion() external pure returns (uint8 majorVersion, uint8 minorVersion, uint8 patchVersion);
+    }
+}
+
+/// Deploy an `ERC1967Proxy` pointing to an already deployed implementation contract.
+///
+/// The proxy is initialized with `init_calldata`, which should be the encoded `initialize()` call
+/// of the implementation. After deployment we check that `proxy_name` is detected as a proxy, points
+/// to `impl_addr` and reports major version 1. Contract specific checks are left to the caller.
+///
+/// A cached proxy that points to a different implementation, e.g. one initialized by an earlier
+/// deployment, is an error.
+///
+/// Returns the proxy address.
+pub async fn deploy_upgradeable(
+    provider: impl Provider,
+    contracts: &mut Contracts,
+    impl_name: Contract,
+    proxy_name: Contract,
+    impl_addr: Address,
+    init_calldata: Bytes,
+) -> Result<Address> {
+    tracing::info!(?impl_name, %impl_addr, "deploying {proxy_name:?}");
+    let proxy_addr = contracts
+        .deploy(
+            proxy_name,
+            ERC1967Proxy::deploy_builder(&provider, impl_addr, init_calldata),
+        )
+        .await?;
+
+    // sanity check
+    if !is_proxy_contract(&provider, proxy_addr).await? {
+        panic!("{proxy_name:?} detected not as a proxy, report error!");
+    }
+
+    // post deploy verification checks
+    let proxy_impl = proxy_implementation(&provider, proxy_addr).await?;
+    ensure!(
+        proxy_impl == impl_addr,
+        "{proxy_name:?} at {proxy_addr:#x} points to {proxy_impl:#x}, expected {impl_name:?} at \
+         {impl_addr:#x}"
+    );
+    let proxy = IUpgradable::new(proxy_addr, &provider);
+    assert_eq!(proxy.getVersion().call().await?.majorVersion, 1);
+
+    Ok(proxy_addr)
+}
+
 /// The primary logic for deploying and initializing an upgradable light client contract.
 ///
 /// Deploy the upgradable proxy contract, point to an already deployed light client contract as its implementation, and invoke `initialize()` on it.
 /// This is run after `deploy_light_client_contract()`, returns the proxy address.
 /// This works for both mock and production light client proxy.
+///
+/// `initialize()` can't set the permissioned `prover`, so it is set in a separate transaction. If
+/// that fails, the returned error says so: the proxy is live without a prover, and calling this
+/// function again with the same `contracts` will skip the deployment and only retry setting it.
 pub async fn deploy_light_client_proxy(
     provider: impl Provider,
     contracts: &mut Contracts,
@@ -321,7 +610,7 @@ pub async fn deploy_light_client_proxy(
     prover: Option<Address>,
 ) -> Result<Address> {
     // deploy the light client implementation contract
-    let impl_addr = deploy_light_client_contract(&provider, contracts, mock).await?;
+    let impl_addr = deploy_light_client_contract(&provider, contracts, mock, None).await?;
     let lc = LightClient::new(impl_addr, &provider);
 
     // prepare the input arg for `initialize()`
@@ -334,39 +623,33 @@ pub async fn deploy_light_client_proxy(
         )
         .calldata()
         .to_owned();
-    // deploy proxy and initialize
-    let lc_proxy_addr = contracts
-        .deploy(
-            Contract::LightClientProxy,
-            ERC1967Proxy::deploy_builder(&provider, impl_addr, init_data),
-        )
-        .await?;
-
-    // sanity check
-    if !is_proxy_contract(&provider, lc_proxy_addr).await? {
-        panic!("LightClientProxy detected not as a proxy, report error!");
-    }
+    let lc_proxy_addr = deploy_upgradeable(
+        &provider,
+        contracts,
+        Contract::LightClient,
+        Contract::LightClientProxy,
+        impl_addr,
+        init_data,
+    )
+    .await?;
 
     // instantiate a proxy instance, cast as LightClient's ABI interface
     let lc_proxy = LightClient::new(lc_proxy_addr, &provider);
 
     // set permissioned prover
     if let Some(prover) = prover {
-        tracing::info!(%lc_proxy_addr, %prover, "Set permissioned prover ");
-        lc_proxy
-            .setPermissionedProver(prover)
-            .send()
-            .await?
-            .get_receipt()
-            .await?;
+        set_permissioned_prover(&provider, lc_proxy_addr, prover, contracts.confirmations())
+            .await
+            .with_context(|| {
+                format!(
+                    "LightClientProxy deployed at {lc_proxy_addr:#x}, but permissioned prover \
+                     {prover:#x} is unset"
+                )
+            })?;
     }
 
     // post deploy verification checks
-    assert_eq!(lc_proxy.getVersion().call().await?.majorVersion, 1);
     assert_eq!(lc_proxy.owner().call().await?._0, admin);
-    if let Some(prover) = prover {
-        assert_eq!(lc_proxy.permissionedProver().call().await?._0, prover);
-    }
     assert_eq!(
         lc_proxy.stateHistoryRetentionPeriod().call().await?._0,
         864000
@@ -379,15 +662,35 @@ pub async fn deploy_light_client_proxy(
     Ok(lc_proxy_addr)
 }
 
+/// The full `(major, minor, patch)` version of LightClientV2.
+pub const LIGHT_CLIENT_V2_VERSION: (u8, u8, u8) = (2, 0, 0);
+
+/// Query the full `(major, minor, patch)` version of the light client (proxy) at `addr`.
+pub async fn light_client_version(provider: impl Provider, addr: Address) -> Result<(u8, u8, u8)> {
+    let version = IUpgradable::new(addr, &provider)
+        .getVersion()
+        .call()
+        .await?;
+    Ok((
+        version.majorVersion,
+        version.minorVersion,
+        version.patchVersion,
+    ))
+}
+
 /// Upgrade the light client proxy to use LightClientV2.
 /// Internally, first detect existence of proxy, then deploy LCV2, then upgrade and initializeV2.
 /// Internal to "deploy LCV2", we deploy PlonkVerifierV2 whose address will be used at LCV2 init time.
+///
+/// If `plonk_verifier_v2` is given, LCV2 is linked against that already-deployed `PlonkVerifierV2`
+/// library instead, bypassing the `contracts` cache.
 pub async fn upgrade_light_client_v2(
     provider: impl Provider,
     contracts: &mut Contracts,
     is_mock: bool,
     blocks_per_epoch: u64,
     epoch_start_block: u64,
+    plonk_verifier_v2: Option<Address>,
 ) -> Result<TransactionReceipt> {
     match contracts.address(Contract::LightClientProxy) {
         // check if proxy already exists
@@ -396,15 +699,25 @@ pub async fn upgrade_light_client_v2(
             let proxy = LightClient::new(proxy_addr, &provider);
             let state_history_retention_period =
                 proxy.stateHistoryRetentionPeriod().call().await?._0;
-            // first deploy PlonkVerifierV2.sol
-            let pv2_addr = contracts
-                .deploy(
-                    Contract::PlonkVerifierV2,
-                    PlonkVerifierV2::deploy_builder(&provider),
-                )
-                .await?;
-
-            assert!(is_contract(&provider, pv2_addr).await?);
+            // first deploy PlonkVerifierV2.sol, unless the caller gave us one to link against
+            let pv2_addr = match plonk_verifier_v2 {
+                Some(addr) => {
+                    if !is_contract(&provider, addr).await? {
+                        return Err(anyhow!("PlonkVerifierV2 not found at {addr:#x}"));
+                    }
+                    addr
+                },
+                None => {
+                    let addr = contracts
+                        .deploy(
+                            Contract::PlonkVerifierV2,
+                            PlonkVerifierV2::deploy_builder(&provider),
+                        )
+                        .await?;
+                    assert!(is_contract(&provider, addr).await?);
+                    addr
+                },
+            };
 
             // then deploy LightClientV2.sol
             let target_lcv2_bytecode = if is_mock {
@@ -412,29 +725,17 @@ pub async fn upgrade_light_client_v2(
             } else {
                 LightClientV2::BYTECODE.encode_hex()
             };
-            let lcv2_linked_bytecode = {
-                match target_lcv2_bytecode
-                    .matches(LIBRARY_PLACEHOLDER_ADDRESS)
-                    .count()
-                {
-                    0 => return Err(anyhow!("lib placeholder not found")),
-                    1 => Bytes::from_hex(target_lcv2_bytecode.replacen(
-                        LIBRARY_PLACEHOLDER_ADDRESS,
-                        &pv2_addr.encode_hex(),
-                        1,
-                    ))?,
-                    _ => {
-                        return Err(anyhow!(
-                            "more than one lib placeholder found, consider using a different value"
-                        ))
-                    },
-                }
-            };
+            let lcv2_linked_bytecode = link_libraries(
+                &target_lcv2_bytecode,
+                &[(contracts.library_placeholder(), pv2_addr, 1)],
+            )?;
             let lcv2_addr = if is_mock {
-                let addr = LightClientV2Mock::deploy_builder(&provider)
-                    .map(|req| req.with_deploy_code(lcv2_linked_bytecode))
-                    .deploy()
-                    .await?;
+                let addr = deploy_and_confirm(
+                    LightClientV2Mock::deploy_builder(&provider)
+                        .map(|req| req.with_deploy_code(lcv2_linked_bytecode)),
+                    contracts.confirmations(),
+                )
+                .await?;
                 tracing::info!("deployed LightClientV2Mock at {addr:#x}");
                 addr
             } else {
@@ -454,16 +755,19 @@ pub async fn upgrade_light_client_v2(
                 .calldata()
                 .to_owned();
             // invoke upgrade on proxy
-            let receipt = proxy
-                .upgradeToAndCall(lcv2_addr, init_data)
-                .send()
-                .await?
-                .get_receipt()
-                .await?;
+            let receipt = send_and_confirm(
+                proxy.upgradeToAndCall(lcv2_addr, init_data),
+                contracts.confirmations(),
+            )
+            .await?;
             if receipt.inner.is_success() {
                 // post deploy verification checks
+                assert_eq!(decode_upgraded_event(&receipt), Some(lcv2_addr));
                 let proxy_as_v2 = LightClientV2::new(proxy_addr, &provider);
-                assert_eq!(proxy_as_v2.getVersion().call().await?.majorVersion, 2);
+                assert_eq!(
+                    light_client_version(&provider, proxy_addr).await?,
+                    LIGHT_CLIENT_V2_VERSION
+                );
                 assert_eq!(
                     proxy_as_v2.blocksPerEpoch().call().await?._0,
                     blocks_per_epoch
@@ -513,21 +817,18 @@ pub async fn deploy_fee_contract_proxy(
 
     // prepare the input arg for `initialize()`
     let init_data = fee.initialize(admin).calldata().to_owned();
-    // deploy proxy and initialize
-    let fee_proxy_addr = contracts
-        .deploy(
-            Contract::FeeContractProxy,
-            ERC1967Proxy::deploy_builder(&provider, fee_addr, init_data),
-        )
-        .await?;
-    // sanity check
-    if !is_proxy_contract(&provider, fee_proxy_addr).await? {
-        panic!("FeeContractProxy detected not as a proxy, report error!");
-    }
+    let fee_proxy_addr = deploy_upgradeable(
+        &provider,
+        contracts,
+        Contract::FeeContract,
+        Contract::FeeContractProxy,
+        fee_addr,
+        init_data,
+    )
+    .await?;
 
     // post deploy verification checks
     let fee_proxy = FeeContract::new(fee_proxy_addr, &provider);
-    assert_eq!(fee_proxy.getVersion().call().await?.majorVersion, 1);
     assert_eq!(fee_proxy.owner().call().await?._0, admin);
 
     Ok(fee_proxy_addr)
@@ -549,20 +850,18 @@ pub async fn deploy_token_proxy(
         .initialize(owner, init_grant_recipient)
         .calldata()
         .to_owned();
-    let token_proxy_addr = contracts
-        .deploy(
-            Contract::EspTokenProxy,
-            ERC1967Proxy::deploy_builder(&provider, token_addr, init_data),
-        )
-        .await?;
-
-    if !is_proxy_contract(&provider, token_proxy_addr).await? {
-        panic!("EspTokenProxy detected not as a proxy, report error!");
-    }
+    let token_proxy_addr = deploy_upgradeable(
+        &provider,
+        contracts,
+        Contract::EspToken,
+        Contract::EspTokenProxy,
+        token_addr,
+        init_data,
+    )
+    .await?;
 
     // post deploy verification checks
     let token_proxy = EspToken::new(token_proxy_addr, &provider);
-    assert_eq!(token_proxy.getVersion().call().await?.majorVersion, 1);
     assert_eq!(token_proxy.owner().call().await?._0, owner);
     assert_eq!(token_proxy.symbol().call().await?._0, "ESP");
     assert_eq!(token_proxy.decimals().call().await?._0, 18);
@@ -601,19 +900,17 @@ pub async fn deploy_stake_table_proxy(
         .initialize(token_addr, light_client_addr, exit_escrow_period, owner)
         .calldata()
         .to_owned();
-    let st_proxy_addr = contracts
-        .deploy(
-            Contract::StakeTableProxy,
-            ERC1967Proxy::deploy_builder(&provider, stake_table_addr, init_data),
-        )
-        .await?;
-
-    if !is_proxy_contract(&provider, st_proxy_addr).await? {
-        panic!("StakeTableProxy detected not as a proxy, report error!");
-    }
+    let st_proxy_addr = deploy_upgradeable(
+        &provider,
+        contracts,
+        Contract::StakeTable,
+        Contract::StakeTableProxy,
+        stake_table_addr,
+        init_data,
+    )
+    .await?;
 
     let st_proxy = StakeTable::new(st_proxy_addr, &provider);
-    assert_eq!(st_proxy.getVersion().call().await?.majorVersion, 1);
     assert_eq!(st_proxy.owner().call().await?._0, owner);
     assert_eq!(st_proxy.token().call().await?._0, token_addr);
     assert_eq!(st_proxy.lightClient().call().await?._0, light_client_addr);
@@ -625,73 +922,533 @@ pub async fn deploy_stake_table_proxy(
     Ok(st_proxy_addr)
 }
 
-/// Common logic for any Ownable contract to transfer ownership
-pub async fn transfer_ownership(
+/// Re-run the post-deploy checks against all contracts in `contracts`, without deploying anything.
+///
+/// Implementation contracts must contain code. Proxies must point to the cached implementation
+/// (if any), report the expected version, be owned by `owner` and be wired to the other cached
+/// proxies. Contracts missing from `contracts` are skipped.
+pub async fn verify_deployment(
     provider: impl Provider,
-    target: Contract,
-    addr: Address,
-    new_owner: Address,
-) -> Result<TransactionReceipt> {
-    let receipt = match target {
-        Contract::LightClient | Contract::LightClientProxy => {
-            tracing::info!(%addr, %new_owner, "Transfer LightClient ownership");
-            let lc = LightClient::new(addr, &provider);
-            lc.transferOwnership(new_owner)
-                .send()
-                .await?
-                .get_receipt()
-                .await?
-        },
-        Contract::FeeContract | Contract::FeeContractProxy => {
-            tracing::info!(%addr, %new_owner, "Transfer FeeContract ownership");
-            let fee = FeeContract::new(addr, &provider);
-            fee.transferOwnership(new_owner)
-                .send()
-                .await?
-                .get_receipt()
-                .await?
-        },
-        Contract::EspToken | Contract::EspTokenProxy => {
-            tracing::info!(%addr, %new_owner, "Transfer EspToken ownership");
-            let token = EspToken::new(addr, &provider);
-            token
-                .transferOwnership(new_owner)
-                .send()
-                .await?
-                .get_receipt()
-                .await?
-        },
-        Contract::StakeTable | Contract::StakeTableProxy => {
-            tracing::info!(%addr, %new_owner, "Transfer StakeTable ownership");
-            let stake_table = StakeTable::new(addr, &provider);
-            stake_table
-                .transferOwnership(new_owner)
-                .send()
-                .await?
-                .get_receipt()
-                .await?
-        },
-        _ => return Err(anyhow!("Not Ownable, can't transfer ownership!")),
-    };
-    Ok(receipt)
-}
+    contracts: &Contracts,
+    owner: Address,
+) -> Result<()> {
+    for &contract in Contract::all() {
+        let Some(addr) = contracts.address(contract) else {
+            continue;
+        };
+        tracing::info!(%addr, "verifying {contract:?}");
+        ensure!(
+            is_contract(&provider, addr).await?,
+            "{contract:?} at {addr:#x} has no code"
+        );
 
-/// helper function to decide if the contract at given address `addr` is a proxy contract
-pub async fn is_proxy_contract(provider: impl Provider, addr: Address) -> Result<bool> {
-    // confirm that the proxy_address is a proxy
-    // using the implementation slot, 0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc, which is the keccak-256 hash of "eip1967.proxy.implementation" subtracted by 1
-    let impl_slot = U256::from_str_radix(
-        "360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc",
-        16,
-    )?;
-    let storage = provider.get_storage_at(addr, impl_slot).await?;
-    let impl_address = Address::from_slice(&storage.to_be_bytes_vec()[12..]);
+        let implementation = match contract {
+            Contract::LightClientProxy => contracts
+                .address(Contract::LightClientV2)
+                .or(contracts.address(Contract::LightClient)),
+            Contract::FeeContractProxy => contracts.address(Contract::FeeContract),
+            Contract::EspTokenProxy => contracts.address(Contract::EspToken),
+            Contract::StakeTableProxy => contracts.address(Contract::StakeTable),
+            // not a proxy, having code is all we can check
+            _ => continue,
+        };
+        let proxy_impl = proxy_implementation(&provider, addr).await?;
+        ensure!(
+            proxy_impl != Address::ZERO,
+            "{contract:?} at {addr:#x} is not a proxy"
+        );
+        if let Some(implementation) = implementation {
+            ensure!(
+                proxy_impl == implementation,
+                "{contract:?} points to {proxy_impl:#x}, expected {implementation:#x}"
+            );
+        }
 
-    // when the implementation address is not equal to zero, it's a proxy
-    Ok(impl_address != Address::default())
-}
+        // the light client version depends on whether it has been upgraded yet, mock light
+        // clients are not cached so we can't tell
+        let expected_version = match contract {
+            Contract::LightClientProxy if contracts.address(Contract::LightClientV2).is_some() => {
+                Some(2)
+            },
+            Contract::LightClientProxy if contracts.address(Contract::LightClient).is_none() => {
+                None
+            },
+            _ => Some(1),
+        };
+        if let Some(expected_version) = expected_version {
+            let version = IUpgradable::new(addr, &provider)
+                .getVersion()
+                .call()
+                .await?
+                .majorVersion;
+            ensure!(
+                version == expected_version,
+                "{contract:?} has version {version}, expected {expected_version}"
+            );
+        }
 
-pub async fn is_contract(provider: impl Provider, address: Address) -> Result<bool> {
+        let proxy_owner = match contract {
+            Contract::LightClientProxy => {
+                LightClient::new(addr, &provider).owner().call().await?._0
+            },
+            Contract::FeeContractProxy => {
+                FeeContract::new(addr, &provider).owner().call().await?._0
+            },
+            Contract::EspTokenProxy => {
+                let token = EspToken::new(addr, &provider);
+                ensure!(
+                    token.symbol().call().await?._0 == "ESP",
+                    "wrong ESP token symbol"
+                );
+                ensure!(
+                    token.decimals().call().await?._0 == 18,
+                    "wrong ESP token decimals"
+                );
+                ensure!(
+                    token.name().call().await?._0 == "Espresso Token",
+                    "wrong ESP token name"
+                );
+                token.owner().call().await?._0
+            },
+            Contract::StakeTableProxy => {
+                let stake_table = StakeTable::new(addr, &provider);
+                if let Some(token) = contracts.address(Contract::EspTokenProxy) {
+                    let wired = stake_table.token().call().await?._0;
+                    ensure!(
+                        wired == token,
+                        "StakeTableProxy uses token {wired:#x}, expected {token:#x}"
+                    );
+                }
+                if let Some(lc) = contracts.address(Contract::LightClientProxy) {
+                    let wired = stake_table.lightClient().call().await?._0;
+                    ensure!(
+                        wired == lc,
+                        "StakeTableProxy uses light client {wired:#x}, expected {lc:#x}"
+                    );
+                }
+                stake_table.owner().call().await?._0
+            },
+            _ => unreachable!("only proxies are checked here"),
+        };
+        ensure!(
+            proxy_owner == owner,
+            "{contract:?} is owned by {proxy_owner:#x}, expected {owner:#x}"
+        );
+    }
+    Ok(())
+}
+
+/// Exercise a freshly deployed stake table end-to-end.
+///
+/// Funds a throwaway account from the `provider` wallet with ETH and a tiny amount of ESP, which
+/// then registers a throwaway validator, delegates to it and checks that the stake table reflects
+/// this. The validator is deregistered again afterwards, so that it never becomes part of the
+/// active validator set.
+#[cfg(any(test, feature = "testing"))]
+pub async fn smoke_test_stake_table(
+    provider: impl Provider + WalletProvider,
+    st_addr: Address,
+    token_addr: Address,
+) -> Result<()> {
+    use alloy::{
+        primitives::{utils::parse_ether, B256},
+        sol_types::SolValue as _,
+    };
+    use ark_ec::CurveGroup as _;
+    use hotshot_types::{
+        light_client::StateKeyPair, signature_key::BLSPubKey, traits::signature_key::SignatureKey,
+    };
+
+    let stake_table = StakeTable::new(st_addr, &provider);
+    let st_token = stake_table.token().call().await?._0;
+    if st_token != token_addr {
+        return Err(anyhow!(
+            "stake table uses token {st_token:#x}, expected {token_addr:#x}"
+        ));
+    }
+
+    let funder = provider.default_signer_address();
+    let amount = U256::from(1);
+    let token = EspToken::new(token_addr, &provider);
+    let balance = token.balanceOf(funder).call().await?._0;
+    if balance < amount {
+        return Err(anyhow!("{funder:#x} has no ESP to fund the smoke test"));
+    }
+
+    // fund a throwaway account
+    let signer = PrivateKeySigner::random();
+    let account = signer.address();
+    let throwaway = ProviderBuilder::new()
+        .wallet(EthereumWallet::from(signer))
+        .on_provider(provider.root().clone());
+    provider
+        .send_transaction(
+            TransactionRequest::default()
+                .with_to(account)
+                .with_value(parse_ether("0.1")?),
+        )
+        .await?
+        .get_receipt()
+        .await?;
+    token
+        .transfer(account, amount)
+        .send()
+        .await?
+        .get_receipt()
+        .await?;
+    tracing::info!("funded smoke test account {account:#x}");
+
+    // register a throwaway validator
+    let seed = B256::random().0;
+    let (bls_vk, bls_sk) = BLSPubKey::generated_from_seed_indexed(seed, 0);
+    let schnorr_vk = StateKeyPair::generate_from_seed_indexed(seed, 0).ver_key();
+    let bls_sig = BLSPubKey::sign(&bls_sk, &account.abi_encode())?;
+    let bls_sig_sol: G1PointSol = bls_sig.sigma.into_affine().into();
+    let commission = 100;
+    let stake_table = StakeTable::new(st_addr, &throwaway);
+    stake_table
+        .registerValidator(
+            bls_vk.to_affine().into(),
+            schnorr_vk.to_affine().into(),
+            bls_sig_sol.into(),
+            commission,
+        )
+        .send()
+        .await?
+        .get_receipt()
+        .await?;
+
+    // delegate to it
+    EspToken::new(token_addr, &throwaway)
+        .approve(st_addr, amount)
+        .send()
+        .await?
+        .get_receipt()
+        .await?;
+    stake_table
+        .delegate(account, amount)
+        .send()
+        .await?
+        .get_receipt()
+        .await?;
+
+    // read it back
+    let validator = stake_table.validators(account).call().await?;
+    if validator.delegatedAmount != amount || validator.status != 1 {
+        return Err(anyhow!(
+            "unexpected validator state: delegated {}, status {}",
+            validator.delegatedAmount,
+            validator.status
+        ));
+    }
+    let delegation = stake_table
+        .delegations(account, account)
+        .call()
+        .await?
+        .amount;
+    if delegation != amount {
+        return Err(anyhow!(
+            "unexpected delegation {delegation}, expected {amount}"
+        ));
+    }
+
+    stake_table
+        .deregisterValidator()
+        .send()
+        .await?
+        .get_receipt()
+        .await?;
+    tracing::info!("stake table at {st_addr:#x} passed smoke test");
+    Ok(())
+}
+
+/// Initial delay between receipt lookups, also used to poll for confirmations
+const RECEIPT_RETRY_DELAY: Duration = Duration::from_millis(500);
+/// How long to wait for the requested number of confirmations before giving up
+const CONFIRMATIONS_TIMEOUT: Duration = Duration::from_secs(600);
+/// How often to look again for a receipt that the RPC can't find (yet)
+const RECEIPT_BACKOFF: BoundedBackoff = BoundedBackoff {
+    max_retries: 4,
+    base: RECEIPT_RETRY_DELAY,
+    factor: 2,
+    max: Duration::from_secs(8),
+};
+
+/// Send a contract call and wait for its receipt, and `confirmations` blocks if non-zero.
+///
+/// Unlike `.send().await?.get_receipt().await?`, a transaction that the RPC briefly fails to find
+/// doesn't abort the whole deployment, fetching its receipt is retried a few times instead.
+pub async fn send_and_confirm<T, P, D>(
+    call: CallBuilder<T, P, D>,
+    confirmations: u64,
+) -> Result<TransactionReceipt>
+where
+    P: Provider,
+    D: CallDecoder,
+{
+    let pending = call
+        .send()
+        .await?
+        .with_required_confirmations(confirmations.max(1));
+    let tx_hash = *pending.tx_hash();
+    let provider = pending.provider().clone();
+    match pending.get_receipt().await {
+        Ok(receipt) => Ok(receipt),
+        Err(err) => {
+            tracing::warn!(%tx_hash, "failed to get receipt, retrying: {err:#}");
+            let receipt = wait_for_receipt(&provider, tx_hash).await?;
+            wait_for_confirmations(&provider, &receipt, confirmations).await?;
+            Ok(receipt)
+        },
+    }
+}
+
+/// Deploy a contract and wait for `confirmations` blocks if non-zero, returning its address.
+pub async fn deploy_and_confirm<T, P>(
+    tx: RawCallBuilder<T, P>,
+    confirmations: u64,
+) -> Result<Address>
+where
+    P: Provider,
+{
+    if confirmations == 0 {
+        return Ok(tx.deploy().await?);
+    }
+    let receipt = tx
+        .send()
+        .await?
+        .with_required_confirmations(confirmations)
+        .get_receipt()
+        .await?;
+    ensure!(
+        receipt.status(),
+        "deployment reverted in {:#x}",
+        receipt.transaction_hash
+    );
+    receipt.contract_address.with_context(|| {
+        format!(
+            "no contract address in receipt {:#x}",
+            receipt.transaction_hash
+        )
+    })
+}
+
+/// Wait until the block of `receipt` has `confirmations` blocks on top of it, counting itself.
+///
+/// Fails if the confirmations don't arrive within [`CONFIRMATIONS_TIMEOUT`], e.g. because the
+/// chain stalled.
+async fn wait_for_confirmations(
+    provider: impl Provider,
+    receipt: &TransactionReceipt,
+    confirmations: u64,
+) -> Result<()> {
+    let Some(block) = receipt.block_number else {
+        return Ok(());
+    };
+    tokio::time::timeout(CONFIRMATIONS_TIMEOUT, async {
+        while provider.get_block_number().await? + 1 < block + confirmations {
+            tokio::time::sleep(RECEIPT_RETRY_DELAY).await;
+        }
+        Ok::<_, anyhow::Error>(())
+    })
+    .await
+    .with_context(|| {
+        format!(
+            "transaction {:#x} not confirmed by {confirmations} blocks within {CONFIRMATIONS_TIMEOUT:?}",
+            receipt.transaction_hash
+        )
+    })?
+}
+
+/// Poll for the receipt of `tx_hash`, with exponential backoff while it is not found.
+async fn wait_for_receipt(provider: impl Provider, tx_hash: B256) -> Result<TransactionReceipt> {
+    retry_with_backoff(
+        &RECEIPT_BACKOFF,
+        |_| true,
+        || async {
+            provider
+                .get_transaction_receipt(tx_hash)
+                .await?
+                .ok_or_else(|| anyhow!("receipt not found"))
+        },
+    )
+    .await
+    .with_context(|| format!("no receipt for transaction {tx_hash}"))
+}
+
+/// Common logic for any Ownable contract to transfer ownership
+///
+/// The transaction is confirmed by `confirmations` blocks if non-zero.
+pub async fn transfer_ownership(
+    provider: impl Provider,
+    target: Contract,
+    addr: Address,
+    new_owner: Address,
+    confirmations: u64,
+) -> Result<TransactionReceipt> {
+    let receipt = match target {
+        Contract::LightClient | Contract::LightClientProxy => {
+            tracing::info!(%addr, %new_owner, "Transfer LightClient ownership");
+            let lc = LightClient::new(addr, &provider);
+            send_and_confirm(lc.transferOwnership(new_owner), confirmations).await?
+        },
+        Contract::FeeContract | Contract::FeeContractProxy => {
+            tracing::info!(%addr, %new_owner, "Transfer FeeContract ownership");
+            let fee = FeeContract::new(addr, &provider);
+            send_and_confirm(fee.transferOwnership(new_owner), confirmations).await?
+        },
+        Contract::EspToken | Contract::EspTokenProxy => {
+            tracing::info!(%addr, %new_owner, "Transfer EspToken ownership");
+            let token = EspToken::new(addr, &provider);
+            send_and_confirm(token.transferOwnership(new_owner), confirmations).await?
+        },
+        Contract::StakeTable | Contract::StakeTableProxy => {
+            tracing::info!(%addr, %new_owner, "Transfer StakeTable ownership");
+            let stake_table = StakeTable::new(addr, &provider);
+            send_and_confirm(stake_table.transferOwnership(new_owner), confirmations).await?
+        },
+        _ => return Err(anyhow!("Not Ownable, can't transfer ownership!")),
+    };
+    Ok(receipt)
+}
+
+/// Set the state history retention period of the light client (proxy) at `lc_addr` to `seconds`.
+///
+/// Must be sent by the owner. The contract only accepts increasing the period, and we additionally
+/// refuse anything beyond [`MAX_HISTORY_RETENTION_SECONDS`] before sending the transaction. The
+/// transaction is confirmed by `confirmations` blocks if non-zero.
+pub async fn set_state_history_retention(
+    provider: impl Provider,
+    lc_addr: Address,
+    seconds: u32,
+    confirmations: u64,
+) -> Result<TransactionReceipt> {
+    ensure!(
+        seconds <= MAX_HISTORY_RETENTION_SECONDS,
+        "state history retention period of {seconds}s exceeds the maximum of \
+         {MAX_HISTORY_RETENTION_SECONDS}s"
+    );
+    tracing::info!(%lc_addr, seconds, "Set LightClient state history retention period");
+    let lc = LightClient::new(lc_addr, &provider);
+    let receipt =
+        send_and_confirm(lc.setstateHistoryRetentionPeriod(seconds), confirmations).await?;
+    ensure!(
+        receipt.inner.is_success(),
+        "setstateHistoryRetentionPeriod reverted in {:#x}",
+        receipt.transaction_hash
+    );
+
+    // post update verification check
+    let retention = lc.stateHistoryRetentionPeriod().call().await?._0;
+    ensure!(
+        retention == seconds,
+        "state history retention period is {retention}s after setting it to {seconds}s"
+    );
+    Ok(receipt)
+}
+
+/// Require that only `prover` can update the state of the light client (proxy) at `lc_addr`.
+///
+/// Must be sent by the owner. Fails if `prover` already is the permissioned prover. The transaction
+/// is confirmed by `confirmations` blocks if non-zero.
+pub async fn set_permissioned_prover(
+    provider: impl Provider,
+    lc_addr: Address,
+    prover: Address,
+    confirmations: u64,
+) -> Result<TransactionReceipt> {
+    tracing::info!(%lc_addr, %prover, "Set permissioned prover");
+    let lc = LightClient::new(lc_addr, &provider);
+    let receipt = send_and_confirm(lc.setPermissionedProver(prover), confirmations).await?;
+    ensure!(
+        receipt.inner.is_success(),
+        "setPermissionedProver reverted in {:#x}",
+        receipt.transaction_hash
+    );
+
+    // post update verification checks
+    ensure!(
+        lc.isPermissionedProverEnabled().call().await?._0,
+        "permissioned prover mode is disabled after setting prover {prover:#x}"
+    );
+    let permissioned_prover = lc.permissionedProver().call().await?._0;
+    ensure!(
+        permissioned_prover == prover,
+        "permissioned prover is {permissioned_prover:#x} after setting it to {prover:#x}"
+    );
+    Ok(receipt)
+}
+
+/// Make proving for the light client (proxy) at `lc_addr` permissionless again.
+///
+/// Must be sent by the owner. Fails if permissioned prover mode is already disabled. The
+/// transaction is confirmed by `confirmations` blocks if non-zero.
+pub async fn disable_permissioned_prover(
+    provider: impl Provider,
+    lc_addr: Address,
+    confirmations: u64,
+) -> Result<TransactionReceipt> {
+    tracing::info!(%lc_addr, "Disable permissioned prover mode");
+    let lc = LightClient::new(lc_addr, &provider);
+    let receipt = send_and_confirm(lc.disablePermissionedProverMode(), confirmations).await?;
+    ensure!(
+        receipt.inner.is_success(),
+        "disablePermissionedProverMode reverted in {:#x}",
+        receipt.transaction_hash
+    );
+
+    // post update verification check
+    ensure!(
+        !lc.isPermissionedProverEnabled().call().await?._0,
+        "permissioned prover mode is still enabled after disabling it"
+    );
+    Ok(receipt)
+}
+
+/// helper function to decide if the contract at given address `addr` is a proxy contract
+pub async fn is_proxy_contract(provider: impl Provider, addr: Address) -> Result<bool> {
+    // when the implementation address is not equal to zero, it's a proxy
+    Ok(proxy_implementation(provider, addr).await? != Address::default())
+}
+
+/// Read the implementation address of the ERC1967 proxy at `addr` (zero if it's not a proxy)
+pub async fn proxy_implementation(provider: impl Provider, addr: Address) -> Result<Address> {
+    // using the implementation slot, 0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc, which is the keccak-256 hash of "eip1967.proxy.implementation" subtracted by 1
+    let impl_slot = U256::from_str_radix(
+        "360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc",
+        16,
+    )?;
+    let storage = provider.get_storage_at(addr, impl_slot).await?;
+    Ok(Address::from_slice(&storage.to_be_bytes_vec()[12..]))
+}
+
+/// Whether `initialize()` has been called through the proxy at `proxy_addr`.
+///
+/// A proxy whose deployment was interrupted before initialization is live but unusable, and
+/// anyone may still initialize it.
+pub async fn is_initialized(provider: impl Provider, proxy_addr: Address) -> Result<bool> {
+    // `_initialized` is the lowest 8 bytes of OpenZeppelin's (v5) `InitializableStorage`, located
+    // at keccak256(abi.encode(uint256(keccak256("openzeppelin.storage.Initializable")) - 1)) & ~bytes32(uint256(0xff))
+    let initializable_slot = U256::from_str_radix(
+        "f0c57e16840df040f15088dc2f81fe391c3923bec73e23a9662efc9c229c6a00",
+        16,
+    )?;
+    let storage = provider
+        .get_storage_at(proxy_addr, initializable_slot)
+        .await?;
+    Ok(storage & U256::from(u64::MAX) != U256::ZERO)
+}
+
+/// The address at which the next contract created by `deployer` with `CREATE` will be deployed.
+///
+/// This is only accurate if `deployer` sends no other transaction before the deployment. Its
+/// pending transactions are accounted for.
+pub async fn predict_create_address(provider: impl Provider, deployer: Address) -> Result<Address> {
+    let nonce = provider.get_transaction_count(deployer).pending().await?;
+    Ok(deployer.create(nonce))
+}
+
+pub async fn is_contract(provider: impl Provider, address: Address) -> Result<bool> {
     if address == Address::ZERO {
         return Ok(false);
     }
@@ -767,10 +1524,78 @@ pub async fn deploy_timelock(
 
 #[cfg(test)]
 mod tests {
-    use alloy::{primitives::utils::parse_units, providers::ProviderBuilder, sol_types::SolValue};
+    use std::collections::HashSet;
+
+    use alloy::{
+        node_bindings::Anvil,
+        primitives::utils::{parse_ether, parse_units},
+        providers::{ext::AnvilApi, ProviderBuilder},
+        sol_types::SolValue,
+        transports::mock::Asserter,
+    };
 
     use super::*;
 
+    #[test]
+    fn test_contract_env_var_round_trip() -> Result<()> {
+        // Adding a variant makes this match non-exhaustive, as a reminder to add it to
+        // `Contract::all` too.
+        let index = |contract: Contract| match contract {
+            Contract::PlonkVerifier => 0,
+            Contract::Timelock => 1,
+            Contract::PlonkVerifierV2 => 2,
+            Contract::LightClient => 3,
+            Contract::LightClientV2 => 4,
+            Contract::LightClientProxy => 5,
+            Contract::FeeContract => 6,
+            Contract::FeeContractProxy => 7,
+            Contract::EspToken => 8,
+            Contract::EspTokenProxy => 9,
+            Contract::StakeTable => 10,
+            Contract::StakeTableProxy => 11,
+        };
+        assert_eq!(
+            Contract::all()
+                .iter()
+                .map(|&c| index(c))
+                .collect::<Vec<_>>(),
+            (0..12).collect::<Vec<_>>()
+        );
+
+        let mut env_vars = HashSet::new();
+        for &contract in Contract::all() {
+            let env_var = contract.to_string();
+            assert!(
+                env_var
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'),
+                "{env_var}"
+            );
+            assert!(env_vars.insert(env_var.clone()), "duplicate {env_var}");
+            assert_eq!(env_var.parse::<Contract>()?, contract);
+        }
+        assert!("NOT_A_CONTRACT".parse::<Contract>().is_err());
+
+        // The written .env file parses back into the same contracts.
+        let mut contracts = Contracts::new();
+        for (i, &contract) in Contract::all().iter().enumerate() {
+            contracts
+                .addresses
+                .insert(contract, Address::with_last_byte(i as u8 + 1));
+        }
+        let mut env = vec![];
+        contracts.write(&mut env)?;
+        let parsed = String::from_utf8(env)?
+            .lines()
+            .map(|line| {
+                let (env_var, address) = line.split_once('=').unwrap();
+                Ok((env_var.parse::<Contract>()?, address.parse::<Address>()?))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+        assert_eq!(parsed, contracts.addresses);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_is_contract() -> Result<(), anyhow::Error> {
         let provider = ProviderBuilder::new().on_anvil_with_wallet();
@@ -791,17 +1616,350 @@ mod tests {
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_is_proxy_contract() -> Result<()> {
-        let provider = ProviderBuilder::new().on_anvil_with_wallet();
-        let deployer = provider.get_accounts().await?[0];
+    #[tokio::test]
+    async fn test_predict_create_address() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let deployer = provider.get_accounts().await?[0];
+
+        // the prediction moves on with every transaction of the deployer
+        for _ in 0..2 {
+            let predicted = predict_create_address(&provider, deployer).await?;
+            let fee_contract = FeeContract::deploy(&provider).await?;
+            assert_eq!(predicted, *fee_contract.address());
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_build_funded_random_provider() -> Result<()> {
+        let anvil = Anvil::new().spawn();
+        let funder = build_provider(
+            "test test test test test test test test test test test junk".to_string(),
+            0,
+            anvil.endpoint_url(),
+        );
+        let amount = parse_ether("1")?;
+        let provider =
+            build_funded_random_provider(anvil.endpoint_url(), funder.clone(), amount).await?;
+        let address = provider.default_signer_address();
+        assert_ne!(address, funder.default_signer_address());
+        assert_eq!(provider.get_balance(address).await?, amount);
+
+        // the random wallet can deploy contracts on its own
+        let fee_contract = FeeContract::deploy(&provider).await?;
+        assert!(is_contract(&provider, *fee_contract.address()).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_build_provider_from_key() -> Result<()> {
+        let anvil = Anvil::new().spawn();
+        let key = B256::from_slice(&anvil.keys()[0].to_bytes());
+        let provider = build_provider_from_key(key, anvil.endpoint_url())?;
+        assert_eq!(provider.default_signer_address(), anvil.addresses()[0]);
+
+        let fee_contract = FeeContract::deploy(&provider).await?;
+        assert!(is_contract(&provider, *fee_contract.address()).await?);
+
+        // zero and keys beyond the curve order are not valid scalars
+        assert!(build_provider_from_key(B256::ZERO, anvil.endpoint_url()).is_err());
+        assert!(build_provider_from_key(B256::repeat_byte(0xff), anvil.endpoint_url()).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_build_impersonated_provider() -> Result<()> {
+        let anvil = Anvil::new().spawn();
+        let fork = Anvil::new().fork(anvil.endpoint()).spawn();
+        let account = Address::random();
+
+        let provider = build_impersonated_provider(fork.endpoint_url(), account).await?;
+        provider
+            .anvil_set_balance(account, parse_ether("1")?)
+            .await?;
+        let tx = TransactionRequest::default()
+            .with_from(account)
+            .with_to(Address::random())
+            .with_value(U256::from(1));
+        let receipt = provider.send_transaction(tx).await?.get_receipt().await?;
+        assert!(receipt.inner.is_success());
+        assert_eq!(receipt.from, account);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_receipt() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let receipt = provider
+            .send_transaction(
+                TransactionRequest::default()
+                    .with_to(Address::random())
+                    .with_value(U256::from(1)),
+            )
+            .await?
+            .get_receipt()
+            .await?;
+
+        // the RPC doesn't find the transaction at first, then fails, then returns the receipt
+        let asserter = Asserter::new();
+        asserter.push_success(&None::<TransactionReceipt>);
+        asserter.push_failure_msg("transient error");
+        asserter.push_success(&receipt);
+        let mock = ProviderBuilder::new().on_mocked_client(asserter);
+        let fetched = wait_for_receipt(&mock, receipt.transaction_hash).await?;
+        assert_eq!(fetched.transaction_hash, receipt.transaction_hash);
+        assert_eq!(fetched.block_number, receipt.block_number);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wait_confirmations() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let admin = provider.get_accounts().await?[0];
+
+        // anvil only mines a block per transaction, keep mining so that confirmations arrive
+        let miner = provider.clone();
+        let mining = tokio::spawn(async move {
+            loop {
+                miner.anvil_mine(Some(1), None).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        });
+
+        let mut contracts = Contracts::new().with_confirmations(2);
+        let fee_proxy = deploy_fee_contract_proxy(&provider, &mut contracts, admin).await?;
+        assert!(is_contract(&provider, fee_proxy).await?);
+
+        let receipt = transfer_ownership(
+            &provider,
+            Contract::FeeContractProxy,
+            fee_proxy,
+            Address::random(),
+            contracts.confirmations(),
+        )
+        .await?;
+        assert!(provider.get_block_number().await? > receipt.block_number.unwrap());
+
+        mining.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_is_proxy_contract() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let deployer = provider.get_accounts().await?[0];
+
+        let fee_contract = FeeContract::deploy(&provider).await?;
+        let init_data = fee_contract.initialize(deployer).calldata().clone();
+        let proxy = ERC1967Proxy::deploy(&provider, *fee_contract.address(), init_data).await?;
+
+        assert!(is_proxy_contract(&provider, *proxy.address()).await?);
+        assert!(!is_proxy_contract(&provider, *fee_contract.address()).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_is_initialized() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let deployer = provider.get_accounts().await?[0];
+        let fee_contract = FeeContract::deploy(&provider).await?;
+
+        // a proxy deployed without init calldata is not initialized
+        let proxy = ERC1967Proxy::deploy(&provider, *fee_contract.address(), Bytes::new()).await?;
+        assert!(is_proxy_contract(&provider, *proxy.address()).await?);
+        assert!(!is_initialized(&provider, *proxy.address()).await?);
+
+        // until `initialize()` is called through it
+        FeeContract::new(*proxy.address(), &provider)
+            .initialize(deployer)
+            .send()
+            .await?
+            .get_receipt()
+            .await?;
+        assert!(is_initialized(&provider, *proxy.address()).await?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_fragments() -> Result<()> {
+        let mut contracts = Contracts::new();
+        let lc_proxy = Address::random();
+        let fee_proxy = Address::random();
+        contracts
+            .addresses
+            .insert(Contract::LightClientProxy, lc_proxy);
+        contracts
+            .addresses
+            .insert(Contract::FeeContractProxy, fee_proxy);
+
+        let dir = tempfile::tempdir()?;
+        contracts.write_fragments(dir.path())?;
+
+        assert_eq!(std::fs::read_dir(dir.path())?.count(), 2);
+        for (contract, address) in [
+            (Contract::LightClientProxy, lc_proxy),
+            (Contract::FeeContractProxy, fee_proxy),
+        ] {
+            let fragment = std::fs::read_to_string(dir.path().join(format!("{contract}.env")))?;
+            assert_eq!(fragment, format!("{contract}={address:#x}\n"));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_shell_export() -> Result<()> {
+        let mut contracts = Contracts::new();
+        contracts
+            .addresses
+            .insert(Contract::LightClientProxy, Address::random());
+        contracts
+            .addresses
+            .insert(Contract::FeeContractProxy, Address::random());
+
+        let mut dotenv = vec![];
+        contracts.write(&mut dotenv)?;
+        let mut export = vec![];
+        contracts.write_with_format(&mut export, EnvFormat::ShellExport)?;
+
+        let dotenv = String::from_utf8(dotenv)?;
+        let export = String::from_utf8(export)?;
+        assert_eq!(export.lines().count(), 2);
+        for (line, dotenv_line) in export.lines().zip(dotenv.lines()) {
+            assert_eq!(line.strip_prefix("export "), Some(dotenv_line));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ensure_deployed() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let mut contracts = Contracts::new();
+
+        let addr = contracts
+            .deploy(
+                Contract::PlonkVerifier,
+                PlonkVerifier::deploy_builder(&provider),
+            )
+            .await?;
+        // the cached deployment is reused as long as it's still there
+        let same_addr = contracts
+            .ensure_deployed(
+                Contract::PlonkVerifier,
+                PlonkVerifier::deploy_builder(&provider),
+                &provider,
+            )
+            .await?;
+        assert_eq!(addr, same_addr);
+
+        // wipe the contract from the chain, the cache is stale now
+        provider.anvil_set_code(addr, Bytes::new()).await?;
+        assert!(!is_contract(&provider, addr).await?);
+
+        let new_addr = contracts
+            .ensure_deployed(
+                Contract::PlonkVerifier,
+                PlonkVerifier::deploy_builder(&provider),
+                &provider,
+            )
+            .await?;
+        assert_ne!(addr, new_addr);
+        assert!(is_contract(&provider, new_addr).await?);
+        assert_eq!(contracts.address(Contract::PlonkVerifier), Some(new_addr));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missing_contracts() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let mut contracts = Contracts::new();
+        contracts
+            .deploy(
+                Contract::PlonkVerifier,
+                PlonkVerifier::deploy_builder(&provider),
+            )
+            .await?;
+        // cached, but there is no code at this address
+        contracts
+            .addresses
+            .insert(Contract::FeeContractProxy, Address::random());
+
+        let required = [
+            Contract::LightClientProxy,
+            Contract::PlonkVerifier,
+            Contract::FeeContractProxy,
+            Contract::StakeTableProxy,
+        ];
+        assert_eq!(
+            contracts.missing(&required),
+            vec![Contract::LightClientProxy, Contract::StakeTableProxy]
+        );
+        assert_eq!(
+            contracts.missing_on_chain(&provider, &required).await?,
+            vec![
+                Contract::LightClientProxy,
+                Contract::FeeContractProxy,
+                Contract::StakeTableProxy
+            ]
+        );
+        assert!(contracts.missing(&[]).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_link_libraries() -> Result<()> {
+        let lib_a = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+        let lib_b = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+        let addr_a = Address::random();
+        let addr_b = Address::random();
+        let bytecode = format!("6080{lib_a}00{lib_b}01{lib_a}02");
+
+        let linked = link_libraries(&bytecode, &[(lib_a, addr_a, 2), (lib_b, addr_b, 1)])?;
+        let expected = [
+            &[0x60, 0x80][..],
+            addr_a.as_slice(),
+            &[0x00],
+            addr_b.as_slice(),
+            &[0x01],
+            addr_a.as_slice(),
+            &[0x02],
+        ]
+        .concat();
+        assert_eq!(linked, Bytes::from(expected));
+
+        // a library placeholder that doesn't occur in the bytecode is an error
+        assert!(link_libraries(&bytecode, &[(LIBRARY_PLACEHOLDER_ADDRESS, addr_a, 1)]).is_err());
+        // so is one that occurs an unexpected number of times
+        assert!(link_libraries(&bytecode, &[(lib_a, addr_a, 1)]).is_err());
+        Ok(())
+    }
 
-        let fee_contract = FeeContract::deploy(&provider).await?;
-        let init_data = fee_contract.initialize(deployer).calldata().clone();
-        let proxy = ERC1967Proxy::deploy(&provider, *fee_contract.address(), init_data).await?;
+    #[test]
+    fn test_link_libraries_placeholder_collision() -> Result<()> {
+        // the default placeholder occurs twice as a constant, so the library was bound against a
+        // different placeholder
+        let placeholder = "eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee";
+        let addr = Address::random();
+        let bytecode = format!(
+            "73{LIBRARY_PLACEHOLDER_ADDRESS}73{placeholder}73{LIBRARY_PLACEHOLDER_ADDRESS}"
+        );
 
-        assert!(is_proxy_contract(&provider, *proxy.address()).await?);
-        assert!(!is_proxy_contract(&provider, *fee_contract.address()).await?);
+        let err = link_libraries(&bytecode, &[(LIBRARY_PLACEHOLDER_ADDRESS, addr, 1)])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("found 2 times"), "{err}");
+        assert!(err.contains(&"1".repeat(40)), "{err}");
+
+        let linked = link_libraries(&bytecode, &[(placeholder, addr, 1)])?;
+        let expected = [
+            &[0x73][..],
+            &[0xff; 20],
+            &[0x73],
+            addr.as_slice(),
+            &[0x73],
+            &[0xff; 20],
+        ]
+        .concat();
+        assert_eq!(linked, Bytes::from(expected));
         Ok(())
     }
 
@@ -811,17 +1969,65 @@ mod tests {
         let mut contracts = Contracts::new();
 
         // first test if LightClientMock can be deployed
-        let mock_lc_addr = deploy_light_client_contract(&provider, &mut contracts, true).await?;
+        let mock_lc_addr =
+            deploy_light_client_contract(&provider, &mut contracts, true, None).await?;
         let pv_addr = contracts.address(Contract::PlonkVerifier).unwrap();
 
         // then deploy the actual LightClient
-        let lc_addr = deploy_light_client_contract(&provider, &mut contracts, false).await?;
+        let lc_addr = deploy_light_client_contract(&provider, &mut contracts, false, None).await?;
         assert_ne!(mock_lc_addr, lc_addr);
         // check that we didn't redeploy PlonkVerifier again, instead use existing ones
         assert_eq!(contracts.address(Contract::PlonkVerifier).unwrap(), pv_addr);
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_deploy_light_client_with_plonk_verifier() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let mut contracts = Contracts::new();
+
+        // deploy the library ourselves, outside of the `contracts` cache
+        let pv_addr = *PlonkVerifier::deploy(&provider).await?.address();
+        let lc_addr =
+            deploy_light_client_contract(&provider, &mut contracts, false, Some(pv_addr)).await?;
+
+        // the light client is linked against our library, and no other library was deployed
+        let code = provider.get_code_at(lc_addr).await?;
+        assert!(code.windows(20).any(|w| w == pv_addr.as_slice()));
+        assert!(contracts.address(Contract::PlonkVerifier).is_none());
+
+        // linking against an address without code fails
+        assert!(deploy_light_client_contract(
+            &provider,
+            &mut contracts,
+            true,
+            Some(Address::random()),
+        )
+        .await
+        .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deploy_light_client_library_placeholder() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let pv_addr = *PlonkVerifier::deploy(&provider).await?.address();
+
+        // the bindings weren't generated with this placeholder, so there is nothing to link
+        let mut contracts = Contracts::new().with_library_placeholder("e".repeat(40));
+        let err = deploy_light_client_contract(&provider, &mut contracts, false, Some(pv_addr))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains(&"e".repeat(40)), "{err:#}");
+        assert!(contracts.address(Contract::LightClient).is_none());
+
+        // the placeholder the bindings were generated with links as usual
+        let mut contracts = Contracts::new().with_library_placeholder(LIBRARY_PLACEHOLDER_ADDRESS);
+        deploy_light_client_contract(&provider, &mut contracts, false, Some(pv_addr)).await?;
+        assert!(contracts.address(Contract::LightClient).is_some());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_deploy_mock_light_client_proxy() -> Result<()> {
         let provider = ProviderBuilder::new().on_anvil_with_wallet();
@@ -916,6 +2122,7 @@ mod tests {
             Contract::LightClientProxy,
             lc_proxy_addr,
             multisig,
+            0,
         )
         .await?;
         assert_eq!(lc.owner().call().await?._0, multisig);
@@ -923,6 +2130,226 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_deploy_light_client_proxy_prover_unset() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let mut contracts = Contracts::new();
+
+        // the deployer isn't the owner, so it's not allowed to set the prover
+        let admin = Address::random();
+        let prover = Address::random();
+        let err = deploy_light_client_proxy(
+            &provider,
+            &mut contracts,
+            false,
+            LightClientStateSol::dummy_genesis(),
+            StakeTableStateSol::dummy_genesis(),
+            admin,
+            Some(prover),
+        )
+        .await
+        .unwrap_err();
+
+        let lc_proxy_addr = contracts.address(Contract::LightClientProxy).unwrap();
+        assert!(
+            err.to_string().contains(&format!(
+                "LightClientProxy deployed at {lc_proxy_addr:#x}, but permissioned prover \
+                 {prover:#x} is unset"
+            )),
+            "{err:#}"
+        );
+        let lc = LightClient::new(lc_proxy_addr, &provider);
+        assert_eq!(lc.owner().call().await?._0, admin);
+        assert_eq!(lc.permissionedProver().call().await?._0, Address::ZERO);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_state_history_retention() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let mut contracts = Contracts::new();
+        let admin = provider.get_accounts().await?[0];
+
+        // `deploy_light_client_proxy` already uses the maximum retention, start lower instead
+        let initial_retention = 86400;
+        let impl_addr =
+            deploy_light_client_contract(&provider, &mut contracts, false, None).await?;
+        let init_data = LightClient::new(impl_addr, &provider)
+            .initialize(
+                LightClientStateSol::dummy_genesis(),
+                StakeTableStateSol::dummy_genesis(),
+                initial_retention,
+                admin,
+            )
+            .calldata()
+            .to_owned();
+        let lc_proxy_addr = deploy_upgradeable(
+            &provider,
+            &mut contracts,
+            Contract::LightClient,
+            Contract::LightClientProxy,
+            impl_addr,
+            init_data,
+        )
+        .await?;
+
+        let new_retention = 2 * initial_retention;
+        let receipt = set_state_history_retention(
+            &provider,
+            lc_proxy_addr,
+            new_retention,
+            contracts.confirmations(),
+        )
+        .await?;
+        assert!(receipt.status());
+        let lc = LightClient::new(lc_proxy_addr, &provider);
+        assert_eq!(
+            lc.stateHistoryRetentionPeriod().call().await?._0,
+            new_retention
+        );
+
+        // exceeding the maximum is rejected before sending
+        assert!(set_state_history_retention(
+            &provider,
+            lc_proxy_addr,
+            MAX_HISTORY_RETENTION_SECONDS + 1,
+            contracts.confirmations(),
+        )
+        .await
+        .is_err());
+        // the contract only allows increasing the retention period
+        assert!(set_state_history_retention(
+            &provider,
+            lc_proxy_addr,
+            initial_retention,
+            contracts.confirmations(),
+        )
+        .await
+        .is_err());
+        assert_eq!(
+            lc.stateHistoryRetentionPeriod().call().await?._0,
+            new_retention
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_permissioned_prover_mode() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let mut contracts = Contracts::new();
+        let admin = provider.get_accounts().await?[0];
+        let lc_proxy_addr = deploy_light_client_proxy(
+            &provider,
+            &mut contracts,
+            false,
+            LightClientStateSol::dummy_genesis(),
+            StakeTableStateSol::dummy_genesis(),
+            admin,
+            None,
+        )
+        .await?;
+        let lc = LightClient::new(lc_proxy_addr, &provider);
+        assert!(!lc.isPermissionedProverEnabled().call().await?._0);
+
+        let prover = Address::random();
+        let receipt =
+            set_permissioned_prover(&provider, lc_proxy_addr, prover, contracts.confirmations())
+                .await?;
+        assert!(receipt.status());
+        assert!(lc.isPermissionedProverEnabled().call().await?._0);
+        assert_eq!(lc.permissionedProver().call().await?._0, prover);
+        // setting the same prover again is rejected by the contract
+        assert!(set_permissioned_prover(
+            &provider,
+            lc_proxy_addr,
+            prover,
+            contracts.confirmations()
+        )
+        .await
+        .is_err());
+
+        let receipt =
+            disable_permissioned_prover(&provider, lc_proxy_addr, contracts.confirmations())
+                .await?;
+        assert!(receipt.status());
+        assert!(!lc.isPermissionedProverEnabled().call().await?._0);
+        assert_eq!(lc.permissionedProver().call().await?._0, Address::ZERO);
+        // disabling it twice is rejected as well
+        assert!(
+            disable_permissioned_prover(&provider, lc_proxy_addr, contracts.confirmations())
+                .await
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deploy_upgradeable() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let mut contracts = Contracts::new();
+        let owner = Address::random();
+
+        let fee_addr = contracts
+            .deploy(
+                Contract::FeeContract,
+                FeeContract::deploy_builder(&provider),
+            )
+            .await?;
+        let init_data = FeeContract::new(fee_addr, &provider)
+            .initialize(owner)
+            .calldata()
+            .to_owned();
+        let proxy_addr = deploy_upgradeable(
+            &provider,
+            &mut contracts,
+            Contract::FeeContract,
+            Contract::FeeContractProxy,
+            fee_addr,
+            init_data.clone(),
+        )
+        .await?;
+        assert_eq!(
+            contracts.address(Contract::FeeContractProxy),
+            Some(proxy_addr)
+        );
+        assert!(is_proxy_contract(&provider, proxy_addr).await?);
+        let fee_proxy = FeeContract::new(proxy_addr, &provider);
+        assert_eq!(fee_proxy.owner().call().await?._0, owner);
+
+        // a second call reuses the cached proxy deployment
+        let cached_addr = deploy_upgradeable(
+            &provider,
+            &mut contracts,
+            Contract::FeeContract,
+            Contract::FeeContractProxy,
+            fee_addr,
+            init_data,
+        )
+        .await?;
+        assert_eq!(cached_addr, proxy_addr);
+
+        // a cached proxy pointing to another implementation is rejected
+        let other_fee_addr = deploy_and_confirm(FeeContract::deploy_builder(&provider), 0).await?;
+        let init_data = FeeContract::new(other_fee_addr, &provider)
+            .initialize(owner)
+            .calldata()
+            .to_owned();
+        let err = deploy_upgradeable(
+            &provider,
+            &mut contracts,
+            Contract::FeeContract,
+            Contract::FeeContractProxy,
+            other_fee_addr,
+            init_data,
+        )
+        .await
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains(&format!("points to {fee_addr:#x}")));
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_deploy_fee_contract_proxy() -> Result<()> {
         let provider = ProviderBuilder::new().on_anvil_with_wallet();
@@ -949,6 +2376,7 @@ mod tests {
             Contract::FeeContractProxy,
             fee_proxy_addr,
             multisig,
+            0,
         )
         .await?;
         assert_eq!(fee.owner().call().await?._0, multisig);
@@ -956,6 +2384,46 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_upgrade_light_client_v2_with_plonk_verifier() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let mut contracts = Contracts::new();
+        let admin = provider.get_accounts().await?[0];
+        deploy_light_client_proxy(
+            &provider,
+            &mut contracts,
+            false,
+            LightClientStateSol::dummy_genesis(),
+            StakeTableStateSol::dummy_genesis(),
+            admin,
+            None,
+        )
+        .await?;
+
+        // linking against an address without code fails
+        assert!(upgrade_light_client_v2(
+            &provider,
+            &mut contracts,
+            false,
+            10,
+            22,
+            Some(Address::random())
+        )
+        .await
+        .is_err());
+
+        // deploy the library ourselves, outside of the `contracts` cache
+        let pv2_addr = *PlonkVerifierV2::deploy(&provider).await?.address();
+        upgrade_light_client_v2(&provider, &mut contracts, false, 10, 22, Some(pv2_addr)).await?;
+
+        // LCV2 is linked against our library, and no other library was deployed
+        let lcv2_addr = contracts.address(Contract::LightClientV2).unwrap();
+        let code = provider.get_code_at(lcv2_addr).await?;
+        assert!(code.windows(20).any(|w| w == pv2_addr.as_slice()));
+        assert!(contracts.address(Contract::PlonkVerifierV2).is_none());
+        Ok(())
+    }
+
     async fn test_upgrade_light_client_to_v2_helper(is_mock: bool) -> Result<()> {
         let provider = ProviderBuilder::new().on_anvil_with_wallet();
         let mut contracts = Contracts::new();
@@ -985,6 +2453,10 @@ mod tests {
             .call()
             .await?
             ._0;
+        assert_eq!(
+            light_client_version(&provider, lc_proxy_addr).await?,
+            (1, 0, 0)
+        );
 
         // then upgrade to v2
         upgrade_light_client_v2(
@@ -993,6 +2465,7 @@ mod tests {
             is_mock,
             blocks_per_epoch,
             epoch_start_block,
+            None,
         )
         .await?;
 
@@ -1009,7 +2482,10 @@ mod tests {
             genesis_stake.abi_encode_params(),
             next_stake.abi_encode_params()
         );
-        assert_eq!(lc.getVersion().call().await?.majorVersion, 2);
+        assert_eq!(
+            light_client_version(&provider, lc_proxy_addr).await?,
+            (2, 0, 0)
+        );
         assert_eq!(lc.blocksPerEpoch().call().await?._0, blocks_per_epoch);
         assert_eq!(lc.epochStartBlock().call().await?._0, epoch_start_block);
         assert_eq!(
@@ -1046,6 +2522,70 @@ mod tests {
         test_upgrade_light_client_to_v2_helper(true).await
     }
 
+    /// Upgrade an existing V1 light client on a fork of a live network.
+    ///
+    /// Only runs if `ESPRESSO_DEPLOYER_FORK_URL` and `ESPRESSO_DEPLOYER_FORK_LIGHT_CLIENT_PROXY`
+    /// are set, since it needs an RPC endpoint of a network with a V1 deployment.
+    #[tokio::test]
+    async fn test_upgrade_light_client_to_v2_on_fork() -> Result<()> {
+        let (Ok(fork_url), Ok(lc_proxy_addr)) = (
+            std::env::var("ESPRESSO_DEPLOYER_FORK_URL"),
+            std::env::var("ESPRESSO_DEPLOYER_FORK_LIGHT_CLIENT_PROXY"),
+        ) else {
+            tracing::warn!("no network to fork configured, skipping");
+            return Ok(());
+        };
+        let lc_proxy_addr = Address::from_str(&lc_proxy_addr)?;
+
+        let fork = Anvil::new().fork(fork_url).spawn();
+        let key = B256::from_slice(&fork.keys()[0].to_bytes());
+        let provider = build_provider_from_key(key, fork.endpoint_url())?;
+        let admin = provider.default_signer_address();
+
+        let lc = LightClient::new(lc_proxy_addr, &provider);
+        assert_eq!(
+            light_client_version(&provider, lc_proxy_addr).await?,
+            (1, 0, 0)
+        );
+        let finalized_state: LightClientStateSol = lc.finalizedState().call().await?.into();
+        let state_history_retention_period = lc.stateHistoryRetentionPeriod().call().await?._0;
+
+        // hand the proxy over to a local account, so that the upgrade can be signed
+        let owner = lc.owner().call().await?._0;
+        let impersonated = build_impersonated_provider(fork.endpoint_url(), owner).await?;
+        impersonated
+            .anvil_set_balance(owner, parse_ether("1")?)
+            .await?;
+        let receipt = LightClient::new(lc_proxy_addr, &impersonated)
+            .transferOwnership(admin)
+            .from(owner)
+            .send()
+            .await?
+            .get_receipt()
+            .await?;
+        assert!(receipt.inner.is_success());
+
+        let mut contracts =
+            Contracts::new().with_address(Contract::LightClientProxy, lc_proxy_addr);
+        upgrade_light_client_v2(&provider, &mut contracts, false, 100, 1000, None).await?;
+
+        let lc = LightClientV2::new(lc_proxy_addr, &provider);
+        assert_eq!(
+            light_client_version(&provider, lc_proxy_addr).await?,
+            (2, 0, 0)
+        );
+        let upgraded_state: LightClientStateSol = lc.finalizedState().call().await?.into();
+        assert_eq!(
+            finalized_state.abi_encode_params(),
+            upgraded_state.abi_encode_params()
+        );
+        assert_eq!(
+            lc.stateHistoryRetentionPeriod().call().await?._0,
+            state_history_retention_period
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_deploy_token_proxy() -> Result<()> {
         let provider = ProviderBuilder::new().on_anvil_with_wallet();
@@ -1079,7 +2619,7 @@ mod tests {
             deploy_token_proxy(&provider, &mut contracts, token_owner, init_recipient).await?;
 
         // deploy light client
-        let lc_addr = deploy_light_client_contract(&provider, &mut contracts, false).await?;
+        let lc_addr = deploy_light_client_contract(&provider, &mut contracts, false, None).await?;
 
         // deploy stake table
         let exit_escrow_period = U256::from(1000);
@@ -1105,6 +2645,118 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_smoke_test_stake_table() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let mut contracts = Contracts::new();
+
+        let admin = provider.get_accounts().await?[0];
+        let token_addr = deploy_token_proxy(&provider, &mut contracts, admin, admin).await?;
+        let lc_addr = deploy_light_client_contract(&provider, &mut contracts, false, None).await?;
+        let st_addr = deploy_stake_table_proxy(
+            &provider,
+            &mut contracts,
+            token_addr,
+            lc_addr,
+            U256::from(1000),
+            admin,
+        )
+        .await?;
+
+        smoke_test_stake_table(&provider, st_addr, token_addr).await?;
+        // the throwaway validator is gone again
+        let stake_table = StakeTable::new(st_addr, &provider);
+        assert_eq!(
+            stake_table
+                .ValidatorExit_filter()
+                .from_block(0)
+                .query()
+                .await?
+                .len(),
+            1
+        );
+
+        // a stake table wired to the wrong token fails
+        assert!(
+            smoke_test_stake_table(&provider, st_addr, Address::random())
+                .await
+                .is_err()
+        );
+        Ok(())
+    }
+
+    async fn deploy_suite_for_testing(
+        provider: impl Provider,
+        contracts: &mut Contracts,
+        admin: Address,
+    ) -> Result<()> {
+        deploy_fee_contract_proxy(&provider, contracts, admin).await?;
+        let token_addr = deploy_token_proxy(&provider, contracts, admin, admin).await?;
+        let lc_addr = deploy_light_client_proxy(
+            &provider,
+            contracts,
+            false,
+            LightClientStateSol::dummy_genesis(),
+            StakeTableStateSol::dummy_genesis(),
+            admin,
+            None,
+        )
+        .await?;
+        upgrade_light_client_v2(&provider, contracts, false, 10, 22, None).await?;
+        deploy_stake_table_proxy(
+            &provider,
+            contracts,
+            token_addr,
+            lc_addr,
+            U256::from(1000),
+            admin,
+        )
+        .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_verify_deployment() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let mut contracts = Contracts::new();
+        let admin = provider.get_accounts().await?[0];
+
+        // an empty deployment has nothing to check
+        verify_deployment(&provider, &contracts, admin).await?;
+
+        deploy_suite_for_testing(&provider, &mut contracts, admin).await?;
+        verify_deployment(&provider, &contracts, admin).await?;
+
+        // the verifier doesn't expect any other owner
+        assert!(verify_deployment(&provider, &contracts, Address::random())
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_verify_deployment_tampered_owner() -> Result<()> {
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let mut contracts = Contracts::new();
+        let admin = provider.get_accounts().await?[0];
+        deploy_suite_for_testing(&provider, &mut contracts, admin).await?;
+
+        let fee_proxy_addr = contracts.address(Contract::FeeContractProxy).unwrap();
+        transfer_ownership(
+            &provider,
+            Contract::FeeContractProxy,
+            fee_proxy_addr,
+            Address::random(),
+            0,
+        )
+        .await?;
+        let err = verify_deployment(&provider, &contracts, admin)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("FeeContractProxy"), "{err}");
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_deploy_timelock() -> Result<()> {
         let provider = ProviderBuilder::new().on_anvil_with_wallet();
@@ -5,10 +5,7 @@ use std::time::Duration;
 use alloy::{primitives::U256, transports::http::reqwest::Url};
 use anyhow::{Context, Result};
 use espresso_types::{config::PublicNetworkConfig, SeqTypes};
-use hotshot_contract_adapter::{
-    field_to_u256,
-    sol_types::{LightClientStateSol, StakeTableStateSol},
-};
+use hotshot_contract_adapter::sol_types::{LightClientStateSol, StakeTableStateSol};
 use hotshot_types::{
     stake_table::HSStakeTable,
     traits::node_implementation::{ConsensusTime, NodeType},
@@ -77,19 +74,13 @@ pub fn light_client_genesis_from_stake_table(
     st: &HSStakeTable<SeqTypes>,
     stake_table_capacity: usize,
 ) -> anyhow::Result<(LightClientStateSol, StakeTableStateSol)> {
-    let st_state = st.commitment(stake_table_capacity)?;
     Ok((
         LightClientStateSol {
             viewNum: 0,
             blockHeight: 0,
             blockCommRoot: U256::from(0u32),
         },
-        StakeTableStateSol {
-            blsKeyComm: field_to_u256(st_state.bls_key_comm),
-            schnorrKeyComm: field_to_u256(st_state.schnorr_key_comm),
-            amountComm: field_to_u256(st_state.amount_comm),
-            threshold: field_to_u256(st_state.threshold),
-        },
+        StakeTableStateSol::with_threshold_from_stake(st, stake_table_capacity)?,
     ))
 }
 
@@ -1,9 +1,9 @@
 use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
 
-/// Initializes logging
-pub fn initialize_logging() {
-    // Parse the `RUST_LOG_SPAN_EVENTS` environment variable
-    let span_event_filter = match std::env::var("RUST_LOG_SPAN_EVENTS") {
+/// Parses the span events to log from the comma-separated `RUST_LOG_SPAN_EVENTS` environment
+/// variable
+pub fn span_events_from_env() -> FmtSpan {
+    match std::env::var("RUST_LOG_SPAN_EVENTS") {
         Ok(val) => val
             .split(',')
             .map(|s| match s.trim() {
@@ -17,7 +17,12 @@ pub fn initialize_logging() {
             })
             .fold(FmtSpan::NONE, |acc, x| acc | x),
         Err(_) => FmtSpan::NONE,
-    };
+    }
+}
+
+/// Initializes logging
+pub fn initialize_logging() {
+    let span_event_filter = span_events_from_env();
 
     // Conditionally initialize in `json` mode
     if std::env::var("RUST_LOG_FORMAT") == Ok("json".to_string()) {
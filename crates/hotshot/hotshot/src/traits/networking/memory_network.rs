@@ -20,7 +20,7 @@ use std::{
 
 use async_lock::{Mutex, RwLock};
 use async_trait::async_trait;
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
 use hotshot_types::{
     boxed_sync,
     traits::{
@@ -53,6 +53,10 @@ pub struct MasterMap<K: SignatureKey> {
 
     /// The list of `MemoryNetwork`s aggregated by topic
     subscribed_map: DashMap<Topic, Vec<(K, MemoryNetwork<K>)>>,
+
+    /// Pairs of (sender, recipient) between which all messages are dropped
+    #[debug(skip)]
+    partitioned: DashSet<(K, K)>,
 }
 
 impl<K: SignatureKey> MasterMap<K> {
@@ -62,13 +66,40 @@ impl<K: SignatureKey> MasterMap<K> {
         Arc::new(MasterMap {
             map: DashMap::new(),
             subscribed_map: DashMap::new(),
+            partitioned: DashSet::new(),
         })
     }
+
+    /// Drop all messages between the nodes in `group_a` and the nodes in `group_b`, in both
+    /// directions, until [`heal`](Self::heal) is called.
+    ///
+    /// Messages within each group are still delivered.
+    pub fn partition(&self, group_a: &[K], group_b: &[K]) {
+        for a in group_a {
+            for b in group_b {
+                self.partitioned.insert((a.clone(), b.clone()));
+                self.partitioned.insert((b.clone(), a.clone()));
+            }
+        }
+    }
+
+    /// Remove all partitions, so that every node can reach every other node again.
+    pub fn heal(&self) {
+        self.partitioned.clear();
+    }
+
+    /// Whether messages from `sender` to `recipient` are currently dropped.
+    fn is_partitioned(&self, sender: &K, recipient: &K) -> bool {
+        self.partitioned
+            .contains(&(sender.clone(), recipient.clone()))
+    }
 }
 
 /// Internal state for a `MemoryNetwork` instance
 #[derive(Debug)]
 struct MemoryNetworkInner<K: SignatureKey> {
+    /// The public key of the node owning this network
+    pub_key: K,
     /// Input for messages
     input: RwLock<Option<Sender<Vec<u8>>>>,
     /// Output for messages
@@ -140,6 +171,7 @@ impl<K: SignatureKey> MemoryNetwork<K> {
         trace!("Task spawned, creating MemoryNetwork");
         let mn = MemoryNetwork {
             inner: Arc::new(MemoryNetworkInner {
+                pub_key: pub_key.clone(),
                 input: RwLock::new(Some(input)),
                 output: Mutex::new(output),
                 master_map: Arc::clone(master_map),
@@ -260,6 +292,14 @@ impl<K: SignatureKey + 'static> ConnectedNetwork<K> for MemoryNetwork<K> {
         {
             // TODO delay/drop etc here
             let (key, node) = node;
+            if self
+                .inner
+                .master_map
+                .is_partitioned(&self.inner.pub_key, key)
+            {
+                trace!(?key, "Dropping message to partitioned node");
+                continue;
+            }
             trace!(?key, "Sending message to node");
             if let Some(ref config) = &self.inner.reliability_config {
                 {
@@ -314,6 +354,14 @@ impl<K: SignatureKey + 'static> ConnectedNetwork<K> for MemoryNetwork<K> {
             }
             // TODO delay/drop etc here
             let (key, node) = node;
+            if self
+                .inner
+                .master_map
+                .is_partitioned(&self.inner.pub_key, key)
+            {
+                trace!(?key, "Dropping message to partitioned node");
+                continue;
+            }
             trace!(?key, "Sending message to node");
             if let Some(ref config) = &self.inner.reliability_config {
                 {
@@ -353,6 +401,15 @@ impl<K: SignatureKey + 'static> ConnectedNetwork<K> for MemoryNetwork<K> {
         trace!("Message bincoded, finding recipient");
         if let Some(node) = self.inner.master_map.map.get(&recipient) {
             let node = node.value().clone();
+            if self
+                .inner
+                .master_map
+                .is_partitioned(&self.inner.pub_key, &recipient)
+            {
+                // A partition looks like a lost message to the sender, not a failed send.
+                trace!(?recipient, "Dropping message to partitioned node");
+                return Ok(());
+            }
             if let Some(ref config) = &self.inner.reliability_config {
                 {
                     let fut = config.chaos_send_msg(
@@ -151,7 +151,7 @@ pub(crate) async fn fetch_proposal<TYPES: NodeType, V: Versions>(
     Ok((leaf, view))
 }
 pub async fn handle_drb_result<TYPES: NodeType, I: NodeImplementation<TYPES>>(
-    membership: &Arc<RwLock<TYPES::Membership>>,
+    membership: &EpochMembershipCoordinator<TYPES>,
     epoch: TYPES::Epoch,
     storage: &I::Storage,
     consensus: &OuterConsensus<TYPES>,
@@ -165,13 +165,13 @@ pub async fn handle_drb_result<TYPES: NodeType, I: NodeImplementation<TYPES>>(
         tracing::error!("Failed to store drb result for epoch {epoch}: {e}");
     }
 
-    membership.write().await.add_drb_result(epoch, drb_result)
+    membership.add_drb_result(epoch, drb_result).await
 }
 /// Start the DRB computation task for the next epoch.
 fn start_drb_task<TYPES: NodeType, I: NodeImplementation<TYPES>>(
     seed: DrbSeedInput,
     epoch: TYPES::Epoch,
-    membership: &Arc<RwLock<TYPES::Membership>>,
+    membership: &EpochMembershipCoordinator<TYPES>,
     storage: &I::Storage,
     consensus: &OuterConsensus<TYPES>,
 ) {
@@ -193,7 +193,7 @@ fn start_drb_task<TYPES: NodeType, I: NodeImplementation<TYPES>>(
 async fn decide_epoch_root<TYPES: NodeType, I: NodeImplementation<TYPES>>(
     decided_leaf: &Leaf2<TYPES>,
     epoch_height: u64,
-    membership: &Arc<RwLock<TYPES::Membership>>,
+    membership: &EpochMembershipCoordinator<TYPES>,
     storage: &I::Storage,
     consensus: &OuterConsensus<TYPES>,
 ) {
@@ -214,17 +214,10 @@ async fn decide_epoch_root<TYPES: NodeType, I: NodeImplementation<TYPES>>(
         tracing::info!("Time taken to store epoch root: {:?}", start.elapsed());
 
         start = Instant::now();
-        let write_callback = {
-            tracing::debug!("Calling add_epoch_root for epoch {next_epoch_number}");
-            let membership_reader = membership.read().await;
-            membership_reader
-                .add_epoch_root(next_epoch_number, decided_leaf.block_header().clone())
-                .await
-        };
-        if let Some(write_callback) = write_callback {
-            let mut membership_writer = membership.write().await;
-            write_callback(&mut *membership_writer);
-        }
+        tracing::debug!("Calling add_epoch_root for epoch {next_epoch_number}");
+        membership
+            .add_epoch_root(next_epoch_number, decided_leaf.block_header().clone())
+            .await;
         tracing::info!("Time taken to add epoch root: {:?}", start.elapsed());
 
         let mut consensus_writer = consensus.write().await;
@@ -396,7 +389,7 @@ pub async fn decide_from_proposal_2<TYPES: NodeType, I: NodeImplementation<TYPES
             decide_epoch_root::<TYPES, I>(
                 &decided_leaf_info.leaf,
                 epoch_height,
-                membership.membership(),
+                membership,
                 storage,
                 &consensus,
             )
@@ -445,7 +438,7 @@ pub async fn decide_from_proposal<TYPES: NodeType, I: NodeImplementation<TYPES>,
     existing_upgrade_cert: Arc<RwLock<Option<UpgradeCertificate<TYPES>>>>,
     public_key: &TYPES::SignatureKey,
     with_epochs: bool,
-    membership: &Arc<RwLock<TYPES::Membership>>,
+    membership: &EpochMembershipCoordinator<TYPES>,
     storage: &I::Storage,
     epoch_height: u64,
 ) -> LeafChainTraversalOutcome<TYPES> {
@@ -144,7 +144,7 @@ async fn store_drb_result<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Vers
             // We don't need to check value existence and consistency because it should be
             // impossible to decide on a block with different DRB results.
             handle_drb_result::<TYPES, I>(
-                task_state.membership.membership(),
+                &task_state.membership,
                 current_epoch_number + 1,
                 &task_state.storage,
                 &task_state.consensus,
@@ -213,7 +213,7 @@ pub(crate) async fn handle_quorum_proposal_validated<
             Arc::clone(&task_state.upgrade_lock.decided_upgrade_certificate),
             &task_state.public_key,
             version >= V::Epochs::VERSION,
-            task_state.membership.membership(),
+            &task_state.membership,
             &task_state.storage,
             task_state.epoch_height,
         )
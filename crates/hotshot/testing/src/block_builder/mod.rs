@@ -121,6 +121,66 @@ pub fn run_builder_source<TYPES, Source>(
     });
 }
 
+/// Like [`run_builder_source`], but serves the builder on an already bound `listener` instead of
+/// binding one from a URL.
+///
+/// The listener stays bound while the builder is down, so nobody else can take its port before
+/// the builder is brought back up.
+///
+/// # Panics
+/// If constructing and launching the builder fails for any reason
+pub fn run_builder_source_on_listener<TYPES, Source>(
+    listener: std::net::TcpListener,
+    mut change_receiver: Receiver<BuilderChange>,
+    source: Source,
+) where
+    TYPES: NodeType,
+    <TYPES as NodeType>::InstanceState: Default,
+    Source: Clone + Send + Sync + tide_disco::method::ReadState + 'static,
+    <Source as ReadState>::State: Sync
+        + Send
+        + v0_1::data_source::BuilderDataSource<TYPES>
+        + v0_99::data_source::BuilderDataSource<TYPES>,
+{
+    spawn(async move {
+        let start_builder = |listener: &std::net::TcpListener, source: Source| -> _ {
+            let listener = listener
+                .try_clone()
+                .expect("Failed to clone the builder listener");
+            let builder_api_0_1 = hotshot_builder_api::v0_1::builder::define_api::<Source, TYPES>(
+                &Options::default(),
+            )
+            .expect("Failed to construct the builder API");
+            let builder_api_0_3 = hotshot_builder_api::v0_99::builder::define_api::<Source, TYPES>(
+                &Options::default(),
+            )
+            .expect("Failed to construct the builder API");
+            let mut app: App<Source, Error> = App::with_state(source);
+            app.register_module(LEGACY_BUILDER_MODULE, builder_api_0_1)
+                .expect("Failed to register the builder API 0.1")
+                .register_module(MARKETPLACE_BUILDER_MODULE, builder_api_0_3)
+                .expect("Failed to register the builder API 0.3");
+            spawn(app.serve(listener, hotshot_builder_api::v0_1::Version::instance()))
+        };
+
+        let mut handle = Some(start_builder(&listener, source.clone()));
+
+        while let Ok(event) = change_receiver.recv().await {
+            match event {
+                BuilderChange::Up if handle.is_none() => {
+                    handle = Some(start_builder(&listener, source.clone()));
+                },
+                BuilderChange::Down => {
+                    if let Some(handle) = handle.take() {
+                        handle.abort();
+                    }
+                },
+                _ => {},
+            }
+        }
+    });
+}
+
 /// Construct a tide disco app that mocks the builder API 0.1.
 ///
 /// # Panics
@@ -48,7 +48,10 @@ use tide_disco::{method::ReadState, App, Url};
 use tokio::spawn;
 use vbs::version::StaticVersionType;
 
-use super::{build_block, run_builder_source, BlockEntry, BuilderTask, TestBuilderImplementation};
+use super::{
+    build_block, run_builder_source, run_builder_source_on_listener, BlockEntry, BuilderTask,
+    TestBuilderImplementation,
+};
 use crate::test_builder::BuilderChange;
 
 pub struct SimpleBuilderImplementation;
@@ -86,6 +89,23 @@ impl SimpleBuilderImplementation {
 
         (source, task)
     }
+
+    /// Like [`TestBuilderImplementation::start`], but serves the builder on an already bound
+    /// `listener`, so callers that need a free port can bind port 0 without racing other binds.
+    pub async fn start_on_listener<TYPES: NodeType>(
+        num_nodes: usize,
+        listener: std::net::TcpListener,
+        changes: HashMap<u64, BuilderChange>,
+    ) -> Box<dyn BuilderTask<TYPES>>
+    where
+        <TYPES as NodeType>::InstanceState: Default,
+    {
+        let (change_sender, change_receiver) = broadcast(128);
+        let (source, task) = Self::create(num_nodes, changes, change_sender).await;
+        run_builder_source_on_listener(listener, change_receiver, source);
+
+        Box::new(task)
+    }
 }
 
 #[async_trait]
@@ -1,6 +1,8 @@
 use std::{
     collections::{BTreeSet, HashMap},
+    pin::pin,
     sync::Arc,
+    time::Duration,
 };
 
 use alloy::primitives::U256;
@@ -10,6 +12,7 @@ use hotshot_utils::{
     anytrace::{self, Error, Level, Result, Wrap, DEFAULT_LOG_LEVEL},
     ensure, line_info, log, warn,
 };
+use tokio::sync::Notify;
 
 use crate::{
     data::Leaf2,
@@ -38,6 +41,10 @@ pub struct EpochMembershipCoordinator<TYPES: NodeType> {
     /// wait for the actual catchup and allert future callers when it's done
     catchup_map: Arc<Mutex<EpochMap<TYPES>>>,
 
+    /// Signalled whenever the membership is updated through the coordinator, so callers of
+    /// [`Self::wait_for_epoch`] can check for their stake table again
+    membership_updated: Arc<Notify>,
+
     /// Callback function to store a drb result when one is calculated during catchup
     storage_add_drb_result_fn: Option<StorageAddDrbResultFn<TYPES>>,
 
@@ -50,6 +57,7 @@ impl<TYPES: NodeType> Clone for EpochMembershipCoordinator<TYPES> {
         Self {
             membership: Arc::clone(&self.membership),
             catchup_map: Arc::clone(&self.catchup_map),
+            membership_updated: Arc::clone(&self.membership_updated),
             storage_add_drb_result_fn: self.storage_add_drb_result_fn.clone(),
             epoch_height: self.epoch_height,
         }
@@ -72,6 +80,7 @@ where
         Self {
             membership,
             catchup_map: Arc::default(),
+            membership_updated: Arc::default(),
             storage_add_drb_result_fn,
             epoch_height,
         }
@@ -150,6 +159,69 @@ where
         ))
     }
 
+    /// Wait until the stake table for `epoch` is available and return the membership for it.
+    ///
+    /// Unlike [`Self::stake_table_for_epoch`], this does not start a catchup, it resolves once the
+    /// stake table has been added through this coordinator (see [`Self::update_membership`]).
+    pub async fn wait_for_epoch(&self, epoch: TYPES::Epoch) -> EpochMembership<TYPES> {
+        loop {
+            // Register for the next update before checking, so an update that lands in between
+            // is not missed
+            let mut updated = pin!(self.membership_updated.notified());
+            updated.as_mut().enable();
+
+            if self.membership.read().await.has_stake_table(epoch) {
+                return EpochMembership {
+                    epoch: Some(epoch),
+                    coordinator: self.clone(),
+                };
+            }
+            updated.await;
+        }
+    }
+
+    /// Like [`Self::wait_for_epoch`], but fails if the stake table for `epoch` is not available
+    /// within `timeout`.
+    pub async fn wait_for_epoch_timeout(
+        &self,
+        epoch: TYPES::Epoch,
+        timeout: Duration,
+    ) -> Result<EpochMembership<TYPES>> {
+        tokio::time::timeout(timeout, self.wait_for_epoch(epoch))
+            .await
+            .map_err(|_| {
+                warn!(
+                    "Stake table for Epoch {:?} not available after {:?}",
+                    epoch, timeout
+                )
+            })
+    }
+
+    /// Apply `update` to the membership and wake up any callers of [`Self::wait_for_epoch`].
+    pub async fn update_membership(&self, update: impl FnOnce(&mut TYPES::Membership)) {
+        update(&mut *self.membership.write().await);
+        self.membership_updated.notify_waiters();
+    }
+
+    /// Add the stake table for `epoch` from its epoch root `block_header`, if it is not already
+    /// known.
+    pub async fn add_epoch_root(&self, epoch: TYPES::Epoch, block_header: TYPES::BlockHeader) {
+        let updater = {
+            let membership_read = self.membership.read().await;
+            membership_read.add_epoch_root(epoch, block_header).await
+        };
+
+        if let Some(updater) = updater {
+            self.update_membership(updater).await;
+        }
+    }
+
+    /// Add the DRB result for `epoch` to the membership.
+    pub async fn add_drb_result(&self, epoch: TYPES::Epoch, drb_result: DrbResult) {
+        self.update_membership(|membership| membership.add_drb_result(epoch, drb_result))
+            .await;
+    }
+
     /// Catches the membership up to the epoch passed as an argument.  
     /// To do this try to get the stake table for the epoch containing this epoch's root
     /// if the root does not exist recursively catchup until you've found it
@@ -184,17 +256,8 @@ where
             anytrace::bail!("get epoch root failed for epoch {:?}", root_epoch);
         };
 
-        let add_epoch_root_updater = {
-            let membership_read = self.membership.read().await;
-            membership_read
-                .add_epoch_root(epoch, root_leaf.block_header().clone())
-                .await
-        };
-
-        if let Some(updater) = add_epoch_root_updater {
-            let mut membership_write = self.membership.write().await;
-            updater(&mut *(membership_write));
-        };
+        self.add_epoch_root(epoch, root_leaf.block_header().clone())
+            .await;
 
         let drb_membership = match root_membership.next_epoch_stake_table().await {
             Ok(drb_membership) => drb_membership,
@@ -233,7 +296,7 @@ where
             }
         }
 
-        self.membership.write().await.add_drb_result(epoch, drb);
+        self.add_drb_result(epoch, drb).await;
         Ok(EpochMembership {
             epoch: Some(epoch),
             coordinator: self.clone(),
@@ -527,11 +590,7 @@ impl<TYPES: NodeType> EpochMembership<TYPES> {
     /// Add the epoch result to the membership
     pub async fn add_drb_result(&self, drb_result: DrbResult) {
         if let Some(epoch) = self.epoch() {
-            self.coordinator
-                .membership
-                .write()
-                .await
-                .add_drb_result(epoch, drb_result);
+            self.coordinator.add_drb_result(epoch, drb_result).await;
         }
     }
 }
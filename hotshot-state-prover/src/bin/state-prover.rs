@@ -3,13 +3,10 @@ use std::time::Duration;
 use alloy::{
     primitives::Address,
     providers::{Provider, ProviderBuilder},
-    signers::{
-        local::{coins_bip39::English, MnemonicBuilder},
-        Signer,
-    },
+    signers::Signer,
 };
 use clap::Parser;
-use espresso_contract_deployer::network_config::fetch_epoch_config_from_sequencer;
+use espresso_contract_deployer::{build_signer, network_config::fetch_epoch_config_from_sequencer};
 use espresso_types::parse_duration;
 use hotshot_state_prover::service::{run_prover_once, run_prover_service, StateProverConfig};
 use hotshot_types::light_client::STAKE_TABLE_CAPACITY;
@@ -102,13 +99,8 @@ async fn main() {
     // prepare config for state prover from user options
     let l1_provider = ProviderBuilder::new().on_http(args.l1_provider.clone());
     let chain_id = l1_provider.get_chain_id().await.unwrap();
-    let signer = MnemonicBuilder::<English>::default()
-        .phrase(args.eth_mnemonic)
-        .index(args.eth_account_index)
-        .expect("wrong mnemonic or index")
-        .build()
-        .expect("fail to build signer")
-        .with_chain_id(Some(chain_id));
+    let signer =
+        build_signer(args.eth_mnemonic, args.eth_account_index).with_chain_id(Some(chain_id));
 
     let (blocks_per_epoch, epoch_start_block) =
         fetch_epoch_config_from_sequencer(&args.sequencer_url)
@@ -163,3 +155,15 @@ async fn main() {
         };
     }
 }
+
+#[cfg(test)]
+mod test {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Args::command().debug_assert();
+    }
+}
@@ -724,6 +724,7 @@ mod test {
             is_mock_v2,
             EPOCH_HEIGHT_FOR_TEST,
             EPOCH_START_BLOCK_FOR_TEST,
+            None,
         )
         .await?;
 
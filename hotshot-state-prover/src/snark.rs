@@ -114,6 +114,7 @@ mod tests {
         rand::{CryptoRng, RngCore},
         One, UniformRand,
     };
+    use hotshot_contract_adapter::light_client::verify_light_client_proof;
     use hotshot_types::{
         light_client::LightClientState, signature_key::SchnorrPubKey,
         traits::signature_key::StateSignatureKey,
@@ -279,6 +280,12 @@ mod tests {
             None
         )
         .is_ok());
+        assert!(verify_light_client_proof(&vk, &public_inputs.to_vec(), &proof).unwrap());
+
+        // a corrupted proof is rejected without an error
+        let mut bad_proof = proof.clone();
+        bad_proof.poly_evals.wires_evals[0] += CircuitField::one();
+        assert!(!verify_light_client_proof(&vk, &public_inputs.to_vec(), &bad_proof).unwrap());
 
         // minimum bad path, other bad cases are checked inside `circuit.rs`
         let mut bad_st_state = st_state;
@@ -16,6 +16,7 @@ use data_source::DataSource;
 use derive_more::derive::Deref;
 use hotshot_types::traits::signature_key::SignatureKey;
 use message::{Message, RequestMessage, ResponseMessage};
+use metrics::RequestResponseMetrics;
 use network::{Bytes, Receiver, Sender};
 use parking_lot::RwLock;
 use rand::seq::SliceRandom;
@@ -33,6 +34,8 @@ use util::BoundedVecDeque;
 pub mod data_source;
 /// The message type. Is the base type for all messages in the request-response protocol
 pub mod message;
+/// The metrics type. Holds the counters the protocol updates as requests and responses flow through it
+pub mod metrics;
 /// The network traits. Is what we use to send and receive messages over the network as
 /// the protocol
 pub mod network;
@@ -174,6 +177,9 @@ impl<
         // The [response] data source that [`RequestResponseProtocol`] will use to derive the
         // response data for a specific request
         data_source: DS,
+        // The counters that [`RequestResponseProtocol`] will update as it sends requests and
+        // receives responses
+        metrics: RequestResponseMetrics,
     ) -> Self {
         // Create the active requests map
         let active_requests = ActiveRequestsMap::default();
@@ -185,6 +191,7 @@ impl<
             recipient_source,
             data_source,
             active_requests,
+            metrics,
             phantom_data: PhantomData,
         });
 
@@ -232,6 +239,8 @@ pub struct RequestResponseInner<
     data_source: DS,
     /// The map of currently active requests
     active_requests: ActiveRequestsMap<Req>,
+    /// The counters we update as requests and responses flow through the protocol
+    metrics: RequestResponseMetrics,
     /// Phantom data to help with type inference
     phantom_data: PhantomData<(K, R, Req, DS)>,
 }
@@ -311,6 +320,9 @@ impl<
         Fut: Future<Output = anyhow::Result<O>> + Send + Sync + 'static,
         O: Send + Sync + 'static + Clone,
     {
+        // Count the request
+        self.metrics.requests_sent.add(1);
+
         timeout(timeout_duration, async move {
             // Calculate the hash of the request
             let request_hash = blake3::hash(&request_message.request.to_bytes().map_err(|e| {
@@ -434,7 +446,10 @@ impl<
                 .map_err(|_| RequestError::Other(anyhow!("channel was closed")))
         })
         .await
-        .map_err(|_| RequestError::Timeout)
+        .map_err(|_| {
+            self.metrics.requests_timed_out.add(1);
+            RequestError::Timeout
+        })
         .and_then(|result| result)
         .and_then(|result| {
             result.downcast::<O>().map_err(|e| {
@@ -565,8 +580,13 @@ impl<
             return;
         };
 
+        // Count the response now that we know it belongs to an active request
+        self.metrics.responses_received.add(1);
+
         // Spawn a task to validate the response and send it to the requester (us)
         let response_validate_timeout = self.config.response_validate_timeout;
+        let responses_invalid = Arc::clone(&self.metrics.responses_invalid);
+        let responses_invalid_clone = Arc::clone(&responses_invalid);
         let response_task = AbortOnDropHandle::new(tokio::spawn(async move {
             if timeout(response_validate_timeout, async move {
                 // Make sure the response is valid for the given request
@@ -579,6 +599,7 @@ impl<
                     Ok(validation_result) => validation_result,
                     Err(e) => {
                         warn!("Received invalid response: {e:#}");
+                        responses_invalid_clone.add(1);
                         return;
                     },
                 };
@@ -590,6 +611,7 @@ impl<
             .is_err()
             {
                 warn!("Timed out while validating response");
+                responses_invalid.add(1);
             }
         }));
 
@@ -633,11 +655,17 @@ impl<R: Request> Drop for ActiveRequestInner<R> {
 mod tests {
     use std::{
         collections::HashMap,
-        sync::{atomic::AtomicBool, Mutex},
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Mutex,
+        },
     };
 
     use async_trait::async_trait;
-    use hotshot_types::signature_key::{BLSPrivKey, BLSPubKey};
+    use hotshot_types::{
+        signature_key::{BLSPrivKey, BLSPubKey},
+        traits::metrics::Counter,
+    };
     use rand::Rng;
     use tokio::{sync::mpsc, task::JoinSet};
 
@@ -690,12 +718,24 @@ mod tests {
     #[derive(Clone)]
     pub struct TestSender {
         network: Arc<HashMap<BLSPubKey, mpsc::Sender<Bytes>>>,
+        /// Whether or not to silently drop all response messages
+        drop_responses: bool,
     }
 
     /// An implementation of the [`Sender`] trait for the [`TestSender`] type
     #[async_trait]
     impl Sender<BLSPubKey> for TestSender {
         async fn send_message(&self, message: &Bytes, recipient: BLSPubKey) -> Result<()> {
+            // Pretend we sent the message if we are dropping responses and this is one
+            if self.drop_responses
+                && matches!(
+                    Message::<TestRequest, BLSPubKey>::from_bytes(message),
+                    Ok(Message::Response(_))
+                )
+            {
+                return Ok(());
+            }
+
             self.network
                 .get(&recipient)
                 .ok_or(anyhow::anyhow!("recipient not found"))?
@@ -817,6 +857,7 @@ mod tests {
         // Create a test sender from the network
         let sender = TestSender {
             network: Arc::new(network),
+            drop_responses: false,
         };
 
         // Return all senders and receivers
@@ -877,6 +918,7 @@ mod tests {
                         take_data: false,
                         taken: Arc::new(AtomicBool::new(false)),
                     },
+                    RequestResponseMetrics::default(),
                 );
 
                 // Add the handle to the handles list so it doesn't get dropped and
@@ -1003,6 +1045,7 @@ mod tests {
                     data_available_time: Instant::now() + Duration::from_secs(2),
                     taken: Arc::new(AtomicBool::new(false)),
                 },
+                RequestResponseMetrics::default(),
             );
 
             // Add the participants to the list
@@ -1053,4 +1096,82 @@ mod tests {
                 .expect("failed to request data");
         }
     }
+
+    /// A test counter that keeps track of its value so we can assert on it
+    #[derive(Clone, Debug, Default)]
+    struct TestCounter(Arc<AtomicUsize>);
+
+    impl TestCounter {
+        /// Get the current value of the counter
+        fn get(&self) -> usize {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+
+    impl Counter for TestCounter {
+        fn add(&self, amount: usize) {
+            self.0.fetch_add(amount, Ordering::Relaxed);
+        }
+    }
+
+    /// Test that the timeout counter is incremented when the network drops all responses
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_timeout_metrics() {
+        // Create the counters we want to assert on
+        let requests_sent = TestCounter::default();
+        let requests_timed_out = TestCounter::default();
+        let responses_received = TestCounter::default();
+        let responses_invalid = TestCounter::default();
+        let metrics = RequestResponseMetrics {
+            requests_sent: Arc::new(requests_sent.clone()),
+            requests_timed_out: Arc::new(requests_timed_out.clone()),
+            responses_received: Arc::new(responses_received.clone()),
+            responses_invalid: Arc::new(responses_invalid.clone()),
+        };
+
+        // Create two participants on a network that drops all responses
+        let mut participants = Vec::new();
+        for (mut sender, receiver, (public_key, private_key)) in create_participants(2) {
+            sender.drop_responses = true;
+
+            // Everyone has the data, but nobody's response will make it through
+            let protocol = RequestResponse::new(
+                default_protocol_config(),
+                sender.clone(),
+                receiver,
+                sender,
+                TestDataSource {
+                    take_data: false,
+                    has_data: true,
+                    data_available_time: Instant::now(),
+                    taken: Arc::new(AtomicBool::new(false)),
+                },
+                metrics.clone(),
+            );
+
+            participants.push((protocol, public_key, private_key));
+        }
+
+        // Make a request from the first participant
+        let (protocol, public_key, private_key) = &participants[0];
+        let request = TestRequest(vec![rand::thread_rng().gen(); 100]);
+        let request_message = RequestMessage::new_signed(public_key, private_key, &request)
+            .expect("failed to create request message");
+
+        // Make sure the request times out
+        let result = protocol
+            .request(
+                request_message,
+                Duration::from_secs(1),
+                |_request, response| async move { Ok(response) },
+            )
+            .await;
+        assert!(matches!(result, Err(RequestError::Timeout)));
+
+        // Make sure the counters reflect the dropped responses
+        assert_eq!(requests_sent.get(), 1);
+        assert_eq!(requests_timed_out.get(), 1);
+        assert_eq!(responses_received.get(), 0);
+        assert_eq!(responses_invalid.get(), 0);
+    }
 }
@@ -0,0 +1,45 @@
+//! This file contains the [`RequestResponseMetrics`] type. It holds the counters that the
+//! [`RequestResponseProtocol`] updates so that we can observe how healthy the network is
+//! (e.g. how often requests time out or peers respond with garbage)
+
+use std::sync::Arc;
+
+use hotshot_types::traits::metrics::{Counter, Metrics, NoMetrics};
+
+/// The counters updated by the request-response protocol
+#[derive(Clone, Debug)]
+pub struct RequestResponseMetrics {
+    /// The number of requests we have made
+    pub(crate) requests_sent: Arc<dyn Counter>,
+    /// The number of requests that timed out before we got a valid response
+    pub(crate) requests_timed_out: Arc<dyn Counter>,
+    /// The number of responses we received for a request that was still active
+    pub(crate) responses_received: Arc<dyn Counter>,
+    /// The number of responses that failed (or timed out during) validation
+    pub(crate) responses_invalid: Arc<dyn Counter>,
+}
+
+impl RequestResponseMetrics {
+    /// Create and register the request-response counters on the given metrics registry
+    pub fn new(metrics: &(impl Metrics + ?Sized)) -> Self {
+        let metrics = metrics.subgroup("request_response".into());
+        Self {
+            requests_sent: metrics.create_counter("requests_sent".into(), None).into(),
+            requests_timed_out: metrics
+                .create_counter("requests_timed_out".into(), None)
+                .into(),
+            responses_received: metrics
+                .create_counter("responses_received".into(), None)
+                .into(),
+            responses_invalid: metrics
+                .create_counter("responses_invalid".into(), None)
+                .into(),
+        }
+    }
+}
+
+impl Default for RequestResponseMetrics {
+    fn default() -> Self {
+        Self::new(&NoMetrics)
+    }
+}
@@ -0,0 +1,4 @@
+CREATE TABLE schema_version (
+  id INTEGER PRIMARY KEY CHECK (id = 0),
+  version INTEGER NOT NULL
+);
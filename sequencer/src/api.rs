@@ -102,15 +102,25 @@ struct ApiState<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Version
     // without waiting.
     #[derivative(Debug = "ignore")]
     consensus: BoxLazy<ConsensusState<N, P, V>>,
+
+    // Submitted transactions with a larger payload are rejected, in addition to those larger than
+    // the maximum block size.
+    max_transaction_size: Option<u64>,
 }
 
 impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> ApiState<N, P, V> {
     fn new(init: impl Future<Output = ConsensusState<N, P, V>> + Send + 'static) -> Self {
         Self {
             consensus: Arc::pin(Lazy::from_future(init.boxed())),
+            max_transaction_size: None,
         }
     }
 
+    fn with_max_transaction_size(mut self, max_transaction_size: Option<u64>) -> Self {
+        self.max_transaction_size = max_transaction_size;
+        self
+    }
+
     async fn state_signer(&self) -> &Arc<RwLock<StateSigner<SequencerApiVersion>>> {
         &self.consensus.as_ref().get().await.get_ref().state_signer
     }
@@ -265,6 +275,10 @@ impl<N: ConnectedNetwork<PubKey>, V: Versions, P: SequencerPersistence> SubmitDa
     for ApiState<N, P, V>
 {
     async fn submit(&self, tx: Transaction) -> anyhow::Result<()> {
+        self.node_state()
+            .await
+            .check_namespace_allowed(tx.namespace())?;
+
         let handle = self.consensus().await;
 
         let consensus_read_lock = handle.read().await;
@@ -294,6 +308,16 @@ impl<N: ConnectedNetwork<PubKey>, V: Versions, P: SequencerPersistence> SubmitDa
             bail!("transaction size ({txn_size}) is greater than max_block_size ({max_block_size})")
         }
 
+        // reject transaction bigger than the configured limit
+        if let Some(max_transaction_size) = self.max_transaction_size {
+            if txn_size > max_transaction_size {
+                bail!(
+                    "transaction size ({txn_size}) is greater than max_transaction_size \
+                     ({max_transaction_size})"
+                )
+            }
+        }
+
         consensus_read_lock.submit_transaction(tx).await?;
         Ok(())
     }
@@ -924,6 +948,7 @@ pub mod test_helpers {
             let (task, builder_url) = run_legacy_builder::<{ NUM_NODES }>(
                 cfg.network_config.builder_port(),
                 chain_config.map(|c| *c.max_block_size),
+                None,
             )
             .await;
             builder_tasks.push(task);
@@ -1853,7 +1878,7 @@ mod test {
         v0_1::{block_reward, RewardAmount},
         v0_3::StakeTableFetcher,
         validators_from_l1_events, EpochVersion, FeeAmount, Header, L1ClientOptions,
-        MarketplaceVersion, MockSequencerVersions, SequencerVersions, ValidatedState,
+        MarketplaceVersion, MockSequencerVersions, NamespaceId, SequencerVersions, ValidatedState,
     };
     use futures::{
         future::{self, join_all},
@@ -1893,7 +1918,7 @@ mod test {
     use crate::{
         catchup::{NullStateCatchup, StatePeers},
         persistence::no_storage,
-        testing::{TestConfig, TestConfigBuilder},
+        testing::{wait_for_decide_on_handle, TestConfig, TestConfigBuilder},
     };
 
     #[tokio::test(flavor = "multi_thread")]
@@ -1916,6 +1941,98 @@ mod test {
         assert_eq!(health.status, HealthStatus::Available);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_submit_disallowed_namespace() {
+        setup_test();
+
+        let port = pick_unused_port().expect("No ports free");
+        let url = format!("http://localhost:{port}").parse().unwrap();
+        let client: Client<ServerError, StaticVersion<0, 1>> = Client::new(url);
+
+        let options = Options::with_port(port).submit(Default::default());
+        let network_config = TestConfigBuilder::default()
+            .allowed_namespaces(vec![NamespaceId::from(1_u32)])
+            .build();
+        let config = TestNetworkConfigBuilder::default()
+            .api_config(options)
+            .network_config(network_config)
+            .build();
+        let network = TestNetwork::new(config, MockSequencerVersions::new()).await;
+        let mut events = network.server.event_stream().await;
+        client.connect(None).await;
+
+        // A transaction for a namespace which is not in the allowlist is rejected.
+        let txn = Transaction::new(NamespaceId::from(2_u32), vec![1, 2, 3, 4]);
+        let err = client
+            .post::<Commitment<Transaction>>("submit/submit")
+            .body_json(&txn)
+            .unwrap()
+            .send()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not allowed"), "{err}");
+
+        // Transactions for allowed namespaces are still sequenced.
+        let txn = Transaction::new(NamespaceId::from(1_u32), vec![1, 2, 3, 4]);
+        client
+            .post::<Commitment<Transaction>>("submit/submit")
+            .body_json(&txn)
+            .unwrap()
+            .send()
+            .await
+            .unwrap();
+        wait_for_decide_on_handle(&mut events, &txn).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_submit_max_transaction_size() {
+        setup_test();
+
+        const MAX_TRANSACTION_SIZE: u64 = 100;
+
+        let port = pick_unused_port().expect("No ports free");
+        let url = format!("http://localhost:{port}").parse().unwrap();
+        let client: Client<ServerError, StaticVersion<0, 1>> = Client::new(url);
+
+        let options = Options::with_port(port).submit(options::Submit {
+            max_transaction_size: Some(MAX_TRANSACTION_SIZE),
+        });
+        let network_config = TestConfigBuilder::default().build();
+        let config = TestNetworkConfigBuilder::default()
+            .api_config(options)
+            .network_config(network_config)
+            .build();
+        let network = TestNetwork::new(config, MockSequencerVersions::new()).await;
+        let mut events = network.server.event_stream().await;
+        client.connect(None).await;
+
+        let oversized = Transaction::new(
+            NamespaceId::from(1_u32),
+            vec![0; MAX_TRANSACTION_SIZE as usize + 1],
+        );
+        let err = client
+            .post::<Commitment<Transaction>>("submit/submit")
+            .body_json(&oversized)
+            .unwrap()
+            .send()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("max_transaction_size"), "{err}");
+
+        let txn = Transaction::new(
+            NamespaceId::from(1_u32),
+            vec![0; MAX_TRANSACTION_SIZE as usize - 1],
+        );
+        client
+            .post::<Commitment<Transaction>>("submit/submit")
+            .body_json(&txn)
+            .unwrap()
+            .send()
+            .await
+            .unwrap();
+        wait_for_decide_on_handle(&mut events, &txn).await;
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn status_test_without_query_module() {
         status_test_helper(|opt| opt).await
@@ -2911,6 +3028,59 @@ mod test {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_try_fetch_config() {
+        setup_test();
+
+        let port = pick_unused_port().expect("No ports free");
+        let url: surf_disco::Url = format!("http://localhost:{port}").parse().unwrap();
+        let client: Client<ServerError, StaticVersion<0, 1>> = Client::new(url.clone());
+
+        let options = Options::with_port(port).config(Default::default());
+        let network_config = TestConfigBuilder::default().build();
+        let config = TestNetworkConfigBuilder::default()
+            .api_config(options)
+            .network_config(network_config)
+            .build();
+        let _network = TestNetwork::new(config, MockSequencerVersions::new()).await;
+        client.connect(None).await;
+
+        let bad_port = pick_unused_port().expect("No ports free");
+        let bad_url: surf_disco::Url = format!("http://localhost:{bad_port}").parse().unwrap();
+        let validator =
+            ValidatorConfig::generated_from_seed_indexed([0; 32], 1, U256::from(1), false);
+
+        // The config is served by the good peer, even though the bad one is listed first.
+        let peers = StatePeers::<StaticVersion<0, 1>>::from_urls(
+            vec![bad_url.clone(), url],
+            Default::default(),
+            &NoMetrics,
+        );
+        let config = peers
+            .try_fetch_config(&validator, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(config.node_index, 1);
+
+        // If no peer serves the config, the error names every peer.
+        let other_bad_port = pick_unused_port().expect("No ports free");
+        let other_bad_url: surf_disco::Url = format!("http://localhost:{other_bad_port}")
+            .parse()
+            .unwrap();
+        let peers = StatePeers::<StaticVersion<0, 1>>::from_urls(
+            vec![bad_url.clone(), other_bad_url.clone()],
+            Default::default(),
+            &NoMetrics,
+        );
+        let err = peers
+            .try_fetch_config(&validator, Duration::from_secs(5))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(bad_url.as_str()), "{err}");
+        assert!(err.contains(other_bad_url.as_str()), "{err}");
+    }
+
     async fn run_hotshot_event_streaming_test(url_suffix: &str) {
         setup_test();
 
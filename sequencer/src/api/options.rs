@@ -162,7 +162,8 @@ impl Options {
             recv_ctx
                 .await
                 .expect("context initialized and sent over channel")
-        });
+        })
+        .with_max_transaction_size(self.submit.and_then(|submit| submit.max_transaction_size));
         let mut tasks = TaskList::default();
 
         // The server state type depends on whether we are running a query or status API or not, so
@@ -584,7 +585,14 @@ impl Http {
 
 /// Options for the submission API module.
 #[derive(Parser, Clone, Copy, Debug, Default)]
-pub struct Submit;
+pub struct Submit {
+    /// Maximum size in bytes of a transaction payload accepted for submission.
+    ///
+    /// Larger transactions are rejected before they reach the mempool. Transactions larger than
+    /// the maximum block size are always rejected.
+    #[clap(long, env = "ESPRESSO_SEQUENCER_MAX_TRANSACTION_SIZE")]
+    pub max_transaction_size: Option<u64>,
+}
 
 /// Options for the status API module.
 #[derive(Parser, Clone, Copy, Debug, Default)]
@@ -1,10 +1,13 @@
 use std::{fs::File, io::stdout, path::PathBuf, thread::sleep, time::Duration};
 
-use alloy::primitives::{Address, U256};
+use alloy::{
+    primitives::{Address, U256},
+    providers::WalletProvider,
+};
 use clap::Parser;
 use espresso_contract_deployer::{
     build_provider, builder::DeployerArgsBuilder, network_config::light_client_genesis, Contract,
-    Contracts, DeployedContracts,
+    Contracts, DeployedContracts, EnvFormat,
 };
 use espresso_types::{config::PublicNetworkConfig, parse_duration};
 use hotshot_types::light_client::STAKE_TABLE_CAPACITY;
@@ -112,9 +115,46 @@ struct Options {
     #[clap(short, long, name = "OUT", env = "ESPRESSO_DEPLOYER_OUT_PATH")]
     out: Option<PathBuf>,
 
+    /// Format of the deployment results.
+    ///
+    /// `shell-export` prefixes each line with `export `, so that sourcing the output in a shell
+    /// sets the variables for child processes.
+    #[clap(
+        long,
+        value_enum,
+        env = "ESPRESSO_DEPLOYER_OUT_FORMAT",
+        default_value = "dotenv"
+    )]
+    format: EnvFormat,
+
+    /// Additionally write one .env fragment per contract into OUTPUT_DIR.
+    ///
+    /// Each fragment is named after the contract's env var and contains only that contract's
+    /// address, so that CI pipelines can selectively source them.
+    #[clap(long, name = "OUTPUT_DIR", env = "ESPRESSO_DEPLOYER_OUTPUT_DIR")]
+    output_dir: Option<PathBuf>,
+
     #[clap(flatten)]
     contracts: DeployedContracts,
 
+    /// Number of block confirmations to wait for after each deployment and follow-up transaction.
+    ///
+    /// On public chains, waiting for a few confirmations avoids building on a deployment that is
+    /// later reorged out. Zero only waits for inclusion.
+    #[clap(
+        long,
+        env = "ESPRESSO_DEPLOYER_WAIT_CONFIRMATIONS",
+        default_value = "0"
+    )]
+    wait_confirmations: u64,
+
+    /// Library address the contract bindings were generated with, to be replaced by the address of
+    /// the deployed library.
+    ///
+    /// Only needed for bindings generated with a non-default placeholder.
+    #[clap(long, env = "ESPRESSO_DEPLOYER_LIBRARY_PLACEHOLDER")]
+    library_placeholder: Option<String>,
+
     /// If toggled, launch a mock LightClient contract with a smaller verification key for testing.
     /// Applies to both V1 and V2 of LightClient.
     #[clap(short, long)]
@@ -147,6 +187,20 @@ struct Options {
     #[clap(long, env = "ESP_TOKEN_INITIAL_GRANT_RECIPIENT_ADDRESS")]
     initial_token_grant_recipient: Option<Address>,
 
+    /// After deploying the stake table, exercise it with a throwaway validator.
+    ///
+    /// The deployer account must hold some ESP tokens to fund the throwaway validator.
+    #[cfg(feature = "testing")]
+    #[clap(long, default_value = "false")]
+    smoke_test_stake_table: bool,
+
+    /// Re-verify all contracts of the deployment, including those passed in, after deploying.
+    ///
+    /// Without any of the deploy options, this only checks an existing deployment. The proxies are
+    /// expected to be owned by MULTISIG_ADDRESS, or the deployer account if it is not provided.
+    #[clap(long, default_value = "false")]
+    verify: bool,
+
     #[clap(flatten)]
     logging: logging::Config,
 }
@@ -156,14 +210,17 @@ async fn main() -> anyhow::Result<()> {
     let opt = Options::parse();
     opt.logging.init();
 
-    let mut contracts = Contracts::from(opt.contracts);
+    let mut contracts = Contracts::from(opt.contracts).with_confirmations(opt.wait_confirmations);
+    if let Some(placeholder) = opt.library_placeholder {
+        contracts = contracts.with_library_placeholder(placeholder);
+    }
 
     let provider = build_provider(opt.mnemonic, opt.account_index, opt.rpc_url);
 
     // First use builder to build constructor input arguments
     let mut args_builder = DeployerArgsBuilder::default();
     args_builder
-        .deployer(provider)
+        .deployer(provider.clone())
         .mock_light_client(opt.use_mock);
     if let Some(multisig) = opt.multisig_address {
         args_builder.multisig(multisig);
@@ -233,6 +290,24 @@ async fn main() -> anyhow::Result<()> {
         args.deploy(&mut contracts, Contract::StakeTableProxy)
             .await?;
     }
+    #[cfg(feature = "testing")]
+    if opt.smoke_test_stake_table {
+        let st_addr = contracts
+            .address(Contract::StakeTableProxy)
+            .ok_or_else(|| anyhow::anyhow!("no stake table proxy address"))?;
+        let token_addr = contracts
+            .address(Contract::EspTokenProxy)
+            .ok_or_else(|| anyhow::anyhow!("no ESP token proxy address"))?;
+        espresso_contract_deployer::smoke_test_stake_table(&provider, st_addr, token_addr).await?;
+    }
+
+    if opt.verify {
+        let owner = opt
+            .multisig_address
+            .unwrap_or_else(|| provider.default_signer_address());
+        espresso_contract_deployer::verify_deployment(&provider, &contracts, owner).await?;
+        tracing::info!("deployment verified");
+    }
 
     // finally print out or persist deployed addresses
     if let Some(out) = &opt.out {
@@ -241,10 +316,25 @@ async fn main() -> anyhow::Result<()> {
             .truncate(true)
             .write(true)
             .open(out)?;
-        contracts.write(file)?;
+        contracts.write_with_format(file, opt.format)?;
     } else {
-        contracts.write(stdout())?;
+        contracts.write_with_format(stdout(), opt.format)?;
+    }
+    if let Some(dir) = &opt.output_dir {
+        contracts.write_fragments(dir)?;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Options::command().debug_assert();
+    }
+}
@@ -314,3 +314,15 @@ async fn main() -> anyhow::Result<()> {
         Command::L1Balance(opt) => l1_balance(opt).await,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Options::command().debug_assert();
+    }
+}
@@ -10,17 +10,14 @@ use alloy::{
     node_bindings::Anvil,
     primitives::{Address, Bytes, U256},
     providers::{Provider, ProviderBuilder, WalletProvider},
-    signers::{
-        k256::ecdsa::SigningKey,
-        local::{coins_bip39::English, LocalSigner, MnemonicBuilder},
-    },
+    signers::{k256::ecdsa::SigningKey, local::LocalSigner},
 };
 use anyhow::Context;
 use async_trait::async_trait;
 use clap::{Parser, ValueEnum};
 use espresso_contract_deployer::{
-    self as deployer, network_config::light_client_genesis_from_stake_table, Contract, Contracts,
-    DeployedContracts, HttpProviderWithWallet,
+    self as deployer, build_signer, network_config::light_client_genesis_from_stake_table,
+    Contract, Contracts, DeployedContracts, HttpProviderWithWallet,
 };
 use espresso_types::{
     parse_duration, v0_99::ChainConfig, EpochVersion, SeqTypes, SequencerVersions, ValidatedState,
@@ -340,12 +337,7 @@ async fn main() -> anyhow::Result<()> {
             .into_iter()
             .chain(iter::repeat(retry_interval)),
     )) {
-        let signer = MnemonicBuilder::<English>::default()
-            .phrase(mnemonic.as_str())
-            .index(account_index)
-            .expect("error building wallet")
-            .build()
-            .expect("error opening wallet");
+        let signer = build_signer(mnemonic.clone(), account_index);
         let wallet = EthereumWallet::from(signer.clone());
         let provider = ProviderBuilder::new()
             .wallet(wallet.clone())
@@ -425,6 +417,7 @@ async fn main() -> anyhow::Result<()> {
             true, // use mock
             blocks_per_epoch,
             epoch_start_block,
+            None, // deploy a fresh PlonkVerifierV2
         )
         .await?;
         if let Some(multisig) = multisig_address {
@@ -433,6 +426,7 @@ async fn main() -> anyhow::Result<()> {
                 Contract::LightClientProxy,
                 lc_proxy_addr,
                 multisig,
+                contracts.confirmations(),
             )
             .await?;
         }
@@ -452,6 +446,7 @@ async fn main() -> anyhow::Result<()> {
                     Contract::FeeContractProxy,
                     fee_proxy_addr,
                     multisig,
+                    contracts.confirmations(),
                 )
                 .await?;
             }
@@ -465,6 +460,7 @@ async fn main() -> anyhow::Result<()> {
                     Contract::EspTokenProxy,
                     token_proxy_addr,
                     multisig,
+                    contracts.confirmations(),
                 )
                 .await?;
             }
@@ -486,12 +482,13 @@ async fn main() -> anyhow::Result<()> {
                     Contract::StakeTableProxy,
                     stake_table_proxy_addr,
                     multisig,
+                    contracts.confirmations(),
                 )
                 .await?;
             }
 
             let staking_priv_keys = network_config.staking_priv_keys();
-            setup_stake_table_contract_for_test(
+            let summary = setup_stake_table_contract_for_test(
                 l1_url.clone(),
                 &provider,
                 l1_contracts
@@ -504,6 +501,7 @@ async fn main() -> anyhow::Result<()> {
                 DelegationConfig::default(),
             )
             .await?;
+            tracing::info!("{summary}");
         }
     }
 
@@ -910,6 +908,7 @@ mod tests {
         node_bindings::{Anvil, AnvilInstance},
         primitives::U256,
     };
+    use clap::CommandFactory;
     use committable::{Commitment, Committable};
     use escargot::CargoBuild;
     use espresso_types::{BlockMerkleTree, Header, NamespaceProofQueryData, SeqTypes, Transaction};
@@ -942,6 +941,11 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cli_definition() {
+        Args::command().debug_assert();
+    }
+
     // If this test failed and you are doing changes on the following stuff, please
     // sync your changes to [`espresso-sequencer-go`](https://github.com/EspressoSystems/espresso-sequencer-go)
     // and open a PR.
@@ -1165,12 +1169,7 @@ mod tests {
 
             let light_client_address = dev_info.l1_light_client_address;
 
-            let signer = MnemonicBuilder::<English>::default()
-                .phrase(TEST_MNEMONIC)
-                .index(0)
-                .unwrap()
-                .build()
-                .unwrap();
+            let signer = build_signer(TEST_MNEMONIC.to_string(), 0);
             let provider = ProviderBuilder::new()
                 .wallet(EthereumWallet::from(signer))
                 .on_http(l1_url.clone());
@@ -1330,12 +1329,7 @@ mod tests {
 
             let light_client_address = dev_info.l1_light_client_address;
 
-            let signer = MnemonicBuilder::<English>::default()
-                .phrase(TEST_MNEMONIC)
-                .index(0)
-                .unwrap()
-                .build()
-                .unwrap();
+            let signer = build_signer(TEST_MNEMONIC.to_string(), 0);
             let provider = ProviderBuilder::new()
                 .wallet(EthereumWallet::from(signer))
                 .on_http(l1_url.clone());
@@ -1361,12 +1355,7 @@ mod tests {
             {
                 tracing::info!("checking hotshot commitment for {chain_id}");
 
-                let signer = MnemonicBuilder::<English>::default()
-                    .phrase(TEST_MNEMONIC)
-                    .index(0)
-                    .unwrap()
-                    .build()
-                    .unwrap();
+                let signer = build_signer(TEST_MNEMONIC.to_string(), 0);
                 let provider = ProviderBuilder::new()
                     .wallet(EthereumWallet::from(signer))
                     .on_http(provider_url.clone());
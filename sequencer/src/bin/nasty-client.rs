@@ -1422,3 +1422,15 @@ async fn main() {
         total_actions.add(1);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Options::command().debug_assert();
+    }
+}
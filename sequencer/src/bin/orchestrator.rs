@@ -144,3 +144,15 @@ async fn main() {
     .await
     .unwrap();
 }
+
+#[cfg(test)]
+mod test {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Args::command().debug_assert();
+    }
+}
@@ -51,3 +51,15 @@ async fn reset_storage<O: DataSourceOptions>(opt: O) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Options::command().debug_assert();
+    }
+}
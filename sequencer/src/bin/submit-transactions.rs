@@ -481,3 +481,15 @@ fn random_transaction(opt: &Options, rng: &mut ChaChaRng) -> Transaction {
 fn random_seed() -> u64 {
     ChaChaRng::from_entropy().next_u64()
 }
+
+#[cfg(test)]
+mod test {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Options::command().debug_assert();
+    }
+}
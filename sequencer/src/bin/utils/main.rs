@@ -40,3 +40,15 @@ async fn main() -> anyhow::Result<()> {
         Command::NsAggregator(opt) => ns_aggregator::run(opt).await,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Options::command().debug_assert();
+    }
+}
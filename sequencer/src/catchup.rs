@@ -21,10 +21,11 @@ use espresso_types::{
     FeeMerkleTree, Leaf2, NodeState, PubKey, SeqTypes, SequencerVersions, ValidatedState,
 };
 use futures::{
-    future::{Future, FutureExt, TryFuture, TryFutureExt},
+    future::{ready, select_ok, Future, FutureExt, TryFuture, TryFutureExt},
     stream::FuturesUnordered,
     StreamExt,
 };
+use hotshot_query_service::{availability::LeafQueryData, types::HeightIndexed};
 use hotshot_types::{
     consensus::Consensus,
     data::ViewNumber,
@@ -32,7 +33,7 @@ use hotshot_types::{
     network::NetworkConfig,
     stake_table::HSStakeTable,
     traits::{
-        metrics::{Counter, CounterFamily, Metrics},
+        metrics::{Counter, CounterFamily, Metrics, NoMetrics},
         network::ConnectedNetwork,
         node_implementation::{ConsensusTime as _, NodeType, Versions},
         ValidatedState as ValidatedStateTrait,
@@ -41,7 +42,10 @@ use hotshot_types::{
     ValidatorConfig,
 };
 use itertools::Itertools;
-use jf_merkle_tree::{prelude::MerkleNode, ForgetableMerkleTreeScheme, MerkleTreeScheme};
+use jf_merkle_tree::{
+    prelude::MerkleNode, ForgetableMerkleTreeScheme, ForgetableUniversalMerkleTreeScheme,
+    MerkleTreeScheme,
+};
 use parking_lot::Mutex;
 use priority_queue::PriorityQueue;
 use serde::de::DeserializeOwned;
@@ -55,6 +59,9 @@ use vbs::version::StaticVersionType;
 
 use crate::api::BlocksFrontier;
 
+/// Initial time each peer gets to serve the network config, before backing off.
+const CONFIG_FETCH_TIMEOUT: Duration = Duration::from_secs(2);
+
 // This newtype is probably not worth having. It's only used to be able to log
 // URLs before doing requests.
 #[derive(Debug, Clone)]
@@ -227,6 +234,10 @@ impl<ApiVer: StaticVersionType> StatePeers<ApiVer> {
         }
     }
 
+    /// Fetch the network config, retrying with backoff until some peer serves it.
+    ///
+    /// Each attempt races all peers (see [`try_fetch_config`](Self::try_fetch_config)), with a
+    /// per-peer timeout that grows with every retry.
     #[tracing::instrument(skip(self, my_own_validator_config))]
     pub async fn fetch_config(
         &self,
@@ -236,18 +247,66 @@ impl<ApiVer: StaticVersionType> StatePeers<ApiVer> {
             .retry(self, move |provider, retry| {
                 let my_own_validator_config = my_own_validator_config.clone();
                 async move {
-                    let cfg = provider
-                        .fetch(retry, |client| {
-                            client.get::<PublicNetworkConfig>("config/hotshot").send()
-                        })
-                        .await?;
-                    cfg.into_network_config(my_own_validator_config)
-                        .context("fetched config, but failed to convert to private config")
+                    let timeout_dur = CONFIG_FETCH_TIMEOUT * (retry as u32 + 1);
+                    provider
+                        .try_fetch_config(&my_own_validator_config, timeout_dur)
+                        .await
                 }
                 .boxed()
             })
             .await
     }
+
+    /// Request the network config from all peers concurrently and return the first one served.
+    ///
+    /// A peer which does not respond within `timeout_dur`, or responds with a config which cannot
+    /// be converted for `my_own_validator_config`, counts as failed. This only fails if every peer
+    /// fails, in which case the error lists the reason for each peer.
+    pub async fn try_fetch_config(
+        &self,
+        my_own_validator_config: &ValidatorConfig<SeqTypes>,
+        timeout_dur: Duration,
+    ) -> anyhow::Result<NetworkConfig<SeqTypes>> {
+        let mut requests = self
+            .clients
+            .iter()
+            .map(|client| async move {
+                client.requests.add(1);
+                let res = match timeout(
+                    timeout_dur,
+                    client.get::<PublicNetworkConfig>("config/hotshot").send(),
+                )
+                .await
+                {
+                    Ok(Ok(cfg)) => cfg
+                        .into_network_config(my_own_validator_config.clone())
+                        .context("fetched config, but failed to convert to private config"),
+                    Ok(Err(err)) => Err(anyhow!("{err}")),
+                    Err(_) => Err(anyhow!("request timed out after {timeout_dur:?}")),
+                };
+                (client, res)
+            })
+            .collect::<FuturesUnordered<_>>();
+
+        let mut failures = vec![];
+        while let Some((client, res)) = requests.next().await {
+            match res {
+                Ok(cfg) => {
+                    tracing::info!(peer = %client.url, "fetched network config");
+                    return Ok(cfg);
+                },
+                Err(err) => {
+                    tracing::warn!(peer = %client.url, "failed to fetch network config: {err:#}");
+                    client.failures.add(1);
+                    failures.push(format!("{}: {err:#}", client.url));
+                },
+            }
+        }
+        bail!(
+            "failed to fetch network config from every peer:\n{}",
+            failures.join("\n")
+        )
+    }
 }
 
 #[async_trait]
@@ -335,35 +394,21 @@ impl<ApiVer: StaticVersionType> StateCatchup for StatePeers<ApiVer> {
         .await
     }
 
-    async fn try_fetch_leaf(
+    async fn try_fetch_leaf_chain(
         &self,
         retry: usize,
         height: u64,
-        stake_table: HSStakeTable<SeqTypes>,
-        success_threshold: U256,
-    ) -> anyhow::Result<Leaf2> {
-        // Get the leaf chain
-        let leaf_chain = self
-            .fetch(retry, |client| async move {
-                let leaf = client
-                    .get::<Vec<Leaf2>>(&format!("catchup/{}/leafchain", height))
-                    .send()
-                    .await?;
-                anyhow::Ok(leaf)
-            })
-            .await
-            .with_context(|| format!("failed to fetch leaf chain at height {height}"))?;
-
-        // Verify it, returning the leaf at the given height
-        verify_leaf_chain(
-            leaf_chain,
-            &stake_table,
-            success_threshold,
-            height,
-            &UpgradeLock::<SeqTypes, SequencerVersions<EpochVersion, EpochVersion>>::new(),
-        )
+        _stake_table: HSStakeTable<SeqTypes>,
+        _success_threshold: U256,
+    ) -> anyhow::Result<Vec<Leaf2>> {
+        self.fetch(retry, |client| async move {
+            let leaf = client
+                .get::<Vec<Leaf2>>(&format!("catchup/{}/leafchain", height))
+                .send()
+                .await?;
+            anyhow::Ok(leaf)
+        })
         .await
-        .with_context(|| format!("failed to verify leaf chain at height {height}"))
     }
 
     #[tracing::instrument(skip(self, _instance))]
@@ -423,6 +468,205 @@ impl<ApiVer: StaticVersionType> StateCatchup for StatePeers<ApiVer> {
     }
 }
 
+/// The maximum number of leaves [`ArchivalCatchup`] fetches looking for a decide.
+const MAX_ARCHIVAL_LEAF_CHAIN_LEN: usize = 100;
+
+/// Catchup from a single archival query service.
+///
+/// Unlike [`StatePeers`], which talks to the `catchup` API of consensus peers, this provider only
+/// needs the standard `availability` and merklized state APIs, so it can be pointed at any query
+/// service which stores the relevant history. Every response is checked against the commitments
+/// we already have before it is used.
+#[derive(Debug, Clone)]
+pub struct ArchivalCatchup<ApiVer: StaticVersionType> {
+    client: surf_disco::Client<ServerError, ApiVer>,
+    url: Url,
+    backoff: BackoffParams,
+}
+
+impl<ApiVer: StaticVersionType> ArchivalCatchup<ApiVer> {
+    pub fn new(url: Url, backoff: BackoffParams) -> Self {
+        Self {
+            client: surf_disco::Client::new(url.clone()),
+            url,
+            backoff,
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, retry: usize, route: &str) -> anyhow::Result<T> {
+        // Use the same escalating timeout as `StatePeers`, so that a slow server delays catchup
+        // at most a little longer on each retry.
+        let timeout_dur = Duration::from_millis(500) * (retry as u32 + 1);
+        timeout(timeout_dur, self.client.get::<T>(route).send())
+            .await
+            .with_context(|| format!("timed out fetching {route} from {}", self.url))?
+            .with_context(|| format!("failed to fetch {route} from {}", self.url))
+    }
+
+    async fn get_leaf(&self, retry: usize, height: u64) -> anyhow::Result<Leaf2> {
+        let leaf = self
+            .get::<LeafQueryData<SeqTypes>>(retry, &format!("availability/leaf/{height}"))
+            .await?;
+        ensure!(
+            leaf.height() == height,
+            "received leaf at height {}, expected {height}",
+            leaf.height()
+        );
+        Ok(leaf.leaf().clone())
+    }
+
+    /// Fetch the leaf at `height` together with the leaves which prove that it was decided.
+    ///
+    /// The query service only serves decided leaves, each of which extends the one before it, so
+    /// we fetch leaves until two of them are in consecutive views, plus the one after that whose
+    /// QC decides them. The result is checked with [`verify_leaf_chain`] like any other.
+    async fn fetch_leaf_chain(&self, retry: usize, height: u64) -> anyhow::Result<Vec<Leaf2>> {
+        let mut chain = vec![self.get_leaf(retry, height).await?];
+        loop {
+            ensure!(
+                chain.len() < MAX_ARCHIVAL_LEAF_CHAIN_LEN,
+                "no decide within {MAX_ARCHIVAL_LEAF_CHAIN_LEN} leaves"
+            );
+            let next = self.get_leaf(retry, height + chain.len() as u64).await?;
+            let direct = next.view_number() == chain[chain.len() - 1].view_number() + 1;
+            chain.push(next);
+            if direct {
+                let decide = self.get_leaf(retry, height + chain.len() as u64).await?;
+                chain.push(decide);
+                return Ok(chain);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<ApiVer: StaticVersionType> StateCatchup for ArchivalCatchup<ApiVer> {
+    #[tracing::instrument(skip(self, _instance))]
+    async fn try_fetch_accounts(
+        &self,
+        retry: usize,
+        _instance: &NodeState,
+        height: u64,
+        _view: ViewNumber,
+        fee_merkle_tree_root: FeeMerkleCommitment,
+        accounts: &[FeeAccount],
+    ) -> anyhow::Result<Vec<FeeAccountProof>> {
+        let mut tree = FeeMerkleTree::from_commitment(fee_merkle_tree_root);
+        for account in accounts {
+            let path = self
+                .get::<<FeeMerkleTree as MerkleTreeScheme>::MembershipProof>(
+                    retry,
+                    &format!("fee-state/{height}/{account}"),
+                )
+                .await?;
+            match path.proof.first() {
+                Some(MerkleNode::Leaf { pos, elem, .. }) => tree.remember(*pos, *elem, &path),
+                Some(MerkleNode::Empty) => tree.non_membership_remember(*account, &path),
+                _ => bail!("invalid proof for fee account {account}"),
+            }
+            .context(format!("invalid proof for fee account {account}"))?;
+        }
+
+        accounts
+            .iter()
+            .map(|account| {
+                let (proof, _) = FeeAccountProof::prove(&tree, (*account).into())
+                    .context(format!("response missing fee account {account}"))?;
+                Ok(proof)
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(skip(self, _instance, mt))]
+    async fn try_remember_blocks_merkle_tree(
+        &self,
+        retry: usize,
+        _instance: &NodeState,
+        height: u64,
+        _view: ViewNumber,
+        mt: &mut BlockMerkleTree,
+    ) -> anyhow::Result<()> {
+        let index = mt.num_leaves() - 1;
+        let frontier = self
+            .get::<BlocksFrontier>(retry, &format!("block-state/{height}/{index}"))
+            .await?;
+        let elem = frontier
+            .elem()
+            .context("provided frontier is missing leaf element")?;
+        mt.remember(index, *elem, &frontier)
+            .context("verifying block proof")?;
+        Ok(())
+    }
+
+    /// Always fails: the query service does not index chain configs by commitment, so other
+    /// providers have to be used to fetch them.
+    async fn try_fetch_chain_config(
+        &self,
+        _retry: usize,
+        commitment: Commitment<ChainConfig>,
+    ) -> anyhow::Result<ChainConfig> {
+        bail!("chain config {commitment} not available from archival query service")
+    }
+
+    async fn try_fetch_leaf_chain(
+        &self,
+        retry: usize,
+        height: u64,
+        _stake_table: HSStakeTable<SeqTypes>,
+        _success_threshold: U256,
+    ) -> anyhow::Result<Vec<Leaf2>> {
+        self.fetch_leaf_chain(retry, height).await
+    }
+
+    #[tracing::instrument(skip(self, _instance))]
+    async fn try_fetch_reward_accounts(
+        &self,
+        retry: usize,
+        _instance: &NodeState,
+        height: u64,
+        _view: ViewNumber,
+        reward_merkle_tree_root: RewardMerkleCommitment,
+        accounts: &[RewardAccount],
+    ) -> anyhow::Result<Vec<RewardAccountProof>> {
+        let mut tree = RewardMerkleTree::from_commitment(reward_merkle_tree_root);
+        for account in accounts {
+            let path = self
+                .get::<<RewardMerkleTree as MerkleTreeScheme>::MembershipProof>(
+                    retry,
+                    &format!("reward-state/{height}/{account}"),
+                )
+                .await?;
+            match path.proof.first() {
+                Some(MerkleNode::Leaf { pos, elem, .. }) => tree.remember(*pos, *elem, &path),
+                Some(MerkleNode::Empty) => tree.non_membership_remember(*account, &path),
+                _ => bail!("invalid proof for reward account {account}"),
+            }
+            .context(format!("invalid proof for reward account {account}"))?;
+        }
+
+        accounts
+            .iter()
+            .map(|account| {
+                let (proof, _) = RewardAccountProof::prove(&tree, (*account).into())
+                    .context(format!("response missing reward account {account}"))?;
+                Ok(proof)
+            })
+            .collect()
+    }
+
+    fn backoff(&self) -> &BackoffParams {
+        &self.backoff
+    }
+
+    fn name(&self) -> String {
+        format!("ArchivalCatchup({})", self.url)
+    }
+
+    fn is_local(&self) -> bool {
+        false
+    }
+}
+
 pub(crate) trait CatchupStorage: Sync {
     /// Get the state of the requested `accounts`.
     ///
@@ -570,32 +814,17 @@ impl<T> StateCatchup for SqlStateCatchup<T>
 where
     T: CatchupStorage + Send + Sync,
 {
-    async fn try_fetch_leaf(
+    async fn try_fetch_leaf_chain(
         &self,
         _retry: usize,
         height: u64,
-        stake_table: HSStakeTable<SeqTypes>,
-        success_threshold: U256,
-    ) -> anyhow::Result<Leaf2> {
-        // Get the leaf chain
-        let leaf_chain = self
-            .db
+        _stake_table: HSStakeTable<SeqTypes>,
+        _success_threshold: U256,
+    ) -> anyhow::Result<Vec<Leaf2>> {
+        self.db
             .get_leaf_chain(height)
             .await
-            .with_context(|| "failed to get leaf chain from DB")?;
-
-        // Verify the leaf chain
-        let leaf = verify_leaf_chain(
-            leaf_chain,
-            &stake_table,
-            success_threshold,
-            height,
-            &UpgradeLock::<SeqTypes, SequencerVersions<EpochVersion, EpochVersion>>::new(),
-        )
-        .await
-        .with_context(|| "failed to verify leaf chain")?;
-
-        Ok(leaf)
+            .with_context(|| "failed to get leaf chain from DB")
     }
     // TODO: add a test for the account proof validation
     // issue # 2102 (https://github.com/EspressoSystems/espresso-sequencer/issues/2102)
@@ -753,13 +982,13 @@ impl NullStateCatchup {
 
 #[async_trait]
 impl StateCatchup for NullStateCatchup {
-    async fn try_fetch_leaf(
+    async fn try_fetch_leaf_chain(
         &self,
         _retry: usize,
         _height: u64,
         _stake_table: HSStakeTable<SeqTypes>,
         _success_threshold: U256,
-    ) -> anyhow::Result<Leaf2> {
+    ) -> anyhow::Result<Vec<Leaf2>> {
         bail!("state catchup is disabled")
     }
 
@@ -822,11 +1051,23 @@ impl StateCatchup for NullStateCatchup {
     }
 }
 
+/// How [`ParallelStateCatchup`] queries its providers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QueryStrategy {
+    /// Query one provider at a time, in the order they were added, until one succeeds.
+    Sequential,
+    /// Query all providers at once and take the first successful response, cancelling the rest.
+    #[default]
+    FanOutFirstSuccess,
+}
+
 /// A catchup implementation that parallelizes requests to many providers.
 /// It returns the result of the first non-erroring provider to complete.
 #[derive(Clone)]
 pub struct ParallelStateCatchup {
     providers: Arc<Mutex<Vec<Arc<dyn StateCatchup>>>>,
+    strategy: QueryStrategy,
+    rejected: Arc<Box<dyn Counter>>,
 }
 
 impl ParallelStateCatchup {
@@ -834,42 +1075,85 @@ impl ParallelStateCatchup {
     pub fn new(providers: &[Arc<dyn StateCatchup>]) -> Self {
         Self {
             providers: Arc::new(Mutex::new(providers.to_vec())),
+            strategy: QueryStrategy::default(),
+            rejected: Arc::new(NoMetrics.create_counter("rejected_responses".into(), None)),
         }
     }
 
+    /// Set the strategy used to query providers.
+    pub fn with_strategy(mut self, strategy: QueryStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Count responses which fail verification in `metrics`.
+    pub fn with_metrics(mut self, metrics: &(impl Metrics + ?Sized)) -> Self {
+        self.rejected = Arc::new(
+            metrics
+                .subgroup("catchup".into())
+                .create_counter("rejected_responses".into(), None),
+        );
+        self
+    }
+
     /// Add a provider to the list of providers
     pub fn add_provider(&self, provider: Arc<dyn StateCatchup>) {
         self.providers.lock().push(provider);
     }
 
     /// Perform an async operation on all local providers, returning the first result to succeed
-    pub async fn on_local_providers<C, F, RT>(&self, closure: C) -> anyhow::Result<RT>
+    /// verification
+    pub async fn on_local_providers<C, F, V, VF, RT>(
+        &self,
+        closure: C,
+        verify: V,
+    ) -> anyhow::Result<RT>
     where
         C: Fn(Arc<dyn StateCatchup>) -> F + Clone + Send + Sync + 'static,
         F: Future<Output = anyhow::Result<RT>> + Send + 'static,
+        V: Fn(&RT) -> VF + Clone + Send + Sync + 'static,
+        VF: Future<Output = anyhow::Result<()>> + Send + 'static,
         RT: Send + Sync + 'static,
     {
-        self.on_providers(|provider| provider.is_local(), closure)
+        self.on_providers(|provider| provider.is_local(), closure, verify)
             .await
     }
 
     /// Perform an async operation on all remote providers, returning the first result to succeed
-    pub async fn on_remote_providers<C, F, RT>(&self, closure: C) -> anyhow::Result<RT>
+    /// verification
+    pub async fn on_remote_providers<C, F, V, VF, RT>(
+        &self,
+        closure: C,
+        verify: V,
+    ) -> anyhow::Result<RT>
     where
         C: Fn(Arc<dyn StateCatchup>) -> F + Clone + Send + Sync + 'static,
         F: Future<Output = anyhow::Result<RT>> + Send + 'static,
+        V: Fn(&RT) -> VF + Clone + Send + Sync + 'static,
+        VF: Future<Output = anyhow::Result<()>> + Send + 'static,
         RT: Send + Sync + 'static,
     {
-        self.on_providers(|provider| !provider.is_local(), closure)
+        self.on_providers(|provider| !provider.is_local(), closure, verify)
             .await
     }
 
     /// Perform an async operation on all providers matching the given predicate, returning the first result to succeed
-    pub async fn on_providers<P, C, F, RT>(&self, predicate: P, closure: C) -> anyhow::Result<RT>
+    /// verification
+    ///
+    /// A response which fails `verify` is counted as rejected and treated like an error, so the
+    /// provider which sent it is not used for the rest of this request.
+    pub async fn on_providers<P, C, F, V, VF, RT>(
+        &self,
+        predicate: P,
+        closure: C,
+        verify: V,
+    ) -> anyhow::Result<RT>
     where
         P: Fn(&Arc<dyn StateCatchup>) -> bool + Clone + Send + Sync + 'static,
         C: Fn(Arc<dyn StateCatchup>) -> F + Clone + Send + Sync + 'static,
         F: Future<Output = anyhow::Result<RT>> + Send + 'static,
+        V: Fn(&RT) -> VF + Clone + Send + Sync + 'static,
+        VF: Future<Output = anyhow::Result<()>> + Send + 'static,
         RT: Send + Sync + 'static,
     {
         // Make sure we have at least one provider
@@ -884,38 +1168,209 @@ impl ParallelStateCatchup {
             return Err(anyhow::anyhow!("no providers matched the given predicate"));
         }
 
-        // Spawn futures for each provider
-        let mut futures = FuturesUnordered::new();
-        for provider in providers {
+        let rejected = self.rejected.clone();
+        let closure = move |provider: Arc<dyn StateCatchup>| {
             let closure = closure.clone();
-            futures.push(AbortOnDropHandle::new(tokio::spawn(closure(provider))));
+            let verify = verify.clone();
+            let rejected = rejected.clone();
+            async move {
+                let name = provider.name();
+                let response = closure(provider).await?;
+                if let Err(err) = verify(&response).await {
+                    rejected.add(1);
+                    bail!("rejected response from {name}: {err:#}");
+                }
+                Ok(response)
+            }
+        };
+
+        match self.strategy {
+            QueryStrategy::Sequential => {
+                for provider in providers {
+                    match closure(provider).await {
+                        Ok(res) => return Ok(res),
+                        Err(err) => {
+                            warn!("Failed to fetch data: {err:#}. Trying next provider...");
+                        },
+                    }
+                }
+                Err(anyhow::anyhow!("no providers returned a successful result"))
+            },
+            QueryStrategy::FanOutFirstSuccess => {
+                // Spawn a task for each provider. Dropping the handles of the tasks which have not
+                // finished yet, once we have a result, aborts them.
+                let futures = providers.into_iter().map(|provider| {
+                    let handle = AbortOnDropHandle::new(tokio::spawn(closure(provider)));
+                    async move {
+                        let result = handle
+                            .await
+                            .context("failed to join on provider")
+                            .and_then(|result| result);
+                        if let Err(err) = &result {
+                            warn!("Failed to fetch data: {err:#}. Trying next provider...");
+                        }
+                        result
+                    }
+                    .boxed()
+                });
+                let (result, _pending) = select_ok(futures)
+                    .await
+                    .context("no providers returned a successful result")?;
+                Ok(result)
+            },
         }
+    }
+}
 
-        // Return the first successful result
-        while let Some(result) = futures.next().await {
-            // Unwrap the inner (join) result
-            let result = match result {
-                Ok(res) => res,
-                Err(err) => {
-                    warn!("Failed to join on provider: {err:#}. Trying next provider...");
-                    continue;
-                },
-            };
+/// Check that a leaf chain from a catchup provider decides a leaf at `height`.
+///
+/// The leaf has to be decided by a chain of QCs signed by `stake_table`, starting with one over
+/// the commitment of that very leaf, so a provider can't make up a leaf, even by attaching a real
+/// QC to it.
+async fn verify_leaf(
+    leaf_chain: Vec<Leaf2>,
+    height: u64,
+    stake_table: HSStakeTable<SeqTypes>,
+    success_threshold: U256,
+) -> anyhow::Result<()> {
+    verify_leaf_chain(
+        leaf_chain,
+        &stake_table,
+        success_threshold,
+        height,
+        &UpgradeLock::<SeqTypes, SequencerVersions<EpochVersion, EpochVersion>>::new(),
+    )
+    .await
+    .with_context(|| format!("invalid leaf chain at height {height}"))?;
+    Ok(())
+}
 
-            // If a provider fails, print why
-            let result = match result {
-                Ok(res) => res,
-                Err(err) => {
-                    warn!("Failed to fetch data: {err:#}. Trying next provider...");
-                    continue;
-                },
-            };
+/// Take the leaf at `height` out of a leaf chain which passed [`verify_leaf`].
+fn decided_leaf(leaf_chain: Vec<Leaf2>, height: u64) -> anyhow::Result<Leaf2> {
+    leaf_chain
+        .into_iter()
+        .find(|leaf| leaf.height() == height)
+        .with_context(|| format!("leaf chain is missing the leaf at height {height}"))
+}
 
-            return Ok(result);
-        }
+/// Fetch the leaf chain at `height` from a single provider, without retrying.
+async fn try_fetch_leaf_chain_from(
+    provider: Arc<dyn StateCatchup>,
+    retry: usize,
+    height: u64,
+    stake_table: HSStakeTable<SeqTypes>,
+    success_threshold: U256,
+) -> anyhow::Result<Vec<Leaf2>> {
+    provider
+        .try_fetch_leaf_chain(retry, height, stake_table, success_threshold)
+        .await
+}
+
+/// Fetch the leaf chain at `height` from a single provider, retrying on failure.
+async fn fetch_leaf_chain_from(
+    provider: Arc<dyn StateCatchup>,
+    height: u64,
+    stake_table: HSStakeTable<SeqTypes>,
+    success_threshold: U256,
+) -> anyhow::Result<Vec<Leaf2>> {
+    let backoff = *provider.backoff();
+    backoff
+        .retry(provider, |provider, retry| {
+            provider.try_fetch_leaf_chain(retry, height, stake_table.clone(), success_threshold)
+        })
+        .await
+}
+
+/// Fetch the chain config with `commitment` from a single provider, without retrying.
+async fn try_fetch_chain_config_from(
+    provider: Arc<dyn StateCatchup>,
+    retry: usize,
+    commitment: Commitment<ChainConfig>,
+) -> anyhow::Result<ChainConfig> {
+    provider.try_fetch_chain_config(retry, commitment).await
+}
+
+/// Fetch the chain config with `commitment` from a single provider, retrying on failure.
+async fn fetch_chain_config_from(
+    provider: Arc<dyn StateCatchup>,
+    commitment: Commitment<ChainConfig>,
+) -> anyhow::Result<ChainConfig> {
+    provider.fetch_chain_config(commitment).await
+}
+
+/// Check that a chain config from a catchup provider matches the requested commitment.
+fn verify_chain_config(
+    cf: &ChainConfig,
+    commitment: Commitment<ChainConfig>,
+) -> anyhow::Result<()> {
+    ensure!(
+        cf.commit() == commitment,
+        "received chain config with mismatched commitment: expected {commitment}, got {}",
+        cf.commit()
+    );
+    Ok(())
+}
+
+/// Check that remembering a frontier from a catchup provider did not change the block Merkle root.
+fn verify_blocks_merkle_tree(
+    mt: &BlockMerkleTree,
+    root: <BlockMerkleTree as MerkleTreeScheme>::Commitment,
+) -> anyhow::Result<()> {
+    ensure!(
+        mt.commitment() == root,
+        "block Merkle tree does not match the expected root"
+    );
+    Ok(())
+}
 
-        Err(anyhow::anyhow!("no providers returned a successful result"))
+/// Check that a catchup provider returned valid proofs for exactly the requested fee accounts.
+fn verify_fee_proofs(
+    proofs: &[FeeAccountProof],
+    accounts: &[FeeAccount],
+    root: FeeMerkleCommitment,
+) -> anyhow::Result<()> {
+    ensure!(
+        proofs.len() == accounts.len(),
+        "expected {} fee account proofs, got {}",
+        accounts.len(),
+        proofs.len()
+    );
+    for (proof, account) in proofs.iter().zip(accounts) {
+        ensure!(
+            FeeAccount(proof.account) == *account,
+            "received proof for fee account {}, expected {account}",
+            proof.account
+        );
+        proof
+            .verify(&root)
+            .context(format!("invalid proof for fee account {account}"))?;
     }
+    Ok(())
+}
+
+/// Check that a catchup provider returned valid proofs for exactly the requested reward accounts.
+fn verify_reward_proofs(
+    proofs: &[RewardAccountProof],
+    accounts: &[RewardAccount],
+    root: RewardMerkleCommitment,
+) -> anyhow::Result<()> {
+    ensure!(
+        proofs.len() == accounts.len(),
+        "expected {} reward account proofs, got {}",
+        accounts.len(),
+        proofs.len()
+    );
+    for (proof, account) in proofs.iter().zip(accounts) {
+        ensure!(
+            RewardAccount(proof.account) == *account,
+            "received proof for reward account {}, expected {account}",
+            proof.account
+        );
+        proof
+            .verify(&root)
+            .context(format!("invalid proof for reward account {account}"))?;
+    }
+    Ok(())
 }
 
 macro_rules! clone {
@@ -931,22 +1386,31 @@ macro_rules! clone {
 /// It returns the result of the first provider to complete.
 #[async_trait]
 impl StateCatchup for ParallelStateCatchup {
-    async fn try_fetch_leaf(
+    async fn try_fetch_leaf_chain(
         &self,
         retry: usize,
         height: u64,
         stake_table: HSStakeTable<SeqTypes>,
         success_threshold: U256,
-    ) -> anyhow::Result<Leaf2> {
-        // Try fetching the leaf on the local providers first
+    ) -> anyhow::Result<Vec<Leaf2>> {
+        let verify = clone! {(stake_table) move |leaf_chain: &Vec<Leaf2>| {
+            verify_leaf(leaf_chain.clone(), height, stake_table.clone(), success_threshold)
+        }};
+
+        // Try fetching the leaf chain on the local providers first
         let local_result = self
-            .on_local_providers(clone! {(stake_table) move |provider| {
-                clone!{(stake_table) async move {
-                    provider
-                        .try_fetch_leaf(retry, height, stake_table, success_threshold)
-                        .await
-                }}
-            }})
+            .on_local_providers(
+                clone! {(stake_table) move |provider| {
+                    try_fetch_leaf_chain_from(
+                        provider,
+                        retry,
+                        height,
+                        stake_table.clone(),
+                        success_threshold,
+                    )
+                }},
+                verify.clone(),
+            )
             .await;
 
         // Check if we were successful locally
@@ -955,16 +1419,35 @@ impl StateCatchup for ParallelStateCatchup {
         }
 
         // If that fails, try the remote ones
-        self.on_remote_providers(clone! {(stake_table) move |provider| {
-            clone!{(stake_table) async move {
-                provider
-                    .try_fetch_leaf(retry, height, stake_table, success_threshold)
-                    .await
-            }}
-        }})
+        self.on_remote_providers(
+            move |provider| {
+                try_fetch_leaf_chain_from(
+                    provider,
+                    retry,
+                    height,
+                    stake_table.clone(),
+                    success_threshold,
+                )
+            },
+            verify,
+        )
         .await
     }
 
+    async fn try_fetch_leaf(
+        &self,
+        retry: usize,
+        height: u64,
+        stake_table: HSStakeTable<SeqTypes>,
+        success_threshold: U256,
+    ) -> anyhow::Result<Leaf2> {
+        // The leaf chain has already been verified against the stake table
+        let leaf_chain = self
+            .try_fetch_leaf_chain(retry, height, stake_table, success_threshold)
+            .await?;
+        decided_leaf(leaf_chain, height)
+    }
+
     async fn try_fetch_accounts(
         &self,
         retry: usize,
@@ -976,21 +1459,27 @@ impl StateCatchup for ParallelStateCatchup {
     ) -> anyhow::Result<Vec<FeeAccountProof>> {
         // Try to get the accounts on local providers first
         let accounts_vec = accounts.to_vec();
+        let verify = clone! {(accounts_vec) move |proofs: &Vec<FeeAccountProof>| {
+            ready(verify_fee_proofs(proofs, &accounts_vec, fee_merkle_tree_root))
+        }};
         let local_result = self
-            .on_local_providers(clone! {(instance, accounts_vec) move |provider| {
-                clone! {(instance, accounts_vec) async move {
-                    provider
-                        .try_fetch_accounts(
-                            retry,
-                            &instance,
-                            height,
-                            view,
-                            fee_merkle_tree_root,
-                            &accounts_vec,
-                        )
-                        .await
-                }}
-            }})
+            .on_local_providers(
+                clone! {(instance, accounts_vec) move |provider| {
+                    clone! {(instance, accounts_vec) async move {
+                        provider
+                            .try_fetch_accounts(
+                                retry,
+                                &instance,
+                                height,
+                                view,
+                                fee_merkle_tree_root,
+                                &accounts_vec,
+                            )
+                            .await
+                    }}
+                }},
+                verify.clone(),
+            )
             .await;
 
         // Check if we were successful locally
@@ -999,19 +1488,22 @@ impl StateCatchup for ParallelStateCatchup {
         }
 
         // If that fails, try the remote ones
-        self.on_remote_providers(clone! {(instance, accounts_vec) move |provider| {
-            clone!{(instance, accounts_vec) async move {
-                provider
-                .try_fetch_accounts(
-                    retry,
-                    &instance,
-                    height,
-                    view,
-                    fee_merkle_tree_root,
-                    &accounts_vec,
-                ).await
-            }}
-        }})
+        self.on_remote_providers(
+            clone! {(instance, accounts_vec) move |provider| {
+                clone!{(instance, accounts_vec) async move {
+                    provider
+                    .try_fetch_accounts(
+                        retry,
+                        &instance,
+                        height,
+                        view,
+                        fee_merkle_tree_root,
+                        &accounts_vec,
+                    ).await
+                }}
+            }},
+            verify,
+        )
         .await
     }
 
@@ -1023,26 +1515,33 @@ impl StateCatchup for ParallelStateCatchup {
         view: ViewNumber,
         mt: &mut BlockMerkleTree,
     ) -> anyhow::Result<()> {
+        let root = mt.commitment();
+        let verify =
+            move |remembered: &BlockMerkleTree| ready(verify_blocks_merkle_tree(remembered, root));
+
         // Try to remember the blocks merkle tree on local providers first
         let local_result = self
-            .on_local_providers(clone! {(mt, instance) move |provider| {
-                let mut mt = mt.clone();
-                clone! {(instance) async move {
-                    // Perform the call
-                    provider
-                        .try_remember_blocks_merkle_tree(
-                            retry,
-                            &instance,
-                            height,
-                            view,
-                            &mut mt,
-                        )
-                        .await?;
-
-                    // Return the merkle tree so we can modify it
-                    Ok(mt)
-                }}
-            }})
+            .on_local_providers(
+                clone! {(mt, instance) move |provider| {
+                    let mut mt = mt.clone();
+                    clone! {(instance) async move {
+                        // Perform the call
+                        provider
+                            .try_remember_blocks_merkle_tree(
+                                retry,
+                                &instance,
+                                height,
+                                view,
+                                &mut mt,
+                            )
+                            .await?;
+
+                        // Return the merkle tree so we can modify it
+                        Ok(mt)
+                    }}
+                }},
+                verify.clone(),
+            )
             .await;
 
         // Check if we were successful locally
@@ -1055,24 +1554,27 @@ impl StateCatchup for ParallelStateCatchup {
 
         // If that fails, try the remote ones
         let remote_result = self
-            .on_remote_providers(clone! {(mt, instance) move |provider| {
-                let mut mt = mt.clone();
-                clone!{(instance) async move {
-                    // Perform the call
-                    provider
-                    .try_remember_blocks_merkle_tree(
-                        retry,
-                        &instance,
-                        height,
-                        view,
-                        &mut mt,
-                    )
-                    .await?;
+            .on_remote_providers(
+                clone! {(mt, instance) move |provider| {
+                    let mut mt = mt.clone();
+                    clone!{(instance) async move {
+                        // Perform the call
+                        provider
+                        .try_remember_blocks_merkle_tree(
+                            retry,
+                            &instance,
+                            height,
+                            view,
+                            &mut mt,
+                        )
+                        .await?;
 
-                    // Return the merkle tree
-                    Ok(mt)
-                }}
-            }})
+                        // Return the merkle tree
+                        Ok(mt)
+                    }}
+                }},
+                verify,
+            )
             .await?;
 
         // Update the original, local merkle tree
@@ -1086,11 +1588,14 @@ impl StateCatchup for ParallelStateCatchup {
         retry: usize,
         commitment: Commitment<ChainConfig>,
     ) -> anyhow::Result<ChainConfig> {
+        let verify = move |cf: &ChainConfig| ready(verify_chain_config(cf, commitment));
+
         // Try fetching the chain config on the local providers first
         let local_result = self
-            .on_local_providers(move |provider| async move {
-                provider.try_fetch_chain_config(retry, commitment).await
-            })
+            .on_local_providers(
+                move |provider| try_fetch_chain_config_from(provider, retry, commitment),
+                verify.clone(),
+            )
             .await;
 
         // Check if we were successful locally
@@ -1099,9 +1604,10 @@ impl StateCatchup for ParallelStateCatchup {
         }
 
         // If that fails, try the remote ones
-        self.on_remote_providers(move |provider| async move {
-            provider.try_fetch_chain_config(retry, commitment).await
-        })
+        self.on_remote_providers(
+            move |provider| try_fetch_chain_config_from(provider, retry, commitment),
+            verify,
+        )
         .await
     }
 
@@ -1116,21 +1622,27 @@ impl StateCatchup for ParallelStateCatchup {
     ) -> anyhow::Result<Vec<RewardAccountProof>> {
         // Try to get the accounts on local providers first
         let accounts_vec = accounts.to_vec();
+        let verify = clone! {(accounts_vec) move |proofs: &Vec<RewardAccountProof>| {
+            ready(verify_reward_proofs(proofs, &accounts_vec, reward_merkle_tree_root))
+        }};
         let local_result = self
-            .on_local_providers(clone! {(instance, accounts_vec) move |provider| {
-                clone! {(instance, accounts_vec) async move {
-                    provider
-                        .try_fetch_reward_accounts(
-                            retry,
-                            &instance,
-                            height,
-                            view,
-                            reward_merkle_tree_root,
-                            &accounts_vec,
-                        )
-                        .await
-                }}
-            }})
+            .on_local_providers(
+                clone! {(instance, accounts_vec) move |provider| {
+                    clone! {(instance, accounts_vec) async move {
+                        provider
+                            .try_fetch_reward_accounts(
+                                retry,
+                                &instance,
+                                height,
+                                view,
+                                reward_merkle_tree_root,
+                                &accounts_vec,
+                            )
+                            .await
+                    }}
+                }},
+                verify.clone(),
+            )
             .await;
 
         // Check if we were successful locally
@@ -1139,19 +1651,22 @@ impl StateCatchup for ParallelStateCatchup {
         }
 
         // If that fails, try the remote ones
-        self.on_remote_providers(clone! {(instance, accounts_vec) move |provider| {
-            clone!{(instance, accounts_vec) async move {
-                provider
-                .try_fetch_reward_accounts(
-                    retry,
-                    &instance,
-                    height,
-                    view,
-                    reward_merkle_tree_root,
-                    &accounts_vec,
-                ).await
-            }}
-        }})
+        self.on_remote_providers(
+            clone! {(instance, accounts_vec) move |provider| {
+                clone!{(instance, accounts_vec) async move {
+                    provider
+                    .try_fetch_reward_accounts(
+                        retry,
+                        &instance,
+                        height,
+                        view,
+                        reward_merkle_tree_root,
+                        &accounts_vec,
+                    ).await
+                }}
+            }},
+            verify,
+        )
         .await
     }
 
@@ -1181,21 +1696,27 @@ impl StateCatchup for ParallelStateCatchup {
     ) -> anyhow::Result<Vec<FeeAccountProof>> {
         // Try to get the accounts on local providers first
         let accounts_vec = accounts.to_vec();
+        let verify = clone! {(accounts_vec) move |proofs: &Vec<FeeAccountProof>| {
+            ready(verify_fee_proofs(proofs, &accounts_vec, fee_merkle_tree_root))
+        }};
         let local_result = self
-            .on_local_providers(clone! {(instance, accounts_vec) move |provider| {
-                clone! {(instance, accounts_vec) async move {
-                    provider
-                        .try_fetch_accounts(
-                            0,
-                            &instance,
-                            height,
-                            view,
-                            fee_merkle_tree_root,
-                            &accounts_vec,
-                        )
-                        .await
-                }}
-            }})
+            .on_local_providers(
+                clone! {(instance, accounts_vec) move |provider| {
+                    clone! {(instance, accounts_vec) async move {
+                        provider
+                            .try_fetch_accounts(
+                                0,
+                                &instance,
+                                height,
+                                view,
+                                fee_merkle_tree_root,
+                                &accounts_vec,
+                            )
+                            .await
+                    }}
+                }},
+                verify.clone(),
+            )
             .await;
 
         // Check if we were successful locally
@@ -1204,18 +1725,21 @@ impl StateCatchup for ParallelStateCatchup {
         }
 
         // If that fails, try the remote ones (with retry)
-        self.on_remote_providers(clone! {(instance, accounts_vec) move |provider| {
-            clone!{(instance, accounts_vec) async move {
-                provider
-                .fetch_accounts(
-                    &instance,
-                    height,
-                    view,
-                    fee_merkle_tree_root,
-                    accounts_vec,
-                ).await
-            }}
-        }})
+        self.on_remote_providers(
+            clone! {(instance, accounts_vec) move |provider| {
+                clone!{(instance, accounts_vec) async move {
+                    provider
+                    .fetch_accounts(
+                        &instance,
+                        height,
+                        view,
+                        fee_merkle_tree_root,
+                        accounts_vec,
+                    ).await
+                }}
+            }},
+            verify,
+        )
         .await
     }
 
@@ -1225,42 +1749,59 @@ impl StateCatchup for ParallelStateCatchup {
         stake_table: HSStakeTable<SeqTypes>,
         success_threshold: U256,
     ) -> anyhow::Result<Leaf2> {
-        // Try fetching the leaf on the local providers first
+        let verify = clone! {(stake_table) move |leaf_chain: &Vec<Leaf2>| {
+            verify_leaf(leaf_chain.clone(), height, stake_table.clone(), success_threshold)
+        }};
+
+        // Try fetching the leaf chain on the local providers first
         let local_result = self
-            .on_local_providers(clone! {(stake_table) move |provider| {
-                clone!{(stake_table) async move {
-                    provider
-                        .try_fetch_leaf(0, height, stake_table, success_threshold)
-                        .await
-                }}
-            }})
+            .on_local_providers(
+                clone! {(stake_table) move |provider| {
+                    try_fetch_leaf_chain_from(
+                        provider,
+                        0,
+                        height,
+                        stake_table.clone(),
+                        success_threshold,
+                    )
+                }},
+                verify.clone(),
+            )
             .await;
 
-        // Check if we were successful locally
-        if local_result.is_ok() {
-            return local_result;
-        }
-
         // If that fails, try the remote ones (with retry)
-        self.on_remote_providers(clone! {(stake_table) move |provider| {
-         clone!{(stake_table) async move {
-             provider
-                 .fetch_leaf(height, stake_table, success_threshold)
-                 .await
-         }}
-        }})
-        .await
+        let leaf_chain = match local_result {
+            Ok(leaf_chain) => leaf_chain,
+            Err(_) => {
+                self.on_remote_providers(
+                    move |provider| {
+                        fetch_leaf_chain_from(
+                            provider,
+                            height,
+                            stake_table.clone(),
+                            success_threshold,
+                        )
+                    },
+                    verify,
+                )
+                .await?
+            },
+        };
+        decided_leaf(leaf_chain, height)
     }
 
     async fn fetch_chain_config(
         &self,
         commitment: Commitment<ChainConfig>,
     ) -> anyhow::Result<ChainConfig> {
+        let verify = move |cf: &ChainConfig| ready(verify_chain_config(cf, commitment));
+
         // Try fetching the chain config on the local providers first
         let local_result = self
-            .on_local_providers(move |provider| async move {
-                provider.try_fetch_chain_config(0, commitment).await
-            })
+            .on_local_providers(
+                move |provider| try_fetch_chain_config_from(provider, 0, commitment),
+                verify.clone(),
+            )
             .await;
 
         // Check if we were successful locally
@@ -1269,9 +1810,10 @@ impl StateCatchup for ParallelStateCatchup {
         }
 
         // If that fails, try the remote ones (with retry)
-        self.on_remote_providers(move |provider| async move {
-            provider.fetch_chain_config(commitment).await
-        })
+        self.on_remote_providers(
+            move |provider| fetch_chain_config_from(provider, commitment),
+            verify,
+        )
         .await
     }
 
@@ -1285,21 +1827,27 @@ impl StateCatchup for ParallelStateCatchup {
     ) -> anyhow::Result<Vec<RewardAccountProof>> {
         // Try to get the accounts on local providers first
         let accounts_vec = accounts.to_vec();
+        let verify = clone! {(accounts_vec) move |proofs: &Vec<RewardAccountProof>| {
+            ready(verify_reward_proofs(proofs, &accounts_vec, reward_merkle_tree_root))
+        }};
         let local_result = self
-            .on_local_providers(clone! {(instance, accounts_vec) move |provider| {
-                clone! {(instance, accounts_vec) async move {
-                    provider
-                        .try_fetch_reward_accounts(
-                            0,
-                            &instance,
-                            height,
-                            view,
-                            reward_merkle_tree_root,
-                            &accounts_vec,
-                        )
-                        .await
-                }}
-            }})
+            .on_local_providers(
+                clone! {(instance, accounts_vec) move |provider| {
+                    clone! {(instance, accounts_vec) async move {
+                        provider
+                            .try_fetch_reward_accounts(
+                                0,
+                                &instance,
+                                height,
+                                view,
+                                reward_merkle_tree_root,
+                                &accounts_vec,
+                            )
+                            .await
+                    }}
+                }},
+                verify.clone(),
+            )
             .await;
 
         // Check if we were successful locally
@@ -1308,18 +1856,21 @@ impl StateCatchup for ParallelStateCatchup {
         }
 
         // If that fails, try the remote ones (with retry)
-        self.on_remote_providers(clone! {(instance, accounts_vec) move |provider| {
-            clone!{(instance, accounts_vec) async move {
-                provider
-                .fetch_reward_accounts(
-                    &instance,
-                    height,
-                    view,
-                    reward_merkle_tree_root,
-                    accounts_vec,
-                ).await
-            }}
-        }})
+        self.on_remote_providers(
+            clone! {(instance, accounts_vec) move |provider| {
+                clone!{(instance, accounts_vec) async move {
+                    provider
+                    .fetch_reward_accounts(
+                        &instance,
+                        height,
+                        view,
+                        reward_merkle_tree_root,
+                        accounts_vec,
+                    ).await
+                }}
+            }},
+            verify,
+        )
         .await
     }
 
@@ -1330,26 +1881,33 @@ impl StateCatchup for ParallelStateCatchup {
         view: ViewNumber,
         mt: &mut BlockMerkleTree,
     ) -> anyhow::Result<()> {
+        let root = mt.commitment();
+        let verify =
+            move |remembered: &BlockMerkleTree| ready(verify_blocks_merkle_tree(remembered, root));
+
         // Try to remember the blocks merkle tree on local providers first
         let local_result = self
-            .on_local_providers(clone! {(mt, instance) move |provider| {
-                let mut mt = mt.clone();
-                clone! {(instance) async move {
-                    // Perform the call
-                    provider
-                        .try_remember_blocks_merkle_tree(
-                            0,
-                            &instance,
-                            height,
-                            view,
-                            &mut mt,
-                        )
-                        .await?;
-
-                    // Return the merkle tree so we can modify it
-                    Ok(mt)
-                }}
-            }})
+            .on_local_providers(
+                clone! {(mt, instance) move |provider| {
+                    let mut mt = mt.clone();
+                    clone! {(instance) async move {
+                        // Perform the call
+                        provider
+                            .try_remember_blocks_merkle_tree(
+                                0,
+                                &instance,
+                                height,
+                                view,
+                                &mut mt,
+                            )
+                            .await?;
+
+                        // Return the merkle tree so we can modify it
+                        Ok(mt)
+                    }}
+                }},
+                verify.clone(),
+            )
             .await;
 
         // Check if we were successful locally
@@ -1363,23 +1921,26 @@ impl StateCatchup for ParallelStateCatchup {
 
         // If that fails, try the remote ones (with retry)
         let remote_result = self
-            .on_remote_providers(clone! {(mt, instance) move |provider| {
-                let mut mt = mt.clone();
-                clone!{(instance) async move {
-                    // Perform the call
-                    provider
-                    .remember_blocks_merkle_tree(
-                        &instance,
-                        height,
-                        view,
-                        &mut mt,
-                    )
-                    .await?;
+            .on_remote_providers(
+                clone! {(mt, instance) move |provider| {
+                    let mut mt = mt.clone();
+                    clone!{(instance) async move {
+                        // Perform the call
+                        provider
+                        .remember_blocks_merkle_tree(
+                            &instance,
+                            height,
+                            view,
+                            &mut mt,
+                        )
+                        .await?;
 
-                    // Return the merkle tree
-                    Ok(mt)
-                }}
-            }})
+                        // Return the merkle tree
+                        Ok(mt)
+                    }}
+                }},
+                verify,
+            )
             .await?;
 
         // Update the original, local merkle tree
@@ -1511,7 +2072,130 @@ pub async fn add_reward_accounts_to_state<
 
 #[cfg(test)]
 mod test {
+    use std::{
+        marker::PhantomData,
+        sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+    };
+
+    use bitvec::bitvec;
+    use espresso_types::{MockSequencerVersions, PubKey};
+    use hotshot_query_service::metrics::PrometheusMetrics;
+    use hotshot_types::{
+        data::{QuorumProposal2, QuorumProposalWrapper},
+        light_client::StateKeyPair,
+        simple_certificate::QuorumCertificate2,
+        simple_vote::{QuorumData2, VersionedVoteData},
+        traits::signature_key::SignatureKey,
+        PeerConfig,
+    };
+    use portpicker::pick_unused_port;
+    use sequencer_utils::test_utils::setup_test;
+    use tide_disco::{Api, App, StatusCode};
+    use tokio::time::sleep;
+
     use super::*;
+    use crate::SequencerApiVersion;
+
+    /// A remote provider which serves a fixed chain config after a delay.
+    #[derive(Debug)]
+    struct DelayedCatchup {
+        delay: Duration,
+        chain_config: ChainConfig,
+        /// The number of responses this provider has finished serving
+        served: Arc<AtomicUsize>,
+        leaf_chain: Option<Vec<Leaf2>>,
+        backoff: BackoffParams,
+    }
+
+    impl DelayedCatchup {
+        fn new(delay: Duration, chain_config: ChainConfig) -> Self {
+            Self {
+                delay,
+                chain_config,
+                served: Default::default(),
+                leaf_chain: None,
+                backoff: BackoffParams::disabled(),
+            }
+        }
+
+        fn with_leaf_chain(mut self, leaf_chain: Vec<Leaf2>) -> Self {
+            self.leaf_chain = Some(leaf_chain);
+            self
+        }
+    }
+
+    #[async_trait]
+    impl StateCatchup for DelayedCatchup {
+        async fn try_fetch_leaf_chain(
+            &self,
+            _retry: usize,
+            _height: u64,
+            _stake_table: HSStakeTable<SeqTypes>,
+            _success_threshold: U256,
+        ) -> anyhow::Result<Vec<Leaf2>> {
+            let leaf_chain = self.leaf_chain.clone().context("not supported")?;
+            sleep(self.delay).await;
+            self.served.fetch_add(1, AtomicOrdering::SeqCst);
+            Ok(leaf_chain)
+        }
+
+        async fn try_fetch_accounts(
+            &self,
+            _retry: usize,
+            _instance: &NodeState,
+            _height: u64,
+            _view: ViewNumber,
+            _fee_merkle_tree_root: FeeMerkleCommitment,
+            _account: &[FeeAccount],
+        ) -> anyhow::Result<Vec<FeeAccountProof>> {
+            bail!("not supported")
+        }
+
+        async fn try_fetch_reward_accounts(
+            &self,
+            _retry: usize,
+            _instance: &NodeState,
+            _height: u64,
+            _view: ViewNumber,
+            _reward_merkle_tree_root: RewardMerkleCommitment,
+            _account: &[RewardAccount],
+        ) -> anyhow::Result<Vec<RewardAccountProof>> {
+            bail!("not supported")
+        }
+
+        async fn try_remember_blocks_merkle_tree(
+            &self,
+            _retry: usize,
+            _instance: &NodeState,
+            _height: u64,
+            _view: ViewNumber,
+            _mt: &mut BlockMerkleTree,
+        ) -> anyhow::Result<()> {
+            bail!("not supported")
+        }
+
+        async fn try_fetch_chain_config(
+            &self,
+            _retry: usize,
+            _commitment: Commitment<ChainConfig>,
+        ) -> anyhow::Result<ChainConfig> {
+            sleep(self.delay).await;
+            self.served.fetch_add(1, AtomicOrdering::SeqCst);
+            Ok(self.chain_config)
+        }
+
+        fn backoff(&self) -> &BackoffParams {
+            &self.backoff
+        }
+
+        fn name(&self) -> String {
+            format!("DelayedCatchup({:?})", self.delay)
+        }
+
+        fn is_local(&self) -> bool {
+            false
+        }
+    }
 
     #[test]
     fn test_peer_priority() {
@@ -1529,4 +2213,286 @@ mod test {
         assert_eq!(peers.pop(), Some((0, good_peer)));
         assert_eq!(peers.pop(), Some((1, bad_peer)));
     }
+
+    /// Build `len` leaves, starting with genesis, each in the view after its parent and justified
+    /// by a QC signed by the single node `key`.
+    ///
+    /// Each leaf is returned with the QC of the next leaf signing it, like the query service does.
+    async fn signed_leaf_chain(
+        len: u64,
+        (pub_key, priv_key): &(PubKey, <PubKey as SignatureKey>::PrivateKey),
+    ) -> Vec<LeafQueryData<SeqTypes>> {
+        let upgrade_lock =
+            UpgradeLock::<SeqTypes, SequencerVersions<EpochVersion, EpochVersion>>::new();
+        let stake_table = [pub_key.stake_table_entry(U256::from(1))];
+        let qc_pp = PubKey::public_parameter(&stake_table, U256::from(1));
+        let (qc_pp, upgrade_lock) = (&qc_pp, &upgrade_lock);
+        let sign = move |leaf: &Leaf2| {
+            let data = QuorumData2 {
+                leaf_commit: Committable::commit(leaf),
+                epoch: None,
+                block_number: Some(leaf.height()),
+            };
+            let view = leaf.view_number();
+            async move {
+                let commit = VersionedVoteData::new(data.clone(), view, upgrade_lock)
+                    .await
+                    .unwrap()
+                    .commit();
+                let sig = PubKey::sign(priv_key, commit.as_ref()).unwrap();
+                let qc_sig = PubKey::assemble(qc_pp, bitvec![1].as_bitslice(), &[sig]);
+                QuorumCertificate2::new(
+                    data,
+                    Commitment::from_raw(commit.into()),
+                    view,
+                    Some(qc_sig),
+                    PhantomData,
+                )
+            }
+        };
+
+        let genesis =
+            Leaf2::genesis::<MockSequencerVersions>(&ValidatedState::default(), &NodeState::mock())
+                .await;
+        let mut leaves = vec![genesis];
+        for height in 1..len {
+            let parent = leaves.last().unwrap().clone();
+            let mut block_header = parent.block_header().clone();
+            *block_header.height_mut() = height;
+            leaves.push(Leaf2::from_quorum_proposal(&QuorumProposalWrapper {
+                proposal: QuorumProposal2 {
+                    block_header,
+                    view_number: parent.view_number() + 1,
+                    epoch: None,
+                    justify_qc: sign(&parent).await,
+                    next_epoch_justify_qc: None,
+                    upgrade_certificate: None,
+                    view_change_evidence: None,
+                    next_drb_result: None,
+                    state_cert: None,
+                },
+            }));
+        }
+
+        let mut chain = vec![];
+        for leaf in leaves {
+            let qc = sign(&leaf).await;
+            chain.push(LeafQueryData::new(leaf, qc).unwrap());
+        }
+        chain
+    }
+
+    /// Serve just the `leaf/:height` route of the availability API from `leaves`.
+    async fn serve_leaves(leaves: Vec<LeafQueryData<SeqTypes>>) -> (Url, AbortOnDropHandle<()>) {
+        let toml = toml::from_str::<toml::Value>(
+            r#"
+            [route.get_leaf]
+            PATH = ["leaf/:height"]
+            ":height" = "Integer"
+            "#,
+        )
+        .unwrap();
+        let mut api =
+            Api::<(), hotshot_query_service::Error, SequencerApiVersion>::new(toml).unwrap();
+        api.get("get_leaf", move |req, _state| {
+            let height: Option<usize> = req.integer_param("height").ok();
+            let leaf = height.and_then(|height| leaves.get(height).cloned());
+            async move {
+                leaf.ok_or_else(|| hotshot_query_service::Error::Custom {
+                    message: "leaf not available".into(),
+                    status: StatusCode::NOT_FOUND,
+                })
+            }
+            .boxed()
+        })
+        .unwrap();
+        let mut app = App::<_, hotshot_query_service::Error>::with_state(());
+        app.register_module("availability", api).unwrap();
+
+        let port = pick_unused_port().expect("no free port");
+        let url: Url = format!("http://localhost:{port}").parse().unwrap();
+        let server = AbortOnDropHandle::new(tokio::spawn({
+            let url = url.clone();
+            async move {
+                let _ = app.serve(url, SequencerApiVersion::instance()).await;
+            }
+        }));
+        (url, server)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_archival_catchup_leaf() {
+        setup_test();
+
+        let key = PubKey::generated_from_seed_indexed([0; 32], 0);
+        let stake_table = HSStakeTable::from(vec![PeerConfig::<SeqTypes> {
+            stake_table_entry: key.0.stake_table_entry(U256::from(1)),
+            state_ver_key: StateKeyPair::default().ver_key(),
+        }]);
+        let leaves = signed_leaf_chain(5, &key).await;
+        let (url, _server) = serve_leaves(leaves.clone()).await;
+        let catchup = ArchivalCatchup::<SequencerApiVersion>::new(url, BackoffParams::disabled());
+
+        // Leaf 1 is decided by the QCs of leaves 2 and 3.
+        let fetched = catchup
+            .try_fetch_leaf(0, 1, stake_table.clone(), U256::from(1))
+            .await
+            .unwrap();
+        assert_eq!(fetched, *leaves[1].leaf());
+
+        // Leaf 3 is not decided yet, there is no leaf 5 to complete the chain.
+        let err = catchup
+            .try_fetch_leaf(0, 3, stake_table.clone(), U256::from(1))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("failed to fetch leaf chain"),
+            "{err:#}"
+        );
+
+        // A chain signed by a node that is not in our stake table is rejected.
+        let forger = PubKey::generated_from_seed_indexed([1; 32], 0);
+        let forged = signed_leaf_chain(5, &forger).await;
+        let (url, _server) = serve_leaves(forged).await;
+        let catchup = ArchivalCatchup::<SequencerApiVersion>::new(url, BackoffParams::disabled());
+        let err = catchup
+            .try_fetch_leaf(0, 1, stake_table, U256::from(1))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("failed to verify leaf chain"),
+            "{err:#}"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_parallel_catchup_fan_out() {
+        setup_test();
+
+        let cf = ChainConfig::default();
+        let slow = Arc::new(DelayedCatchup::new(Duration::from_secs(2), cf));
+        let fast = Arc::new(DelayedCatchup::new(Duration::from_millis(10), cf));
+
+        let catchup =
+            ParallelStateCatchup::new(&[slow.clone() as Arc<dyn StateCatchup>, fast.clone()])
+                .with_strategy(QueryStrategy::FanOutFirstSuccess);
+        let fetched = catchup
+            .try_fetch_chain_config(0, cf.commit())
+            .await
+            .unwrap();
+        assert_eq!(fetched, cf);
+        assert_eq!(fast.served.load(AtomicOrdering::SeqCst), 1);
+
+        // The slow request was cancelled as soon as the fast one succeeded.
+        sleep(slow.delay * 2).await;
+        assert_eq!(slow.served.load(AtomicOrdering::SeqCst), 0);
+
+        // Querying sequentially waits for the first provider instead, and never asks the second.
+        let catchup =
+            ParallelStateCatchup::new(&[slow.clone() as Arc<dyn StateCatchup>, fast.clone()])
+                .with_strategy(QueryStrategy::Sequential);
+        let fetched = catchup
+            .try_fetch_chain_config(0, cf.commit())
+            .await
+            .unwrap();
+        assert_eq!(fetched, cf);
+        assert_eq!(slow.served.load(AtomicOrdering::SeqCst), 1);
+        assert_eq!(fast.served.load(AtomicOrdering::SeqCst), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_parallel_catchup_rejects_corrupt_response() {
+        setup_test();
+
+        let cf = ChainConfig::default();
+        let corrupt_cf = ChainConfig {
+            max_block_size: 1.into(),
+            ..cf
+        };
+
+        // The corrupt provider answers first, but its response does not match the commitment.
+        let corrupt = Arc::new(DelayedCatchup::new(Duration::from_millis(10), corrupt_cf));
+        let honest = Arc::new(DelayedCatchup::new(Duration::from_millis(500), cf));
+
+        let metrics = PrometheusMetrics::default();
+        let catchup = ParallelStateCatchup::new(&[corrupt as Arc<dyn StateCatchup>, honest])
+            .with_metrics(&metrics);
+        let fetched = catchup
+            .try_fetch_chain_config(0, cf.commit())
+            .await
+            .unwrap();
+        assert_eq!(fetched, cf);
+
+        let rejected = metrics
+            .get_subgroup(["catchup"])
+            .unwrap()
+            .get_counter("rejected_responses")
+            .unwrap();
+        assert_eq!(rejected.get(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_parallel_catchup_rejects_forged_leaf() {
+        setup_test();
+
+        let key = PubKey::generated_from_seed_indexed([0; 32], 0);
+        let stake_table = HSStakeTable::from(vec![PeerConfig::<SeqTypes> {
+            stake_table_entry: key.0.stake_table_entry(U256::from(1)),
+            state_ver_key: StateKeyPair::default().ver_key(),
+        }]);
+        // Leaf 1 is decided by the QCs of leaves 2 and 3.
+        let leaf_chain = signed_leaf_chain(5, &key)
+            .await
+            .into_iter()
+            .skip(1)
+            .map(|leaf| leaf.leaf().clone())
+            .collect::<Vec<_>>();
+
+        // The first forger makes up leaf 1, but keeps its real justify QC, which only certifies
+        // the parent of the leaf.
+        let mut forged_leaf = leaf_chain.clone();
+        *forged_leaf[0].block_header_mut().timestamp_mut() += 1;
+
+        // The second forger signs a whole chain, but is not in our stake table.
+        let forger = PubKey::generated_from_seed_indexed([1; 32], 0);
+        let forged_chain = signed_leaf_chain(5, &forger)
+            .await
+            .into_iter()
+            .skip(1)
+            .map(|leaf| leaf.leaf().clone())
+            .collect::<Vec<_>>();
+
+        // Both forgers answer before the honest provider.
+        let cf = ChainConfig::default();
+        let forgers = [forged_leaf, forged_chain].map(|leaf_chain| {
+            Arc::new(DelayedCatchup::new(Duration::from_millis(10), cf).with_leaf_chain(leaf_chain))
+        });
+        let honest = Arc::new(
+            DelayedCatchup::new(Duration::from_millis(500), cf).with_leaf_chain(leaf_chain.clone()),
+        );
+
+        let metrics = PrometheusMetrics::default();
+        let catchup = ParallelStateCatchup::new(&[
+            forgers[0].clone() as Arc<dyn StateCatchup>,
+            forgers[1].clone(),
+            honest.clone(),
+        ])
+        .with_metrics(&metrics);
+        let fetched = catchup
+            .try_fetch_leaf(0, 1, stake_table, U256::from(1))
+            .await
+            .unwrap();
+        assert_eq!(fetched, leaf_chain[0]);
+        for forger in &forgers {
+            assert_eq!(forger.served.load(AtomicOrdering::SeqCst), 1);
+        }
+        assert_eq!(honest.served.load(AtomicOrdering::SeqCst), 1);
+
+        let rejected = metrics
+            .get_subgroup(["catchup"])
+            .unwrap()
+            .get_counter("rejected_responses")
+            .unwrap();
+        assert_eq!(rejected.get(), 2);
+    }
 }
@@ -9,8 +9,9 @@ use anyhow::Context;
 use async_lock::RwLock;
 use derivative::Derivative;
 use espresso_types::{
+    config::PublicHotShotConfig,
     v0::traits::{EventConsumer as PersistenceEventConsumer, SequencerPersistence},
-    NodeState, PubKey, SolverAuctionResultsProvider, Transaction, ValidatedState,
+    NamespaceId, NodeState, PubKey, SolverAuctionResultsProvider, Transaction, ValidatedState,
 };
 use futures::{
     future::{join_all, Future},
@@ -32,20 +33,21 @@ use hotshot_types::{
     PeerConfig, ValidatorConfig,
 };
 use parking_lot::Mutex;
-use request_response::RequestResponseConfig;
+use request_response::{metrics::RequestResponseMetrics, RequestResponseConfig};
 use tokio::{spawn, sync::mpsc::channel, task::JoinHandle};
 use tracing::{Instrument, Level};
 use url::Url;
 
 use crate::{
     catchup::ParallelStateCatchup,
-    external_event_handler::ExternalEventHandler,
+    event_metrics::EventMetrics,
+    external_event_handler::{ExternalEventHandler, ExternalEventHandlerConfig},
     proposal_fetcher::ProposalFetcherConfig,
     request_response::{
         data_source::DataSource, network::Sender as RequestResponseSender,
         recipient_source::RecipientSource, RequestResponseProtocol,
     },
-    state_signature::StateSigner,
+    state_signature::{StateRelayBackoff, StateSigner},
     Node, SeqTypes, SequencerApiVersion,
 };
 
@@ -101,11 +103,13 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
         persistence: Arc<P>,
         network: Arc<N>,
         state_relay_server: Option<Url>,
+        state_relay_backoff: StateRelayBackoff,
         metrics: &dyn Metrics,
         stake_table_capacity: usize,
         event_consumer: impl PersistenceEventConsumer + 'static,
         _: V,
         proposal_fetcher_cfg: ProposalFetcherConfig,
+        external_event_cfg: ExternalEventHandlerConfig,
     ) -> anyhow::Result<Self> {
         let config = &network_config.config;
         let pub_key = validator_config.public_key;
@@ -160,14 +164,16 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
             stake_table_commit,
             stake_table_epoch,
             stake_table_capacity,
-        );
+        )
+        .with_relay_backoff(state_relay_backoff)
+        .with_metrics(metrics);
         if let Some(url) = state_relay_server {
             state_signer = state_signer.with_relay_server(url);
         }
 
         // Create the channel for sending outbound messages from the external event handler
         let (outbound_message_sender, outbound_message_receiver) = channel(20);
-        let (request_response_sender, request_response_receiver) = channel(20);
+        let (request_response_sender, request_response_receiver) = external_event_cfg.channel();
 
         // Configure the request-response protocol
         let request_response_config = RequestResponseConfig {
@@ -198,6 +204,7 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
             },
             validator_config.public_key,
             validator_config.private_key.clone(),
+            RequestResponseMetrics::new(metrics),
         );
 
         // Add the request-response protocol to the list of providers for state catchup. Since the interior is mutable,
@@ -209,11 +216,13 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
         let mut tasks = TaskList::default();
         let external_event_handler = ExternalEventHandler::<V>::new(
             &mut tasks,
+            external_event_cfg,
             request_response_sender,
             outbound_message_receiver,
             network,
             pub_key,
             handle.hotshot.upgrade_lock.clone(),
+            metrics,
         )
         .await
         .with_context(|| "Failed to create external event handler")?;
@@ -254,6 +263,7 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
         metrics: &dyn Metrics,
     ) -> Self {
         let events = handle.event_stream();
+        let metrics_events = handle.event_stream();
 
         let node_id = node_state.node_id;
         let mut ctx = Self {
@@ -277,6 +287,12 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
             metrics,
         );
 
+        // Count consensus events in the node's metrics.
+        ctx.spawn(
+            "event metrics",
+            EventMetrics::new(metrics).run(metrics_events),
+        );
+
         // Spawn event handling loop.
         ctx.spawn(
             "event handler",
@@ -318,6 +334,40 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
         self.handle.read().await.event_stream()
     }
 
+    /// Stream decide events for blocks containing transactions in namespace `ns`.
+    ///
+    /// All other events are dropped, and the leaf chain of each decide is trimmed to the leaves
+    /// whose block has `ns` in its namespace table. Note that the `qc` of a trimmed decide still
+    /// signs the newest leaf of the original chain, which may have been removed.
+    pub async fn event_stream_for_namespace(
+        &self,
+        ns: NamespaceId,
+    ) -> impl Stream<Item = Event<SeqTypes>> {
+        self.event_stream()
+            .await
+            .filter_map(move |mut event| async move {
+                let EventType::Decide { leaf_chain, .. } = &mut event.event else {
+                    return None;
+                };
+                let leaves = leaf_chain
+                    .iter()
+                    .filter(|info| {
+                        info.leaf
+                            .block_header()
+                            .ns_table()
+                            .find_ns_id(&ns)
+                            .is_some()
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if leaves.is_empty() {
+                    return None;
+                }
+                *leaf_chain = Arc::new(leaves);
+                Some(event)
+            })
+    }
+
     pub async fn submit_transaction(&self, tx: Transaction) -> anyhow::Result<()> {
         self.handle.read().await.submit_transaction(tx).await?;
         Ok(())
@@ -341,6 +391,11 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
         self.handle.read().await.decided_leaf().await
     }
 
+    /// Height of the latest decided leaf.
+    pub async fn decided_height(&self) -> u64 {
+        self.decided_leaf().await.height()
+    }
+
     pub async fn state(&self, view: ViewNumber) -> Option<Arc<ValidatedState>> {
         self.handle.read().await.state(view).await
     }
@@ -425,6 +480,14 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Sequence
     pub fn network_config(&self) -> NetworkConfig<SeqTypes> {
         self.network_config.clone()
     }
+
+    /// Serialize the effective HotShot config of this node to JSON, for debugging.
+    ///
+    /// Secret material, such as private keys, is redacted.
+    pub fn hotshot_config_json(&self) -> anyhow::Result<serde_json::Value> {
+        let config = PublicHotShotConfig::from(self.network_config.config.clone());
+        Ok(serde_json::to_value(config)?)
+    }
 }
 
 impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence, V: Versions> Drop
@@ -0,0 +1,209 @@
+//! Export of consensus events as newline-delimited JSON.
+//!
+//! This is a lightweight alternative to running the query service when all an operator wants is to
+//! see what events the node is producing, e.g. for debugging.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use clap::Parser;
+use espresso_types::{v0::traits::EventConsumer, Event};
+use parking_lot::Mutex;
+
+#[derive(Clone, Debug, Parser)]
+pub struct EventExportOptions {
+    /// File to append consensus events to, one JSON object per line.
+    ///
+    /// If not provided, events are not exported.
+    #[clap(long, env = "ESPRESSO_SEQUENCER_EVENT_EXPORT_PATH")]
+    pub event_export_path: Option<PathBuf>,
+
+    /// Size in bytes after which the event export file is rotated.
+    ///
+    /// On rotation, the current file is renamed by appending `.1` to its path, replacing any
+    /// previously rotated file, and a new file is started.
+    #[clap(
+        long,
+        env = "ESPRESSO_SEQUENCER_EVENT_EXPORT_MAX_FILE_SIZE",
+        default_value = "104857600"
+    )]
+    pub event_export_max_file_size: u64,
+}
+
+impl Default for EventExportOptions {
+    fn default() -> Self {
+        Self::parse_from(std::iter::empty::<String>())
+    }
+}
+
+impl EventExportOptions {
+    /// Create the configured event exporter, if any.
+    pub fn create(&self) -> anyhow::Result<Option<JsonFileEventConsumer>> {
+        self.event_export_path
+            .as_ref()
+            .map(|path| JsonFileEventConsumer::new(path, self.event_export_max_file_size))
+            .transpose()
+    }
+}
+
+/// An [`EventConsumer`] which appends each event to a file as a line of JSON.
+#[derive(Debug)]
+pub struct JsonFileEventConsumer {
+    path: PathBuf,
+    max_file_size: u64,
+    file: Mutex<OpenFile>,
+}
+
+#[derive(Debug)]
+struct OpenFile {
+    file: File,
+    size: u64,
+}
+
+impl OpenFile {
+    fn open(path: &Path) -> anyhow::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("opening event export file {}", path.display()))?;
+        let size = file.metadata()?.len();
+        Ok(Self { file, size })
+    }
+}
+
+impl JsonFileEventConsumer {
+    /// Export events to `path`, rotating the file once it grows past `max_file_size` bytes.
+    ///
+    /// If `path` already exists, new events are appended to it.
+    pub fn new(path: impl Into<PathBuf>, max_file_size: u64) -> anyhow::Result<Self> {
+        let path = path.into();
+        let file = OpenFile::open(&path)?;
+        Ok(Self {
+            path,
+            max_file_size,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// The path of the file that was most recently rotated out.
+    pub fn rotated_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".1");
+        path.into()
+    }
+
+    fn write_line(&self, line: &[u8]) -> anyhow::Result<()> {
+        let mut file = self.file.lock();
+
+        // Rotate before writing if this line would take us over the limit. A file always gets at
+        // least one line, even if that line alone is bigger than the limit.
+        if file.size > 0 && file.size + line.len() as u64 > self.max_file_size {
+            fs::rename(&self.path, self.rotated_path())
+                .with_context(|| format!("rotating event export file {}", self.path.display()))?;
+            *file = OpenFile::open(&self.path)?;
+        }
+
+        file.file.write_all(line)?;
+        file.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventConsumer for JsonFileEventConsumer {
+    async fn handle_event(&self, event: &Event) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        self.write_line(&line)
+    }
+}
+
+/// An [`EventConsumer`] which forwards each event to two other consumers.
+#[derive(Debug)]
+pub struct TeeEventConsumer<A, B>(pub A, pub B);
+
+#[async_trait]
+impl<A: EventConsumer, B: EventConsumer> EventConsumer for TeeEventConsumer<A, B> {
+    async fn handle_event(&self, event: &Event) -> anyhow::Result<()> {
+        // Give both consumers a chance to see the event, even if the first one fails.
+        let res = self.0.handle_event(event).await;
+        self.1.handle_event(event).await?;
+        res
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hotshot::types::EventType;
+    use hotshot_types::{data::ViewNumber, traits::node_implementation::ConsensusTime};
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn event(i: u64) -> Event {
+        Event {
+            view_number: ViewNumber::new(i),
+            event: EventType::ViewFinished {
+                view_number: ViewNumber::new(i),
+            },
+        }
+    }
+
+    fn read_lines(path: &Path) -> Vec<serde_json::Value> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_json_file_event_consumer() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("events.jsonl");
+        let consumer = JsonFileEventConsumer::new(&path, u64::MAX).unwrap();
+
+        for i in 0..3 {
+            consumer.handle_event(&event(i)).await.unwrap();
+        }
+
+        let expected = (0..3)
+            .map(|i| serde_json::to_value(event(i)).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(read_lines(&path), expected);
+    }
+
+    #[tokio::test]
+    async fn test_json_file_event_consumer_rotation() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("events.jsonl");
+
+        // Every event serializes to the same length, so we can size the file to hold exactly two.
+        let line_len = serde_json::to_vec(&event(0)).unwrap().len() as u64 + 1;
+        let consumer = JsonFileEventConsumer::new(&path, 2 * line_len).unwrap();
+
+        for i in 0..5 {
+            consumer.handle_event(&event(i)).await.unwrap();
+        }
+
+        // Events 0 and 1 were rotated out and then overwritten by the next rotation.
+        let to_json = |i| serde_json::to_value(event(i)).unwrap();
+        assert_eq!(
+            read_lines(&consumer.rotated_path()),
+            vec![to_json(2), to_json(3)]
+        );
+        assert_eq!(read_lines(&path), vec![to_json(4)]);
+
+        // Reopening the file appends to it rather than truncating it.
+        drop(consumer);
+        let consumer = JsonFileEventConsumer::new(&path, 2 * line_len).unwrap();
+        consumer.handle_event(&event(5)).await.unwrap();
+        assert_eq!(read_lines(&path), vec![to_json(4), to_json(5)]);
+    }
+}
@@ -0,0 +1,176 @@
+//! Metrics derived from consensus events.
+//!
+//! The metrics are registered on the node's metrics, so they are exported along with all other
+//! metrics in the Prometheus format by the status API.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use espresso_types::Event;
+use futures::stream::{Stream, StreamExt};
+use hotshot::types::EventType;
+use hotshot_types::{
+    event::LeafInfo,
+    traits::{
+        metrics::{Counter, Gauge, Metrics},
+        BlockPayload,
+    },
+};
+
+#[derive(Clone, Debug)]
+pub(crate) struct EventMetrics {
+    decides: Arc<dyn Counter>,
+    timeouts: Arc<dyn Counter>,
+    view_changes: Arc<dyn Counter>,
+    transactions_decided: Arc<dyn Counter>,
+    current_view: Arc<dyn Gauge>,
+    /// The highest view seen so far, since events from older views can arrive late.
+    last_view: Arc<AtomicU64>,
+    decided_height: Arc<dyn Gauge>,
+}
+
+impl EventMetrics {
+    pub(crate) fn new(metrics: &(impl Metrics + ?Sized)) -> Self {
+        let gauges = metrics.subgroup("events".into());
+        Self {
+            decides: metrics.create_counter("decides_total".into(), None).into(),
+            timeouts: metrics.create_counter("timeouts_total".into(), None).into(),
+            view_changes: metrics
+                .create_counter("view_changes_total".into(), None)
+                .into(),
+            transactions_decided: metrics
+                .create_counter("transactions_decided_total".into(), None)
+                .into(),
+            // HotShot already registers a `current_view` gauge at the top level.
+            current_view: gauges.create_gauge("current_view".into(), None).into(),
+            last_view: Default::default(),
+            decided_height: gauges.create_gauge("decided_height".into(), None).into(),
+        }
+    }
+
+    /// Update the counters with a single event.
+    pub(crate) fn handle_event(&self, event: &Event) {
+        let view = *event.view_number;
+        if self.last_view.fetch_max(view, Ordering::Relaxed) < view {
+            self.current_view.set(view as usize);
+        }
+        match &event.event {
+            EventType::Decide { leaf_chain, .. } => {
+                self.decides.add(1);
+                // The newest leaf comes first.
+                if let Some(LeafInfo { leaf, .. }) = leaf_chain.first() {
+                    self.decided_height.set(leaf.height() as usize);
+                }
+                // Leaves whose payload we don't have are not counted.
+                let txns: usize = leaf_chain
+                    .iter()
+                    .filter_map(|LeafInfo { leaf, .. }| {
+                        let payload = leaf.block_payload()?;
+                        Some(payload.num_transactions(payload.ns_table()))
+                    })
+                    .sum();
+                self.transactions_decided.add(txns);
+            },
+            EventType::ViewTimeout { .. } => self.timeouts.add(1),
+            EventType::ViewFinished { .. } => self.view_changes.add(1),
+            _ => {},
+        }
+    }
+
+    /// Count the events from `events` until the stream ends.
+    pub(crate) async fn run(self, mut events: impl Stream<Item = Event> + Unpin) {
+        while let Some(event) = events.next().await {
+            self.handle_event(&event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use espresso_types::{
+        Leaf2, MockSequencerVersions, NamespaceId, NodeState, Payload, SeqTypes, Transaction,
+        ValidatedState,
+    };
+    use hotshot_query_service::metrics::PrometheusMetrics;
+    use hotshot_types::{
+        data::ViewNumber, simple_certificate::QuorumCertificate2,
+        traits::node_implementation::ConsensusTime,
+    };
+
+    use super::*;
+
+    fn event(view: u64, event: EventType<SeqTypes>) -> Event {
+        Event {
+            view_number: ViewNumber::new(view),
+            event,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_metrics() {
+        let metrics = PrometheusMetrics::default();
+        let event_metrics = EventMetrics::new(&metrics);
+
+        let state = ValidatedState::default();
+        let node_state = NodeState::mock();
+        let txns = (0u32..3).map(|i| Transaction::new(NamespaceId::from(i % 2), vec![i as u8]));
+        let (payload, _) = Payload::from_transactions(txns, &state, &node_state)
+            .await
+            .unwrap();
+        let mut leaf = Leaf2::genesis::<MockSequencerVersions>(&state, &node_state).await;
+        leaf.fill_block_payload_unchecked(payload);
+        *leaf.block_header_mut().height_mut() = 5;
+        let qc = QuorumCertificate2::genesis::<MockSequencerVersions>(&state, &node_state).await;
+
+        let events = vec![
+            event(
+                1,
+                EventType::ViewFinished {
+                    view_number: ViewNumber::new(1),
+                },
+            ),
+            event(
+                2,
+                EventType::ViewFinished {
+                    view_number: ViewNumber::new(2),
+                },
+            ),
+            event(
+                2,
+                EventType::Decide {
+                    leaf_chain: Arc::new(vec![LeafInfo::new(
+                        leaf,
+                        Arc::new(state.clone()),
+                        None,
+                        None,
+                        None,
+                    )]),
+                    qc: Arc::new(qc),
+                    block_size: None,
+                },
+            ),
+            // An event from an older view arriving late does not move the current view back.
+            event(
+                1,
+                EventType::ViewTimeout {
+                    view_number: ViewNumber::new(1),
+                },
+            ),
+        ];
+        event_metrics.run(futures::stream::iter(events)).await;
+
+        for (name, expected) in [
+            ("decides_total", 1),
+            ("timeouts_total", 1),
+            ("view_changes_total", 2),
+            ("transactions_decided_total", 3),
+        ] {
+            assert_eq!(metrics.get_counter(name).unwrap().get(), expected, "{name}");
+        }
+        let gauges = metrics.get_subgroup(["events"]).unwrap();
+        assert_eq!(gauges.get_gauge("current_view").unwrap().get(), 2);
+        assert_eq!(gauges.get_gauge("decided_height").unwrap().get(), 5);
+    }
+}
@@ -1,20 +1,29 @@
 //! Should probably rename this to "external" or something
 
-use std::{marker::PhantomData, sync::Arc};
+use std::{
+    marker::PhantomData,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use clap::{Parser, ValueEnum};
 use espresso_types::{PubKey, SeqTypes};
 use hotshot::types::Message;
 use hotshot_types::{
     message::{MessageKind, UpgradeLock},
     traits::{
+        metrics::{Counter, Metrics},
         network::{BroadcastDelay, ConnectedNetwork, Topic},
         node_implementation::Versions,
     },
 };
 use request_response::network::Bytes;
 use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::mpsc::{channel, error::TrySendError, Receiver, Sender};
 
 use crate::context::TaskList;
 
@@ -24,12 +33,86 @@ pub enum ExternalMessage {
     RequestResponse(Vec<u8>),
 }
 
+/// What to do with an external message when the request-response protocol isn't keeping up
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum BackpressurePolicy {
+    /// Wait until there is room in the channel, slowing down the handling of further events
+    #[default]
+    Block,
+    /// Drop the message so that further events are not held up
+    Drop,
+}
+
+/// Configuration for the channel between the external event handler and the request-response
+/// protocol
+#[derive(Clone, Copy, Debug, Parser)]
+pub struct ExternalEventHandlerConfig {
+    /// The number of external messages that can be queued for the request-response protocol, must
+    /// be positive
+    #[clap(
+        long = "external-event-channel-capacity",
+        env = "ESPRESSO_SEQUENCER_EXTERNAL_EVENT_CHANNEL_CAPACITY",
+        default_value = "20"
+    )]
+    pub channel_capacity: NonZeroUsize,
+
+    /// What to do with external messages once the channel is full
+    #[clap(
+        long = "external-event-backpressure-policy",
+        env = "ESPRESSO_SEQUENCER_EXTERNAL_EVENT_BACKPRESSURE_POLICY",
+        value_enum,
+        default_value = "block"
+    )]
+    pub backpressure_policy: BackpressurePolicy,
+}
+
+impl Default for ExternalEventHandlerConfig {
+    fn default() -> Self {
+        Self::parse_from(std::iter::empty::<String>())
+    }
+}
+
+impl ExternalEventHandlerConfig {
+    /// Create the channel the external event handler forwards request-response messages over
+    pub fn channel(&self) -> (Sender<Bytes>, Receiver<Bytes>) {
+        channel(self.channel_capacity.get())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ExternalEventHandlerMetrics {
+    /// Messages that were dropped because the channel was full
+    dropped: Arc<dyn Counter>,
+    /// Messages that had to wait for room in the channel
+    blocked: Arc<dyn Counter>,
+}
+
+impl ExternalEventHandlerMetrics {
+    fn new(metrics: &(impl Metrics + ?Sized)) -> Self {
+        let metrics = metrics.subgroup("external_event_handler".into());
+        Self {
+            dropped: metrics.create_counter("dropped".into(), None).into(),
+            blocked: metrics.create_counter("blocked".into(), None).into(),
+        }
+    }
+}
+
 /// The external event handler
 #[derive(Clone)]
 pub struct ExternalEventHandler<V: Versions> {
     /// The sender to the request-response protocol
     request_response_sender: Sender<Bytes>,
 
+    /// What to do when the request-response protocol isn't keeping up
+    backpressure_policy: BackpressurePolicy,
+
+    /// Whether the channel to the request-response protocol was full the last time we sent to it.
+    /// Used so we only warn once each time the channel fills up
+    channel_full: Arc<AtomicBool>,
+
+    /// Counters for dropped and blocked messages
+    metrics: ExternalEventHandlerMetrics,
+
     /// The type phantom
     phantom: PhantomData<V>,
 }
@@ -44,13 +127,16 @@ pub enum OutboundMessage {
 
 impl<V: Versions> ExternalEventHandler<V> {
     /// Creates a new `ExternalEventHandler` with the given network
+    #[allow(clippy::too_many_arguments)]
     pub async fn new<N: ConnectedNetwork<PubKey>>(
         tasks: &mut TaskList,
+        config: ExternalEventHandlerConfig,
         request_response_sender: Sender<Bytes>,
         outbound_message_receiver: Receiver<OutboundMessage>,
         network: Arc<N>,
         public_key: PubKey,
         hotshot_upgrade_lock: UpgradeLock<SeqTypes, V>,
+        metrics: &dyn Metrics,
     ) -> Result<Self> {
         // Spawn the outbound message handling loop
         tasks.spawn(
@@ -65,6 +151,9 @@ impl<V: Versions> ExternalEventHandler<V> {
 
         Ok(Self {
             request_response_sender,
+            backpressure_policy: config.backpressure_policy,
+            channel_full: Default::default(),
+            metrics: ExternalEventHandlerMetrics::new(metrics),
             phantom: PhantomData,
         })
     }
@@ -82,14 +171,45 @@ impl<V: Versions> ExternalEventHandler<V> {
         match external_message {
             ExternalMessage::RequestResponse(request_response) => {
                 // Send the inner message to the request-response protocol
-                self.request_response_sender
-                    .send(request_response.into())
+                self.forward_request_response(request_response.into())
                     .await?;
             },
         }
         Ok(())
     }
 
+    /// Forwards a message to the request-response protocol, applying the backpressure policy if
+    /// the channel is full
+    async fn forward_request_response(&self, message: Bytes) -> Result<()> {
+        let message = match self.request_response_sender.try_send(message) {
+            Ok(()) => {
+                self.channel_full.store(false, Ordering::Relaxed);
+                return Ok(());
+            },
+            Err(TrySendError::Closed(_)) => bail!("request-response channel closed"),
+            Err(TrySendError::Full(message)) => message,
+        };
+
+        // Only warn when the channel fills up, not for every message while it stays full
+        if !self.channel_full.swap(true, Ordering::Relaxed) {
+            tracing::warn!(
+                policy = ?self.backpressure_policy,
+                "Request-response channel is full, the consumer is not keeping up",
+            );
+        }
+
+        match self.backpressure_policy {
+            BackpressurePolicy::Block => {
+                self.metrics.blocked.add(1);
+                self.request_response_sender.send(message).await?;
+            },
+            BackpressurePolicy::Drop => {
+                self.metrics.dropped.add(1);
+            },
+        }
+        Ok(())
+    }
+
     /// The main loop for sending outbound messages.
     async fn outbound_message_loop<N: ConnectedNetwork<PubKey>>(
         mut receiver: Receiver<OutboundMessage>,
@@ -150,3 +270,104 @@ impl<V: Versions> ExternalEventHandler<V> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use espresso_types::MockSequencerVersions;
+    use hotshot_query_service::metrics::PrometheusMetrics;
+    use tokio::time::{sleep, timeout};
+
+    use super::*;
+
+    fn handler(
+        policy: BackpressurePolicy,
+        metrics: &PrometheusMetrics,
+    ) -> (ExternalEventHandler<MockSequencerVersions>, Receiver<Bytes>) {
+        let config = ExternalEventHandlerConfig {
+            channel_capacity: NonZeroUsize::new(2).unwrap(),
+            backpressure_policy: policy,
+        };
+        let (sender, receiver) = config.channel();
+        let handler = ExternalEventHandler {
+            request_response_sender: sender,
+            backpressure_policy: config.backpressure_policy,
+            channel_full: Default::default(),
+            metrics: ExternalEventHandlerMetrics::new(metrics),
+            phantom: PhantomData,
+        };
+        (handler, receiver)
+    }
+
+    fn message(i: u8) -> Vec<u8> {
+        bincode::serialize(&ExternalMessage::RequestResponse(vec![i])).unwrap()
+    }
+
+    #[test]
+    fn test_channel_capacity_must_be_positive() {
+        let parse = |capacity: &str| {
+            ExternalEventHandlerConfig::try_parse_from([
+                "sequencer",
+                "--external-event-channel-capacity",
+                capacity,
+            ])
+        };
+        assert!(parse("0").is_err());
+        assert_eq!(parse("1").unwrap().channel_capacity.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_backpressure_drop() {
+        let metrics = PrometheusMetrics::default();
+        let (handler, mut receiver) = handler(BackpressurePolicy::Drop, &metrics);
+
+        // Nobody is consuming, so everything past the channel capacity gets dropped without
+        // blocking the handler.
+        for i in 0..5 {
+            timeout(Duration::from_secs(1), handler.handle_event(&message(i)))
+                .await
+                .unwrap()
+                .unwrap();
+        }
+        let dropped = metrics
+            .get_subgroup(["external_event_handler"])
+            .unwrap()
+            .get_counter("dropped")
+            .unwrap();
+        assert_eq!(dropped.get(), 3);
+
+        // The messages that made it through are the oldest ones.
+        assert_eq!(*receiver.recv().await.unwrap(), vec![0]);
+        assert_eq!(*receiver.recv().await.unwrap(), vec![1]);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_backpressure_block() {
+        let metrics = PrometheusMetrics::default();
+        let (handler, mut receiver) = handler(BackpressurePolicy::Block, &metrics);
+
+        // A slow consumer that takes a while to get to each message.
+        let consumer = tokio::spawn(async move {
+            let mut received = vec![];
+            while let Some(message) = receiver.recv().await {
+                sleep(Duration::from_millis(50)).await;
+                received.push(message[0]);
+            }
+            received
+        });
+
+        // Every message is delivered, but once the channel is full we have to wait for the
+        // consumer.
+        for i in 0..5 {
+            handler.handle_event(&message(i)).await.unwrap();
+        }
+        let subgroup = metrics.get_subgroup(["external_event_handler"]).unwrap();
+        assert!(subgroup.get_counter("blocked").unwrap().get() > 0);
+        assert_eq!(subgroup.get_counter("dropped").unwrap().get(), 0);
+
+        drop(handler);
+        assert_eq!(consumer.await.unwrap(), vec![0, 1, 2, 3, 4]);
+    }
+}
@@ -1,6 +1,8 @@
 pub mod api;
 pub mod catchup;
 pub mod context;
+pub mod event_export;
+mod event_metrics;
 pub mod genesis;
 mod proposal_fetcher;
 mod request_response;
@@ -21,11 +23,13 @@ use async_lock::{Mutex, RwLock};
 use catchup::{ParallelStateCatchup, StatePeers};
 use context::SequencerContext;
 use espresso_types::{
+    eth_signature_key::EthKeyPair,
     traits::{EventConsumer, MembershipPersistence},
     v0_3::StakeTableFetcher,
     BackoffParams, EpochCommittees, L1ClientOptions, NodeState, PubKey, SeqTypes,
     SolverAuctionResultsProvider, ValidatedState,
 };
+use external_event_handler::ExternalEventHandlerConfig;
 use genesis::L1Finalized;
 use hotshot_libp2p_networking::network::behaviours::dht::store::persistent::DhtNoPersistence;
 use hotshot_query_service::data_source::storage::SqlStorage;
@@ -33,6 +37,7 @@ use libp2p::Multiaddr;
 use network::libp2p::split_off_peer_id;
 use options::Identity;
 use proposal_fetcher::ProposalFetcherConfig;
+use state_signature::StateRelayBackoff;
 use tokio::select;
 use tracing::info;
 use url::Url;
@@ -45,9 +50,9 @@ use espresso_types::v0::traits::SequencerPersistence;
 pub use genesis::Genesis;
 use hotshot::{
     traits::implementations::{
-        derive_libp2p_multiaddr, derive_libp2p_peer_id, CdnMetricsValue, CdnTopic,
-        CombinedNetworks, GossipConfig, KeyPair, Libp2pNetwork, MemoryNetwork, PushCdnNetwork,
-        RequestResponseConfig, WrappedSignatureKey,
+        derive_libp2p_multiaddr, CdnMetricsValue, CdnTopic, CombinedNetworks, GossipConfig,
+        KeyPair, Libp2pNetwork, MemoryNetwork, PushCdnNetwork, RequestResponseConfig,
+        WrappedSignatureKey,
     },
     types::SignatureKey,
 };
@@ -60,7 +65,7 @@ use hotshot_types::{
     traits::{
         metrics::{Metrics, NoMetrics},
         network::ConnectedNetwork,
-        node_implementation::{NodeImplementation, NodeType, Versions},
+        node_implementation::{NodeImplementation, Versions},
         storage::{storage_add_drb_result, Storage},
     },
     utils::BuilderCommitment,
@@ -109,8 +114,12 @@ impl<N: ConnectedNetwork<PubKey>, P: SequencerPersistence> NodeImplementation<Se
 pub struct NetworkParams {
     /// The address where a CDN marshal is located
     pub cdn_endpoint: String,
+    /// How long to wait for the CDN before falling back to Libp2p
+    pub combined_network_delay: Duration,
     pub orchestrator_url: Url,
     pub state_relay_server_url: Url,
+    pub state_relay_backoff: StateRelayBackoff,
+    pub external_event_handler: ExternalEventHandlerConfig,
     pub private_staking_key: BLSPrivKey,
     pub private_state_key: StateSignKey,
     pub state_peers: Vec<Url>,
@@ -302,14 +311,16 @@ where
     };
 
     // Derive our Libp2p public key from our private key
-    let libp2p_public_key = derive_libp2p_peer_id::<<SeqTypes as NodeType>::SignatureKey>(
-        &validator_config.private_key,
-    )
-    .with_context(|| "Failed to derive Libp2p peer ID")?;
+    let libp2p_public_key =
+        network::libp2p::peer_id_from_private_key(&validator_config.private_key)?;
 
     // Print the libp2p public key
     info!("Starting Libp2p with PeerID: {}", libp2p_public_key);
 
+    crate::persistence::check_and_migrate(&persistence)
+        .await
+        .context("failed to migrate persisted data")?;
+
     let (mut network_config, wait_for_orchestrator) = match (
         persistence.load_config().await?,
         network_params.config_peers,
@@ -476,7 +487,7 @@ where
     }
 
     // Create the list of parallel catchup providers
-    let state_catchup_providers = ParallelStateCatchup::new(&[]);
+    let state_catchup_providers = ParallelStateCatchup::new(&[]).with_metrics(metrics);
 
     // Add the state peers to the list
     let state_peers = StatePeers::<SequencerApiVersion>::from_urls(
@@ -536,6 +547,7 @@ where
         epoch_height: Some(epoch_height),
         state_catchup: Arc::new(state_catchup_providers.clone()),
         coordinator: coordinator.clone(),
+        allowed_namespaces: genesis.allowed_namespaces,
     };
 
     // Initialize the Libp2p network
@@ -575,7 +587,7 @@ where
         Arc::from(CombinedNetworks::new(
             cdn_network,
             p2p_network,
-            Some(Duration::from_secs(1)),
+            Some(network_params.combined_network_delay),
         ))
     };
 
@@ -589,11 +601,13 @@ where
         persistence,
         network,
         Some(network_params.state_relay_server_url),
+        network_params.state_relay_backoff,
         metrics,
         genesis.stake_table.capacity,
         event_consumer,
         seq_versions,
         proposal_fetcher_config,
+        network_params.external_event_handler,
     )
     .await?;
     if wait_for_orchestrator {
@@ -602,6 +616,29 @@ where
     Ok(ctx)
 }
 
+/// Derive a [`ValidatorConfig`] from the Ethereum account at `index` of `mnemonic`.
+///
+/// The staking and state keys are derived deterministically from the account's private key, so
+/// the same mnemonic and index always result in the same validator. Intended for quick development
+/// setups, production keys should be generated and stored independently.
+pub fn validator_config_from_mnemonic(
+    mnemonic: &str,
+    index: u32,
+    is_da: bool,
+) -> anyhow::Result<ValidatorConfig<SeqTypes>> {
+    let seed = EthKeyPair::from_mnemonic(mnemonic, index)
+        .context("failed to derive key from mnemonic")?
+        .signer()
+        .to_bytes()
+        .0;
+    Ok(ValidatorConfig::generated_from_seed_indexed(
+        seed,
+        0,
+        U256::ONE,
+        is_da,
+    ))
+}
+
 pub fn empty_builder_commitment() -> BuilderCommitment {
     BuilderCommitment::from_bytes([])
 }
@@ -629,7 +666,9 @@ pub mod testing {
             local::{LocalSigner, PrivateKeySigner},
         },
     };
+    use anyhow::ensure;
     use async_lock::RwLock;
+    use async_trait::async_trait;
     use catchup::NullStateCatchup;
     use committable::Committable;
     use espresso_contract_deployer::{
@@ -639,8 +678,8 @@ pub mod testing {
     use espresso_types::{
         eth_signature_key::EthKeyPair,
         v0::traits::{EventConsumer, NullEventConsumer, PersistenceOptions, StateCatchup},
-        EpochVersion, Event, FeeAccount, L1Client, NetworkConfig, PubKey, SeqTypes, Transaction,
-        Upgrade, UpgradeMap,
+        EpochVersion, Event, FeeAccount, L1Client, NamespaceId, NetworkConfig, PubKey, SeqTypes,
+        Transaction, Upgrade, UpgradeMap,
     };
     use futures::{
         future::join_all,
@@ -651,20 +690,20 @@ pub mod testing {
             implementations::{MasterMap, MemoryNetwork},
             BlockPayload,
         },
-        types::EventType::Decide,
+        types::EventType::{self, Decide},
     };
     use hotshot_builder_core_refactored::service::{
         BuilderConfig as LegacyBuilderConfig, GlobalState as LegacyGlobalState,
     };
-    use hotshot_testing::block_builder::{
-        BuilderTask, SimpleBuilderImplementation, TestBuilderImplementation,
-    };
+    use hotshot_testing::block_builder::{BuilderTask, SimpleBuilderImplementation};
     use hotshot_types::{
         event::LeafInfo,
         light_client::StateKeyPair,
         signature_key::BLSKeyPair,
         traits::{
-            block_contents::BlockHeader, metrics::NoMetrics, network::Topic,
+            block_contents::BlockHeader,
+            metrics::NoMetrics,
+            network::{NetworkReliability, SynchronousNetwork, Topic},
             signature_key::BuilderSignatureKey,
         },
         HotShotConfig, PeerConfig,
@@ -672,6 +711,7 @@ pub mod testing {
     use portpicker::pick_unused_port;
     use rand::SeedableRng as _;
     use rand_chacha::ChaCha20Rng;
+    use sequencer_utils::wait_for_http;
     use staking_cli::demo::{setup_stake_table_contract_for_test, DelegationConfig};
     use tokio::spawn;
     use vbs::version::Version;
@@ -714,12 +754,24 @@ pub mod testing {
         }
     }
 
+    /// Bind a listener for a builder on `port`, or on a port assigned by the OS if none is given.
+    fn bind_builder_listener(port: Option<u16>) -> (std::net::TcpListener, u16) {
+        let listener = std::net::TcpListener::bind(("0.0.0.0", port.unwrap_or(0)))
+            .unwrap_or_else(|err| panic!("Failed to bind builder listener: {err}"));
+        let port = listener
+            .local_addr()
+            .expect("Failed to read builder listener address")
+            .port();
+        (listener, port)
+    }
+
     pub async fn run_legacy_builder<const NUM_NODES: usize>(
         port: Option<u16>,
         max_block_size: Option<u64>,
+        base_fee: Option<u64>,
     ) -> (Box<dyn BuilderTask<SeqTypes>>, Url) {
         let builder_key_pair = TestConfig::<0>::builder_key();
-        let port = port.unwrap_or_else(|| pick_unused_port().expect("No ports available"));
+        let (listener, port) = bind_builder_listener(port);
 
         // This should never fail.
         let url: Url = format!("http://localhost:{port}")
@@ -736,7 +788,7 @@ pub mod testing {
                 txn_garbage_collect_duration: Duration::from_secs(60),
                 txn_channel_capacity: BUILDER_CHANNEL_CAPACITY_FOR_TEST,
                 tx_status_cache_capacity: 81920,
-                base_fee: 10,
+                base_fee: base_fee.unwrap_or(10),
             },
             NodeState::default(),
             max_block_size.unwrap_or(300),
@@ -748,23 +800,24 @@ pub mod testing {
             .into_app()
             .expect("Failed to create builder tide-disco app");
 
-        spawn(
-            app.serve(
-                format!("http://0.0.0.0:{port}")
-                    .parse::<Url>()
-                    .expect("Failed to parse builder listener"),
-                EpochVersion::instance(),
-            ),
-        );
+        spawn(app.serve(listener, EpochVersion::instance()));
+        wait_for_builder(&url).await;
 
         // Pass on the builder task to be injected in the testing harness
         (Box::new(LegacyBuilderImplementation { global_state }), url)
     }
 
+    /// Wait until the builder at `url` is serving requests.
+    async fn wait_for_builder(url: &Url) {
+        wait_for_http(url, Duration::from_millis(100), 100)
+            .await
+            .unwrap_or_else(|err| panic!("Builder did not become ready: {err}"));
+    }
+
     pub async fn run_test_builder<const NUM_NODES: usize>(
         port: Option<u16>,
     ) -> (Box<dyn BuilderTask<SeqTypes>>, Url) {
-        let port = port.unwrap_or_else(|| pick_unused_port().expect("No ports available"));
+        let (listener, port) = bind_builder_listener(port);
 
         // This should never fail.
         let url: Url = format!("http://localhost:{port}")
@@ -772,18 +825,15 @@ pub mod testing {
             .expect("Failed to parse builder URL");
         tracing::info!("Starting test builder on {url}");
 
-        (
-            <SimpleBuilderImplementation as TestBuilderImplementation<SeqTypes>>::start(
-                NUM_NODES,
-                format!("http://0.0.0.0:{port}")
-                    .parse()
-                    .expect("Failed to parse builder listener"),
-                (),
-                HashMap::new(),
-            )
-            .await,
-            url,
+        let task = SimpleBuilderImplementation::start_on_listener::<SeqTypes>(
+            NUM_NODES,
+            listener,
+            HashMap::new(),
         )
+        .await;
+        wait_for_builder(&url).await;
+
+        (task, url)
     }
 
     pub struct TestConfigBuilder<const NUM_NODES: usize> {
@@ -797,6 +847,8 @@ pub mod testing {
         state_relay_url: Option<Url>,
         builder_port: Option<u16>,
         upgrades: BTreeMap<Version, Upgrade>,
+        latency: Option<Duration>,
+        allowed_namespaces: Option<Vec<NamespaceId>>,
     }
 
     pub fn staking_priv_keys(
@@ -922,6 +974,25 @@ pub mod testing {
             self
         }
 
+        /// Make only the first `n` nodes members of the DA committee.
+        pub fn da_committee_size(mut self, n: usize) -> Self {
+            self.config.da_staked_committee_size = n;
+            self.config.known_da_nodes.truncate(n);
+            self
+        }
+
+        /// Delay every message between nodes by exactly `delay`.
+        pub fn with_latency(mut self, delay: Duration) -> Self {
+            self.latency = Some(delay);
+            self
+        }
+
+        /// Only accept submitted transactions for `namespaces`, as if set in the genesis file.
+        pub fn allowed_namespaces(mut self, namespaces: Vec<NamespaceId>) -> Self {
+            self.allowed_namespaces = Some(namespaces);
+            self
+        }
+
         pub fn epoch_height(mut self, epoch_height: u64) -> Self {
             self.config.epoch_height = epoch_height;
             self
@@ -932,8 +1003,36 @@ pub mod testing {
             self
         }
 
+        pub fn next_view_timeout(mut self, timeout: Duration) -> Self {
+            self.config.next_view_timeout = timeout.as_millis() as u64;
+            self
+        }
+
+        pub fn builder_timeout(mut self, timeout: Duration) -> Self {
+            self.config.builder_timeout = timeout;
+            self
+        }
+
+        /// Build the config, panicking if it is invalid. See [`try_build`](Self::try_build).
         pub fn build(self) -> TestConfig<NUM_NODES> {
-            TestConfig {
+            self.try_build().expect("invalid test config")
+        }
+
+        /// Build the config, failing if its timeouts would keep the network from making progress.
+        pub fn try_build(self) -> anyhow::Result<TestConfig<NUM_NODES>> {
+            let next_view_timeout = Duration::from_millis(self.config.next_view_timeout);
+            ensure!(
+                !next_view_timeout.is_zero(),
+                "next_view_timeout must be greater than zero"
+            );
+            ensure!(
+                self.config.builder_timeout <= next_view_timeout,
+                "builder_timeout ({:?}) must not exceed next_view_timeout ({next_view_timeout:?}), \
+                 or leaders time out before they can propose",
+                self.config.builder_timeout,
+            );
+
+            Ok(TestConfig {
                 config: self.config,
                 priv_keys: self.priv_keys,
                 state_key_pairs: self.state_key_pairs,
@@ -944,7 +1043,9 @@ pub mod testing {
                 builder_port: self.builder_port,
                 upgrades: self.upgrades,
                 anvil_provider: self.anvil_provider,
-            }
+                latency: self.latency,
+                allowed_namespaces: self.allowed_namespaces,
+            })
         }
     }
 
@@ -1022,6 +1123,8 @@ pub mod testing {
                 state_relay_url: None,
                 builder_port: None,
                 upgrades: Default::default(),
+                latency: None,
+                allowed_namespaces: None,
             }
         }
     }
@@ -1038,6 +1141,8 @@ pub mod testing {
         state_relay_url: Option<Url>,
         builder_port: Option<u16>,
         upgrades: BTreeMap<Version, Upgrade>,
+        latency: Option<Duration>,
+        allowed_namespaces: Option<Vec<NamespaceId>>,
     }
 
     impl<const NUM_NODES: usize> TestConfig<NUM_NODES> {
@@ -1068,6 +1173,27 @@ pub mod testing {
             self.anvil_provider.as_ref()
         }
 
+        /// Drop all messages between the nodes with indices in `group_a` and those in `group_b`
+        /// until [`heal`](Self::heal) is called.
+        pub fn partition(&self, group_a: &[usize], group_b: &[usize]) {
+            let keys = |group: &[usize]| {
+                group
+                    .iter()
+                    .map(|&i| {
+                        self.config.known_nodes_with_stake[i]
+                            .stake_table_entry
+                            .stake_key
+                    })
+                    .collect::<Vec<_>>()
+            };
+            self.master_map.partition(&keys(group_a), &keys(group_b));
+        }
+
+        /// Undo all partitions created with [`partition`](Self::partition).
+        pub fn heal(&self) {
+            self.master_map.heal();
+        }
+
         pub fn get_upgrade_map(&self) -> UpgradeMap {
             self.upgrades.clone().into()
         }
@@ -1083,6 +1209,17 @@ pub mod testing {
         pub async fn init_nodes<V: Versions>(
             &self,
             bind_version: V,
+        ) -> Vec<SequencerContext<network::Memory, NoStorage, V>> {
+            self.init_nodes_with_metrics(bind_version, &[]).await
+        }
+
+        /// Initialize all nodes, where node `i` reports to `metrics[i]`.
+        ///
+        /// Nodes without an entry in `metrics` do not report metrics.
+        pub async fn init_nodes_with_metrics<V: Versions>(
+            &self,
+            bind_version: V,
+            metrics: &[&dyn Metrics],
         ) -> Vec<SequencerContext<network::Memory, NoStorage, V>> {
             join_all((0..self.num_nodes()).map(|i| async move {
                 self.init_node(
@@ -1091,7 +1228,7 @@ pub mod testing {
                     no_storage::Options,
                     Some(NullStateCatchup::default()),
                     None,
-                    &NoMetrics,
+                    metrics.get(i).copied().unwrap_or(&NoMetrics),
                     STAKE_TABLE_CAPACITY_FOR_TEST,
                     NullEventConsumer,
                     bind_version,
@@ -1140,11 +1277,19 @@ pub mod testing {
                 vec![Topic::Global]
             };
 
+            // A synchronous network whose bounds coincide delays every message by the same amount.
+            let reliability = self.latency.map(|delay| {
+                let delay_ms = delay.as_millis() as u64;
+                Box::new(SynchronousNetwork {
+                    delay_high_ms: delay_ms,
+                    delay_low_ms: delay_ms,
+                }) as Box<dyn NetworkReliability>
+            });
             let network = Arc::new(MemoryNetwork::new(
                 &my_peer_config.stake_table_entry.stake_key,
                 &self.master_map,
                 &topics,
-                None,
+                reliability,
             ));
 
             // Make sure the builder account is funded.
@@ -1226,7 +1371,8 @@ pub mod testing {
             .with_current_version(V::Base::version())
             .with_genesis(state)
             .with_epoch_height(config.epoch_height)
-            .with_upgrades(upgrades);
+            .with_upgrades(upgrades)
+            .with_allowed_namespaces(self.allowed_namespaces.clone());
 
             tracing::info!(
                 i,
@@ -1250,11 +1396,13 @@ pub mod testing {
                 persistence,
                 network,
                 self.state_relay_url.clone(),
+                Default::default(),
                 metrics,
                 stake_table_capacity,
                 event_consumer,
                 bind_version,
                 Default::default(),
+                Default::default(),
             )
             .await
             .unwrap()
@@ -1265,6 +1413,66 @@ pub mod testing {
         }
     }
 
+    /// Tallies of the events seen by a [`CountingEventConsumer`], by event type.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct EventCounts {
+        pub decides: usize,
+        pub decided_leaves: usize,
+        pub views_finished: usize,
+        pub view_timeouts: usize,
+        pub errors: usize,
+        pub other: usize,
+    }
+
+    /// An [`EventConsumer`] which discards events after counting them.
+    ///
+    /// Clones share the same counts, so a test can hand one clone to a node and keep another to
+    /// check what was delivered.
+    #[derive(Clone, Debug, Default)]
+    pub struct CountingEventConsumer {
+        counts: Arc<parking_lot::Mutex<EventCounts>>,
+    }
+
+    impl CountingEventConsumer {
+        pub fn counts(&self) -> EventCounts {
+            *self.counts.lock()
+        }
+
+        pub fn decide_count(&self) -> usize {
+            self.counts.lock().decides
+        }
+
+        pub fn decided_leaf_count(&self) -> usize {
+            self.counts.lock().decided_leaves
+        }
+
+        pub fn view_finished_count(&self) -> usize {
+            self.counts.lock().views_finished
+        }
+
+        pub fn view_timeout_count(&self) -> usize {
+            self.counts.lock().view_timeouts
+        }
+    }
+
+    #[async_trait]
+    impl EventConsumer for CountingEventConsumer {
+        async fn handle_event(&self, event: &Event) -> anyhow::Result<()> {
+            let mut counts = self.counts.lock();
+            match &event.event {
+                Decide { leaf_chain, .. } => {
+                    counts.decides += 1;
+                    counts.decided_leaves += leaf_chain.len();
+                },
+                EventType::ViewFinished { .. } => counts.views_finished += 1,
+                EventType::ViewTimeout { .. } => counts.view_timeouts += 1,
+                EventType::Error { .. } => counts.errors += 1,
+                _ => counts.other += 1,
+            }
+            Ok(())
+        }
+    }
+
     // Wait for decide event, make sure it matches submitted transaction. Return the block number
     // containing the transaction.
     pub async fn wait_for_decide_on_handle(
@@ -1298,29 +1506,71 @@ pub mod testing {
             }
         }
     }
+
+    /// Submit `txn` to `handle` and wait for it to be decided. Return the block number containing
+    /// the transaction.
+    ///
+    /// `events` should be subscribed before calling this, so that the decide cannot be missed.
+    pub async fn submit_and_wait<N, P, V>(
+        handle: &SequencerContext<N, P, V>,
+        events: &mut (impl Stream<Item = Event> + Unpin),
+        txn: Transaction,
+    ) -> u64
+    where
+        N: ConnectedNetwork<PubKey>,
+        P: SequencerPersistence,
+        V: Versions,
+    {
+        handle
+            .submit_transaction(txn.clone())
+            .await
+            .expect("Failed to submit transaction");
+        tracing::info!("Submitted transaction to handle: {txn:?}");
+        wait_for_decide_on_handle(events, &txn).await
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashSet;
 
     use alloy::node_bindings::Anvil;
-    use espresso_types::{Header, MockSequencerVersions, NamespaceId, Payload, Transaction};
-    use futures::StreamExt;
-    use hotshot::types::EventType::Decide;
+    use committable::Committable;
+    use espresso_types::{
+        check_header_succession, FeeAmount, Header, MockSequencerVersions, NamespaceId, Transaction,
+    };
+    use futures::{future::join_all, StreamExt};
+    use hotshot::types::EventType::{self, Decide};
     use hotshot_example_types::node_types::TestVersions;
+    use hotshot_query_service::metrics::PrometheusMetrics;
     use hotshot_types::{
-        data::vid_commitment,
         event::LeafInfo,
-        traits::block_contents::{
-            BlockHeader, BlockPayload, EncodeBytes, GENESIS_VID_NUM_STORAGE_NODES,
-        },
+        traits::block_contents::{BlockHeader, BlockPayload, EncodeBytes},
     };
     use sequencer_utils::test_utils::setup_test;
-    use testing::{wait_for_decide_on_handle, TestConfigBuilder};
+    use testing::{
+        submit_and_wait, wait_for_decide_on_handle, CountingEventConsumer, EventCounts,
+        TestConfigBuilder,
+    };
 
     use self::testing::run_test_builder;
     use super::*;
 
+    #[test]
+    fn test_validator_config_from_mnemonic() {
+        let mnemonic = "test test test test test test test test test test test junk";
+        let config = validator_config_from_mnemonic(mnemonic, 0, true).unwrap();
+        let again = validator_config_from_mnemonic(mnemonic, 0, true).unwrap();
+        assert_eq!(config.public_config(), again.public_config());
+        assert!(config.is_da);
+
+        let other = validator_config_from_mnemonic(mnemonic, 1, false).unwrap();
+        assert_ne!(other.public_key, config.public_key);
+        assert_ne!(other.state_public_key, config.state_public_key);
+
+        assert!(validator_config_from_mnemonic("not a mnemonic", 0, false).is_err());
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_skeleton_instantiation() {
         setup_test();
@@ -1360,6 +1610,375 @@ mod test {
         wait_for_decide_on_handle(&mut events, &txn).await;
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_da_committee_size() {
+        setup_test();
+        let anvil = Anvil::new().spawn();
+        const NUM_NODES: usize = 5;
+        let config = TestConfigBuilder::<NUM_NODES>::default()
+            .l1_url(anvil.endpoint_url())
+            .da_committee_size(3)
+            .build();
+
+        let hotshot_config = config.hotshot_config();
+        assert_eq!(hotshot_config.da_staked_committee_size, 3);
+        assert_eq!(hotshot_config.known_da_nodes.len(), 3);
+        let non_da = config
+            .known_nodes_with_stake()
+            .iter()
+            .filter(|node| !hotshot_config.known_da_nodes.contains(node))
+            .count();
+        assert_eq!(non_da, 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_invalid_timeouts() {
+        setup_test();
+        let anvil = Anvil::new().spawn();
+        const NUM_NODES: usize = 5;
+        let err = TestConfigBuilder::<NUM_NODES>::default()
+            .l1_url(anvil.endpoint_url())
+            .next_view_timeout(Duration::from_secs(1))
+            .builder_timeout(Duration::from_secs(2))
+            .try_build()
+            .err()
+            .expect("builder_timeout exceeding next_view_timeout should be rejected");
+        let err = err.to_string();
+        assert!(err.contains("builder_timeout"), "{err}");
+        assert!(err.contains("next_view_timeout"), "{err}");
+
+        // The defaults are valid.
+        TestConfigBuilder::<NUM_NODES>::default()
+            .l1_url(anvil.endpoint_url())
+            .try_build()
+            .unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_legacy_builders() {
+        setup_test();
+        const NUM_NODES: usize = 5;
+        let builders =
+            join_all((0..8).map(|_| testing::run_legacy_builder::<NUM_NODES>(None, None, None)))
+                .await;
+
+        let ports = builders
+            .iter()
+            .map(|(_, url)| url.port().unwrap())
+            .collect::<HashSet<_>>();
+        assert_eq!(ports.len(), builders.len());
+        for port in ports {
+            std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_legacy_builder_base_fee() {
+        setup_test();
+        let anvil = Anvil::new().spawn();
+        const NUM_NODES: usize = 5;
+        const BASE_FEE: u64 = 42;
+        let mut config = TestConfigBuilder::<NUM_NODES>::default()
+            .l1_url(anvil.endpoint_url())
+            .build();
+
+        let (builder_task, builder_url) =
+            testing::run_legacy_builder::<NUM_NODES>(None, None, Some(BASE_FEE)).await;
+
+        config.set_builder_urls(vec1::vec1![builder_url]);
+        let handles = config.init_nodes(MockSequencerVersions::new()).await;
+        builder_task.start(Box::new(handles[0].event_stream().await));
+
+        let mut events = handles[0].event_stream().await;
+        for handle in handles.iter() {
+            handle.start_consensus().await;
+        }
+
+        let txn = Transaction::new(NamespaceId::from(1_u32), vec![1, 2, 3]);
+        handles[0].submit_transaction(txn.clone()).await.unwrap();
+
+        // Find the block containing the transaction.
+        let commitment = txn.commit();
+        let leaf = loop {
+            let Decide { leaf_chain, .. } = events.next().await.unwrap().event else {
+                continue;
+            };
+            if let Some(info) = leaf_chain.iter().find(|LeafInfo { leaf, .. }| {
+                leaf.block_payload().is_some_and(|payload| {
+                    payload
+                        .transaction_commitments(leaf.block_header().metadata())
+                        .contains(&commitment)
+                })
+            }) {
+                break info.leaf.clone();
+            }
+        };
+
+        // The builder pays its base fee for every byte of the block.
+        let block_size = leaf.block_payload().unwrap().encode().len() as u64;
+        let fee_info = leaf.block_header().fee_info();
+        assert_eq!(fee_info[0].amount(), FeeAmount::from(BASE_FEE * block_size));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_test_builder_ready() {
+        setup_test();
+        const NUM_NODES: usize = 5;
+        let (_builder_task, url) = run_test_builder::<NUM_NODES>(None).await;
+
+        // No retry: the builder is already serving when its URL is returned.
+        reqwest::get(url).await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_submit_and_wait() {
+        setup_test();
+        let anvil = Anvil::new().spawn();
+        let url = anvil.endpoint_url();
+        const NUM_NODES: usize = 5;
+        let mut config = TestConfigBuilder::<NUM_NODES>::default()
+            .l1_url(url)
+            .build();
+
+        let (builder_task, builder_url) = run_test_builder::<NUM_NODES>(None).await;
+
+        config.set_builder_urls(vec1::vec1![builder_url]);
+        let handles = config.init_nodes(MockSequencerVersions::new()).await;
+        builder_task.start(Box::new(handles[0].event_stream().await));
+
+        let mut events = handles[0].event_stream().await;
+        for handle in handles.iter() {
+            handle.start_consensus().await;
+        }
+
+        let txn = Transaction::new(NamespaceId::from(1_u32), vec![1, 2, 3]);
+        let height = submit_and_wait(&handles[0], &mut events, txn).await;
+        assert!(height > 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_event_stream_for_namespace() {
+        setup_test();
+        let anvil = Anvil::new().spawn();
+        let url = anvil.endpoint_url();
+        const NUM_NODES: usize = 5;
+        let mut config = TestConfigBuilder::<NUM_NODES>::default()
+            .l1_url(url)
+            .build();
+
+        let (builder_task, builder_url) = run_test_builder::<NUM_NODES>(None).await;
+
+        config.set_builder_urls(vec1::vec1![builder_url]);
+        let handles = config.init_nodes(MockSequencerVersions::new()).await;
+        builder_task.start(Box::new(handles[0].event_stream().await));
+
+        let ns = NamespaceId::from(2_u32);
+        let mut events = handles[0].event_stream().await;
+        let mut filtered = handles[0].event_stream_for_namespace(ns).await;
+        for handle in handles.iter() {
+            handle.start_consensus().await;
+        }
+
+        // Sequence a transaction in another namespace, then one in ours.
+        let other = Transaction::new(NamespaceId::from(1_u32), vec![1, 2, 3]);
+        submit_and_wait(&handles[0], &mut events, other).await;
+        let txn = Transaction::new(ns, vec![4, 5, 6]);
+        let height = submit_and_wait(&handles[0], &mut events, txn).await;
+
+        // The filtered stream skips every block before the one with our transaction.
+        let event = filtered.next().await.unwrap();
+        let Decide { leaf_chain, .. } = event.event else {
+            panic!("expected decide event, got {event:?}");
+        };
+        let heights = leaf_chain
+            .iter()
+            .map(|LeafInfo { leaf, .. }| leaf.height())
+            .collect::<Vec<_>>();
+        assert_eq!(heights, vec![height]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_decided_height() {
+        setup_test();
+        // Assign `config` so it isn't dropped early.
+        let anvil = Anvil::new().spawn();
+        let url = anvil.endpoint_url();
+        const NUM_NODES: usize = 5;
+        let mut config = TestConfigBuilder::<NUM_NODES>::default()
+            .l1_url(url)
+            .build();
+
+        let (builder_task, builder_url) = run_test_builder::<NUM_NODES>(None).await;
+
+        config.set_builder_urls(vec1::vec1![builder_url]);
+        let handles = config.init_nodes(MockSequencerVersions::new()).await;
+
+        let handle_0 = &handles[0];
+        assert_eq!(handle_0.decided_height().await, 0);
+
+        // Hook the builder up to the event stream from the first node
+        builder_task.start(Box::new(handle_0.event_stream().await));
+
+        for handle in handles.iter() {
+            handle.start_consensus().await;
+        }
+
+        tokio::time::timeout(Duration::from_secs(60), async {
+            while handle_0.decided_height().await == 0 {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .expect("decided height did not advance past genesis");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_event_metrics_gauges() {
+        setup_test();
+        let anvil = Anvil::new().spawn();
+        let url = anvil.endpoint_url();
+        const NUM_NODES: usize = 5;
+        let mut config = TestConfigBuilder::<NUM_NODES>::default()
+            .l1_url(url)
+            .build();
+
+        let (builder_task, builder_url) = run_test_builder::<NUM_NODES>(None).await;
+
+        config.set_builder_urls(vec1::vec1![builder_url]);
+        let metrics = PrometheusMetrics::default();
+        let handles = config
+            .init_nodes_with_metrics(MockSequencerVersions::new(), &[&metrics])
+            .await;
+        builder_task.start(Box::new(handles[0].event_stream().await));
+
+        let gauges = metrics.get_subgroup(["events"]).unwrap();
+        let current_view = gauges.get_gauge("current_view").unwrap();
+        let decided_height = gauges.get_gauge("decided_height").unwrap();
+        assert_eq!(current_view.get(), 0);
+        assert_eq!(decided_height.get(), 0);
+
+        for handle in handles.iter() {
+            handle.start_consensus().await;
+        }
+
+        tokio::time::timeout(Duration::from_secs(60), async {
+            while current_view.get() == 0 || decided_height.get() == 0 {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .expect("gauges did not advance");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_partition() {
+        setup_test();
+        let anvil = Anvil::new().spawn();
+        let url = anvil.endpoint_url();
+        const NUM_NODES: usize = 5;
+        let mut config = TestConfigBuilder::<NUM_NODES>::default()
+            .l1_url(url)
+            .build();
+
+        let (builder_task, builder_url) = run_test_builder::<NUM_NODES>(None).await;
+
+        config.set_builder_urls(vec1::vec1![builder_url]);
+        let handles = config.init_nodes(MockSequencerVersions::new()).await;
+        builder_task.start(Box::new(handles[0].event_stream().await));
+
+        // Isolate one node before consensus starts.
+        config.partition(&[0, 1, 2, 3], &[4]);
+        for handle in handles.iter() {
+            handle.start_consensus().await;
+        }
+
+        // The majority still has a quorum and keeps deciding.
+        tokio::time::timeout(Duration::from_secs(60), async {
+            while handles[0].decided_height().await < 3 {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .expect("majority did not decide during the partition");
+
+        // The isolated node can never form a quorum on its own, nor hear about the decides.
+        assert_eq!(handles[4].decided_height().await, 0);
+
+        // Once the partition heals, the isolated node catches up with the majority.
+        let majority_height = handles[0].decided_height().await;
+        config.heal();
+        tokio::time::timeout(Duration::from_secs(60), async {
+            while handles[4].decided_height().await < majority_height {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .expect("isolated node did not catch up after the partition healed");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_latency_causes_view_timeout() {
+        setup_test();
+        let anvil = Anvil::new().spawn();
+        let url = anvil.endpoint_url();
+        const NUM_NODES: usize = 5;
+        // Deliver messages only after the 5s view timeout of the test config has expired.
+        let mut config = TestConfigBuilder::<NUM_NODES>::default()
+            .l1_url(url)
+            .with_latency(Duration::from_secs(6))
+            .build();
+
+        let (builder_task, builder_url) = run_test_builder::<NUM_NODES>(None).await;
+
+        config.set_builder_urls(vec1::vec1![builder_url]);
+        let handles = config.init_nodes(MockSequencerVersions::new()).await;
+        builder_task.start(Box::new(handles[0].event_stream().await));
+
+        let mut events = handles[0].event_stream().await;
+        for handle in handles.iter() {
+            handle.start_consensus().await;
+        }
+
+        let timed_out = tokio::time::timeout(Duration::from_secs(60), async {
+            loop {
+                if let EventType::ViewTimeout { view_number } = events.next().await.unwrap().event {
+                    break view_number;
+                }
+            }
+        })
+        .await
+        .expect("no view timed out");
+
+        // The timeout moves the node on to a later view, despite the latency.
+        tokio::time::timeout(Duration::from_secs(120), async {
+            while handles[0].consensus().read().await.cur_view().await <= timed_out {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        })
+        .await
+        .expect("view did not advance after timing out");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_hotshot_config_json() {
+        setup_test();
+        // Assign `config` so it isn't dropped early.
+        let anvil = Anvil::new().spawn();
+        let url = anvil.endpoint_url();
+        const NUM_NODES: usize = 2;
+        let config = TestConfigBuilder::<NUM_NODES>::default()
+            .l1_url(url)
+            .build();
+
+        let handles = config.init_nodes(MockSequencerVersions::new()).await;
+        let json = handles[0].hotshot_config_json().unwrap();
+        assert_eq!(json["epoch_height"], config.hotshot_config().epoch_height);
+        assert_eq!(
+            json["epoch_start_block"],
+            config.hotshot_config().epoch_start_block
+        );
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_header_invariants() {
         setup_test();
@@ -1389,30 +2008,12 @@ mod test {
             handle.start_consensus().await;
         }
 
-        let mut parent = {
-            // TODO refactor repeated code from other tests
-            let (genesis_payload, genesis_ns_table) =
-                Payload::from_transactions([], &ValidatedState::default(), &NodeState::mock())
-                    .await
-                    .unwrap();
-            let genesis_commitment = {
-                // TODO we should not need to collect payload bytes just to compute vid_commitment
-                let payload_bytes = genesis_payload.encode();
-                vid_commitment::<TestVersions>(
-                    &payload_bytes,
-                    &genesis_ns_table.encode(),
-                    GENESIS_VID_NUM_STORAGE_NODES,
-                    <TestVersions as Versions>::Base::VERSION,
-                )
-            };
-            let genesis_state = NodeState::mock();
-            Header::genesis(
-                &genesis_state,
-                genesis_commitment,
-                empty_builder_commitment(),
-                genesis_ns_table,
-            )
-        };
+        let mut parent = Header::genesis_from_state::<TestVersions>(
+            &NodeState::mock(),
+            empty_builder_commitment(),
+        )
+        .await
+        .unwrap();
 
         loop {
             let event = events.next().await.unwrap();
@@ -1430,10 +2031,7 @@ mod test {
                     parent = header;
                     continue;
                 }
-                assert_eq!(header.height(), parent.height() + 1);
-                assert!(header.timestamp() >= parent.timestamp());
-                assert!(header.l1_head() >= parent.l1_head());
-                assert!(header.l1_finalized() >= parent.l1_finalized());
+                check_header_succession(&parent, &header).unwrap();
                 parent = header;
             }
 
@@ -1442,4 +2040,74 @@ mod test {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_counting_event_consumer() {
+        use hotshot_types::{
+            data::Leaf2, simple_certificate::QuorumCertificate2,
+            traits::node_implementation::ConsensusTime,
+        };
+
+        let leaf =
+            Leaf2::genesis::<TestVersions>(&ValidatedState::default(), &NodeState::mock()).await;
+        let qc = QuorumCertificate2::genesis::<TestVersions>(
+            &ValidatedState::default(),
+            &NodeState::mock(),
+        )
+        .await;
+        let leaf_info = LeafInfo::new(leaf, Default::default(), None, None, None);
+        let view_number = ViewNumber::genesis();
+        let decide = |num_leaves| espresso_types::Event {
+            view_number,
+            event: Decide {
+                leaf_chain: Arc::new(vec![leaf_info.clone(); num_leaves]),
+                qc: Arc::new(qc.clone()),
+                block_size: None,
+            },
+        };
+        let view_finished = espresso_types::Event {
+            view_number,
+            event: EventType::ViewFinished { view_number },
+        };
+        let view_timeout = espresso_types::Event {
+            view_number,
+            event: EventType::ViewTimeout { view_number },
+        };
+
+        // Hand a clone to the "node" and keep one for ourselves, to check the counts are shared.
+        let counter = CountingEventConsumer::default();
+        let consumer = counter.clone();
+        for event in [
+            decide(1),
+            view_finished.clone(),
+            decide(3),
+            view_timeout,
+            view_finished,
+            // Not counted separately.
+            espresso_types::Event {
+                view_number,
+                event: EventType::Transactions {
+                    transactions: vec![],
+                },
+            },
+        ] {
+            consumer.handle_event(&event).await.unwrap();
+        }
+
+        assert_eq!(counter.decide_count(), 2);
+        assert_eq!(counter.decided_leaf_count(), 4);
+        assert_eq!(counter.view_finished_count(), 2);
+        assert_eq!(counter.view_timeout_count(), 1);
+        assert_eq!(
+            counter.counts(),
+            EventCounts {
+                decides: 2,
+                decided_leaves: 4,
+                views_finished: 2,
+                view_timeouts: 1,
+                errors: 0,
+                other: 1,
+            }
+        );
+    }
 }
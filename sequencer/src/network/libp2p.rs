@@ -1,4 +1,6 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use hotshot::traits::implementations::derive_libp2p_peer_id;
+use hotshot_types::signature_key::{BLSPrivKey, BLSPubKey};
 use libp2p::{multiaddr::Protocol, Multiaddr, PeerId};
 
 /// Split off the peer ID from a multiaddress, returning the shortened address and the peer ID.
@@ -12,3 +14,35 @@ pub fn split_off_peer_id(mut address: Multiaddr) -> Result<(PeerId, Multiaddr)>
 
     Ok((peer_id, address))
 }
+
+/// Derive the Libp2p peer ID a node will use from its private staking key.
+///
+/// This is the same ID the node logs on startup, so operators can compute it ahead of time, e.g. to
+/// pre-seed bootstrap lists, without loading a network config.
+pub fn peer_id_from_private_key(key: &BLSPrivKey) -> Result<PeerId> {
+    derive_libp2p_peer_id::<BLSPubKey>(key).context("Failed to derive Libp2p peer ID")
+}
+
+#[cfg(test)]
+mod test {
+    use hotshot::types::SignatureKey;
+
+    use super::*;
+
+    #[test]
+    fn test_peer_id_from_private_key() {
+        let (_, key) = BLSPubKey::generated_from_seed_indexed([0; 32], 0);
+        let peer_id = peer_id_from_private_key(&key).unwrap();
+        assert_eq!(peer_id, peer_id_from_private_key(&key).unwrap());
+
+        let (_, other_key) = BLSPubKey::generated_from_seed_indexed([0; 32], 1);
+        assert_ne!(peer_id, peer_id_from_private_key(&other_key).unwrap());
+
+        // The peer ID for a fixed key must never change, or bootstrap lists break.
+        let key = BLSPrivKey::from_bytes(&[1; 32]);
+        assert_eq!(
+            peer_id_from_private_key(&key).unwrap().to_string(),
+            "12D3KooWNtgrpxpwLD3QXRnXRhQ4bcifCZzJgFGm6Bmqyb9irJeD"
+        );
+    }
+}
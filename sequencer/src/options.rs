@@ -21,7 +21,10 @@ use sequencer_utils::logging;
 use tagged_base64::TaggedBase64;
 use url::Url;
 
-use crate::{api, persistence, proposal_fetcher::ProposalFetcherConfig};
+use crate::{
+    api, event_export::EventExportOptions, external_event_handler::ExternalEventHandlerConfig,
+    persistence, proposal_fetcher::ProposalFetcherConfig, state_signature::StateRelayBackoff,
+};
 
 // This options struct is a bit unconventional. The sequencer has multiple optional modules which
 // can be added, in any combination, to the service. These include, for example, the API server.
@@ -65,6 +68,18 @@ pub struct Options {
     )]
     pub cdn_endpoint: String,
 
+    /// How long to wait for a message to go through the CDN before also sending it over Libp2p.
+    ///
+    /// High-latency deployments may want to increase this to avoid duplicating traffic on both
+    /// networks.
+    #[clap(
+        long,
+        env = "ESPRESSO_SEQUENCER_COMBINED_NETWORK_DELAY",
+        default_value = "1s",
+        value_parser = parse_duration
+    )]
+    pub combined_network_delay: Duration,
+
     /// The address to bind to for Libp2p (in `host:port` form)
     #[clap(
         long,
@@ -248,6 +263,18 @@ pub struct Options {
     #[derivative(Debug(format_with = "Display::fmt"))]
     pub state_relay_server_url: Url,
 
+    /// Exponential backoff for retrying failed posts to the state relay server.
+    #[clap(flatten)]
+    pub state_relay_backoff: StateRelayBackoff,
+
+    /// Capacity and backpressure handling for messages forwarded to the request-response protocol.
+    #[clap(flatten)]
+    pub external_event_handler: ExternalEventHandlerConfig,
+
+    /// Export of consensus events to a local file.
+    #[clap(flatten)]
+    pub event_export: EventExportOptions,
+
     /// Path to TOML file containing genesis state.
     #[clap(
         long,
@@ -653,3 +680,15 @@ pub struct Modules {
     pub hotshot_events: Option<api::options::HotshotEvents>,
     pub explorer: Option<api::options::Explorer>,
 }
+
+#[cfg(test)]
+mod test {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Options::command().debug_assert();
+    }
+}
@@ -8,18 +8,63 @@
 //! an extension that node operators can opt into. This module defines the minimum level of
 //! persistence which is _required_ to run a node.
 
+use anyhow::{bail, ensure, Context};
 use async_trait::async_trait;
-use espresso_types::v0_99::ChainConfig;
+use espresso_types::{v0::traits::SequencerPersistence, v0_99::ChainConfig};
 
 pub mod fs;
 pub mod no_storage;
 pub mod sql;
 
+/// The version of the persisted data layout written by this code.
+///
+/// When a change to the persisted data requires a forward migration, bump this and add the
+/// migration as a new step in [`check_and_migrate`].
+pub const SCHEMA_VERSION: u32 = 1;
+
 #[async_trait]
 pub trait ChainConfigPersistence: Sized + Send + Sync {
     async fn insert_chain_config(&mut self, chain_config: ChainConfig) -> anyhow::Result<()>;
 }
 
+/// Bring persisted data up to [`SCHEMA_VERSION`].
+///
+/// Storage without a saved version predates versioning and is treated as version 0. Fails without
+/// touching the storage if it was written by a newer version of the code than this one.
+///
+/// The consensus data migration runs on every start regardless of the version, since it is
+/// resumable and keeps track of its own progress.
+pub async fn check_and_migrate(persistence: &impl SequencerPersistence) -> anyhow::Result<()> {
+    let version = persistence.load_schema_version().await?.unwrap_or(0);
+    ensure!(
+        version <= SCHEMA_VERSION,
+        "DB version {version}, code expects {SCHEMA_VERSION}: storage was written by a newer \
+         version of the sequencer"
+    );
+
+    persistence
+        .migrate_consensus()
+        .await
+        .context("failed to migrate consensus data")?;
+    if version == SCHEMA_VERSION {
+        return Ok(());
+    }
+
+    tracing::warn!(
+        from = version,
+        to = SCHEMA_VERSION,
+        "migrating persisted data"
+    );
+    for from in version..SCHEMA_VERSION {
+        match from {
+            // Version 1 is the first versioned layout, it only adds the version itself.
+            0 => {},
+            _ => bail!("no migration from schema version {from}"),
+        }
+    }
+    persistence.save_schema_version(SCHEMA_VERSION).await
+}
+
 #[cfg(any(test, feature = "testing"))]
 mod testing {
 
@@ -54,8 +99,8 @@ mod persistence_tests {
     use espresso_types::{
         traits::{EventConsumer, NullEventConsumer, PersistenceOptions},
         v0_3::{StakeTableFetcher, Validator},
-        Event, L1Client, Leaf, Leaf2, NodeState, PubKey, SeqTypes, SequencerVersions,
-        ValidatedState,
+        Event, L1Client, Leaf, Leaf2, NetworkConfig, NodeState, PubKey, SeqTypes,
+        SequencerVersions, ValidatedState,
     };
     use futures::{future::join_all, StreamExt, TryStreamExt};
     use hotshot::{
@@ -176,6 +221,48 @@ mod persistence_tests {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    pub async fn test_check_and_migrate<P: TestablePersistence>() {
+        setup_test();
+
+        let tmp = P::tmp_storage().await;
+        let storage = P::connect(&tmp).await;
+
+        // Fresh storage has no version and is stamped with the current one.
+        assert_eq!(storage.load_schema_version().await.unwrap(), None);
+        check_and_migrate(&storage).await.unwrap();
+        assert_eq!(
+            storage.load_schema_version().await.unwrap(),
+            Some(SCHEMA_VERSION)
+        );
+
+        // A config written at an older version is still loaded after migrating.
+        let cfg = NetworkConfig {
+            node_index: 7,
+            ..Default::default()
+        };
+        storage.save_config(&cfg).await.unwrap();
+        storage.save_schema_version(0).await.unwrap();
+        check_and_migrate(&storage).await.unwrap();
+        assert_eq!(
+            storage.load_schema_version().await.unwrap(),
+            Some(SCHEMA_VERSION)
+        );
+        let loaded = storage.load_config().await.unwrap().unwrap();
+        assert_eq!(loaded.node_index, 7);
+
+        // Storage written by newer code is rejected.
+        storage
+            .save_schema_version(SCHEMA_VERSION + 1)
+            .await
+            .unwrap();
+        let err = check_and_migrate(&storage).await.unwrap_err();
+        assert!(err.to_string().contains(&format!(
+            "DB version {}, code expects {SCHEMA_VERSION}",
+            SCHEMA_VERSION + 1
+        )));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     pub async fn test_epoch_info<P: TestablePersistence>() {
         setup_test();
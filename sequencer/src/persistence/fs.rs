@@ -153,6 +153,10 @@ impl Inner {
         self.path.join("highest_voted_view")
     }
 
+    fn schema_version_path(&self) -> PathBuf {
+        self.path.join("schema_version")
+    }
+
     /// Path to a directory containing decided leaves.
     fn decided_leaf_path(&self) -> PathBuf {
         self.path.join("decided_leaves")
@@ -577,6 +581,31 @@ impl SequencerPersistence for Persistence {
         Ok(cfg.to_file(path.display().to_string())?)
     }
 
+    async fn load_schema_version(&self) -> anyhow::Result<Option<u32>> {
+        let inner = self.inner.read().await;
+        let path = inner.schema_version_path();
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let bytes = fs::read(&path)?
+            .try_into()
+            .map_err(|bytes| anyhow!("malformed schema version file: {bytes:?}"))?;
+        Ok(Some(u32::from_le_bytes(bytes)))
+    }
+
+    async fn save_schema_version(&self, version: u32) -> anyhow::Result<()> {
+        let mut inner = self.inner.write().await;
+        let path = &inner.schema_version_path();
+        inner.replace(
+            path,
+            |_| Ok(true),
+            |mut file| {
+                file.write_all(&version.to_le_bytes())?;
+                Ok(())
+            },
+        )
+    }
+
     async fn load_latest_acted_view(&self) -> anyhow::Result<Option<ViewNumber>> {
         let inner = self.inner.read().await;
         let path = inner.voted_view_path();
@@ -1655,7 +1684,10 @@ mod test {
     use vbs::version::StaticVersionType;
 
     use super::*;
-    use crate::{persistence::testing::TestablePersistence, BLSPubKey};
+    use crate::{
+        persistence::{check_and_migrate, testing::TestablePersistence, SCHEMA_VERSION},
+        BLSPubKey,
+    };
 
     #[test]
     fn test_config_migrations_add_builder_urls() {
@@ -1937,7 +1969,10 @@ mod test {
                 .unwrap();
         }
 
-        storage.migrate_consensus().await.unwrap();
+        // The node restarts at the current schema version, so there is no version-specific step,
+        // but the consensus data is still migrated.
+        storage.save_schema_version(SCHEMA_VERSION).await.unwrap();
+        check_and_migrate(&storage).await.unwrap();
         let inner = storage.inner.read().await;
         let decided_leaves = fs::read_dir(inner.decided_leaf2_path()).unwrap();
         let decided_leaves_count = decided_leaves
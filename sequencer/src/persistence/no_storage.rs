@@ -1,7 +1,10 @@
 //! Mock implementation of persistence, for testing.
 #![cfg(any(test, feature = "testing"))]
 
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
 
 use anyhow::bail;
 use async_trait::async_trait;
@@ -25,6 +28,7 @@ use hotshot_types::{
         LightClientStateUpdateCertificate, NextEpochQuorumCertificate2, QuorumCertificate2,
         UpgradeCertificate,
     },
+    vote::HasViewNumber,
 };
 use indexmap::IndexMap;
 
@@ -61,6 +65,14 @@ impl SequencerPersistence for NoStorage {
         Ok(())
     }
 
+    async fn load_schema_version(&self) -> anyhow::Result<Option<u32>> {
+        Ok(None)
+    }
+
+    async fn save_schema_version(&self, _: u32) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     async fn append_decided_leaves(
         &self,
         view_number: ViewNumber,
@@ -278,3 +290,410 @@ impl MembershipPersistence for NoStorage {
         Ok(None)
     }
 }
+
+/// Like [`NoStorage`], but keeps the saved config and decided leaves in memory, so that tests can
+/// assert on what a node persisted.
+///
+/// Clones share the same records.
+#[derive(Clone, Debug, Default)]
+pub struct RecordingPersistence {
+    config: Arc<Mutex<Option<NetworkConfig>>>,
+    leaves: Arc<Mutex<BTreeMap<ViewNumber, (Leaf2, QuorumCertificate2<SeqTypes>)>>>,
+}
+
+impl RecordingPersistence {
+    /// The last config saved, if any.
+    pub fn saved_config(&self) -> Option<NetworkConfig> {
+        self.config.lock().unwrap().clone()
+    }
+
+    /// The decided leaf of `view`, if it was persisted.
+    pub fn decided_leaf(&self, view: ViewNumber) -> Option<Leaf2> {
+        self.leaves
+            .lock()
+            .unwrap()
+            .get(&view)
+            .map(|(leaf, _)| leaf.clone())
+    }
+
+    /// All persisted decided leaves, in view order.
+    pub fn decided_leaves(&self) -> Vec<Leaf2> {
+        self.leaves
+            .lock()
+            .unwrap()
+            .values()
+            .map(|(leaf, _)| leaf.clone())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl SequencerPersistence for RecordingPersistence {
+    async fn load_config(&self) -> anyhow::Result<Option<NetworkConfig>> {
+        Ok(self.saved_config())
+    }
+
+    async fn save_config(&self, cfg: &NetworkConfig) -> anyhow::Result<()> {
+        *self.config.lock().unwrap() = Some(cfg.clone());
+        Ok(())
+    }
+
+    async fn load_schema_version(&self) -> anyhow::Result<Option<u32>> {
+        NoStorage.load_schema_version().await
+    }
+
+    async fn save_schema_version(&self, version: u32) -> anyhow::Result<()> {
+        NoStorage.save_schema_version(version).await
+    }
+
+    async fn append_decided_leaves(
+        &self,
+        view_number: ViewNumber,
+        leaves: impl IntoIterator<Item = (&LeafInfo<SeqTypes>, QuorumCertificate2<SeqTypes>)> + Send,
+        consumer: &impl EventConsumer,
+    ) -> anyhow::Result<()> {
+        let leaves = leaves
+            .into_iter()
+            .map(|(info_ref, qc)| (info_ref.clone(), qc))
+            .collect::<Vec<_>>();
+        {
+            let mut recorded = self.leaves.lock().unwrap();
+            for (info, qc) in &leaves {
+                recorded.insert(info.leaf.view_number(), (info.leaf.clone(), qc.clone()));
+            }
+        }
+        NoStorage
+            .append_decided_leaves(
+                view_number,
+                leaves.iter().map(|(info, qc)| (info, qc.clone())),
+                consumer,
+            )
+            .await
+    }
+
+    async fn load_anchor_leaf(
+        &self,
+    ) -> anyhow::Result<Option<(Leaf2, QuorumCertificate2<SeqTypes>)>> {
+        Ok(self
+            .leaves
+            .lock()
+            .unwrap()
+            .last_key_value()
+            .map(|(_, anchor)| anchor.clone()))
+    }
+
+    async fn load_latest_acted_view(&self) -> anyhow::Result<Option<ViewNumber>> {
+        NoStorage.load_latest_acted_view().await
+    }
+
+    async fn load_da_proposal(
+        &self,
+        view: ViewNumber,
+    ) -> anyhow::Result<Option<Proposal<SeqTypes, DaProposal2<SeqTypes>>>> {
+        NoStorage.load_da_proposal(view).await
+    }
+
+    async fn load_vid_share(
+        &self,
+        view: ViewNumber,
+    ) -> anyhow::Result<Option<Proposal<SeqTypes, VidDisperseShare<SeqTypes>>>> {
+        NoStorage.load_vid_share(view).await
+    }
+
+    async fn load_quorum_proposals(
+        &self,
+    ) -> anyhow::Result<BTreeMap<ViewNumber, Proposal<SeqTypes, QuorumProposalWrapper<SeqTypes>>>>
+    {
+        NoStorage.load_quorum_proposals().await
+    }
+
+    async fn load_quorum_proposal(
+        &self,
+        view: ViewNumber,
+    ) -> anyhow::Result<Proposal<SeqTypes, QuorumProposalWrapper<SeqTypes>>> {
+        NoStorage.load_quorum_proposal(view).await
+    }
+
+    async fn load_upgrade_certificate(
+        &self,
+    ) -> anyhow::Result<Option<UpgradeCertificate<SeqTypes>>> {
+        NoStorage.load_upgrade_certificate().await
+    }
+
+    async fn append_vid(
+        &self,
+        proposal: &Proposal<SeqTypes, ADVZDisperseShare<SeqTypes>>,
+    ) -> anyhow::Result<()> {
+        NoStorage.append_vid(proposal).await
+    }
+
+    async fn append_vid2(
+        &self,
+        proposal: &Proposal<SeqTypes, VidDisperseShare2<SeqTypes>>,
+    ) -> anyhow::Result<()> {
+        NoStorage.append_vid2(proposal).await
+    }
+
+    async fn append_da(
+        &self,
+        proposal: &Proposal<SeqTypes, DaProposal<SeqTypes>>,
+        vid_commit: VidCommitment,
+    ) -> anyhow::Result<()> {
+        NoStorage.append_da(proposal, vid_commit).await
+    }
+
+    async fn record_action(
+        &self,
+        view: ViewNumber,
+        epoch: Option<EpochNumber>,
+        action: HotShotAction,
+    ) -> anyhow::Result<()> {
+        NoStorage.record_action(view, epoch, action).await
+    }
+
+    async fn append_quorum_proposal2(
+        &self,
+        proposal: &Proposal<SeqTypes, QuorumProposalWrapper<SeqTypes>>,
+    ) -> anyhow::Result<()> {
+        NoStorage.append_quorum_proposal2(proposal).await
+    }
+
+    async fn store_upgrade_certificate(
+        &self,
+        decided_upgrade_certificate: Option<UpgradeCertificate<SeqTypes>>,
+    ) -> anyhow::Result<()> {
+        NoStorage
+            .store_upgrade_certificate(decided_upgrade_certificate)
+            .await
+    }
+
+    async fn store_next_epoch_quorum_certificate(
+        &self,
+        high_qc: NextEpochQuorumCertificate2<SeqTypes>,
+    ) -> anyhow::Result<()> {
+        NoStorage.store_next_epoch_quorum_certificate(high_qc).await
+    }
+
+    async fn load_next_epoch_quorum_certificate(
+        &self,
+    ) -> anyhow::Result<Option<NextEpochQuorumCertificate2<SeqTypes>>> {
+        NoStorage.load_next_epoch_quorum_certificate().await
+    }
+
+    async fn append_da2(
+        &self,
+        proposal: &Proposal<SeqTypes, DaProposal2<SeqTypes>>,
+        vid_commit: VidCommitment,
+    ) -> anyhow::Result<()> {
+        NoStorage.append_da2(proposal, vid_commit).await
+    }
+
+    async fn append_proposal2(
+        &self,
+        proposal: &Proposal<SeqTypes, QuorumProposalWrapper<SeqTypes>>,
+    ) -> anyhow::Result<()> {
+        NoStorage.append_proposal2(proposal).await
+    }
+
+    async fn migrate_anchor_leaf(&self) -> anyhow::Result<()> {
+        NoStorage.migrate_anchor_leaf().await
+    }
+
+    async fn migrate_da_proposals(&self) -> anyhow::Result<()> {
+        NoStorage.migrate_da_proposals().await
+    }
+
+    async fn migrate_vid_shares(&self) -> anyhow::Result<()> {
+        NoStorage.migrate_vid_shares().await
+    }
+
+    async fn migrate_quorum_proposals(&self) -> anyhow::Result<()> {
+        NoStorage.migrate_quorum_proposals().await
+    }
+
+    async fn migrate_quorum_certificates(&self) -> anyhow::Result<()> {
+        NoStorage.migrate_quorum_certificates().await
+    }
+
+    async fn add_drb_result(
+        &self,
+        epoch: EpochNumber,
+        drb_result: DrbResult,
+    ) -> anyhow::Result<()> {
+        NoStorage.add_drb_result(epoch, drb_result).await
+    }
+
+    async fn add_epoch_root(
+        &self,
+        epoch: EpochNumber,
+        block_header: <SeqTypes as NodeType>::BlockHeader,
+    ) -> anyhow::Result<()> {
+        NoStorage.add_epoch_root(epoch, block_header).await
+    }
+
+    async fn load_start_epoch_info(&self) -> anyhow::Result<Vec<InitializerEpochInfo<SeqTypes>>> {
+        NoStorage.load_start_epoch_info().await
+    }
+
+    async fn add_state_cert(
+        &self,
+        state_cert: LightClientStateUpdateCertificate<SeqTypes>,
+    ) -> anyhow::Result<()> {
+        NoStorage.add_state_cert(state_cert).await
+    }
+
+    async fn load_state_cert(
+        &self,
+    ) -> anyhow::Result<Option<LightClientStateUpdateCertificate<SeqTypes>>> {
+        NoStorage.load_state_cert().await
+    }
+}
+
+#[async_trait]
+impl MembershipPersistence for RecordingPersistence {
+    async fn load_stake(
+        &self,
+        epoch: EpochNumber,
+    ) -> anyhow::Result<Option<IndexMap<alloy::primitives::Address, Validator<BLSPubKey>>>> {
+        NoStorage.load_stake(epoch).await
+    }
+
+    async fn load_latest_stake(&self, limit: u64) -> anyhow::Result<Option<Vec<IndexedStake>>> {
+        NoStorage.load_latest_stake(limit).await
+    }
+
+    async fn store_stake(
+        &self,
+        epoch: EpochNumber,
+        stake: IndexMap<alloy::primitives::Address, Validator<BLSPubKey>>,
+    ) -> anyhow::Result<()> {
+        NoStorage.store_stake(epoch, stake).await
+    }
+
+    async fn store_events(
+        &self,
+        l1_block: u64,
+        events: Vec<(EventKey, StakeTableEvent)>,
+    ) -> anyhow::Result<()> {
+        NoStorage.store_events(l1_block, events).await
+    }
+
+    async fn load_events(&self) -> anyhow::Result<Option<(u64, Vec<(EventKey, StakeTableEvent)>)>> {
+        NoStorage.load_events().await
+    }
+}
+
+/// A [`MembershipPersistence`] which keeps stake tables and events in memory.
+///
+/// This allows testing membership logic, such as reloading stake tables, without a database.
+/// Clones share the same store.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryMembershipPersistence {
+    stake: Arc<
+        Mutex<HashMap<EpochNumber, IndexMap<alloy::primitives::Address, Validator<BLSPubKey>>>>,
+    >,
+    events: Arc<Mutex<Option<(u64, Vec<(EventKey, StakeTableEvent)>)>>>,
+}
+
+#[async_trait]
+impl MembershipPersistence for InMemoryMembershipPersistence {
+    async fn load_stake(
+        &self,
+        epoch: EpochNumber,
+    ) -> anyhow::Result<Option<IndexMap<alloy::primitives::Address, Validator<BLSPubKey>>>> {
+        Ok(self.stake.lock().unwrap().get(&epoch).cloned())
+    }
+
+    async fn load_latest_stake(&self, limit: u64) -> anyhow::Result<Option<Vec<IndexedStake>>> {
+        let stake = self.stake.lock().unwrap();
+        let mut tables = stake
+            .iter()
+            .map(|(epoch, st)| (*epoch, st.clone()))
+            .collect::<Vec<_>>();
+        tables.sort_by(|(e1, _), (e2, _)| e2.cmp(e1));
+        tables.truncate(limit as usize);
+        Ok(Some(tables))
+    }
+
+    async fn store_stake(
+        &self,
+        epoch: EpochNumber,
+        stake: IndexMap<alloy::primitives::Address, Validator<BLSPubKey>>,
+    ) -> anyhow::Result<()> {
+        self.stake.lock().unwrap().insert(epoch, stake);
+        Ok(())
+    }
+
+    async fn store_events(
+        &self,
+        l1_block: u64,
+        events: Vec<(EventKey, StakeTableEvent)>,
+    ) -> anyhow::Result<()> {
+        *self.events.lock().unwrap() = Some((l1_block, events));
+        Ok(())
+    }
+
+    async fn load_events(&self) -> anyhow::Result<Option<(u64, Vec<(EventKey, StakeTableEvent)>)>> {
+        Ok(self.events.lock().unwrap().clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_recording_persistence_config() {
+        let storage = RecordingPersistence::default();
+        assert!(storage.load_config().await.unwrap().is_none());
+
+        let cfg = NetworkConfig {
+            node_index: 42,
+            ..Default::default()
+        };
+        // save through a clone, the records are shared
+        storage.clone().save_config(&cfg).await.unwrap();
+
+        let loaded = storage.load_config().await.unwrap().unwrap();
+        assert_eq!(loaded.node_index, 42);
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(storage.saved_config().unwrap()).unwrap()
+        );
+        assert!(storage.decided_leaves().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_membership_persistence() {
+        let storage = InMemoryMembershipPersistence::default();
+        assert!(storage
+            .load_stake(EpochNumber::new(1))
+            .await
+            .unwrap()
+            .is_none());
+        assert!(storage.load_events().await.unwrap().is_none());
+
+        let tables = (1..=3)
+            .map(|epoch| {
+                let validator = Validator::mock();
+                let st = IndexMap::from([(validator.account, validator)]);
+                (EpochNumber::new(epoch), st)
+            })
+            .collect::<Vec<_>>();
+        for (epoch, st) in &tables {
+            storage.store_stake(*epoch, st.clone()).await.unwrap();
+        }
+
+        // reload through a clone, the store is shared
+        let reloaded = storage.clone();
+        assert_eq!(
+            reloaded.load_stake(EpochNumber::new(2)).await.unwrap(),
+            Some(tables[1].1.clone())
+        );
+        let latest = reloaded.load_latest_stake(2).await.unwrap().unwrap();
+        assert_eq!(latest, vec![tables[2].clone(), tables[1].clone()]);
+
+        storage.store_events(10, vec![]).await.unwrap();
+        assert_eq!(reloaded.load_events().await.unwrap(), Some((10, vec![])));
+    }
+}
@@ -33,23 +33,23 @@ impl<
         P: SequencerPersistence,
     > StateCatchup for RequestResponseProtocol<I, V, N, P>
 {
-    async fn try_fetch_leaf(
+    async fn try_fetch_leaf_chain(
         &self,
         _retry: usize,
         height: u64,
         stake_table: HSStakeTable<SeqTypes>,
         success_threshold: U256,
-    ) -> anyhow::Result<Leaf2> {
+    ) -> anyhow::Result<Vec<Leaf2>> {
         // Timeout after a few batches
         let timeout_duration = self.config.request_batch_interval * 3;
 
-        // Fetch the leaf
+        // Fetch the leaf chain
         timeout(
             timeout_duration,
-            self.fetch_leaf(height, stake_table, success_threshold),
+            self.fetch_leaf_chain(height, stake_table, success_threshold),
         )
         .await
-        .with_context(|| "timed out while fetching leaf")?
+        .with_context(|| "timed out while fetching leaf chain")?
     }
 
     async fn try_fetch_accounts(
@@ -202,13 +202,13 @@ impl<
         Ok(response)
     }
 
-    async fn fetch_leaf(
+    async fn fetch_leaf_chain(
         &self,
         height: u64,
         stake_table: HSStakeTable<SeqTypes>,
         success_threshold: U256,
-    ) -> anyhow::Result<Leaf2> {
-        tracing::info!("Fetching leaf for height: {height}");
+    ) -> anyhow::Result<Vec<Leaf2>> {
+        tracing::info!("Fetching leaf chain for height: {height}");
 
         // Clone things we need in the first closure
         let stake_table_clone = stake_table.clone();
@@ -222,9 +222,9 @@ impl<
                     return Err(anyhow::anyhow!("expected leaf response"));
                 };
 
-                // Verify the leaf chain
-                let leaf = verify_leaf_chain(
-                    leaf_chain,
+                // Verify the leaf chain, so that we keep waiting for a peer with a valid one
+                verify_leaf_chain(
+                    leaf_chain.clone(),
                     &stake_table_clone,
                     success_threshold,
                     height,
@@ -233,7 +233,7 @@ impl<
                 .await
                 .with_context(|| "leaf chain verification failed")?;
 
-                Ok(leaf)
+                Ok(leaf_chain)
             }
         };
 
@@ -247,9 +247,9 @@ impl<
                 response_validation_fn,
             )
             .await
-            .with_context(|| "failed to request leaf")?;
+            .with_context(|| "failed to request leaf chain")?;
 
-        tracing::info!("Fetched leaf for height: {height}");
+        tracing::info!("Fetched leaf chain for height: {height}");
 
         Ok(response)
     }
@@ -6,7 +6,9 @@ use hotshot_types::traits::{network::ConnectedNetwork, node_implementation::Vers
 use network::Sender;
 use recipient_source::RecipientSource;
 use request::Request;
-use request_response::{network::Bytes, RequestResponse, RequestResponseConfig};
+use request_response::{
+    metrics::RequestResponseMetrics, network::Bytes, RequestResponse, RequestResponseConfig,
+};
 use tokio::sync::mpsc::Receiver;
 
 pub mod catchup;
@@ -71,6 +73,8 @@ impl<
         public_key: PubKey,
         // The private key of this node
         private_key: BLSPrivKey,
+        // The counters that [`RequestResponseProtocol`] will update
+        metrics: RequestResponseMetrics,
     ) -> Self {
         Self {
             inner: RequestResponse::new(
@@ -79,6 +83,7 @@ impl<
                 receiver,
                 recipient_source,
                 data_source,
+                metrics,
             ),
             config,
             public_key,
@@ -1,6 +1,5 @@
-use anyhow::Context;
 use clap::Parser;
-use espresso_types::traits::SequencerPersistence;
+use espresso_types::traits::EventConsumer;
 #[allow(unused_imports)]
 use espresso_types::{
     traits::NullEventConsumer, FeeVersion, SequencerVersions, SolverAuctionResultsProvider, V0_0,
@@ -12,6 +11,7 @@ use vbs::version::StaticVersionType;
 use super::{
     api::{self, data_source::DataSourceOptions},
     context::SequencerContext,
+    event_export::TeeEventConsumer,
     init_node, network,
     options::{Modules, Options},
     persistence, Genesis, L1Params, NetworkParams,
@@ -115,35 +115,26 @@ where
     Ok(())
 }
 
-pub(crate) async fn init_with_storage<S, V>(
-    genesis: Genesis,
-    modules: Modules,
-    opt: Options,
-    mut storage_opt: S,
-    versions: V,
-) -> anyhow::Result<SequencerContext<network::Production, S::Persistence, V>>
-where
-    S: DataSourceOptions,
-    V: Versions,
-{
+/// Collect the arguments for constructing the network, including the CDN and Libp2p networks and
+/// the [`CombinedNetworks`](hotshot::traits::implementations::CombinedNetworks) falling back from
+/// one to the other.
+fn network_params(opt: &Options) -> anyhow::Result<NetworkParams> {
     let (private_staking_key, private_state_key) = opt.private_keys()?;
-    let l1_params = L1Params {
-        urls: opt.l1_provider_url,
-        options: opt.l1_options,
-    };
-
-    let network_params = NetworkParams {
-        cdn_endpoint: opt.cdn_endpoint,
-        libp2p_advertise_address: opt.libp2p_advertise_address,
-        libp2p_bind_address: opt.libp2p_bind_address,
-        libp2p_bootstrap_nodes: opt.libp2p_bootstrap_nodes,
-        orchestrator_url: opt.orchestrator_url,
-        state_relay_server_url: opt.state_relay_server_url,
-        public_api_url: opt.public_api_url,
+    Ok(NetworkParams {
+        cdn_endpoint: opt.cdn_endpoint.clone(),
+        combined_network_delay: opt.combined_network_delay,
+        libp2p_advertise_address: opt.libp2p_advertise_address.clone(),
+        libp2p_bind_address: opt.libp2p_bind_address.clone(),
+        libp2p_bootstrap_nodes: opt.libp2p_bootstrap_nodes.clone(),
+        orchestrator_url: opt.orchestrator_url.clone(),
+        state_relay_server_url: opt.state_relay_server_url.clone(),
+        state_relay_backoff: opt.state_relay_backoff,
+        external_event_handler: opt.external_event_handler,
+        public_api_url: opt.public_api_url.clone(),
         private_staking_key,
         private_state_key,
-        state_peers: opt.state_peers,
-        config_peers: opt.config_peers,
+        state_peers: opt.state_peers.clone(),
+        config_peers: opt.config_peers.clone(),
         catchup_backoff: opt.catchup_backoff,
         libp2p_history_gossip: opt.libp2p_history_gossip,
         libp2p_history_length: opt.libp2p_history_length,
@@ -166,15 +157,35 @@ where
         libp2p_heartbeat_initial_delay: opt.libp2p_heartbeat_initial_delay,
         libp2p_gossip_factor: opt.libp2p_gossip_factor,
         libp2p_gossip_lazy: opt.libp2p_gossip_lazy,
+    })
+}
+
+pub(crate) async fn init_with_storage<S, V>(
+    genesis: Genesis,
+    modules: Modules,
+    opt: Options,
+    mut storage_opt: S,
+    versions: V,
+) -> anyhow::Result<SequencerContext<network::Production, S::Persistence, V>>
+where
+    S: DataSourceOptions,
+    V: Versions,
+{
+    let network_params = network_params(&opt)?;
+    let l1_params = L1Params {
+        urls: opt.l1_provider_url,
+        options: opt.l1_options,
     };
 
     let proposal_fetcher_config = opt.proposal_fetcher_config;
 
+    // Optionally tee decided events into a JSON file alongside whatever else consumes them.
+    let event_exporter: Box<dyn EventConsumer> = match opt.event_export.create()? {
+        Some(exporter) => Box::new(exporter),
+        None => Box::new(NullEventConsumer),
+    };
+
     let persistence = storage_opt.create().await?;
-    persistence
-        .migrate_consensus()
-        .await
-        .context("failed to migrate consensus data")?;
 
     // Initialize HotShot. If the user requested the HTTP module, we must initialize the handle in
     // a special way, in order to populate the API with consensus metrics. Otherwise, we initialize
@@ -217,7 +228,7 @@ where
                             l1_params,
                             storage,
                             versions,
-                            consumer,
+                            TeeEventConsumer(consumer, event_exporter),
                             opt.is_da,
                             opt.identity,
                             proposal_fetcher_config,
@@ -237,7 +248,7 @@ where
                 l1_params,
                 None,
                 versions,
-                NullEventConsumer,
+                event_exporter,
                 opt.is_da,
                 opt.identity,
                 proposal_fetcher_config,
@@ -270,6 +281,39 @@ mod test {
         SequencerApiVersion,
     };
 
+    #[test]
+    fn test_combined_network_delay_option() {
+        let (_, priv_key) = PubKey::generated_from_seed_indexed([0; 32], 0);
+        let state_key = StateKeyPair::generate_from_seed_indexed([0; 32], 0);
+        let args = [
+            "sequencer",
+            "--private-staking-key",
+            &priv_key.to_tagged_base64().expect("valid key").to_string(),
+            "--private-state-key",
+            &state_key
+                .sign_key_ref()
+                .to_tagged_base64()
+                .expect("valid key")
+                .to_string(),
+            "--genesis-file",
+            "genesis.toml",
+        ]
+        .map(String::from);
+
+        // The delay is handed on to `CombinedNetworks::new` through the network parameters.
+        let opt = Options::parse_from(&args);
+        let params = network_params(&opt).unwrap();
+        assert_eq!(params.combined_network_delay, Duration::from_secs(1));
+
+        let opt = Options::parse_from(
+            args.iter()
+                .cloned()
+                .chain(["--combined-network-delay".into(), "5s".into()]),
+        );
+        let params = network_params(&opt).unwrap();
+        assert_eq!(params.combined_network_delay, Duration::from_secs(5));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_startup_before_orchestrator() {
         setup_test();
@@ -292,6 +336,7 @@ mod test {
             upgrade_version: Version { major: 0, minor: 2 },
             epoch_height: None,
             epoch_start_block: None,
+            allowed_namespaces: None,
         };
         genesis.to_file(&genesis_file).unwrap();
 
@@ -1,12 +1,17 @@
 //! Utilities for generating and storing the most recent light client state signatures.
 
 use std::{
+    cmp::min,
     collections::{HashMap, VecDeque},
+    fmt::Display,
+    future::Future,
     sync::Arc,
+    time::Duration,
 };
 
 use async_lock::RwLock;
-use espresso_types::{traits::SequencerPersistence, PubKey};
+use clap::Parser;
+use espresso_types::{parse_duration, traits::SequencerPersistence, PubKey};
 use hotshot::types::{Event, EventType, SchnorrPubKey};
 use hotshot_types::{
     event::LeafInfo,
@@ -16,12 +21,15 @@ use hotshot_types::{
     },
     traits::{
         block_contents::BlockHeader,
+        metrics::{Counter, Metrics, NoMetrics},
         network::ConnectedNetwork,
         node_implementation::{NodeType, Versions},
         signature_key::StateSignatureKey,
     },
     utils::{is_ge_epoch_root, option_epoch_from_block_number},
 };
+use sequencer_utils::backoff::{retry_with_backoff, Backoff};
+use snafu::Snafu;
 use surf_disco::{Client, Url};
 use tide_disco::error::ServerError;
 use vbs::version::StaticVersionType;
@@ -34,6 +42,99 @@ pub mod relay_server;
 /// Capacity for the in memory signature storage.
 const SIGNATURE_STORAGE_CAPACITY: usize = 100;
 
+/// Bounded exponential backoff for posting state signatures to the relay server.
+#[derive(Clone, Copy, Debug, Parser)]
+pub struct StateRelayBackoff {
+    /// Maximum number of times to retry posting a signature to the relay server.
+    #[clap(
+        long = "state-relay-max-retries",
+        env = "ESPRESSO_SEQUENCER_STATE_RELAY_MAX_RETRIES",
+        default_value = "3"
+    )]
+    pub max_retries: usize,
+
+    /// Exponential backoff exponent.
+    #[clap(
+        long = "state-relay-backoff-factor",
+        env = "ESPRESSO_SEQUENCER_STATE_RELAY_BACKOFF_FACTOR",
+        default_value = "2"
+    )]
+    pub factor: u32,
+
+    /// Exponential backoff base delay.
+    #[clap(
+        long = "state-relay-base-retry-delay",
+        env = "ESPRESSO_SEQUENCER_STATE_RELAY_BASE_RETRY_DELAY",
+        default_value = "500ms",
+        value_parser = parse_duration
+    )]
+    pub base: Duration,
+
+    /// Exponential max delay.
+    #[clap(
+        long = "state-relay-max-retry-delay",
+        env = "ESPRESSO_SEQUENCER_STATE_RELAY_MAX_RETRY_DELAY",
+        default_value = "5s",
+        value_parser = parse_duration
+    )]
+    pub max: Duration,
+}
+
+impl Default for StateRelayBackoff {
+    fn default() -> Self {
+        Self::parse_from(std::iter::empty::<String>())
+    }
+}
+
+impl Backoff for StateRelayBackoff {
+    fn should_retry(&self, retries: usize) -> bool {
+        retries < self.max_retries
+    }
+
+    fn first_delay(&self) -> Duration {
+        self.base
+    }
+
+    fn next_delay(&self, delay: Duration) -> Duration {
+        min(delay * self.factor, self.max)
+    }
+}
+
+/// Reasons a light client state signature can fail local verification.
+#[derive(Debug, Snafu, PartialEq, Eq)]
+pub enum StateSigError {
+    #[snafu(display("state signature key {key} is not a known state key"))]
+    UnknownKey { key: StateVerKey },
+
+    #[snafu(display("invalid state signature from {key} for block height {block_height}"))]
+    InvalidSignature { key: StateVerKey, block_height: u64 },
+}
+
+/// Verify a light client state signature against a set of known Schnorr state keys.
+///
+/// This is cheap compared to a round trip to the relay server, so it can be used to catch key
+/// mismatches locally before a signature (our own or a peer's) is sent over the network.
+pub fn verify_state_signature(
+    bundle: &StateSignatureRequestBody,
+    state_keys: &[StateVerKey],
+) -> Result<(), StateSigError> {
+    if !state_keys.contains(&bundle.key) {
+        return Err(StateSigError::UnknownKey {
+            key: bundle.key.clone(),
+        });
+    }
+    if !bundle
+        .key
+        .verify_state_sig(&bundle.signature, &bundle.state, &bundle.next_stake)
+    {
+        return Err(StateSigError::InvalidSignature {
+            key: bundle.key.clone(),
+            block_height: bundle.state.block_height,
+        });
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct StateSigner<ApiVer: StaticVersionType> {
     /// Key for signing a new light client state
@@ -56,6 +157,12 @@ pub struct StateSigner<ApiVer: StaticVersionType> {
 
     /// The state relay server url
     relay_server_client: Option<Client<ServerError, ApiVer>>,
+
+    /// Backoff for retrying failed posts to the relay server
+    relay_backoff: StateRelayBackoff,
+
+    /// Number of signatures we gave up on relaying after exhausting all retries
+    relay_failures: Arc<dyn Counter>,
 }
 
 impl<ApiVer: StaticVersionType> StateSigner<ApiVer> {
@@ -74,6 +181,8 @@ impl<ApiVer: StaticVersionType> StateSigner<ApiVer> {
             stake_table_capacity,
             signatures: Default::default(),
             relay_server_client: Default::default(),
+            relay_backoff: Default::default(),
+            relay_failures: NoMetrics.create_counter(String::new(), None).into(),
         }
     }
 
@@ -83,6 +192,21 @@ impl<ApiVer: StaticVersionType> StateSigner<ApiVer> {
         self
     }
 
+    /// Retry failed posts to the relay server with the given backoff.
+    pub fn with_relay_backoff(mut self, backoff: StateRelayBackoff) -> Self {
+        self.relay_backoff = backoff;
+        self
+    }
+
+    /// Register the state signer's metrics.
+    pub fn with_metrics(mut self, metrics: &(impl Metrics + ?Sized)) -> Self {
+        self.relay_failures = metrics
+            .subgroup("state_signer".into())
+            .create_counter("relay_failures".into(), None)
+            .into();
+        self
+    }
+
     pub(super) async fn handle_event<N, P, V>(
         &mut self,
         event: &Event<SeqTypes>,
@@ -157,15 +281,28 @@ impl<ApiVer: StaticVersionType> StateSigner<ApiVer> {
                         next_stake: self.voting_stake_table,
                         signature,
                     };
-                    if let Err(error) = client
-                        .post::<()>("api/state")
-                        .body_binary(&request_body)
-                        .unwrap()
-                        .send()
-                        .await
+                    if let Err(err) =
+                        verify_state_signature(&request_body, std::slice::from_ref(&self.ver_key))
                     {
-                        tracing::warn!("Error posting signature to the relay server: {:?}", error);
+                        tracing::error!("Not posting signature to the relay server: {err}");
+                        return;
                     }
+
+                    // Relay the signature in the background so that retries never hold up the
+                    // handling of consensus events.
+                    let client = client.clone();
+                    let backoff = self.relay_backoff;
+                    let relay_failures = self.relay_failures.clone();
+                    tokio::spawn(async move {
+                        relay_with_retry(&backoff, &*relay_failures, || {
+                            client
+                                .post::<()>("api/state")
+                                .body_binary(&request_body)
+                                .unwrap()
+                                .send()
+                        })
+                        .await
+                    });
                 }
             },
             Err(err) => {
@@ -210,6 +347,23 @@ impl<ApiVer: StaticVersionType> StateSigner<ApiVer> {
     }
 }
 
+/// Post a signature to the relay server, retrying with `backoff` and counting the post in
+/// `relay_failures` if it never succeeds.
+async fn relay_with_retry<F, Fut, E>(
+    backoff: &StateRelayBackoff,
+    relay_failures: &dyn Counter,
+    post: F,
+) where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), E>>,
+    E: Display,
+{
+    if let Err(error) = retry_with_backoff(backoff, |_| true, post).await {
+        tracing::warn!("Error posting signature to the relay server: {error:#}");
+        relay_failures.add(1);
+    }
+}
+
 /// A rolling in-memory storage for the most recent light client state signatures.
 #[derive(Debug, Default)]
 pub struct StateSignatureMemStorage {
@@ -230,3 +384,110 @@ impl StateSignatureMemStorage {
         self.pool.get(&height).cloned()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use hotshot_query_service::metrics::PrometheusMetrics;
+    use hotshot_types::light_client::StateKeyPair;
+
+    use super::*;
+
+    fn signed_request_body(key_pair: &StateKeyPair) -> StateSignatureRequestBody {
+        let state = LightClientState {
+            view_number: 10,
+            block_height: 5,
+            block_comm_root: Default::default(),
+        };
+        let next_stake = StakeTableState::default();
+        let signature = <SchnorrPubKey as StateSignatureKey>::sign_state(
+            &key_pair.sign_key(),
+            &state,
+            &next_stake,
+        )
+        .unwrap();
+        StateSignatureRequestBody {
+            key: key_pair.ver_key(),
+            state,
+            next_stake,
+            signature,
+        }
+    }
+
+    #[test]
+    fn test_verify_state_signature() {
+        let key_pair = StateKeyPair::generate_from_seed_indexed([0; 32], 0);
+        let other_key_pair = StateKeyPair::generate_from_seed_indexed([0; 32], 1);
+        let state_keys = [other_key_pair.ver_key(), key_pair.ver_key()];
+
+        // A correctly signed body from a known key verifies.
+        let body = signed_request_body(&key_pair);
+        verify_state_signature(&body, &state_keys).unwrap();
+
+        // A body signed by a key we don't know about is rejected.
+        assert_eq!(
+            verify_state_signature(&body, &state_keys[..1]),
+            Err(StateSigError::UnknownKey {
+                key: body.key.clone()
+            })
+        );
+
+        // Tampering with the signed state invalidates the signature.
+        let mut tampered = body.clone();
+        tampered.state.block_height += 1;
+        assert_eq!(
+            verify_state_signature(&tampered, &state_keys),
+            Err(StateSigError::InvalidSignature {
+                key: tampered.key.clone(),
+                block_height: tampered.state.block_height,
+            })
+        );
+
+        // So does swapping in a signature from a different key.
+        let mut tampered = body.clone();
+        tampered.signature = signed_request_body(&other_key_pair).signature;
+        assert!(matches!(
+            verify_state_signature(&tampered, &state_keys),
+            Err(StateSigError::InvalidSignature { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_relay_with_retry() {
+        let backoff = StateRelayBackoff {
+            max_retries: 3,
+            factor: 2,
+            base: Duration::from_millis(10),
+            max: Duration::from_millis(50),
+        };
+        let metrics = PrometheusMetrics::default();
+        let relay_failures = metrics.create_counter("relay_failures".into(), None);
+
+        // A relay that fails twice and then accepts the signature.
+        let attempts = AtomicUsize::new(0);
+        relay_with_retry(&backoff, &*relay_failures, || {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt < 2 {
+                    Err("relay unavailable")
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(metrics.get_counter("relay_failures").unwrap().get(), 0);
+
+        // A relay that never recovers is given up on after `max_retries` retries.
+        let attempts = AtomicUsize::new(0);
+        relay_with_retry(&backoff, &*relay_failures, || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            async { Err::<(), _>("relay unavailable") }
+        })
+        .await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+        assert_eq!(metrics.get_counter("relay_failures").unwrap().get(), 1);
+    }
+}
@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use alloy::{
+    primitives::{Address, U256},
+    providers::Provider,
+};
+use anyhow::{bail, Result};
+use hotshot_contract_adapter::sol_types::EspToken;
+use serde::Serialize;
+
+/// The ESP token balance of an account, as observed while watching it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct BalanceUpdate {
+    pub address: Address,
+    pub balance: U256,
+}
+
+/// Poll the ESP token balance of `address` every `interval` and call `on_update` whenever it
+/// changes, starting with the current balance.
+///
+/// Returns the last balance once it is at least `until`. Without a target, watching only stops
+/// after `timeout`. Running into the timeout before reaching the target is an error.
+pub async fn watch_token_balance(
+    provider: impl Provider,
+    token_addr: Address,
+    address: Address,
+    until: Option<U256>,
+    interval: Duration,
+    timeout: Option<Duration>,
+    mut on_update: impl FnMut(&BalanceUpdate),
+) -> Result<U256> {
+    let token = EspToken::new(token_addr, &provider);
+    let mut last = None;
+    let watch = async {
+        loop {
+            let balance = token.balanceOf(address).call().await?._0;
+            if last != Some(balance) {
+                on_update(&BalanceUpdate { address, balance });
+                last = Some(balance);
+            }
+            if until.is_some_and(|target| balance >= target) {
+                return Ok(balance);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    };
+    let Some(timeout) = timeout else {
+        return watch.await;
+    };
+    match tokio::time::timeout(timeout, watch).await {
+        Ok(result) => result,
+        Err(_) => match (until, last) {
+            (None, Some(balance)) => Ok(balance),
+            (Some(target), _) => bail!("balance did not reach {target} within {timeout:?}"),
+            (None, None) => bail!("failed to get the balance within {timeout:?}"),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloy::primitives::utils::parse_ether;
+
+    use super::*;
+    use crate::deploy::TestSystem;
+
+    #[tokio::test]
+    async fn test_watch_token_balance() -> Result<()> {
+        let system = TestSystem::deploy().await?;
+        let address = Address::random();
+        let amount = parse_ether("42")?;
+
+        let mut updates = vec![];
+        let watch = watch_token_balance(
+            &system.provider,
+            system.token,
+            address,
+            Some(amount),
+            Duration::from_millis(100),
+            Some(Duration::from_secs(30)),
+            |update| updates.push(update.balance),
+        );
+        let transfer = async {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            EspToken::new(system.token, &system.provider)
+                .transfer(address, amount)
+                .send()
+                .await?
+                .get_receipt()
+                .await?;
+            anyhow::Ok(())
+        };
+        let (balance, transferred) = tokio::join!(watch, transfer);
+        transferred?;
+        assert_eq!(balance?, amount);
+        assert_eq!(updates, vec![U256::ZERO, amount]);
+
+        // A target that is never reached runs into the timeout.
+        let err = watch_token_balance(
+            &system.provider,
+            system.token,
+            address,
+            Some(amount + U256::from(1)),
+            Duration::from_millis(100),
+            Some(Duration::from_millis(500)),
+            |_| {},
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("did not reach"));
+        Ok(())
+    }
+}
@@ -0,0 +1,346 @@
+use std::path::Path;
+
+use alloy::{
+    primitives::Address,
+    providers::{Provider, ProviderBuilder},
+};
+use anyhow::{Context as _, Result};
+use ark_ec::CurveGroup as _;
+use hotshot_contract_adapter::sol_types::{G2PointSol, StakeTable};
+use serde::Deserialize;
+use url::Url;
+
+use crate::{
+    parse::{parse_bls_priv_key, parse_commission, parse_state_priv_key, Commission},
+    registration::register_validator,
+    BLSKeyPair, BLSPrivKey, StateSignKey, ValidSignerConfig,
+};
+
+/// A validator registration read from a batch file.
+pub struct BatchRegistration {
+    pub consensus_private_key: BLSPrivKey,
+    pub state_private_key: StateSignKey,
+    pub commission: Commission,
+}
+
+#[derive(Deserialize)]
+struct RawBatchRegistration {
+    consensus_private_key: String,
+    state_private_key: String,
+    commission: String,
+}
+
+impl TryFrom<RawBatchRegistration> for BatchRegistration {
+    type Error = anyhow::Error;
+
+    fn try_from(raw: RawBatchRegistration) -> Result<Self> {
+        Ok(Self {
+            consensus_private_key: parse_bls_priv_key(raw.consensus_private_key.trim())
+                .context("invalid consensus private key")?,
+            state_private_key: parse_state_priv_key(raw.state_private_key.trim())
+                .context("invalid state private key")?,
+            commission: parse_commission(raw.commission.trim())?,
+        })
+    }
+}
+
+/// Read and validate all validator registrations of a batch file.
+///
+/// Files ending in `.json` contain an array of objects with `consensus_private_key`,
+/// `state_private_key` and `commission` string fields. Any other file is read as CSV with one
+/// `consensus_private_key,state_private_key,commission` row per line. Empty lines, lines starting
+/// with `#` and a header row are ignored.
+///
+/// Fails on the first invalid row, so that no registration is sent for a partially valid file.
+pub fn read_batch_file(path: &Path) -> Result<Vec<BatchRegistration>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read batch file {}", path.display()))?;
+    let raw = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str::<Vec<RawBatchRegistration>>(&contents)?
+    } else {
+        parse_csv(&contents)?
+    };
+    raw.into_iter()
+        .enumerate()
+        .map(|(i, raw)| {
+            BatchRegistration::try_from(raw).with_context(|| format!("invalid row {}", i + 1))
+        })
+        .collect()
+}
+
+fn parse_csv(contents: &str) -> Result<Vec<RawBatchRegistration>> {
+    let mut rows = vec![];
+    for (line_number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("consensus_private_key") {
+            continue;
+        }
+        let [consensus_private_key, state_private_key, commission] = line
+            .split(',')
+            .collect::<Vec<_>>()
+            .try_into()
+            .map_err(|fields: Vec<_>| {
+                anyhow::anyhow!(
+                    "line {}: expected 3 fields, got {}",
+                    line_number + 1,
+                    fields.len()
+                )
+            })?;
+        rows.push(RawBatchRegistration {
+            consensus_private_key: consensus_private_key.to_string(),
+            state_private_key: state_private_key.to_string(),
+            commission: commission.to_string(),
+        });
+    }
+    Ok(rows)
+}
+
+/// Outcome of a batch registration.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    pub registered: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Register each validator of the batch.
+///
+/// The stake table allows only one validator per account, so the `n`-th registration is sent from
+/// the account `n` indexes after the one configured in `signer`. These accounts are not funded by
+/// this function, each one that still has to register needs ETH to pay for its transaction. If any
+/// of them has none, nothing is sent.
+///
+/// Registrations whose account or consensus key is already registered are skipped. Failures are
+/// reported, and don't prevent the remaining validators from being registered.
+pub async fn batch_register(
+    rpc_url: Url,
+    stake_table_addr: Address,
+    signer: ValidSignerConfig,
+    registrations: Vec<BatchRegistration>,
+) -> Result<BatchSummary> {
+    let mut summary = BatchSummary::default();
+    let mut pending = vec![];
+    let mut unfunded = vec![];
+    for (i, registration) in registrations.into_iter().enumerate() {
+        let row = i + 1;
+        let (wallet, account) = offset_signer(&signer, i as u32).wallet().await?;
+        let provider = ProviderBuilder::new()
+            .wallet(wallet)
+            .on_http(rpc_url.clone());
+        let bls_key_pair: BLSKeyPair = registration.consensus_private_key.into();
+
+        if is_registered(&provider, stake_table_addr, account, &bls_key_pair).await? {
+            tracing::info!("row {row}: validator {account} already registered, skipping");
+            summary.skipped += 1;
+            continue;
+        }
+        if provider.get_balance(account).await?.is_zero() {
+            unfunded.push(format!("row {row}: {account}"));
+        }
+        pending.push((
+            row,
+            provider,
+            account,
+            bls_key_pair,
+            registration.state_private_key,
+            registration.commission,
+        ));
+    }
+    if !unfunded.is_empty() {
+        anyhow::bail!(
+            "the accounts of these rows have no ETH to pay for the registration, fund them first: \
+             {}",
+            unfunded.join(", ")
+        );
+    }
+
+    for (row, provider, account, bls_key_pair, state_private_key, commission) in pending {
+        match register_validator(
+            &provider,
+            stake_table_addr,
+            commission,
+            account,
+            bls_key_pair,
+            (&state_private_key).into(),
+        )
+        .await
+        {
+            Ok(receipt) => {
+                tracing::info!(
+                    "row {row}: registered validator {account} with commission {}, transaction \
+                     hash: {}",
+                    commission,
+                    receipt.transaction_hash
+                );
+                summary.registered += 1;
+            },
+            Err(err) => {
+                tracing::error!("row {row}: failed to register validator {account}: {err:#}");
+                summary.failed += 1;
+            },
+        }
+    }
+    tracing::info!(
+        "registered {}, skipped {}, failed {}",
+        summary.registered,
+        summary.skipped,
+        summary.failed
+    );
+    Ok(summary)
+}
+
+fn offset_signer(signer: &ValidSignerConfig, offset: u32) -> ValidSignerConfig {
+    match signer {
+        ValidSignerConfig::Mnemonic {
+            mnemonic,
+            account_index,
+        } => ValidSignerConfig::Mnemonic {
+            mnemonic: mnemonic.clone(),
+            account_index: account_index + offset,
+        },
+        ValidSignerConfig::Ledger { account_index } => ValidSignerConfig::Ledger {
+            account_index: account_index + offset as usize,
+        },
+    }
+}
+
+async fn is_registered(
+    provider: impl Provider,
+    stake_table_addr: Address,
+    account: Address,
+    bls_key_pair: &BLSKeyPair,
+) -> Result<bool> {
+    let stake_table = StakeTable::new(stake_table_addr, &provider);
+    if stake_table.validators(account).call().await?.status != 0 {
+        return Ok(true);
+    }
+    let bls_vk_sol: G2PointSol = bls_key_pair.ver_key().to_affine().into();
+    let key_hash = stake_table._hashBlsKey(bls_vk_sol).call().await?._0;
+    Ok(stake_table.blsKeys(key_hash).call().await?.used)
+}
+
+#[cfg(test)]
+mod test {
+    use alloy::primitives::utils::parse_ether;
+    use rand::{rngs::StdRng, SeedableRng as _};
+
+    use super::*;
+    use crate::{deploy::TestSystem, DEV_MNEMONIC};
+
+    fn csv_row(seed: u8, commission: &str) -> Result<String> {
+        let mut rng = StdRng::from_seed([seed; 32]);
+        let (_, bls_key_pair, state_key_pair) = TestSystem::gen_keys(&mut rng);
+        Ok(format!(
+            "{},{},{commission}",
+            bls_key_pair.sign_key_ref().to_tagged_base64()?,
+            state_key_pair.sign_key().to_tagged_base64()?
+        ))
+    }
+
+    #[test]
+    fn test_read_batch_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let csv = dir.path().join("validators.csv");
+        let contents = format!(
+            "consensus_private_key,state_private_key,commission\n{}\n\n# comment\n{}\n",
+            csv_row(1, "1.5")?,
+            csv_row(2, "10")?
+        );
+        std::fs::write(&csv, contents)?;
+        let registrations = read_batch_file(&csv)?;
+        assert_eq!(registrations.len(), 2);
+        assert_eq!(registrations[0].commission.to_evm(), 150);
+        assert_eq!(registrations[1].commission.to_evm(), 1000);
+
+        let json = dir.path().join("validators.json");
+        let fields = csv_row(3, "0.01")?;
+        let fields = fields.split(',').collect::<Vec<_>>();
+        std::fs::write(
+            &json,
+            format!(
+                r#"[{{"consensus_private_key": "{}", "state_private_key": "{}", "commission": "{}"}}]"#,
+                fields[0], fields[1], fields[2]
+            ),
+        )?;
+        let registrations = read_batch_file(&json)?;
+        assert_eq!(registrations.len(), 1);
+        assert_eq!(registrations[0].commission.to_evm(), 1);
+
+        // a single bad row fails the whole file
+        for bad_row in [
+            csv_row(4, "100.01")?,
+            "not-a-key,not-a-key,1".to_string(),
+            "too,few".to_string(),
+        ] {
+            std::fs::write(&csv, format!("{}\n{bad_row}\n", csv_row(1, "1")?))?;
+            assert!(read_batch_file(&csv).is_err(), "{bad_row}");
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_batch_register() -> Result<()> {
+        let system = TestSystem::deploy().await?;
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("validators.csv");
+        std::fs::write(
+            &path,
+            format!("{}\n{}\n", csv_row(1, "1")?, csv_row(2, "2")?),
+        )?;
+        // Anvil only funds the first 20 dev accounts.
+        let signer = ValidSignerConfig::Mnemonic {
+            mnemonic: DEV_MNEMONIC.to_string(),
+            account_index: 20,
+        };
+
+        // nothing is sent while the offset accounts have no ETH
+        let err = batch_register(
+            system.rpc_url.clone(),
+            system.stake_table,
+            signer.clone(),
+            read_batch_file(&path)?,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("row 2"), "{err:#}");
+
+        for offset in 0..2 {
+            let (_, account) = offset_signer(&signer, offset).wallet().await?;
+            system.transfer_eth(account, parse_ether("1")?).await?;
+        }
+        let summary = batch_register(
+            system.rpc_url.clone(),
+            system.stake_table,
+            signer.clone(),
+            read_batch_file(&path)?,
+        )
+        .await?;
+        assert_eq!(
+            summary,
+            BatchSummary {
+                registered: 2,
+                skipped: 0,
+                failed: 0
+            }
+        );
+
+        // running the same batch again doesn't register anything
+        let summary = batch_register(
+            system.rpc_url.clone(),
+            system.stake_table,
+            signer,
+            read_batch_file(&path)?,
+        )
+        .await?;
+        assert_eq!(
+            summary,
+            BatchSummary {
+                registered: 0,
+                skipped: 2,
+                failed: 0
+            }
+        );
+        Ok(())
+    }
+}
@@ -1,10 +1,88 @@
-use alloy::{primitives::Address, providers::Provider, rpc::types::TransactionReceipt};
-use anyhow::Result;
+use std::{fmt, time::Duration};
+
+use alloy::{
+    eips::BlockId,
+    primitives::{utils::format_ether, Address, U256},
+    providers::Provider,
+    rpc::types::TransactionReceipt,
+};
+use anyhow::{Context as _, Result};
 use hotshot_contract_adapter::{
     evm::DecodeRevert as _,
     sol_types::StakeTable::{self, StakeTableErrors},
 };
 
+/// The state of an undelegation that has not been claimed yet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WithdrawalStatus {
+    /// There are no undelegated funds to withdraw.
+    Nothing,
+    /// The escrow period has elapsed, `amount` can be withdrawn now.
+    Claimable { amount: U256 },
+    /// `amount` can be withdrawn once `remaining` has passed.
+    Locked { amount: U256, remaining: Duration },
+}
+
+impl WithdrawalStatus {
+    pub fn is_claimable(&self) -> bool {
+        matches!(self, Self::Claimable { .. })
+    }
+}
+
+impl fmt::Display for WithdrawalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nothing => write!(f, "nothing to withdraw"),
+            Self::Claimable { amount } => {
+                write!(f, "{} ESP can be withdrawn now", format_ether(*amount))
+            },
+            Self::Locked { amount, remaining } => write!(
+                f,
+                "{} ESP can be withdrawn in {} seconds",
+                format_ether(*amount),
+                remaining.as_secs()
+            ),
+        }
+    }
+}
+
+/// Check whether `delegator` can claim its withdrawal from `validator_address`.
+///
+/// The unlock time is compared to the timestamp of the latest L1 block, which is what the contract
+/// checks against when the claim is executed.
+pub async fn withdrawal_status(
+    provider: impl Provider,
+    stake_table: Address,
+    validator_address: Address,
+    delegator: Address,
+) -> Result<WithdrawalStatus> {
+    let st = StakeTable::new(stake_table, &provider);
+    let undelegation = st
+        .undelegations(validator_address, delegator)
+        .call()
+        .await?;
+    if undelegation.amount.is_zero() {
+        return Ok(WithdrawalStatus::Nothing);
+    }
+    let now = provider
+        .get_block(BlockId::latest())
+        .await?
+        .context("latest L1 block not found")?
+        .header
+        .timestamp;
+    let unlocks_at = u64::try_from(undelegation.unlocksAt)?;
+    Ok(if now >= unlocks_at {
+        WithdrawalStatus::Claimable {
+            amount: undelegation.amount,
+        }
+    } else {
+        WithdrawalStatus::Locked {
+            amount: undelegation.amount,
+            remaining: Duration::from_secs(unlocks_at - now),
+        }
+    })
+}
+
 pub async fn claim_withdrawal(
     provider: impl Provider,
     stake_table: Address,
@@ -38,8 +116,6 @@ pub async fn claim_validator_exit(
 
 #[cfg(test)]
 mod test {
-    use alloy::primitives::U256;
-
     use super::*;
     use crate::deploy::TestSystem;
 
@@ -63,6 +139,38 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_withdrawal_status() -> Result<()> {
+        let system = TestSystem::deploy().await?;
+        let amount = U256::from(123);
+        let validator_address = system.deployer_address;
+        let (provider, stake_table) = (&system.provider, system.stake_table);
+        let status =
+            move || withdrawal_status(provider, stake_table, validator_address, validator_address);
+        system.register_validator().await?;
+        system.delegate(amount).await?;
+        assert_eq!(status().await?, WithdrawalStatus::Nothing);
+
+        system.undelegate(amount).await?;
+        assert_eq!(
+            status().await?,
+            WithdrawalStatus::Locked {
+                amount,
+                remaining: system.exit_escrow_period
+            }
+        );
+        // Claiming now reverts with the decoded reason.
+        let err = claim_withdrawal(&system.provider, system.stake_table, validator_address)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("PrematureWithdrawal"), "{err:#}");
+
+        system.warp_to_unlock_time().await?;
+        assert_eq!(status().await?, WithdrawalStatus::Claimable { amount });
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_claim_validator_exit() -> Result<()> {
         let system = TestSystem::deploy().await?;
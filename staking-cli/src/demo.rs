@@ -12,18 +12,25 @@ use alloy::{
 };
 use anyhow::Result;
 use clap::ValueEnum;
-use espresso_contract_deployer::{build_provider, build_random_provider, build_signer};
+#[cfg(feature = "testing")]
+use espresso_contract_deployer::smoke_test_stake_table;
+use espresso_contract_deployer::{build_provider, build_random_provider, is_contract};
 use hotshot_contract_adapter::{
     evm::DecodeRevert,
-    sol_types::EspToken::{self, EspTokenErrors},
+    sol_types::{
+        EspToken::{self, EspTokenErrors},
+        LightClient, StakeTable,
+    },
 };
 use hotshot_types::{light_client::StateKeyPair, signature_key::BLSKeyPair};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
+use serde::Serialize;
 use url::Url;
 
 use crate::{
     delegation::delegate,
+    mnemonic_signer,
     parse::{parse_bls_priv_key, parse_state_priv_key, Commission},
     registration::register_validator,
     Config,
@@ -49,6 +56,72 @@ impl fmt::Display for DelegationConfig {
     }
 }
 
+/// A validator registered by the demo setup.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DemoValidator {
+    pub account: Address,
+    /// The commission in basis points, as stored by the contract.
+    pub commission: u16,
+    /// The amount the validator delegated to itself.
+    pub self_delegation: U256,
+    /// The L1 block the validator was registered in.
+    pub registered_in_block: u64,
+}
+
+/// Everything deployed and configured by the demo stake table setup.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DeploymentSummary {
+    pub token: Address,
+    pub stake_table: Address,
+    pub light_client: Address,
+    /// The owner of the stake table contract.
+    pub admin: Address,
+    /// The account that funded the validators and delegators.
+    pub token_holder: Address,
+    /// The permissioned light client prover, `None` if proving is permissionless or the light
+    /// client is not deployed.
+    pub permissioned_prover: Option<Address>,
+    pub validators: Vec<DemoValidator>,
+    /// The L1 block number before the setup started.
+    pub start_block: u64,
+    /// The L1 block number after the setup completed.
+    pub end_block: u64,
+}
+
+impl fmt::Display for DeploymentSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Demo stake table setup in L1 blocks {}..={}",
+            self.start_block, self.end_block
+        )?;
+        writeln!(f, "  ESP token:           {}", self.token)?;
+        writeln!(f, "  stake table:         {}", self.stake_table)?;
+        writeln!(f, "  light client:        {}", self.light_client)?;
+        writeln!(f, "  admin:               {}", self.admin)?;
+        writeln!(f, "  token holder:        {}", self.token_holder)?;
+        match self.permissioned_prover {
+            Some(prover) => writeln!(f, "  permissioned prover: {prover}")?,
+            None => writeln!(f, "  permissioned prover: none")?,
+        }
+        write!(f, "  validators:          {}", self.validators.len())?;
+        for validator in &self.validators {
+            write!(f, "\n    {} ", validator.account)?;
+            match Commission::try_from(validator.commission) {
+                Ok(commission) => write!(f, "commission={commission}")?,
+                Err(_) => write!(f, "commission={} (invalid)", validator.commission)?,
+            }
+            write!(
+                f,
+                " self_delegation={} ESP registered_in_block={}",
+                format_ether(validator.self_delegation),
+                validator.registered_in_block
+            )?;
+        }
+        Ok(())
+    }
+}
+
 /// Setup validator by sending them tokens and ethers, and registering them on stake table
 pub async fn setup_stake_table_contract_for_test(
     rpc_url: Url,
@@ -57,9 +130,10 @@ pub async fn setup_stake_table_contract_for_test(
     token_address: Address,
     validators: Vec<(PrivateKeySigner, BLSKeyPair, StateKeyPair)>,
     config: DelegationConfig,
-) -> Result<()> {
+) -> Result<DeploymentSummary> {
     tracing::info!(%stake_table_address, "staking to stake table contract for demo");
 
+    let start_block = token_holder.get_block_number().await?;
     let token_holder_addr = token_holder.default_signer_address();
 
     tracing::info!("ESP token address: {token_address}");
@@ -81,6 +155,8 @@ pub async fn setup_stake_table_contract_for_test(
     let seed = [42u8; 32];
     let mut rng = ChaCha20Rng::from_seed(seed);
 
+    let mut registered = vec![];
+
     for (val_index, (signer, bls_key_pair, state_key_pair)) in validators.into_iter().enumerate() {
         let validator_address = signer.address();
         let validator_wallet: EthereumWallet = EthereumWallet::from(signer);
@@ -147,6 +223,12 @@ pub async fn setup_stake_table_contract_for_test(
         )
         .await?;
         assert!(receipt.status());
+        registered.push(DemoValidator {
+            account: validator_address,
+            commission: commission.to_evm(),
+            self_delegation: delegate_amount,
+            registered_in_block: receipt.block_number.unwrap_or_default(),
+        });
 
         tracing::info!(
             "delegate {delegate_amount_esp} ESP for validator {val_index} from {validator_address}"
@@ -181,7 +263,31 @@ pub async fn setup_stake_table_contract_for_test(
         }
     }
     tracing::info!("completed staking for demo");
-    Ok(())
+
+    let stake_table = StakeTable::new(stake_table_address, token_holder);
+    let light_client = stake_table.lightClient().call().await?._0;
+    let admin = stake_table.owner().call().await?._0;
+    let permissioned_prover = if is_contract(token_holder, light_client).await? {
+        let prover = LightClient::new(light_client, token_holder)
+            .permissionedProver()
+            .call()
+            .await?
+            ._0;
+        (prover != Address::ZERO).then_some(prover)
+    } else {
+        None
+    };
+    Ok(DeploymentSummary {
+        token: token_address,
+        stake_table: stake_table_address,
+        light_client,
+        admin,
+        token_holder: token_holder_addr,
+        permissioned_prover,
+        validators: registered,
+        start_block,
+        end_block: token_holder.get_block_number().await?,
+    })
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -268,7 +374,7 @@ pub async fn stake_for_demo(
     config: &Config,
     num_validators: u16,
     delegation_config: DelegationConfig,
-) -> Result<()> {
+) -> Result<DeploymentSummary> {
     tracing::info!("staking to stake table contract for demo");
 
     // let grant_recipient = mk_signer(config.signer.account_index.unwrap())?;
@@ -290,10 +396,10 @@ pub async fn stake_for_demo(
 
     let mut validator_keys = vec![];
     for val_index in 0..num_validators {
-        let signer = build_signer(
-            config.signer.mnemonic.clone().unwrap(),
+        let signer = mnemonic_signer(
+            config.signer.mnemonic.as_ref().unwrap(),
             20u32 + val_index as u32,
-        );
+        )?;
 
         let consensus_private_key = parse_bls_priv_key(&dotenvy::var(format!(
             "ESPRESSO_DEMO_SEQUENCER_STAKING_PRIVATE_KEY_{val_index}"
@@ -317,10 +423,26 @@ pub async fn stake_for_demo(
         validator_keys,
         delegation_config,
     )
-    .await?;
+    .await
+}
 
-    tracing::info!("completed staking for demo");
-    Ok(())
+/// Exercise the demo stake table with [`smoke_test_stake_table`].
+///
+/// The throwaway validator is funded from the same grant recipient account as
+/// [`stake_for_demo`].
+#[cfg(feature = "testing")]
+pub async fn smoke_test_demo_stake_table(config: &Config) -> Result<()> {
+    let grant_recipient = build_provider(
+        config.signer.mnemonic.clone().unwrap(),
+        config.signer.account_index.unwrap(),
+        config.rpc_url.clone(),
+    );
+    smoke_test_stake_table(
+        &grant_recipient,
+        config.stake_table_address,
+        config.token_address,
+    )
+    .await
 }
 
 #[cfg(test)]
@@ -369,6 +491,79 @@ mod test {
         Ok((val1, val2))
     }
 
+    #[tokio::test]
+    async fn test_deployment_summary() -> Result<()> {
+        setup_test();
+        let system = TestSystem::deploy().await?;
+
+        let mut rng = StdRng::from_seed([42u8; 32]);
+        let keys = (0..3).map(|_| TestSystem::gen_keys(&mut rng)).collect();
+        let summary = setup_stake_table_contract_for_test(
+            system.rpc_url.clone(),
+            &system.provider,
+            system.stake_table,
+            system.token,
+            keys,
+            DelegationConfig::EqualAmounts,
+        )
+        .await?;
+
+        assert_eq!(summary.validators.len(), 3);
+        assert_eq!(summary.stake_table, system.stake_table);
+        assert_eq!(summary.token, system.token);
+        assert_eq!(summary.admin, system.deployer_address);
+        assert_eq!(summary.token_holder, system.deployer_address);
+        // the test system's light client is just an address without code
+        assert_eq!(summary.permissioned_prover, None);
+        for validator in &summary.validators {
+            assert!(summary.start_block < validator.registered_in_block);
+            assert!(validator.registered_in_block <= summary.end_block);
+            assert_eq!(validator.self_delegation, parse_ether("100")?);
+        }
+
+        let json = serde_json::to_value(&summary)?;
+        assert_eq!(json["validators"].as_array().unwrap().len(), 3);
+        assert!(summary.to_string().contains("validators:          3"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_smoke_test_demo_stake_table() -> Result<()> {
+        setup_test();
+        let system = TestSystem::deploy().await?;
+
+        let mut rng = StdRng::from_seed([42u8; 32]);
+        let keys = (0..2).map(|_| TestSystem::gen_keys(&mut rng)).collect();
+        let summary = setup_stake_table_contract_for_test(
+            system.rpc_url.clone(),
+            &system.provider,
+            system.stake_table,
+            system.token,
+            keys,
+            DelegationConfig::MultipleDelegators,
+        )
+        .await?;
+        let l1_block_number = system.provider.get_block_number().await?;
+        let before =
+            stake_table_info(system.rpc_url.clone(), system.stake_table, l1_block_number).await?;
+
+        smoke_test_stake_table(&system.provider, system.stake_table, system.token).await?;
+
+        // the throwaway validator is gone again and the demo validators are untouched
+        let l1_block_number = system.provider.get_block_number().await?;
+        let after = stake_table_info(system.rpc_url, system.stake_table, l1_block_number).await?;
+        assert_eq!(after, before);
+        assert_eq!(
+            after.iter().map(|v| v.account).collect::<Vec<_>>(),
+            summary
+                .validators
+                .iter()
+                .map(|v| v.account)
+                .collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_stake_for_demo_equal_amounts() -> Result<()> {
         let (val1, val2) = shared_setup(DelegationConfig::EqualAmounts).await?;
@@ -14,13 +14,14 @@ use alloy::{
     signers::local::PrivateKeySigner,
 };
 use anyhow::Result;
-use espresso_contract_deployer::build_signer;
 use hotshot_contract_adapter::sol_types::{ERC1967Proxy, EspToken, StakeTable};
 use hotshot_types::light_client::StateKeyPair;
 use rand::{rngs::StdRng, CryptoRng, RngCore, SeedableRng as _};
 use url::Url;
 
-use crate::{parse::Commission, registration::register_validator, BLSKeyPair, DEV_MNEMONIC};
+use crate::{
+    dev_accounts, parse::Commission, registration::register_validator, BLSKeyPair, DEV_MNEMONIC,
+};
 
 type TestProvider = FillProvider<
     JoinFill<JoinedRecommendedFillers, WalletFiller<EthereumWallet>>,
@@ -57,12 +58,8 @@ impl TestSystem {
         let deployer_address = provider.default_signer_address();
         // I don't know how to get the signer out of the provider, by default anvil uses the dev
         // mnemonic and the default signer is the first account.
-        let signer = build_signer(DEV_MNEMONIC.to_string(), 0);
-        assert_eq!(
-            signer.address(),
-            deployer_address,
-            "Signer address mismatch"
-        );
+        let (signer, signer_address) = dev_accounts(1).remove(0);
+        assert_eq!(signer_address, deployer_address, "Signer address mismatch");
 
         // `EspToken.sol`
         let token_impl = EspToken::deploy(provider.clone()).await?;
@@ -73,32 +70,18 @@ impl TestSystem {
 
         let token_proxy =
             ERC1967Proxy::deploy(provider.clone(), *token_impl.address(), data).await?;
-        let token = EspToken::new(*token_proxy.address(), provider.clone());
 
         // `StakeTable.sol`
-        let stake_table_impl = StakeTable::deploy(provider.clone()).await?;
-        let data = stake_table_impl
-            .initialize(
-                *token_proxy.address(),
-                "0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF".parse()?, // fake LC address
-                U256::from(exit_escrow_period.as_secs()),
-                deployer_address,
-            )
-            .calldata()
-            .clone();
-
-        let st_proxy =
-            ERC1967Proxy::deploy(provider.clone(), *stake_table_impl.address(), data).await?;
-
         let approval_amount = parse_ether("1000000")?;
-        // Approve the stake table contract so it can transfer tokens to itself
-        let receipt = token
-            .approve(*st_proxy.address(), approval_amount)
-            .send()
-            .await?
-            .get_receipt()
-            .await?;
-        assert!(receipt.status());
+        let stake_table = deploy_stake_table(
+            &provider,
+            *token_proxy.address(),
+            "0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF".parse()?, // fake LC address
+            exit_escrow_period,
+            deployer_address,
+            approval_amount,
+        )
+        .await?;
 
         let mut rng = StdRng::from_seed([42u8; 32]);
         let (_, bls_key_pair, state_key_pair) = Self::gen_keys(&mut rng);
@@ -108,7 +91,7 @@ impl TestSystem {
             signer,
             deployer_address,
             token: *token_proxy.address(),
-            stake_table: *st_proxy.address(),
+            stake_table,
             exit_escrow_period,
             rpc_url,
             bls_key_pair,
@@ -118,6 +101,20 @@ impl TestSystem {
         })
     }
 
+    /// Replace the stake table with a new one that is wired to the light client at `light_client`.
+    pub async fn redeploy_stake_table(&mut self, light_client: Address) -> Result<()> {
+        self.stake_table = deploy_stake_table(
+            &self.provider,
+            self.token,
+            light_client,
+            self.exit_escrow_period,
+            self.deployer_address,
+            self.approval_amount,
+        )
+        .await?;
+        Ok(())
+    }
+
     /// Note: Generates random keys, the Ethereum key won't match the deployer key.
     pub fn gen_keys(
         rng: &mut (impl RngCore + CryptoRng),
@@ -249,6 +246,41 @@ impl TestSystem {
     }
 }
 
+/// Deploy a `StakeTable.sol` proxy and approve it to transfer `approval_amount` of the admin's
+/// tokens.
+async fn deploy_stake_table(
+    provider: &TestProvider,
+    token: Address,
+    light_client: Address,
+    exit_escrow_period: Duration,
+    admin: Address,
+    approval_amount: U256,
+) -> Result<Address> {
+    let stake_table_impl = StakeTable::deploy(provider.clone()).await?;
+    let data = stake_table_impl
+        .initialize(
+            token,
+            light_client,
+            U256::from(exit_escrow_period.as_secs()),
+            admin,
+        )
+        .calldata()
+        .clone();
+
+    let st_proxy =
+        ERC1967Proxy::deploy(provider.clone(), *stake_table_impl.address(), data).await?;
+
+    // Approve the stake table contract so it can transfer tokens to itself
+    let receipt = EspToken::new(token, provider)
+        .approve(*st_proxy.address(), approval_amount)
+        .send()
+        .await?
+        .get_receipt()
+        .await?;
+    assert!(receipt.status());
+    Ok(*st_proxy.address())
+}
+
 #[derive(Clone, Copy)]
 pub enum Signer {
     Ledger,
@@ -0,0 +1,240 @@
+use std::fmt;
+
+use alloy::{
+    primitives::{utils::format_ether, Address, B256, U256},
+    providers::Provider,
+    sol_types::SolEventInterface as _,
+};
+use anyhow::{Context as _, Result};
+use hotshot_contract_adapter::sol_types::StakeTable::StakeTableEvents;
+use hotshot_types::{light_client::StateVerKey, signature_key::BLSPubKey};
+use serde::Serialize;
+
+use crate::parse::Commission;
+
+/// A stake table event, decoded from a transaction log.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "event")]
+pub enum DecodedEvent {
+    ValidatorRegistered {
+        account: Address,
+        bls_vk: String,
+        schnorr_vk: String,
+        /// The commission in basis points, as stored by the contract.
+        commission: u16,
+    },
+    ValidatorExit {
+        validator: Address,
+    },
+    Delegated {
+        delegator: Address,
+        validator: Address,
+        amount: U256,
+    },
+    Undelegated {
+        delegator: Address,
+        validator: Address,
+        amount: U256,
+    },
+    ConsensusKeysUpdated {
+        account: Address,
+        bls_vk: String,
+        schnorr_vk: String,
+    },
+    Withdrawal {
+        account: Address,
+        amount: U256,
+    },
+    /// An administrative event of the contract, such as an upgrade.
+    Other {
+        name: String,
+    },
+}
+
+impl From<StakeTableEvents> for DecodedEvent {
+    fn from(event: StakeTableEvents) -> Self {
+        match event {
+            StakeTableEvents::ValidatorRegistered(event) => Self::ValidatorRegistered {
+                account: event.account,
+                bls_vk: BLSPubKey::from(event.blsVk).to_string(),
+                schnorr_vk: StateVerKey::from(event.schnorrVk).to_string(),
+                commission: event.commission,
+            },
+            StakeTableEvents::ValidatorExit(event) => Self::ValidatorExit {
+                validator: event.validator,
+            },
+            StakeTableEvents::Delegated(event) => Self::Delegated {
+                delegator: event.delegator,
+                validator: event.validator,
+                amount: event.amount,
+            },
+            StakeTableEvents::Undelegated(event) => Self::Undelegated {
+                delegator: event.delegator,
+                validator: event.validator,
+                amount: event.amount,
+            },
+            StakeTableEvents::ConsensusKeysUpdated(event) => Self::ConsensusKeysUpdated {
+                account: event.account,
+                bls_vk: BLSPubKey::from(event.blsVK).to_string(),
+                schnorr_vk: StateVerKey::from(event.schnorrVK).to_string(),
+            },
+            StakeTableEvents::Withdrawal(event) => Self::Withdrawal {
+                account: event.account,
+                amount: event.amount,
+            },
+            StakeTableEvents::Initialized(_) => Self::other("Initialized"),
+            StakeTableEvents::OwnershipTransferred(_) => Self::other("OwnershipTransferred"),
+            StakeTableEvents::Upgrade(_) => Self::other("Upgrade"),
+            StakeTableEvents::Upgraded(_) => Self::other("Upgraded"),
+        }
+    }
+}
+
+impl DecodedEvent {
+    fn other(name: &str) -> Self {
+        Self::Other {
+            name: name.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DecodedEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ValidatorRegistered {
+                account,
+                bls_vk,
+                schnorr_vk,
+                commission,
+            } => {
+                write!(f, "ValidatorRegistered: validator={account} ")?;
+                match Commission::try_from(*commission) {
+                    Ok(commission) => write!(f, "commission={commission}")?,
+                    Err(_) => write!(f, "commission={commission} (invalid)")?,
+                }
+                write!(f, " bls_vk={bls_vk} schnorr_vk={schnorr_vk}")
+            },
+            Self::ValidatorExit { validator } => write!(f, "ValidatorExit: validator={validator}"),
+            Self::Delegated {
+                delegator,
+                validator,
+                amount,
+            } => write!(
+                f,
+                "Delegated: delegator={delegator} validator={validator} amount={} ESP",
+                format_ether(*amount)
+            ),
+            Self::Undelegated {
+                delegator,
+                validator,
+                amount,
+            } => write!(
+                f,
+                "Undelegated: delegator={delegator} validator={validator} amount={} ESP",
+                format_ether(*amount)
+            ),
+            Self::ConsensusKeysUpdated {
+                account,
+                bls_vk,
+                schnorr_vk,
+            } => write!(
+                f,
+                "ConsensusKeysUpdated: validator={account} bls_vk={bls_vk} schnorr_vk={schnorr_vk}"
+            ),
+            Self::Withdrawal { account, amount } => write!(
+                f,
+                "Withdrawal: account={account} amount={} ESP",
+                format_ether(*amount)
+            ),
+            Self::Other { name } => write!(f, "{name}"),
+        }
+    }
+}
+
+/// Decode the stake table events emitted by a transaction.
+///
+/// Logs of other contracts, such as the token transfers of a delegation, are ignored.
+pub async fn decode_stake_table_events(
+    provider: impl Provider,
+    stake_table_addr: Address,
+    tx_hash: B256,
+) -> Result<Vec<DecodedEvent>> {
+    let receipt = provider
+        .get_transaction_receipt(tx_hash)
+        .await?
+        .with_context(|| format!("no receipt for transaction {tx_hash}"))?;
+    receipt
+        .inner
+        .logs()
+        .iter()
+        .filter(|log| log.address() == stake_table_addr)
+        .map(|log| {
+            let event = StakeTableEvents::decode_log(&log.inner, true)
+                .with_context(|| format!("failed to decode stake table log {:?}", log.log_index))?;
+            Ok(event.data.into())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use hotshot_contract_adapter::sol_types::StakeTable;
+
+    use super::*;
+    use crate::{deploy::TestSystem, registration::register_validator};
+
+    #[tokio::test]
+    async fn test_decode_stake_table_events() -> Result<()> {
+        let system = TestSystem::deploy().await?;
+        let receipt = register_validator(
+            &system.provider,
+            system.stake_table,
+            system.commission,
+            system.deployer_address,
+            system.bls_key_pair.clone(),
+            system.state_key_pair.ver_key(),
+        )
+        .await?;
+        let events = decode_stake_table_events(
+            &system.provider,
+            system.stake_table,
+            receipt.transaction_hash,
+        )
+        .await?;
+        assert_eq!(
+            events,
+            vec![DecodedEvent::ValidatorRegistered {
+                account: system.deployer_address,
+                bls_vk: system.bls_key_pair.ver_key().to_string(),
+                schnorr_vk: system.state_key_pair.ver_key().to_string(),
+                commission: system.commission.to_evm(),
+            }]
+        );
+
+        // The token transfer of the delegation is not part of the decoded events.
+        let amount = U256::from(123);
+        system.approve(amount).await?;
+        let receipt = StakeTable::new(system.stake_table, &system.provider)
+            .delegate(system.deployer_address, amount)
+            .send()
+            .await?
+            .get_receipt()
+            .await?;
+        assert!(receipt.inner.logs().len() > 1);
+        let events = decode_stake_table_events(
+            &system.provider,
+            system.stake_table,
+            receipt.transaction_hash,
+        )
+        .await?;
+        assert_eq!(
+            events,
+            vec![DecodedEvent::Delegated {
+                delegator: system.deployer_address,
+                validator: system.deployer_address,
+                amount,
+            }]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+use std::fmt;
+
+use alloy::{
+    primitives::{Address, U256},
+    providers::Provider,
+};
+use anyhow::Result;
+use espresso_contract_deployer::network_config::light_client_genesis_from_stake_table;
+use espresso_types::{v0_3::Validator, SeqTypes};
+use hotshot_contract_adapter::sol_types::{LightClient, StakeTable, StakeTableStateSol};
+use hotshot_types::{
+    signature_key::BLSPubKey, stake_table::HSStakeTable, traits::signature_key::SignatureKey,
+    PeerConfig,
+};
+use url::Url;
+
+use crate::info::stake_table_info;
+
+/// A field of the genesis stake table state, computed locally and as stored on chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldComparison {
+    pub field: &'static str,
+    pub expected: U256,
+    pub actual: U256,
+}
+
+impl FieldComparison {
+    pub fn matches(&self) -> bool {
+        self.expected == self.actual
+    }
+}
+
+impl fmt::Display for FieldComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.matches() {
+            write!(f, "{}: match ({})", self.field, self.expected)
+        } else {
+            write!(
+                f,
+                "{}: MISMATCH expected={} on-chain={}",
+                self.field, self.expected, self.actual
+            )
+        }
+    }
+}
+
+/// Compare the locally computed stake table state with the one stored on chain, field by field.
+pub fn compare_stake_table_states(
+    expected: &StakeTableStateSol,
+    actual: &StakeTableStateSol,
+) -> Vec<FieldComparison> {
+    [
+        ("threshold", expected.threshold, actual.threshold),
+        ("blsKeyComm", expected.blsKeyComm, actual.blsKeyComm),
+        (
+            "schnorrKeyComm",
+            expected.schnorrKeyComm,
+            actual.schnorrKeyComm,
+        ),
+        ("amountComm", expected.amountComm, actual.amountComm),
+    ]
+    .into_iter()
+    .map(|(field, expected, actual)| FieldComparison {
+        field,
+        expected,
+        actual,
+    })
+    .collect()
+}
+
+/// The genesis stake table state the deployer computes for a light client with the stake table
+/// `validators`.
+pub fn expected_genesis_stake(
+    validators: &[Validator<BLSPubKey>],
+    stake_table_capacity: usize,
+) -> Result<StakeTableStateSol> {
+    let peers = validators
+        .iter()
+        .map(|v| PeerConfig::<SeqTypes> {
+            stake_table_entry: v.stake_table_key.stake_table_entry(v.stake),
+            state_ver_key: v.state_ver_key.clone(),
+        })
+        .collect::<Vec<_>>();
+    let (_, st_state) =
+        light_client_genesis_from_stake_table(&HSStakeTable::from(peers), stake_table_capacity)?;
+    Ok(st_state)
+}
+
+/// Compare the genesis stake table state of the light client, that the stake table is wired to,
+/// with the state computed from the stake table at `l1_block_number`.
+pub async fn verify_genesis_stake(
+    provider: impl Provider,
+    rpc_url: Url,
+    stake_table_address: Address,
+    l1_block_number: u64,
+    stake_table_capacity: usize,
+) -> Result<Vec<FieldComparison>> {
+    let validators = stake_table_info(rpc_url, stake_table_address, l1_block_number).await?;
+    let expected = expected_genesis_stake(&validators, stake_table_capacity)?;
+
+    let lc_address = StakeTable::new(stake_table_address, &provider)
+        .lightClient()
+        .call()
+        .await?
+        ._0;
+    let actual: StakeTableStateSol = LightClient::new(lc_address, &provider)
+        .genesisStakeTableState()
+        .call()
+        .await?
+        .into();
+    Ok(compare_stake_table_states(&expected, &actual))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use alloy::primitives::utils::parse_ether;
+    use espresso_contract_deployer::{deploy_light_client_proxy, Contracts};
+    use hotshot_contract_adapter::sol_types::LightClientStateSol;
+    use hotshot_types::light_client::STAKE_TABLE_CAPACITY;
+    use rand::{rngs::StdRng, SeedableRng as _};
+
+    use super::*;
+    use crate::deploy::TestSystem;
+
+    fn validators(stakes: &[u64]) -> Vec<Validator<BLSPubKey>> {
+        let mut rng = StdRng::from_seed([7u8; 32]);
+        stakes
+            .iter()
+            .map(|stake| {
+                let (signer, bls_key_pair, state_key_pair) = TestSystem::gen_keys(&mut rng);
+                Validator {
+                    account: signer.address(),
+                    stake_table_key: bls_key_pair.ver_key(),
+                    state_ver_key: state_key_pair.ver_key(),
+                    stake: U256::from(*stake),
+                    commission: 0,
+                    delegators: HashMap::new(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_genesis_stake() -> Result<()> {
+        let on_chain = expected_genesis_stake(&validators(&[1, 2, 3]), STAKE_TABLE_CAPACITY)?;
+
+        let expected = expected_genesis_stake(&validators(&[1, 2, 3]), STAKE_TABLE_CAPACITY)?;
+        let comparison = compare_stake_table_states(&expected, &on_chain);
+        assert_eq!(comparison.len(), 4);
+        assert!(comparison.iter().all(FieldComparison::matches));
+
+        // Same keys with different stake only changes the stake dependent fields.
+        let expected = expected_genesis_stake(&validators(&[1, 2, 9]), STAKE_TABLE_CAPACITY)?;
+        let mismatched = compare_stake_table_states(&expected, &on_chain)
+            .into_iter()
+            .filter(|c| !c.matches())
+            .map(|c| c.field)
+            .collect::<Vec<_>>();
+        assert_eq!(mismatched, vec!["threshold", "amountComm"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_verify_genesis_stake() -> Result<()> {
+        let mut system = TestSystem::deploy().await?;
+        let stake = parse_ether("100")?;
+        let validator = Validator {
+            account: system.deployer_address,
+            stake_table_key: system.bls_key_pair.ver_key(),
+            state_ver_key: system.state_key_pair.ver_key(),
+            stake,
+            commission: 0,
+            delegators: HashMap::new(),
+        };
+        let genesis_stake = expected_genesis_stake(&[validator], STAKE_TABLE_CAPACITY)?;
+        let light_client = deploy_light_client_proxy(
+            &system.provider,
+            &mut Contracts::new(),
+            false,
+            LightClientStateSol::dummy_genesis(),
+            genesis_stake,
+            system.deployer_address,
+            None,
+        )
+        .await?;
+        system.redeploy_stake_table(light_client).await?;
+        system.register_validator().await?;
+        system.delegate(stake).await?;
+
+        let verify = || async {
+            let block = system.provider.get_block_number().await?;
+            verify_genesis_stake(
+                &system.provider,
+                system.rpc_url.clone(),
+                system.stake_table,
+                block,
+                STAKE_TABLE_CAPACITY,
+            )
+            .await
+        };
+        assert!(verify().await?.iter().all(FieldComparison::matches));
+
+        // Once the stake changes it no longer matches the genesis of the light client.
+        system.delegate(stake).await?;
+        let mismatched = verify()
+            .await?
+            .into_iter()
+            .filter(|c| !c.matches())
+            .map(|c| c.field)
+            .collect::<Vec<_>>();
+        assert_eq!(mismatched, vec!["threshold", "amountComm"]);
+        Ok(())
+    }
+}
@@ -1,13 +1,23 @@
-use alloy::primitives::{utils::format_ether, Address};
-use anyhow::Result;
+use std::{collections::BTreeSet, future::Future, ops::RangeInclusive, path::Path};
+
+use alloy::{
+    eips::BlockId,
+    hex,
+    primitives::{keccak256, utils::format_ether, Address, U256},
+    providers::Provider,
+};
+use anyhow::{bail, Context as _, Result};
+use clap::ValueEnum;
 use espresso_types::{
-    v0_3::{StakeTableFetcher, Validator},
+    v0_3::{StakeTableFetcher, Validator, ValidatorRegistry},
     L1Client,
 };
-use hotshot_types::signature_key::BLSPubKey;
+use hotshot_contract_adapter::sol_types::{LightClientV2, StakeTable};
+use hotshot_types::{signature_key::BLSPubKey, utils::epoch_from_block_number};
+use serde::{Deserialize, Serialize};
 use url::Url;
 
-use crate::parse::Commission;
+use crate::{genesis::FieldComparison, parse::Commission};
 
 pub async fn stake_table_info(
     l1_url: Url,
@@ -24,19 +34,391 @@ pub async fn stake_table_info(
         .collect())
 }
 
-pub fn display_stake_table(stake_table: Vec<Validator<BLSPubKey>>, compact: bool) -> Result<()> {
-    let mut stake_table = stake_table.clone();
-    stake_table.sort_by(|a, b| a.stake.cmp(&b.stake));
+/// The total stake held in the exit escrow of the stake table at `l1_block_number`.
+///
+/// This is the stake of undelegations and of delegations to exited validators that has not been
+/// withdrawn yet, whether or not the escrow period already passed. The contract doesn't track
+/// this total, so it is summed up from the accounts appearing in the stake table events. The events
+/// are queried in ranges of at most `max_block_range` L1 blocks, like the `StakeTableFetcher` does.
+pub async fn total_escrowed_stake(
+    provider: impl Provider,
+    stake_table_address: Address,
+    l1_block_number: u64,
+    max_block_range: u64,
+) -> Result<U256> {
+    let stake_table = StakeTable::new(stake_table_address, &provider);
+    let block = BlockId::number(l1_block_number);
+    let from_block = stake_table
+        .initializedAtBlock()
+        .block(block)
+        .call()
+        .await?
+        ._0
+        .to::<u64>();
+    let chunks = block_chunks(from_block, l1_block_number, max_block_range)?;
+
+    let mut undelegated = BTreeSet::new();
+    let mut exited = BTreeSet::new();
+    for range in chunks.clone() {
+        undelegated.extend(
+            stake_table
+                .Undelegated_filter()
+                .from_block(*range.start())
+                .to_block(*range.end())
+                .query()
+                .await?
+                .into_iter()
+                .map(|(event, _)| (event.validator, event.delegator)),
+        );
+        exited.extend(
+            stake_table
+                .ValidatorExit_filter()
+                .from_block(*range.start())
+                .to_block(*range.end())
+                .query()
+                .await?
+                .into_iter()
+                .map(|(event, _)| event.validator),
+        );
+    }
+    let mut exited_delegations = BTreeSet::new();
+    if !exited.is_empty() {
+        for range in chunks {
+            exited_delegations.extend(
+                stake_table
+                    .Delegated_filter()
+                    .from_block(*range.start())
+                    .to_block(*range.end())
+                    .query()
+                    .await?
+                    .into_iter()
+                    .filter(|(event, _)| exited.contains(&event.validator))
+                    .map(|(event, _)| (event.validator, event.delegator)),
+            );
+        }
+    }
+
+    let mut total = U256::ZERO;
+    for (validator, delegator) in undelegated {
+        total += stake_table
+            .undelegations(validator, delegator)
+            .block(block)
+            .call()
+            .await?
+            .amount;
+    }
+    for (validator, delegator) in exited_delegations {
+        total += stake_table
+            .delegations(validator, delegator)
+            .block(block)
+            .call()
+            .await?
+            .amount;
+    }
+    Ok(total)
+}
+
+/// Split the L1 blocks `from..=to` into consecutive ranges of at most `max_len` blocks.
+fn block_chunks(from: u64, to: u64, max_len: u64) -> Result<Vec<RangeInclusive<u64>>> {
+    if max_len == 0 {
+        bail!("max block range must be positive");
+    }
+    Ok((from..=to)
+        .step_by(max_len as usize)
+        .map(|start| start..=to.min(start + (max_len - 1)))
+        .collect())
+}
+
+/// Get the validators and the keys ever registered from the stake table events up to and
+/// including `l1_block_number`, e.g. to start a [`StakeTableCache`].
+pub async fn stake_table_registry(
+    l1_url: Url,
+    stake_table_address: Address,
+    l1_block_number: u64,
+) -> Result<ValidatorRegistry> {
+    let l1 = L1Client::new(vec![l1_url])?;
+    StakeTableFetcher::fetch_validator_registry(l1, stake_table_address, l1_block_number).await
+}
+
+/// A stake table cached on disk, to be updated incrementally.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StakeTableCache {
+    /// The last L1 block whose events are included in `registry`.
+    pub l1_block: u64,
+    /// The validators, and the keys used by current and past validators which can't be registered
+    /// again.
+    pub registry: ValidatorRegistry,
+}
+
+impl StakeTableCache {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read stake table cache {}", path.display()))?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string(self)?)
+            .with_context(|| format!("failed to write stake table cache {}", path.display()))
+    }
+}
 
+/// Update a cached stake table with the stake table events after the last block of the cache, up
+/// to and including `l1_block_number`.
+pub async fn update_stake_table_info(
+    l1_url: Url,
+    stake_table_address: Address,
+    cache: StakeTableCache,
+    l1_block_number: u64,
+) -> Result<ValidatorRegistry> {
+    if l1_block_number < cache.l1_block {
+        bail!(
+            "stake table cache already includes events up to block {}, after block \
+             {l1_block_number}",
+            cache.l1_block
+        );
+    }
+    if l1_block_number == cache.l1_block {
+        return Ok(cache.registry);
+    }
+
+    let l1 = L1Client::new(vec![l1_url])?;
+    StakeTableFetcher::update_validators(
+        l1,
+        stake_table_address,
+        cache.registry,
+        cache.l1_block + 1,
+        l1_block_number,
+    )
+    .await
+}
+
+/// The epoch configuration of an epoch-enabled network, as stored in the light client contract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EpochConfig {
+    pub epoch_height: u64,
+    pub epoch_start_block: u64,
+}
+
+impl EpochConfig {
+    /// Read the epoch configuration from the light client at `light_client_address`.
+    ///
+    /// Fails if the light client hasn't been upgraded to V2, i.e. epochs are not enabled.
+    pub async fn fetch(provider: impl Provider, light_client_address: Address) -> Result<Self> {
+        let not_enabled =
+            || format!("network is not epoch-enabled (light client {light_client_address:#x})");
+        let lc = LightClientV2::new(light_client_address, &provider);
+        let epoch_height = lc
+            .blocksPerEpoch()
+            .call()
+            .await
+            .with_context(not_enabled)?
+            ._0;
+        let epoch_start_block = lc
+            .epochStartBlock()
+            .call()
+            .await
+            .with_context(not_enabled)?
+            ._0;
+        if epoch_height == 0 {
+            bail!(not_enabled());
+        }
+        Ok(Self {
+            epoch_height,
+            epoch_start_block,
+        })
+    }
+
+    /// The HotShot blocks of `epoch`.
+    pub fn hotshot_blocks(&self, epoch: u64) -> Result<RangeInclusive<u64>> {
+        if self.epoch_height == 0 {
+            bail!("network is not epoch-enabled");
+        }
+        let first_epoch = epoch_from_block_number(self.epoch_start_block, self.epoch_height);
+        if epoch < first_epoch {
+            bail!("epoch {epoch} is before the first epoch {first_epoch}");
+        }
+        Ok((epoch - 1) * self.epoch_height + 1..=epoch * self.epoch_height)
+    }
+}
+
+/// Compare locally configured epoch parameters with those applied in the light client.
+pub fn compare_epoch_configs(local: &EpochConfig, on_chain: &EpochConfig) -> Vec<FieldComparison> {
+    [
+        ("blocksPerEpoch", local.epoch_height, on_chain.epoch_height),
+        (
+            "epochStartBlock",
+            local.epoch_start_block,
+            on_chain.epoch_start_block,
+        ),
+    ]
+    .into_iter()
+    .map(|(field, expected, actual)| FieldComparison {
+        field,
+        expected: U256::from(expected),
+        actual: U256::from(actual),
+    })
+    .collect()
+}
+
+/// The epoch configuration of the light client that the stake table is wired to.
+pub async fn fetch_epoch_config(
+    provider: impl Provider,
+    stake_table_address: Address,
+) -> Result<EpochConfig> {
+    let lc_address = StakeTable::new(stake_table_address, &provider)
+        .lightClient()
+        .call()
+        .await?
+        ._0;
+    EpochConfig::fetch(&provider, lc_address).await
+}
+
+/// An entry of the light client state history: a HotShot block height finalized in an L1 block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FinalizedHeight {
+    pub l1_block: u64,
+    pub hotshot_block: u64,
+}
+
+/// The L1 blocks in which the light client finalized `hotshot_blocks`.
+///
+/// `history(i)` returns the `i`-th light client state history entry, for `i` in `first..len`.
+/// Entries are ordered by HotShot block height, so only a few of them are looked up. If the last of
+/// the HotShot blocks is not finalized yet, the range ends at the latest entry.
+pub async fn l1_block_range<F, Fut>(
+    hotshot_blocks: RangeInclusive<u64>,
+    first: u64,
+    len: u64,
+    history: F,
+) -> Result<RangeInclusive<u64>>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Result<FinalizedHeight>>,
+{
+    let (start_block, end_block) = hotshot_blocks.into_inner();
+    let start = first_finalizing(&history, first, len, start_block).await?;
+    if start == len {
+        bail!("HotShot block {start_block} is not finalized by the light client yet");
+    }
+    let start_entry = history(start).await?;
+    if start == first && first > 0 && start_entry.hotshot_block > start_block {
+        bail!("HotShot block {start_block} is no longer in the light client state history");
+    }
+
+    let end = first_finalizing(&history, start, len, end_block).await?;
+    let end_entry = history(end.min(len - 1)).await?;
+    Ok(start_entry.l1_block..=end_entry.l1_block)
+}
+
+/// Binary search for the first entry in `first..len` that finalizes HotShot block `height`.
+///
+/// Returns `len` if there is none.
+async fn first_finalizing<F, Fut>(history: &F, first: u64, len: u64, height: u64) -> Result<u64>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Result<FinalizedHeight>>,
+{
+    let (mut lo, mut hi) = (first, len);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if history(mid).await?.hotshot_block < height {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    Ok(lo)
+}
+
+/// The L1 blocks in which the light client, that the stake table is wired to, finalized the
+/// HotShot blocks of `epoch`.
+pub async fn epoch_l1_block_range(
+    provider: impl Provider,
+    stake_table_address: Address,
+    epoch: u64,
+) -> Result<RangeInclusive<u64>> {
+    let lc_address = StakeTable::new(stake_table_address, &provider)
+        .lightClient()
+        .call()
+        .await?
+        ._0;
+    let hotshot_blocks = EpochConfig::fetch(&provider, lc_address)
+        .await?
+        .hotshot_blocks(epoch)?;
+    let lc = LightClientV2::new(lc_address, &provider);
+    let first = lc.stateHistoryFirstIndex().call().await?._0;
+    let len = u64::try_from(lc.getStateHistoryCount().call().await?._0)?;
+    l1_block_range(hotshot_blocks, first, len, |i| {
+        let lc = &lc;
+        async move {
+            let entry = lc.stateHistoryCommitments(U256::from(i)).call().await?;
+            Ok(FinalizedHeight {
+                l1_block: entry.l1BlockHeight,
+                hotshot_block: entry.hotShotBlockHeight,
+            })
+        }
+    })
+    .await
+}
+
+/// Format a BLS key for display.
+///
+/// With `compact`, the key is abbreviated. With `redact`, the key is replaced by the first 8 hex
+/// digits of its keccak hash, so that it can be shared while still being recognizable across rows.
+pub fn format_bls_key(key: &BLSPubKey, compact: bool, redact: bool) -> String {
+    let bls_key = key.to_string();
+    if redact {
+        let hash = keccak256(bls_key.as_bytes());
+        format!("redacted:{}", hex::encode(&hash[..4]))
+    } else if compact {
+        let end = bls_key.chars().map(|c| c.len_utf8()).take(40).sum();
+        format!("{}..", &bls_key[..end])
+    } else {
+        bls_key
+    }
+}
+
+/// The order in which the validators of the stake table are displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum SortBy {
+    /// Ascending by stake.
+    #[default]
+    Stake,
+    /// Ascending by account address.
+    Address,
+    /// Ascending by commission.
+    Commission,
+}
+
+/// Sort the validators by `sort_by` and select up to `limit` of them, starting at `offset`.
+pub fn page_stake_table(
+    mut stake_table: Vec<Validator<BLSPubKey>>,
+    sort_by: SortBy,
+    offset: usize,
+    limit: Option<usize>,
+) -> Vec<Validator<BLSPubKey>> {
+    match sort_by {
+        SortBy::Stake => stake_table.sort_by_key(|validator| validator.stake),
+        SortBy::Address => stake_table.sort_by_key(|validator| validator.account),
+        SortBy::Commission => stake_table.sort_by_key(|validator| validator.commission),
+    }
+    stake_table
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
+/// Display the validators in the given order, see [`page_stake_table`].
+pub fn display_stake_table(
+    stake_table: Vec<Validator<BLSPubKey>>,
+    compact: bool,
+    redact_keys: bool,
+) -> Result<()> {
     for validator in stake_table.iter() {
         let comm: Commission = validator.commission.try_into()?;
-        let bls_key = validator.stake_table_key.to_string();
-        let key_str = if compact {
-            let end = bls_key.chars().map(|c| c.len_utf8()).take(40).sum();
-            format!("{}..", &bls_key[..end])
-        } else {
-            bls_key.to_string()
-        };
+        let key_str = format_bls_key(&validator.stake_table_key, compact, redact_keys);
         tracing::info!(
             "Validator {}: {key_str} comm={comm} stake={} ESP",
             validator.account,
@@ -60,3 +442,226 @@ pub fn display_stake_table(stake_table: Vec<Validator<BLSPubKey>>, compact: bool
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use alloy::{primitives::utils::parse_ether, providers::Provider as _};
+    use rand::{rngs::StdRng, SeedableRng as _};
+
+    use super::*;
+    use crate::{claim::claim_withdrawal, deploy::TestSystem};
+
+    #[test]
+    fn test_format_bls_key_redacted() {
+        let mut rng = StdRng::from_seed([1; 32]);
+        let (_, key, _) = TestSystem::gen_keys(&mut rng);
+        let (_, other_key, _) = TestSystem::gen_keys(&mut rng);
+
+        // two rows with the same key
+        let rows = [key.ver_key(), key.ver_key(), other_key.ver_key()]
+            .map(|vk| format_bls_key(&vk, false, true));
+        assert_eq!(rows[0], rows[1]);
+        assert_ne!(rows[0], rows[2]);
+        assert_eq!(rows[0].len(), "redacted:".len() + 8);
+        assert!(!rows[0].contains(&key.ver_key().to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_total_escrowed_stake() -> Result<()> {
+        let system = TestSystem::deploy().await?;
+        system.register_validator().await?;
+        system.delegate(parse_ether("300")?).await?;
+        let escrowed = || async {
+            let block = system.provider.get_block_number().await?;
+            // A tiny block range to exercise the paging of the event queries.
+            total_escrowed_stake(&system.provider, system.stake_table, block, 2).await
+        };
+        assert_eq!(escrowed().await?, U256::ZERO);
+
+        system.undelegate(parse_ether("100")?).await?;
+        assert_eq!(escrowed().await?, parse_ether("100")?);
+
+        // The remaining delegation goes into the escrow when the validator exits.
+        system.deregister_validator().await?;
+        assert_eq!(escrowed().await?, parse_ether("300")?);
+
+        // Withdrawn stake leaves the escrow.
+        system.warp_to_unlock_time().await?;
+        claim_withdrawal(
+            &system.provider,
+            system.stake_table,
+            system.deployer_address,
+        )
+        .await?;
+        assert_eq!(escrowed().await?, parse_ether("200")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_chunks() -> Result<()> {
+        assert_eq!(block_chunks(3, 9, 3)?, vec![3..=5, 6..=8, 9..=9]);
+        assert_eq!(block_chunks(3, 8, 3)?, vec![3..=5, 6..=8]);
+        assert_eq!(block_chunks(3, 3, 10)?, vec![3..=3]);
+        assert!(block_chunks(4, 3, 10)?.is_empty());
+        assert!(block_chunks(3, 9, 0).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_epoch_l1_block_range() -> Result<()> {
+        let config = EpochConfig {
+            epoch_height: 10,
+            epoch_start_block: 25,
+        };
+        assert!(config.hotshot_blocks(2).is_err());
+        assert_eq!(config.hotshot_blocks(3)?, 21..=30);
+        assert!(EpochConfig {
+            epoch_height: 0,
+            epoch_start_block: 0,
+        }
+        .hotshot_blocks(1)
+        .is_err());
+
+        let history = [(100, 28), (105, 32), (110, 36), (115, 41), (120, 45)].map(
+            |(l1_block, hotshot_block)| FinalizedHeight {
+                l1_block,
+                hotshot_block,
+            },
+        );
+        let len = history.len() as u64;
+        let range = |epoch, first| {
+            let hotshot_blocks = config.hotshot_blocks(epoch);
+            async move {
+                l1_block_range(hotshot_blocks?, first, len, |i| {
+                    let entry = history[i as usize];
+                    async move { Ok(entry) }
+                })
+                .await
+            }
+        };
+
+        assert_eq!(range(3, 0).await?, 100..=105);
+        assert_eq!(range(4, 0).await?, 105..=115);
+        // the epoch isn't over yet
+        assert_eq!(range(5, 0).await?, 115..=120);
+        // the epoch hasn't started yet
+        assert!(range(6, 0).await.is_err());
+        // the start of the epoch has been pruned from the history
+        assert!(range(3, 1).await.is_err());
+        assert_eq!(range(5, 1).await?, 115..=120);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compare_epoch_configs() {
+        let on_chain = EpochConfig {
+            epoch_height: 100,
+            epoch_start_block: 250,
+        };
+        let comparison = compare_epoch_configs(&on_chain, &on_chain);
+        assert_eq!(comparison.len(), 2);
+        assert!(comparison.iter().all(FieldComparison::matches));
+
+        let local = EpochConfig {
+            epoch_height: 100,
+            epoch_start_block: 300,
+        };
+        let mismatched = compare_epoch_configs(&local, &on_chain)
+            .into_iter()
+            .filter(|c| !c.matches())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            mismatched,
+            vec![FieldComparison {
+                field: "epochStartBlock",
+                expected: U256::from(300),
+                actual: U256::from(250),
+            }]
+        );
+        assert_eq!(
+            mismatched[0].to_string(),
+            "epochStartBlock: MISMATCH expected=300 on-chain=250"
+        );
+    }
+
+    #[test]
+    fn test_page_stake_table() {
+        let mut rng = StdRng::from_seed([2; 32]);
+        let validators = [(3, 20, 0x11), (1, 30, 0x33), (2, 10, 0x22), (4, 40, 0x44)]
+            .into_iter()
+            .map(|(stake, commission, address)| {
+                let (_, bls_key_pair, state_key_pair) = TestSystem::gen_keys(&mut rng);
+                Validator {
+                    account: Address::repeat_byte(address),
+                    stake_table_key: bls_key_pair.ver_key(),
+                    state_ver_key: state_key_pair.ver_key(),
+                    stake: U256::from(stake),
+                    commission,
+                    delegators: Default::default(),
+                }
+            })
+            .collect::<Vec<_>>();
+        let page = |sort_by, offset, limit| {
+            page_stake_table(validators.clone(), sort_by, offset, limit)
+                .into_iter()
+                .map(|validator| validator.stake.to::<u64>())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(page(SortBy::Stake, 0, None), vec![1, 2, 3, 4]);
+        assert_eq!(page(SortBy::Address, 0, None), vec![3, 2, 1, 4]);
+        assert_eq!(page(SortBy::Commission, 0, None), vec![2, 3, 1, 4]);
+        assert_eq!(page(SortBy::Stake, 1, Some(2)), vec![2, 3]);
+        assert_eq!(page(SortBy::Commission, 3, Some(2)), vec![4]);
+        assert_eq!(page(SortBy::Address, 4, None), Vec::<u64>::new());
+        assert_eq!(page(SortBy::Stake, 0, Some(0)), Vec::<u64>::new());
+    }
+
+    #[tokio::test]
+    async fn test_update_stake_table_info() -> Result<()> {
+        let system = TestSystem::deploy().await?;
+        system.register_validator().await?;
+        let cached_block = system.provider.get_block_number().await?;
+        let cache = StakeTableCache {
+            l1_block: cached_block,
+            registry: stake_table_registry(
+                system.rpc_url.clone(),
+                system.stake_table,
+                cached_block,
+            )
+            .await?,
+        };
+
+        let amount = parse_ether("1.5")?;
+        system.approve(amount).await?;
+        system.delegate(amount).await?;
+        system.undelegate(parse_ether("0.5")?).await?;
+        let l1_block = system.provider.get_block_number().await?;
+
+        let update = |cache: StakeTableCache, l1_block| {
+            update_stake_table_info(system.rpc_url.clone(), system.stake_table, cache, l1_block)
+        };
+        let full =
+            stake_table_registry(system.rpc_url.clone(), system.stake_table, l1_block).await?;
+        let incremental = update(cache.clone(), l1_block).await?;
+        assert_eq!(incremental, full);
+        let validators = full.validators.values().collect::<Vec<_>>();
+        assert_eq!(validators[0].stake, parse_ether("1")?);
+
+        // After the validator exits, its keys are still known to the incrementally updated
+        // registry, though it was cached before the exit.
+        system.deregister_validator().await?;
+        let l1_block = system.provider.get_block_number().await?;
+        let full =
+            stake_table_registry(system.rpc_url.clone(), system.stake_table, l1_block).await?;
+        let incremental = update(cache.clone(), l1_block).await?;
+        assert_eq!(incremental, full);
+        assert!(full.validators.is_empty());
+        assert_eq!(full.used_bls_keys.len(), 1);
+
+        // a cache can't be updated to a block it already includes
+        assert_eq!(update(cache.clone(), cached_block).await?, cache.registry);
+        assert!(update(cache.clone(), cached_block - 1).await.is_err());
+        Ok(())
+    }
+}
@@ -1,38 +1,99 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
 use alloy::{
     eips::BlockId,
     network::EthereumWallet,
-    primitives::{utils::parse_ether, Address, U256},
+    primitives::{utils::parse_ether, Address, B256, U256},
     signers::{
         ledger::{HDPath, LedgerError, LedgerSigner},
-        local::{coins_bip39::English, MnemonicBuilder},
+        local::{coins_bip39::English, MnemonicBuilder, PrivateKeySigner},
     },
 };
-use anyhow::{bail, Result};
+use anyhow::{bail, Context as _, Result};
 use clap::{Parser, Subcommand};
 use clap_serde_derive::ClapSerde;
 use demo::DelegationConfig;
+use espresso_types::parse_duration;
 pub(crate) use hotshot_types::{
-    light_client::{StateSignKey, StateVerKey},
+    light_client::{StateSignKey, StateVerKey, STAKE_TABLE_CAPACITY},
     signature_key::BLSPrivKey,
 };
+use info::SortBy;
 pub(crate) use jf_signature::bls_over_bn254::KeyPair as BLSKeyPair;
 use parse::Commission;
+use retry::RetryConfig;
 use sequencer_utils::logging;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+pub mod balance;
+pub mod batch;
 pub mod claim;
 pub mod delegation;
 pub mod demo;
+pub mod events;
+pub mod genesis;
 pub mod info;
 pub mod l1;
 pub mod parse;
 pub mod registration;
+pub mod retry;
+pub mod simulate;
 
 pub mod deploy;
 
 pub const DEV_MNEMONIC: &str = "test test test test test test test test test test test junk";
 
+/// Derive the signer of `mnemonic` at `account_index`.
+pub fn mnemonic_signer(mnemonic: &str, account_index: u32) -> Result<PrivateKeySigner> {
+    Ok(MnemonicBuilder::<English>::default()
+        .phrase(mnemonic)
+        .index(account_index)?
+        .build()?)
+}
+
+/// Derive the first `count` accounts of the [`DEV_MNEMONIC`], in account index order.
+pub fn dev_accounts(count: usize) -> Vec<(PrivateKeySigner, Address)> {
+    (0..count as u32)
+        .map(|index| {
+            let signer = mnemonic_signer(DEV_MNEMONIC, index).expect("valid dev mnemonic");
+            let address = signer.address();
+            (signer, address)
+        })
+        .collect()
+}
+
+/// An account derived from a mnemonic.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DerivedAccount {
+    pub account_index: u32,
+    pub address: Address,
+}
+
+/// Derive the addresses of `count` consecutive accounts of `mnemonic`, starting at
+/// `account_index`.
+///
+/// This is purely local key derivation, no RPC is involved.
+pub fn derive_accounts(
+    mnemonic: &str,
+    account_index: u32,
+    count: usize,
+) -> Result<Vec<DerivedAccount>> {
+    (account_index..)
+        .take(count)
+        .map(|account_index| {
+            let signer = mnemonic_signer(mnemonic, account_index)?;
+            Ok(DerivedAccount {
+                account_index,
+                address: signer.address(),
+            })
+        })
+        .collect()
+}
+
 /// CLI to interact with the Espresso stake table contract
 #[derive(ClapSerde, Clone, Debug, Deserialize, Serialize)]
 #[command(version, about, long_about = None)]
@@ -53,6 +114,29 @@ pub struct Config {
     #[clap(flatten)]
     pub signer: SignerConfig,
 
+    /// Dry-run write commands with `eth_call` instead of sending transactions.
+    ///
+    /// Reports the decoded revert reason if the transaction would fail. Rejected for commands that
+    /// don't send a transaction.
+    #[clap(long)]
+    #[serde(skip)]
+    pub simulate: bool,
+
+    /// Number of times to retry sending a transaction after a transport error.
+    ///
+    /// Only failures to reach the RPC, like timeouts or dropped connections, are retried. Reverts
+    /// are deterministic and fail immediately.
+    #[clap(long, env = "MAX_RETRIES")]
+    #[default(0)]
+    #[serde(skip)]
+    pub max_retries: u32,
+
+    /// Delay before the first retry, doubled after every further retry.
+    #[clap(long, env = "RETRY_INTERVAL", value_parser = parse_duration)]
+    #[default(Duration::from_secs(1))]
+    #[serde(skip)]
+    pub retry_interval: Duration,
+
     #[clap(flatten)]
     #[serde(skip)]
     pub logging: logging::Config,
@@ -121,10 +205,7 @@ impl ValidSignerConfig {
                 mnemonic,
                 account_index,
             } => {
-                let signer = MnemonicBuilder::<English>::default()
-                    .phrase(mnemonic)
-                    .index(*account_index)?
-                    .build()?;
+                let signer = mnemonic_signer(mnemonic, *account_index)?;
                 let account = signer.address();
                 let wallet = EthereumWallet::from(signer);
                 Ok((wallet, account))
@@ -173,12 +254,34 @@ impl Default for Commands {
     fn default() -> Self {
         Commands::StakeTable {
             l1_block_number: None,
+            epoch: None,
             compact: false,
+            redact_keys: false,
+            state_cache: None,
+            sort_by: SortBy::default(),
+            offset: 0,
+            limit: None,
+            show_escrow: false,
         }
     }
 }
 
 impl Config {
+    /// Load the config file at `path` and apply the options `opt` on top of it.
+    ///
+    /// Command line flags take precedence over environment variables, which take precedence over
+    /// the config file. Flags and environment variables are both parsed into `opt` by clap, which
+    /// only falls back to the environment if a flag is not given. If the config file can't be
+    /// read, the config is built from `opt` alone.
+    pub fn from_file(path: &Path, opt: &mut <Config as ClapSerde>::Opt) -> Result<Self> {
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            return Ok(Self::from(opt));
+        };
+        let config = toml::from_str::<Self>(&contents)
+            .with_context(|| format!("Error in configuration file at {}", path.display()))?;
+        Ok(config.merge(opt))
+    }
+
     pub fn apply_env_var_overrides(self) -> Result<Self> {
         let mut config = self.clone();
         if self.token_address == Address::ZERO {
@@ -199,6 +302,53 @@ impl Config {
         }
         Ok(config)
     }
+
+    /// Check the config for common mistakes, without connecting to the RPC or a ledger.
+    ///
+    /// Returns a description of every issue found, so an empty list means the config is valid.
+    pub fn validate(&self) -> Vec<String> {
+        let mut issues = vec![];
+        if !matches!(self.rpc_url.scheme(), "http" | "https" | "ws" | "wss") {
+            issues.push(format!(
+                "RPC URL {} must use http(s) or ws(s), use --rpc-url or L1_PROVIDER",
+                self.rpc_url
+            ));
+        } else if self.rpc_url.host().is_none() {
+            issues.push(format!("RPC URL {} has no host", self.rpc_url));
+        }
+        if self.token_address == Address::ZERO {
+            issues.push(
+                "Token address is not set, use --token-address or ESP_TOKEN_ADDRESS".to_string(),
+            );
+        }
+        if self.stake_table_address == Address::ZERO {
+            issues.push(
+                "Stake table address is not set, use --stake-table-address or STAKE_TABLE_ADDRESS"
+                    .to_string(),
+            );
+        }
+        match ValidSignerConfig::try_from(self.signer.clone()) {
+            Ok(ValidSignerConfig::Mnemonic {
+                mnemonic,
+                account_index,
+            }) => {
+                if let Err(err) = mnemonic_signer(&mnemonic, account_index) {
+                    issues.push(format!("Invalid mnemonic: {err}"));
+                }
+            },
+            // Whether the ledger is connected and unlocked is only checked when signing.
+            Ok(ValidSignerConfig::Ledger { .. }) => {},
+            Err(err) => issues.push(format!("Invalid signer: {err}")),
+        }
+        issues
+    }
+
+    pub fn retry_config(&self) -> RetryConfig {
+        RetryConfig {
+            max_retries: self.max_retries,
+            interval: self.retry_interval,
+        }
+    }
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -207,6 +357,8 @@ pub enum Commands {
     Version,
     /// Display the current configuration
     Config,
+    /// Check the configuration for mistakes, without connecting to the network.
+    Validate,
     /// Initialize the config file with deployment and wallet info.
     Init {
         /// The mnemonic to use when deriving the key.
@@ -235,12 +387,102 @@ pub enum Commands {
         #[clap(long)]
         l1_block_number: Option<BlockId>,
 
+        /// Show the stake table as of the start of this epoch, instead of an L1 block.
+        ///
+        /// The epoch is mapped to L1 blocks via the light client state history, so the network must
+        /// be epoch-enabled.
+        #[clap(long, conflicts_with = "l1_block_number")]
+        epoch: Option<u64>,
+
         /// Abbreviate the very long BLS public keys.
         #[clap(long)]
         compact: bool,
+
+        /// Replace the BLS public keys with a short hash, to share the stake table without
+        /// revealing the keys.
+        #[clap(long, conflicts_with = "compact")]
+        redact_keys: bool,
+
+        /// File to cache the stake table in, updated with the resulting stake table.
+        ///
+        /// If the file exists, only the stake table events after the last block included in it
+        /// are processed and applied to the cached stake table.
+        #[clap(long)]
+        state_cache: Option<PathBuf>,
+
+        /// The order in which to show the validators.
+        #[clap(long, value_enum, default_value = "stake")]
+        sort_by: SortBy,
+
+        /// Skip this many validators, after sorting.
+        #[clap(long, default_value_t = 0)]
+        offset: usize,
+
+        /// Show at most this many validators, after sorting.
+        #[clap(long)]
+        limit: Option<usize>,
+
+        /// Also show the total stake in the exit escrow.
+        ///
+        /// This scans all stake table events since the contract was deployed, which can take a
+        /// while.
+        #[clap(long)]
+        show_escrow: bool,
+    },
+    /// Decode the stake table events emitted by a transaction.
+    DecodeEvent {
+        /// The hash of the transaction.
+        #[clap(long)]
+        tx_hash: B256,
+
+        /// Print the events as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+    /// Compare the light client's genesis stake table state with the one computed from the
+    /// current stake table.
+    VerifyGenesisStake {
+        /// The L1 block at which to read the stake table, defaults to the latest block.
+        #[clap(long)]
+        l1_block_number: Option<u64>,
+
+        /// Stake table capacity of the light client circuit.
+        #[clap(long, env = "ESPRESSO_SEQUENCER_STAKE_TABLE_CAPACITY", default_value_t = STAKE_TABLE_CAPACITY)]
+        stake_table_capacity: usize,
+    },
+    /// Compare locally configured epoch parameters with those applied in the light client.
+    ///
+    /// The light client is the one the stake table contract is wired to. Exits with an error if
+    /// any of the parameters differ.
+    CompareParams {
+        /// The configured number of HotShot blocks per epoch, `epoch_height` in the genesis file.
+        #[clap(long)]
+        epoch_height: u64,
+
+        /// The configured first epoch block, `epoch_start_block` in the genesis file.
+        #[clap(long)]
+        epoch_start_block: u64,
     },
     /// Print the signer account address.
     Account,
+    /// Print the addresses derived from a mnemonic, without connecting to the L1.
+    DeriveAddress {
+        /// The mnemonic to derive from, defaults to the configured mnemonic.
+        #[clap(long, env = "MNEMONIC")]
+        mnemonic: Option<String>,
+
+        /// The first mnemonic account index to derive.
+        #[clap(long, env = "ACCOUNT_INDEX", default_value_t = 0)]
+        account_index: u32,
+
+        /// The number of consecutive accounts to derive, defaults to 1.
+        #[clap(long)]
+        count: Option<usize>,
+
+        /// Print the accounts as JSON.
+        #[clap(long)]
+        json: bool,
+    },
     /// Register to become a validator.
     RegisterValidator {
         /// The consensus signing key. Used to sign a message to prove ownership of the key.
@@ -257,6 +499,15 @@ pub enum Commands {
         #[clap(long, value_parser = parse::parse_commission, env = "COMMISSION")]
         commission: Commission,
     },
+    /// Register many validators from a CSV or JSON file.
+    ///
+    /// Each row contains the consensus private key, state private key and commission of a
+    /// validator. Row `n` is registered from the account `n` indexes after the configured one.
+    /// These accounts must be funded with ETH beforehand, nothing is sent otherwise.
+    BatchRegister {
+        /// Path of the file, `.json` files are read as JSON, anything else as CSV.
+        path: PathBuf,
+    },
     /// Update a validators Espresso consensus signing keys.
     UpdateConsensusKeys {
         /// The consensus signing key. Used to sign a message to prove ownership of the key.
@@ -293,9 +544,19 @@ pub enum Commands {
         amount: U256,
     },
     /// Claim withdrawal after an undelegation.
+    ///
+    /// Refuses to send the transaction while the exit escrow period has not elapsed yet.
     ClaimWithdrawal {
         #[clap(long)]
         validator_address: Address,
+
+        /// Only report whether the withdrawal can be claimed, and how long remains otherwise.
+        #[clap(long)]
+        check_only: bool,
+
+        /// Send the transaction even if the withdrawal does not seem to be claimable yet.
+        #[clap(long, conflicts_with = "check_only")]
+        force: bool,
     },
     /// Claim withdrawal after validator exit.
     ClaimValidatorExit {
@@ -307,6 +568,26 @@ pub enum Commands {
         /// The address to check.
         #[clap(long)]
         address: Option<Address>,
+
+        /// Keep polling the balance and print it whenever it changes.
+        #[clap(long)]
+        watch: bool,
+
+        /// Stop watching once the balance reaches this amount of ESP.
+        #[clap(long, value_parser = parse_ether, requires = "watch")]
+        until: Option<U256>,
+
+        /// How often to poll the balance while watching.
+        #[clap(long, value_parser = parse_duration, default_value = "2s")]
+        interval: Duration,
+
+        /// Stop watching after this long, fails if the `--until` target was not reached.
+        #[clap(long, value_parser = parse_duration, requires = "watch")]
+        timeout: Option<Duration>,
+
+        /// Print the balance as JSON.
+        #[clap(long)]
+        json: bool,
     },
     /// Check ESP token allowance of stake table contract.
     TokenAllowance {
@@ -334,5 +615,81 @@ pub enum Commands {
 
         #[arg(long, value_enum, default_value_t = DelegationConfig::default())]
         delegation_config: DelegationConfig,
+
+        /// Afterwards, exercise the stake table with a throwaway validator.
+        ///
+        /// The validator is deregistered again, so it never becomes part of the demo's validator
+        /// set.
+        #[cfg(feature = "testing")]
+        #[clap(long)]
+        smoke_test: bool,
     },
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dev_accounts() {
+        let accounts = dev_accounts(3);
+        assert_eq!(accounts.len(), 3);
+        // anvil's well-known account 0
+        assert_eq!(
+            accounts[0].1,
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+                .parse::<Address>()
+                .unwrap()
+        );
+        for (signer, address) in accounts {
+            assert_eq!(signer.address(), address);
+        }
+    }
+
+    fn decaf_config() -> Config {
+        toml::from_str(include_str!("../config.decaf.toml")).unwrap()
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let config = decaf_config();
+        assert_eq!(config.validate(), Vec::<String>::new());
+
+        // The ledger is not contacted.
+        let mut config = decaf_config();
+        config.signer.mnemonic = None;
+        config.signer.ledger = true;
+        assert_eq!(config.validate(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_validate_invalid_config() {
+        let mut config = decaf_config();
+        config.rpc_url = "ftp://localhost:8545".parse().unwrap();
+        config.token_address = Address::ZERO;
+        config.stake_table_address = Address::ZERO;
+        let issues = config.validate();
+        assert_eq!(issues.len(), 3, "{issues:?}");
+        assert!(issues[0].contains("RPC URL"));
+        assert!(issues[1].contains("Token address"));
+        assert!(issues[2].contains("Stake table address"));
+
+        let mut config = decaf_config();
+        config.signer.mnemonic = None;
+        let issues = config.validate();
+        assert_eq!(issues.len(), 1, "{issues:?}");
+        assert!(issues[0].contains("Either mnemonic or --ledger"));
+
+        let mut config = decaf_config();
+        config.signer.account_index = None;
+        let issues = config.validate();
+        assert_eq!(issues.len(), 1, "{issues:?}");
+        assert!(issues[0].contains("Account index"));
+
+        let mut config = decaf_config();
+        config.signer.mnemonic = Some("not a valid mnemonic".to_string());
+        let issues = config.validate();
+        assert_eq!(issues.len(), 1, "{issues:?}");
+        assert!(issues[0].contains("Invalid mnemonic"));
+    }
+}
@@ -10,16 +10,27 @@ use alloy::{
 use anyhow::Result;
 use clap::Parser;
 use clap_serde_derive::ClapSerde;
-use hotshot_contract_adapter::{
-    evm::DecodeRevert as _,
-    sol_types::EspToken::{self, EspTokenErrors},
+use espresso_types::L1ClientOptions;
+use hotshot_contract_adapter::sol_types::{
+    EspToken::{self, EspTokenErrors},
+    StakeTable::{self, StakeTableErrors},
 };
 use staking_cli::{
-    claim::{claim_validator_exit, claim_withdrawal},
-    delegation::{approve, delegate, undelegate},
+    balance::{watch_token_balance, BalanceUpdate},
+    batch::{batch_register, read_batch_file},
+    claim::withdrawal_status,
     demo::stake_for_demo,
-    info::{display_stake_table, stake_table_info},
-    registration::{deregister_validator, register_validator, update_consensus_keys},
+    derive_accounts,
+    events::decode_stake_table_events,
+    genesis::verify_genesis_stake,
+    info::{
+        compare_epoch_configs, display_stake_table, epoch_l1_block_range, fetch_epoch_config,
+        page_stake_table, stake_table_registry, total_escrowed_stake, update_stake_table_info,
+        EpochConfig, StakeTableCache,
+    },
+    registration::consensus_keys_payload,
+    retry::send_with_retries,
+    simulate::simulate,
     Commands, Config, ValidSignerConfig,
 };
 use sysinfo::System;
@@ -101,24 +112,17 @@ pub async fn main() -> Result<()> {
     cli.config.logging.clone().unwrap_or_default().init();
 
     let config_path = cli.config_path();
-    // Get config file
-    let config = if let Ok(f) = std::fs::read_to_string(&config_path) {
-        // parse toml
-        match toml::from_str::<Config>(&f) {
-            Ok(config) => config.merge(&mut cli.config),
-            Err(err) => {
-                // This is a user error print the hopefully helpful error
-                // message without backtrace and exit.
-                exit_err(
-                    format!("Error in configuration file at {}", config_path.display()),
-                    err,
-                );
-            },
-        }
-    } else {
-        // If there is no config file return only config parsed from clap
-        Config::from(&mut cli.config)
-    };
+    let config = Config::from_file(&config_path, &mut cli.config).unwrap_or_else(|err| {
+        // This is a user error print the hopefully helpful error
+        // message without backtrace and exit.
+        exit(format!("{err:#}"))
+    });
+
+    // Read-only commands return before the simulation below, don't let them silently ignore the
+    // flag.
+    if config.simulate && !config.commands.can_simulate() {
+        exit("--simulate only applies to commands that send a transaction")
+    }
 
     // Run the init command first because config values required by other
     // commands are not present.
@@ -186,6 +190,25 @@ pub async fn main() -> Result<()> {
             println!("Arch: {}", System::cpu_arch());
             return Ok(());
         },
+        Commands::DeriveAddress {
+            mnemonic,
+            account_index,
+            count,
+            json,
+        } => {
+            let Some(mnemonic) = mnemonic.or(config.signer.mnemonic) else {
+                exit("No mnemonic given, use --mnemonic or MNEMONIC");
+            };
+            let accounts = derive_accounts(&mnemonic, account_index, count.unwrap_or(1))?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&accounts)?);
+            } else {
+                for account in accounts {
+                    println!("{}: {}", account.account_index, account.address);
+                }
+            }
+            return Ok(());
+        },
         _ => {}, // Other commands handled after shared setup.
     }
 
@@ -194,24 +217,151 @@ pub async fn main() -> Result<()> {
 
     // Commands that don't need a signer
     match config.commands {
+        Commands::Validate => {
+            let issues = config.validate();
+            if issues.is_empty() {
+                println!("Config is valid");
+                return Ok(());
+            }
+            for issue in &issues {
+                println!("{issue}");
+            }
+            exit(format!("Found {} issue(s) in the config", issues.len()));
+        },
         Commands::StakeTable {
             l1_block_number,
+            epoch,
             compact,
+            redact_keys,
+            state_cache,
+            sort_by,
+            offset,
+            limit,
+            show_escrow,
         } => {
             let provider = ProviderBuilder::new().on_http(config.rpc_url.clone());
-            let query_block = l1_block_number.unwrap_or(BlockId::latest());
+            let query_block = match epoch {
+                Some(epoch) => {
+                    let l1_blocks =
+                        epoch_l1_block_range(&provider, config.stake_table_address, epoch).await?;
+                    tracing::info!(
+                        "Epoch {epoch} was finalized in L1 blocks {}..={}",
+                        l1_blocks.start(),
+                        l1_blocks.end()
+                    );
+                    BlockId::number(*l1_blocks.start())
+                },
+                None => l1_block_number.unwrap_or(BlockId::latest()),
+            };
             let l1_block = provider.get_block(query_block).await?.unwrap_or_else(|| {
                 exit_err("Failed to get block {query_block}", "Block not found");
             });
             let l1_block_resolved = l1_block.header.number;
             tracing::info!("Getting stake table info at block {l1_block_resolved}");
-            let stake_table = stake_table_info(
+            let stake_table = match &state_cache {
+                Some(path) if path.exists() => {
+                    update_stake_table_info(
+                        config.rpc_url.clone(),
+                        config.stake_table_address,
+                        StakeTableCache::load(path)?,
+                        l1_block_resolved,
+                    )
+                    .await?
+                },
+                _ => {
+                    stake_table_registry(
+                        config.rpc_url.clone(),
+                        config.stake_table_address,
+                        l1_block_resolved,
+                    )
+                    .await?
+                },
+            };
+            if let Some(path) = state_cache {
+                StakeTableCache {
+                    l1_block: l1_block_resolved,
+                    registry: stake_table.clone(),
+                }
+                .save(&path)?;
+            }
+            let stake_table = stake_table.validators.into_values().collect();
+            display_stake_table(
+                page_stake_table(stake_table, sort_by, offset, limit),
+                compact,
+                redact_keys,
+            )?;
+            if show_escrow {
+                let escrowed = total_escrowed_stake(
+                    &provider,
+                    config.stake_table_address,
+                    l1_block_resolved,
+                    L1ClientOptions::default().l1_events_max_block_range,
+                )
+                .await?;
+                tracing::info!("Stake in exit escrow: {} ESP", format_ether(escrowed));
+            }
+            return Ok(());
+        },
+        Commands::DecodeEvent { tx_hash, json } => {
+            let provider = ProviderBuilder::new().on_http(config.rpc_url.clone());
+            let events =
+                decode_stake_table_events(&provider, config.stake_table_address, tx_hash).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&events)?);
+            } else if events.is_empty() {
+                println!("No stake table events in transaction {tx_hash}");
+            } else {
+                for event in events {
+                    println!("{event}");
+                }
+            }
+            return Ok(());
+        },
+        Commands::VerifyGenesisStake {
+            l1_block_number,
+            stake_table_capacity,
+        } => {
+            let provider = ProviderBuilder::new().on_http(config.rpc_url.clone());
+            let l1_block_number = match l1_block_number {
+                Some(number) => number,
+                None => provider.get_block_number().await?,
+            };
+            let comparison = verify_genesis_stake(
+                &provider,
                 config.rpc_url.clone(),
                 config.stake_table_address,
-                l1_block_resolved,
+                l1_block_number,
+                stake_table_capacity,
             )
             .await?;
-            display_stake_table(stake_table, compact)?;
+            for field in &comparison {
+                println!("{field}");
+            }
+            if !comparison.iter().all(|field| field.matches()) {
+                exit(format!(
+                    "Genesis stake table state does not match the stake table at L1 block \
+                     {l1_block_number}"
+                ));
+            }
+            return Ok(());
+        },
+        Commands::CompareParams {
+            epoch_height,
+            epoch_start_block,
+        } => {
+            let provider = ProviderBuilder::new().on_http(config.rpc_url.clone());
+            let local = EpochConfig {
+                epoch_height,
+                epoch_start_block,
+            };
+            let on_chain = fetch_epoch_config(&provider, config.stake_table_address).await?;
+            let comparison = compare_epoch_configs(&local, &on_chain);
+            for field in &comparison {
+                println!("{field}");
+            }
+            if !comparison.iter().all(|field| field.matches()) {
+                exit("Configured epoch parameters do not match the light client");
+            }
             return Ok(());
         },
         _ => {}, // Other commands handled below.
@@ -243,7 +393,26 @@ pub async fn main() -> Result<()> {
 
     let token_addr = config.token_address;
     let token = EspToken::new(config.token_address, &provider);
+    let stake_table = StakeTable::new(stake_table_addr, &provider);
+
+    if config.simulate {
+        tracing::info!("Simulating the transaction from {account}");
+        if let Err(err) = simulate(
+            &provider,
+            stake_table_addr,
+            token_addr,
+            account,
+            &config.commands,
+        )
+        .await
+        {
+            exit_err("Simulation failed", err);
+        }
+        tracing::info!("Simulation succeeded, no transaction was sent");
+        return Ok(());
+    }
 
+    let retry = config.retry_config();
     let result = match config.commands {
         Commands::RegisterValidator {
             consensus_private_key,
@@ -251,79 +420,172 @@ pub async fn main() -> Result<()> {
             commission,
         } => {
             tracing::info!("Registering validator {account} with commission {commission}");
-            register_validator(
-                &provider,
-                stake_table_addr,
-                commission,
+            let (bls_vk, schnorr_vk, sig) = consensus_keys_payload(
                 account,
-                (consensus_private_key).into(),
-                (&state_private_key).into(),
+                &consensus_private_key.into(),
+                &(&state_private_key).into(),
+            );
+            let tx = stake_table
+                .registerValidator(bls_vk, schnorr_vk, sig.into(), commission.to_evm())
+                .into_transaction_request();
+            send_with_retries::<StakeTableErrors, _, _>(retry, &provider, account, tx).await
+        },
+        Commands::BatchRegister { path } => {
+            // Validate the whole file before sending any transaction.
+            let registrations = read_batch_file(&path)?;
+            tracing::info!("Registering {} validators", registrations.len());
+            let summary = batch_register(
+                config.rpc_url.clone(),
+                stake_table_addr,
+                TryInto::<ValidSignerConfig>::try_into(config.signer.clone())?,
+                registrations,
             )
-            .await
+            .await?;
+            if summary.failed > 0 {
+                exit(format!(
+                    "{} of the validator registrations failed",
+                    summary.failed
+                ));
+            }
+            return Ok(());
         },
         Commands::UpdateConsensusKeys {
             consensus_private_key,
             state_private_key,
         } => {
             tracing::info!("Updating validator {account} with new keys");
-            update_consensus_keys(
-                &provider,
-                stake_table_addr,
+            let (bls_vk, schnorr_vk, sig) = consensus_keys_payload(
                 account,
-                (consensus_private_key).into(),
-                (&state_private_key).into(),
-            )
-            .await
+                &consensus_private_key.into(),
+                &(&state_private_key).into(),
+            );
+            let tx = stake_table
+                .updateConsensusKeys(bls_vk, schnorr_vk, sig.into())
+                .into_transaction_request();
+            send_with_retries::<StakeTableErrors, _, _>(retry, &provider, account, tx).await
         },
         Commands::DeregisterValidator {} => {
             tracing::info!("Deregistering validator {account}");
-            deregister_validator(&provider, stake_table_addr).await
+            let tx = stake_table.deregisterValidator().into_transaction_request();
+            send_with_retries::<StakeTableErrors, _, _>(retry, &provider, account, tx).await
         },
         Commands::Approve { amount } => {
             tracing::info!(
                 "Approving stake table {} to spend {amount}",
                 config.stake_table_address
             );
-            approve(&provider, token_addr, stake_table_addr, amount).await
+            let tx = token
+                .approve(stake_table_addr, amount)
+                .into_transaction_request();
+            send_with_retries::<EspTokenErrors, _, _>(retry, &provider, account, tx).await
         },
         Commands::Delegate {
             validator_address,
             amount,
         } => {
             tracing::info!("Delegating {amount} to {validator_address}");
-            delegate(&provider, stake_table_addr, validator_address, amount).await
+            let tx = stake_table
+                .delegate(validator_address, amount)
+                .into_transaction_request();
+            send_with_retries::<StakeTableErrors, _, _>(retry, &provider, account, tx).await
         },
         Commands::Undelegate {
             validator_address,
             amount,
         } => {
             tracing::info!("Undelegating {amount} from {validator_address}");
-            undelegate(&provider, stake_table_addr, validator_address, amount).await
+            let tx = stake_table
+                .undelegate(validator_address, amount)
+                .into_transaction_request();
+            send_with_retries::<StakeTableErrors, _, _>(retry, &provider, account, tx).await
         },
-        Commands::ClaimWithdrawal { validator_address } => {
+        Commands::ClaimWithdrawal {
+            validator_address,
+            check_only,
+            force,
+        } => {
+            let status =
+                withdrawal_status(&provider, stake_table_addr, validator_address, account).await?;
+            if check_only {
+                println!("Withdrawal from {validator_address}: {status}");
+                return Ok(());
+            }
+            if !status.is_claimable() {
+                if !force {
+                    exit(format!(
+                        "Withdrawal from {validator_address} is not claimable: {status}. Pass \
+                         --force to send the transaction anyway"
+                    ));
+                }
+                tracing::warn!("Withdrawal from {validator_address} is not claimable: {status}");
+            }
             tracing::info!("Claiming withdrawal for {validator_address}");
-            claim_withdrawal(&provider, stake_table_addr, validator_address).await
+            let tx = stake_table
+                .claimWithdrawal(validator_address)
+                .into_transaction_request();
+            send_with_retries::<StakeTableErrors, _, _>(retry, &provider, account, tx).await
         },
         Commands::ClaimValidatorExit { validator_address } => {
             tracing::info!("Claiming validator exit for {validator_address}");
-            claim_validator_exit(&provider, stake_table_addr, validator_address).await
+            let tx = stake_table
+                .claimValidatorExit(validator_address)
+                .into_transaction_request();
+            send_with_retries::<StakeTableErrors, _, _>(retry, &provider, account, tx).await
         },
         Commands::StakeForDemo {
             num_validators,
             delegation_config,
+            #[cfg(feature = "testing")]
+            smoke_test,
         } => {
             tracing::info!(
                 "Staking for demo with {num_validators} validators and config {delegation_config}"
             );
-            stake_for_demo(&config, num_validators, delegation_config)
+            let summary = stake_for_demo(&config, num_validators, delegation_config)
                 .await
                 .unwrap();
+            println!("{summary}");
+            #[cfg(feature = "testing")]
+            if smoke_test {
+                staking_cli::demo::smoke_test_demo_stake_table(&config).await?;
+            }
             return Ok(());
         },
-        Commands::TokenBalance { address } => {
+        Commands::TokenBalance {
+            address,
+            watch,
+            until,
+            interval,
+            timeout,
+            json,
+        } => {
             let address = address.unwrap_or(account);
-            let balance = format_ether(token.balanceOf(address).call().await?._0);
-            tracing::info!("Token balance for {address}: {balance} ESP");
+            let print = |update: &BalanceUpdate| {
+                if json {
+                    println!("{}", serde_json::to_string(update).unwrap());
+                } else {
+                    println!(
+                        "Token balance for {address}: {} ESP",
+                        format_ether(update.balance)
+                    );
+                }
+            };
+            if watch {
+                if let Err(err) = watch_token_balance(
+                    &provider, token_addr, address, until, interval, timeout, print,
+                )
+                .await
+                {
+                    exit_err("Failed to watch balance", err);
+                }
+            } else {
+                let balance = token.balanceOf(address).call().await?._0;
+                if json {
+                    print(&BalanceUpdate { address, balance });
+                } else {
+                    tracing::info!("Token balance for {address}: {} ESP", format_ether(balance));
+                }
+            }
             return Ok(());
         },
         Commands::TokenAllowance { owner } => {
@@ -341,13 +603,8 @@ pub async fn main() -> Result<()> {
         Commands::Transfer { amount, to } => {
             let amount_esp = format_ether(amount);
             tracing::info!("Transferring {amount_esp} ESP to {to}");
-            Ok(token
-                .transfer(to, amount)
-                .send()
-                .await
-                .maybe_decode_revert::<EspTokenErrors>()?
-                .get_receipt()
-                .await?)
+            let tx = token.transfer(to, amount).into_transaction_request();
+            send_with_retries::<EspTokenErrors, _, _>(retry, &provider, account, tx).await
         },
         _ => unreachable!(),
     };
@@ -359,3 +616,15 @@ pub async fn main() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Args::command().debug_assert();
+    }
+}
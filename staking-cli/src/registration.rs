@@ -15,7 +15,7 @@ use jf_signature::constants::CS_ID_BLS_BN254;
 
 use crate::{parse::Commission, BLSKeyPair, StateVerKey};
 
-fn prepare_bls_payload(
+pub(crate) fn prepare_bls_payload(
     bls_key_pair: &BLSKeyPair,
     validator_address: Address,
 ) -> (G2PointSol, G1PointSol) {
@@ -28,6 +28,17 @@ fn prepare_bls_payload(
     (bls_vk_sol, sig_sol)
 }
 
+/// The key arguments of `registerValidator` and `updateConsensusKeys`: the BLS key, the Schnorr key
+/// and the BLS signature proving possession of the BLS key.
+pub fn consensus_keys_payload(
+    validator_address: Address,
+    bls_key_pair: &BLSKeyPair,
+    schnorr_vk: &StateVerKey,
+) -> (G2PointSol, EdOnBN254PointSol, G1PointSol) {
+    let (bls_vk_sol, sig_sol) = prepare_bls_payload(bls_key_pair, validator_address);
+    (bls_vk_sol, schnorr_vk.to_affine().into(), sig_sol)
+}
+
 pub async fn register_validator(
     provider: impl Provider,
     stake_table_addr: Address,
@@ -37,8 +48,8 @@ pub async fn register_validator(
     schnorr_vk: StateVerKey,
 ) -> Result<TransactionReceipt> {
     let stake_table = StakeTable::new(stake_table_addr, &provider);
-    let (bls_vk_sol, sig_sol) = prepare_bls_payload(&bls_key_pair, validator_address);
-    let schnorr_vk_sol: EdOnBN254PointSol = schnorr_vk.to_affine().into();
+    let (bls_vk_sol, schnorr_vk_sol, sig_sol) =
+        consensus_keys_payload(validator_address, &bls_key_pair, &schnorr_vk);
     Ok(stake_table
         .registerValidator(
             bls_vk_sol,
@@ -61,8 +72,8 @@ pub async fn update_consensus_keys(
     schnorr_vk: StateVerKey,
 ) -> Result<TransactionReceipt> {
     let stake_table = StakeTable::new(stake_table_addr, &provider);
-    let (bls_vk_sol, sig_sol) = prepare_bls_payload(&bls_key_pair, validator_address);
-    let schnorr_vk_sol: EdOnBN254PointSol = schnorr_vk.to_affine().into();
+    let (bls_vk_sol, schnorr_vk_sol, sig_sol) =
+        consensus_keys_payload(validator_address, &bls_key_pair, &schnorr_vk);
     Ok(stake_table
         .updateConsensusKeys(bls_vk_sol, schnorr_vk_sol, sig_sol.into())
         .send()
@@ -0,0 +1,285 @@
+use std::{fmt::Debug, future::Future, time::Duration};
+
+use alloy::{
+    contract::Error as ContractError,
+    eips::eip2718::Encodable2718 as _,
+    network::Ethereum,
+    primitives::{Address, TxHash},
+    providers::{
+        fillers::{FillProvider, TxFiller},
+        PendingTransactionBuilder, Provider, SendableTx,
+    },
+    rpc::types::{TransactionReceipt, TransactionRequest},
+    sol_types::SolInterface,
+    transports::RpcError,
+};
+use anyhow::{bail, Result};
+use hotshot_contract_adapter::evm::DecodeRevert as _;
+use sequencer_utils::backoff::{retry_with_backoff, Backoff};
+
+/// How often and how patiently to retry sending a transaction.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryConfig {
+    pub max_retries: u32,
+    pub interval: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Whether `err` is a failure to reach the RPC, which may succeed when retried.
+///
+/// Error responses of the RPC, including reverts, are not transport errors.
+pub fn is_transport_error(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<ContractError>(),
+        Some(ContractError::TransportError(RpcError::Transport(_)))
+    )
+}
+
+impl Backoff for RetryConfig {
+    fn should_retry(&self, retries: usize) -> bool {
+        retries < self.max_retries as usize
+    }
+
+    fn first_delay(&self) -> Duration {
+        self.interval
+    }
+
+    fn next_delay(&self, delay: Duration) -> Duration {
+        delay * 2
+    }
+}
+
+/// Run `send` until it succeeds or fails with anything but a transport error, at most
+/// `max_retries + 1` times.
+///
+/// `send` must be safe to repeat after a transport error, so it must not broadcast a transaction:
+/// use [`send_with_retries`] for that.
+pub async fn with_retries<T, F, Fut>(config: RetryConfig, send: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    retry_with_backoff(&config, is_transport_error, send).await
+}
+
+/// Send `tx` from `from`, retrying transport errors without ever executing it twice.
+///
+/// Retrying a whole send after a transport error is not safe: the transaction may have been
+/// broadcast even though the response was lost, and a retry would then send a second transaction
+/// with the next nonce. Instead, the transaction is filled and signed exactly once, with a fixed
+/// nonce, and only the broadcast of that signed transaction is retried. Once the node knows the
+/// transaction we wait for the receipt of its hash.
+///
+/// Reverts detected while estimating gas are decoded as `E`.
+pub async fn send_with_retries<E, F, P>(
+    config: RetryConfig,
+    provider: &FillProvider<F, P, Ethereum>,
+    from: Address,
+    tx: TransactionRequest,
+) -> Result<TransactionReceipt>
+where
+    E: SolInterface + Debug,
+    F: TxFiller<Ethereum>,
+    P: Provider<Ethereum>,
+{
+    // Nothing is broadcast before the transaction is signed, so all of this can be retried. Fix
+    // the nonce up front so that a failed attempt does not advance the nonce cached by the filler.
+    let nonce = with_retries(config, || async {
+        Ok(provider
+            .get_transaction_count(from)
+            .pending()
+            .await
+            .map_err(ContractError::from)?)
+    })
+    .await?;
+    let tx = tx.from(from).nonce(nonce);
+    let SendableTx::Envelope(envelope) = with_retries(config, || async {
+        provider
+            .fill(tx.clone())
+            .await
+            .map_err(ContractError::from)
+            .maybe_decode_revert::<E>()
+    })
+    .await?
+    else {
+        bail!("provider has no wallet to sign the transaction");
+    };
+
+    let hash = *envelope.tx_hash();
+    let raw = envelope.encoded_2718();
+    tracing::info!(%hash, nonce, "sending transaction");
+    with_retries(config, || broadcast(provider, &raw, hash)).await?;
+
+    Ok(
+        PendingTransactionBuilder::new(provider.root().clone(), hash)
+            .get_receipt()
+            .await?,
+    )
+}
+
+/// Broadcast the signed transaction `raw` with hash `hash`.
+///
+/// Succeeds if the node accepts the transaction or if it already knows it, e.g. because an
+/// earlier attempt reached the node but its response was lost.
+async fn broadcast(provider: &impl Provider, raw: &[u8], hash: TxHash) -> Result<()> {
+    let err = match provider.send_raw_transaction(raw).await {
+        Ok(_) => return Ok(()),
+        Err(err) => err,
+    };
+    if let Ok(Some(_)) = provider.get_transaction_by_hash(hash).await {
+        tracing::info!(%hash, "transaction was already broadcast");
+        return Ok(());
+    }
+    Err(ContractError::from(err).into())
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use alloy::{
+        network::TransactionBuilder as _, primitives::U256, rpc::json_rpc::ErrorPayload,
+        transports::TransportErrorKind,
+    };
+    use hotshot_contract_adapter::{
+        evm::DecodeRevert as _,
+        sol_types::{
+            EspToken::{self, EspTokenErrors},
+            StakeTable::StakeTableErrors,
+        },
+    };
+
+    use super::*;
+    use crate::deploy::TestSystem;
+
+    /// A mock of sending a transaction that fails with `err` for the first `failures` attempts and
+    /// returns the number of the successful attempt.
+    async fn mock_send(
+        attempts: &AtomicU32,
+        failures: u32,
+        err: impl Fn() -> ContractError,
+    ) -> Result<u32> {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        let result = if attempt <= failures {
+            Err(err())
+        } else {
+            Ok(attempt)
+        };
+        result.maybe_decode_revert::<StakeTableErrors>()
+    }
+
+    fn config(max_retries: u32) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            interval: Duration::from_millis(1),
+        }
+    }
+
+    fn transport_error() -> ContractError {
+        ContractError::TransportError(TransportErrorKind::custom_str("connection reset"))
+    }
+
+    fn revert_error() -> ContractError {
+        ContractError::TransportError(RpcError::ErrorResp(ErrorPayload {
+            code: 3,
+            message: "execution reverted".into(),
+            data: None,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_retry_transport_errors() {
+        let attempts = AtomicU32::new(0);
+        let result = with_retries(config(3), || mock_send(&attempts, 2, transport_error)).await;
+        assert_eq!(result.unwrap(), 3);
+
+        // Give up once the retries are exhausted.
+        let attempts = AtomicU32::new(0);
+        let err = with_retries(config(1), || mock_send(&attempts, 2, transport_error))
+            .await
+            .unwrap_err();
+        assert!(is_transport_error(&err));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_on_revert() {
+        let attempts = AtomicU32::new(0);
+        let err = with_retries(config(3), || mock_send(&attempts, 2, revert_error))
+            .await
+            .unwrap_err();
+        assert!(!is_transport_error(&err));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retries() -> Result<()> {
+        let system = TestSystem::deploy().await?;
+        let token = EspToken::new(system.token, &system.provider);
+        let receiver = Address::random();
+        let amount = U256::from(123);
+
+        let tx = token.transfer(receiver, amount).into_transaction_request();
+        let receipt = send_with_retries::<EspTokenErrors, _, _>(
+            config(3),
+            &system.provider,
+            system.deployer_address,
+            tx,
+        )
+        .await?;
+        assert!(receipt.status());
+        assert_eq!(system.balance(receiver).await?, amount);
+
+        // Reverts are decoded and not retried.
+        let tx = token
+            .transfer(receiver, U256::MAX)
+            .into_transaction_request();
+        let err = send_with_retries::<EspTokenErrors, _, _>(
+            config(3),
+            &system.provider,
+            system.deployer_address,
+            tx,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("ERC20InsufficientBalance"),
+            "{err:#}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rebroadcast_executes_once() -> Result<()> {
+        let system = TestSystem::deploy().await?;
+        let receiver = Address::random();
+        let amount = U256::from(123);
+        let tx = TransactionRequest::default()
+            .with_to(receiver)
+            .with_value(amount);
+        let SendableTx::Envelope(envelope) = system.provider.fill(tx).await? else {
+            panic!("transaction was not signed");
+        };
+        let hash = *envelope.tx_hash();
+        let raw = envelope.encoded_2718();
+
+        // The second broadcast stands in for a retry after the response to the first was lost.
+        broadcast(&system.provider, &raw, hash).await?;
+        broadcast(&system.provider, &raw, hash).await?;
+
+        let receipt = PendingTransactionBuilder::new(system.provider.root().clone(), hash)
+            .get_receipt()
+            .await?;
+        assert!(receipt.status());
+        assert_eq!(system.provider.get_balance(receiver).await?, amount);
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+use alloy::{primitives::Address, providers::Provider};
+use anyhow::{bail, Result};
+use hotshot_contract_adapter::{
+    evm::DecodeRevert as _,
+    sol_types::{
+        EdOnBN254PointSol,
+        EspToken::{self, EspTokenErrors},
+        StakeTable::{self, StakeTableErrors},
+    },
+};
+
+use crate::{registration::prepare_bls_payload, BLSKeyPair, Commands, StateVerKey};
+
+impl Commands {
+    /// Whether this command sends a single transaction that [`simulate`] can dry-run.
+    pub fn can_simulate(&self) -> bool {
+        matches!(
+            self,
+            Commands::RegisterValidator { .. }
+                | Commands::UpdateConsensusKeys { .. }
+                | Commands::DeregisterValidator {}
+                | Commands::Approve { .. }
+                | Commands::Delegate { .. }
+                | Commands::Undelegate { .. }
+                | Commands::ClaimWithdrawal { .. }
+                | Commands::ClaimValidatorExit { .. }
+                | Commands::Transfer { .. }
+        )
+    }
+}
+
+/// Dry-run a write command with `eth_call` from `account`, without sending a transaction.
+///
+/// If the call would revert the error contains the decoded revert reason.
+pub async fn simulate(
+    provider: impl Provider,
+    stake_table_addr: Address,
+    token_addr: Address,
+    account: Address,
+    command: &Commands,
+) -> Result<()> {
+    let stake_table = StakeTable::new(stake_table_addr, &provider);
+    let token = EspToken::new(token_addr, &provider);
+    match command {
+        Commands::RegisterValidator {
+            consensus_private_key,
+            state_private_key,
+            commission,
+        } => {
+            let bls_key_pair: BLSKeyPair = consensus_private_key.clone().into();
+            let (bls_vk_sol, sig_sol) = prepare_bls_payload(&bls_key_pair, account);
+            let schnorr_vk: StateVerKey = state_private_key.into();
+            let schnorr_vk_sol: EdOnBN254PointSol = schnorr_vk.to_affine().into();
+            stake_table
+                .registerValidator(
+                    bls_vk_sol,
+                    schnorr_vk_sol,
+                    sig_sol.into(),
+                    commission.to_evm(),
+                )
+                .from(account)
+                .call()
+                .await
+                .maybe_decode_revert::<StakeTableErrors>()?;
+        },
+        Commands::UpdateConsensusKeys {
+            consensus_private_key,
+            state_private_key,
+        } => {
+            let bls_key_pair: BLSKeyPair = consensus_private_key.clone().into();
+            let (bls_vk_sol, sig_sol) = prepare_bls_payload(&bls_key_pair, account);
+            let schnorr_vk: StateVerKey = state_private_key.into();
+            let schnorr_vk_sol: EdOnBN254PointSol = schnorr_vk.to_affine().into();
+            stake_table
+                .updateConsensusKeys(bls_vk_sol, schnorr_vk_sol, sig_sol.into())
+                .from(account)
+                .call()
+                .await
+                .maybe_decode_revert::<StakeTableErrors>()?;
+        },
+        Commands::DeregisterValidator {} => {
+            stake_table
+                .deregisterValidator()
+                .from(account)
+                .call()
+                .await
+                .maybe_decode_revert::<StakeTableErrors>()?;
+        },
+        Commands::Approve { amount } => {
+            token
+                .approve(stake_table_addr, *amount)
+                .from(account)
+                .call()
+                .await
+                .maybe_decode_revert::<EspTokenErrors>()?;
+        },
+        Commands::Delegate {
+            validator_address,
+            amount,
+        } => {
+            stake_table
+                .delegate(*validator_address, *amount)
+                .from(account)
+                .call()
+                .await
+                .maybe_decode_revert::<StakeTableErrors>()?;
+        },
+        Commands::Undelegate {
+            validator_address,
+            amount,
+        } => {
+            stake_table
+                .undelegate(*validator_address, *amount)
+                .from(account)
+                .call()
+                .await
+                .maybe_decode_revert::<StakeTableErrors>()?;
+        },
+        Commands::ClaimWithdrawal {
+            validator_address, ..
+        } => {
+            stake_table
+                .claimWithdrawal(*validator_address)
+                .from(account)
+                .call()
+                .await
+                .maybe_decode_revert::<StakeTableErrors>()?;
+        },
+        Commands::ClaimValidatorExit { validator_address } => {
+            stake_table
+                .claimValidatorExit(*validator_address)
+                .from(account)
+                .call()
+                .await
+                .maybe_decode_revert::<StakeTableErrors>()?;
+        },
+        Commands::Transfer { to, amount } => {
+            token
+                .transfer(*to, *amount)
+                .from(account)
+                .call()
+                .await
+                .maybe_decode_revert::<EspTokenErrors>()?;
+        },
+        _ => bail!("this command does not send a single transaction and can not be simulated"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::deploy::TestSystem;
+
+    #[tokio::test]
+    async fn test_simulate_register_validator() -> Result<()> {
+        let system = TestSystem::deploy().await?;
+        let command = Commands::RegisterValidator {
+            consensus_private_key: system.bls_key_pair.sign_key_ref().clone(),
+            state_private_key: system.state_key_pair.sign_key(),
+            commission: system.commission,
+        };
+        assert!(command.can_simulate());
+        assert!(!Commands::Account.can_simulate());
+        simulate(
+            &system.provider,
+            system.stake_table,
+            system.token,
+            system.deployer_address,
+            &command,
+        )
+        .await?;
+
+        // Simulating does not register the validator.
+        system.register_validator().await?;
+
+        let err = simulate(
+            &system.provider,
+            system.stake_table,
+            system.token,
+            system.deployer_address,
+            &command,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("ValidatorAlreadyRegistered"));
+        Ok(())
+    }
+}
@@ -11,6 +11,7 @@ use anyhow::Result;
 use rand::{rngs::StdRng, SeedableRng as _};
 use sequencer_utils::test_utils::setup_test;
 use staking_cli::{demo::DelegationConfig, deploy::Signer, *};
+use url::Url;
 
 use crate::deploy::TestSystem;
 
@@ -159,6 +160,86 @@ fn test_cli_create_file_ledger() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_cli_config_precedence() -> Result<()> {
+    setup_test();
+    let tmpdir = tempfile::tempdir()?;
+    let config_path = tmpdir.path().join("config.toml");
+    let mut config: Config = toml::from_str(include_str!("../config.decaf.toml"))?;
+    config.rpc_url = "http://file.example".parse()?;
+    std::fs::write(&config_path, toml::to_string(&config)?)?;
+
+    let rpc_url = |env: Option<&str>, flag: Option<&str>| -> Result<Url> {
+        let mut cmd = base_cmd();
+        cmd.env_remove("L1_PROVIDER").arg("-c").arg(&config_path);
+        if let Some(url) = env {
+            cmd.env("L1_PROVIDER", url);
+        }
+        if let Some(url) = flag {
+            cmd.args(["--rpc-url", url]);
+        }
+        let output = cmd.arg("config").output()?;
+        output.assert_success();
+        // skip the line with the config file path
+        let (_, toml) = output.utf8().split_once('\n').unwrap();
+        Ok(toml::from_str::<Config>(toml)?.rpc_url)
+    };
+
+    assert_eq!(rpc_url(None, None)?, config.rpc_url);
+    assert_eq!(
+        rpc_url(Some("http://env.example"), None)?,
+        "http://env.example".parse()?
+    );
+    assert_eq!(
+        rpc_url(Some("http://env.example"), Some("http://cli.example"))?,
+        "http://cli.example".parse()?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_cli_derive_address() -> Result<()> {
+    setup_test();
+    // anvil's well-known accounts 1 and 2
+    let expected = vec![
+        DerivedAccount {
+            account_index: 1,
+            address: "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse()?,
+        },
+        DerivedAccount {
+            account_index: 2,
+            address: "0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC".parse()?,
+        },
+    ];
+
+    let out = base_cmd()
+        .arg("derive-address")
+        .args(["--mnemonic", DEV_MNEMONIC])
+        .args(["--account-index", "1"])
+        .args(["--count", "2"])
+        .output()?
+        .assert_success()
+        .utf8();
+    assert_eq!(
+        out,
+        format!("1: {}\n2: {}\n", expected[0].address, expected[1].address)
+    );
+
+    let out = base_cmd()
+        .arg("derive-address")
+        .args(["--mnemonic", DEV_MNEMONIC])
+        .args(["--account-index", "1"])
+        .args(["--count", "2"])
+        .arg("--json")
+        .output()?
+        .assert_success()
+        .utf8();
+    let accounts: Vec<DerivedAccount> = serde_json::from_str(&out)?;
+    assert_eq!(accounts, expected);
+
+    Ok(())
+}
+
 // TODO: ideally we would test that the decoding works for all the commands
 #[tokio::test]
 async fn test_cli_contract_revert() -> Result<()> {
@@ -248,6 +329,40 @@ async fn test_cli_delegate() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_cli_decode_event() -> Result<()> {
+    setup_test();
+    let system = TestSystem::deploy().await?;
+    let receipt = registration::register_validator(
+        &system.provider,
+        system.stake_table,
+        system.commission,
+        system.deployer_address,
+        system.bls_key_pair.clone(),
+        system.state_key_pair.ver_key(),
+    )
+    .await?;
+
+    let mut cmd = base_cmd();
+    system.args(&mut cmd, Signer::Mnemonic);
+    let out = cmd
+        .arg("decode-event")
+        .arg("--tx-hash")
+        .arg(receipt.transaction_hash.to_string())
+        .arg("--json")
+        .output()?
+        .assert_success()
+        .utf8();
+    let events: serde_json::Value = serde_json::from_str(&out)?;
+    assert_eq!(events[0]["event"], "ValidatorRegistered");
+    assert_eq!(
+        events[0]["account"].as_str().unwrap().parse::<Address>()?,
+        system.deployer_address
+    );
+    assert_eq!(events[0]["commission"], system.commission.to_evm());
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_cli_deregister_validator() -> Result<()> {
     setup_test();
@@ -300,6 +415,37 @@ async fn test_cli_claim_withdrawal() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_cli_claim_withdrawal_not_claimable() -> Result<()> {
+    setup_test();
+    let system = TestSystem::deploy().await?;
+    let amount = U256::from(123);
+    system.register_validator().await?;
+    system.delegate(amount).await?;
+    system.undelegate(amount).await?;
+
+    let mut cmd = base_cmd();
+    system.args(&mut cmd, Signer::Mnemonic);
+    let output = cmd
+        .arg("claim-withdrawal")
+        .arg("--validator-address")
+        .arg(system.deployer_address.to_string())
+        .arg("--check-only")
+        .output()?;
+    output.assert_success();
+    assert!(String::from_utf8(output.stdout)?.contains("can be withdrawn in"));
+
+    // Without --check-only the command refuses to send the transaction.
+    let mut cmd = base_cmd();
+    system.args(&mut cmd, Signer::Mnemonic);
+    cmd.arg("claim-withdrawal")
+        .arg("--validator-address")
+        .arg(system.deployer_address.to_string())
+        .output()?
+        .assert_failure();
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_cli_claim_validator_exit() -> Result<()> {
     setup_test();
@@ -346,6 +492,20 @@ async fn test_cli_stake_for_demo_three_validators() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_cli_stake_for_demo_smoke_test() -> Result<()> {
+    setup_test();
+    let system = TestSystem::deploy().await?;
+
+    let mut cmd = base_cmd();
+    system.args(&mut cmd, Signer::Mnemonic);
+    cmd.arg("stake-for-demo")
+        .arg("--smoke-test")
+        .output()?
+        .assert_success();
+    Ok(())
+}
+
 async fn stake_for_demo_delegation_config_helper(config: DelegationConfig) -> Result<()> {
     setup_test();
     let system = TestSystem::deploy().await?;
@@ -4,22 +4,54 @@ use async_trait::async_trait;
 use committable::Committable;
 use hotshot_query_service::availability::QueryablePayload;
 use hotshot_types::{
-    data::ViewNumber,
-    traits::{BlockPayload, EncodeBytes},
+    data::{vid_commitment, VidCommitment, ViewNumber},
+    traits::{
+        block_contents::GENESIS_VID_NUM_STORAGE_NODES, node_implementation::Versions, BlockPayload,
+        EncodeBytes,
+    },
     utils::BuilderCommitment,
     vid::advz::{ADVZCommon, ADVZScheme},
 };
 use jf_vid::VidScheme;
 use sha2::Digest;
 use thiserror::Error;
+use vbs::version::StaticVersionType;
 
 use crate::{
     v0::impls::{NodeState, ValidatedState},
     v0_1::ChainConfig,
-    Index, Iter, NamespaceId, NsIndex, NsPayload, NsPayloadBuilder, NsPayloadRange, NsTable,
+    Index, Iter, Leaf2, NamespaceId, NsIndex, NsPayload, NsPayloadBuilder, NsPayloadRange, NsTable,
     NsTableBuilder, Payload, PayloadByteLen, SeqTypes, Transaction, TxProof,
 };
 
+/// Decode the transactions in namespace `ns` from the payload of a decided leaf.
+///
+/// Returns no transactions if the leaf does not have its payload attached.
+pub fn namespace_transactions(leaf: &Leaf2, ns: NamespaceId) -> Vec<Transaction> {
+    leaf.block_payload()
+        .map(|payload| payload.namespace_transactions(&ns))
+        .unwrap_or_default()
+}
+
+/// Compute the VID commitment of the payload built from `txns`.
+///
+/// The payload and its namespace table are encoded and committed to for
+/// [`GENESIS_VID_NUM_STORAGE_NODES`] storage nodes, using the base version of `V`.
+pub async fn payload_vid_commitment<V: Versions>(
+    txns: &[Transaction],
+    state: &ValidatedState,
+    node_state: &NodeState,
+) -> Result<VidCommitment, BlockBuildingError> {
+    let (payload, ns_table) =
+        Payload::from_transactions(txns.iter().cloned(), state, node_state).await?;
+    Ok(vid_commitment::<V>(
+        &payload.encode(),
+        &ns_table.encode(),
+        GENESIS_VID_NUM_STORAGE_NODES,
+        V::Base::VERSION,
+    ))
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Error, Debug, Eq, PartialEq)]
 pub enum BlockBuildingError {
     #[error("Parent state commitment {0} of block doesn't match current state commitment")]
@@ -49,6 +81,14 @@ impl Payload {
         ns_payload.export_tx(&ns_id, index.tx())
     }
 
+    /// All transactions in namespace `ns_id`, or none if the namespace is not in this payload.
+    pub fn namespace_transactions(&self, ns_id: &NamespaceId) -> Vec<Transaction> {
+        match self.ns_table.find_ns_id(ns_id) {
+            Some(index) => self.ns_payload(&index).export_all_txs(ns_id),
+            None => vec![],
+        }
+    }
+
     // CRATE-VISIBLE HELPERS START HERE
 
     pub(crate) fn read_ns_payload(&self, range: &NsPayloadRange) -> &NsPayload {
@@ -156,6 +196,20 @@ impl BlockPayload<SeqTypes> for Payload {
             }
         };
 
+        // Leave out transactions which validators would reject the block for.
+        let transactions = transactions
+            .into_iter()
+            .filter(
+                |tx| match instance_state.check_namespace_allowed(tx.namespace()) {
+                    Ok(()) => true,
+                    Err(err) => {
+                        tracing::warn!("skip transaction: {err:#}");
+                        false
+                    },
+                },
+            )
+            .collect::<Vec<_>>();
+
         Self::from_transactions_sync(transactions, ChainConfig::from(chain_config))
     }
 
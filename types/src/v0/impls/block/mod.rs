@@ -3,4 +3,5 @@ mod namespace_payload;
 mod test;
 mod uint_bytes;
 
+pub use full_payload::{namespace_transactions, payload_vid_commitment};
 pub use uint_bytes::*;
@@ -3,14 +3,22 @@ use std::collections::BTreeMap;
 
 use hotshot::traits::BlockPayload;
 use hotshot_query_service::availability::QueryablePayload;
-use hotshot_types::{data::VidCommitment, traits::EncodeBytes, vid::advz::advz_scheme};
+use hotshot_types::{
+    data::{vid_commitment, VidCommitment},
+    traits::{
+        block_contents::GENESIS_VID_NUM_STORAGE_NODES, node_implementation::Versions, EncodeBytes,
+    },
+    vid::advz::advz_scheme,
+};
 use jf_vid::VidScheme;
 use rand::RngCore;
 use sequencer_utils::test_utils::setup_test;
+use vbs::version::StaticVersionType;
 
 use crate::{
-    v0_1::ADVZNsProof, v0_99::ChainConfig, BlockSize, NamespaceId, NodeState, Payload, Transaction,
-    TxProof, ValidatedState,
+    namespace_transactions, payload_vid_commitment, v0_1::ADVZNsProof, v0_99::ChainConfig,
+    BlockSize, Leaf2, MockSequencerVersions, NamespaceId, NodeState, Payload, Transaction, TxProof,
+    ValidatedState,
 };
 
 #[tokio::test(flavor = "multi_thread")]
@@ -163,6 +171,60 @@ async fn enforce_max_block_size() {
     assert_eq!(block.len(block.ns_table()), tx_count_expected - 1);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn namespace_transactions_from_leaf() {
+    setup_test();
+    let mut rng = jf_utils::test_rng();
+    let test = ValidTest::from_tx_lengths(vec![vec![5, 8], vec![7], vec![10, 5, 8]], &mut rng);
+    let block =
+        Payload::from_transactions(test.all_txs(), &Default::default(), &Default::default())
+            .await
+            .unwrap()
+            .0;
+
+    let mut leaf =
+        Leaf2::genesis::<MockSequencerVersions>(&ValidatedState::default(), &NodeState::mock())
+            .await;
+    leaf.fill_block_payload_unchecked(block);
+
+    for (ns_id, txs) in &test.nss {
+        assert_eq!(namespace_transactions(&leaf, *ns_id), *txs);
+    }
+
+    // A namespace which is not in the block has no transactions.
+    let missing = (0u32..)
+        .map(NamespaceId::from)
+        .find(|ns_id| !test.nss.contains_key(ns_id))
+        .unwrap();
+    assert!(namespace_transactions(&leaf, missing).is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn payload_vid_commitment_matches_manual_computation() {
+    setup_test();
+    let mut rng = jf_utils::test_rng();
+    let test = ValidTest::from_tx_lengths(vec![vec![5, 8], vec![7]], &mut rng);
+    let state = ValidatedState::default();
+    let node_state = NodeState::mock();
+
+    for txns in [vec![], test.all_txs()] {
+        let (payload, ns_table) = Payload::from_transactions(txns.clone(), &state, &node_state)
+            .await
+            .unwrap();
+        let expected = vid_commitment::<MockSequencerVersions>(
+            &payload.encode(),
+            &ns_table.encode(),
+            GENESIS_VID_NUM_STORAGE_NODES,
+            <MockSequencerVersions as Versions>::Base::VERSION,
+        );
+        let commitment =
+            payload_vid_commitment::<MockSequencerVersions>(&txns, &state, &node_state)
+                .await
+                .unwrap();
+        assert_eq!(commitment, expected);
+    }
+}
+
 // TODO lots of infra here that could be reused in other tests.
 pub struct ValidTest {
     pub nss: BTreeMap<NamespaceId, Vec<Transaction>>,
@@ -156,6 +156,16 @@ impl From<u64> for FeeAmount {
     }
 }
 
+impl TryFrom<FeeAmount> for u128 {
+    type Error = anyhow::Error;
+
+    fn try_from(amt: FeeAmount) -> anyhow::Result<Self> {
+        amt.0
+            .try_into()
+            .with_context(|| format!("fee amount {amt} exceeds u128::MAX"))
+    }
+}
+
 impl From<FeeAmount> for MonetaryValue {
     fn from(value: FeeAmount) -> Self {
         MonetaryValue::eth(value.0.to::<u128>() as i128)
@@ -446,10 +456,30 @@ pub fn retain_accounts(
     Ok(snapshot)
 }
 
+/// Verify a proof of the fee balance of `account` against the fee state root `expected_root`.
+///
+/// This is the check catchup does on accounts fetched from peers, for use by clients that only
+/// know a fee state root, such as light clients. Returns the proven balance, which is zero if
+/// `proof` shows that `account` is not in the fee state.
+pub fn verify_account_proof(
+    account: FeeAccount,
+    proof: &FeeAccountProof,
+    expected_root: &FeeMerkleCommitment,
+) -> anyhow::Result<FeeAmount> {
+    ensure!(
+        FeeAccount(proof.account) == account,
+        "proof is for account {}, expected {account}",
+        proof.account
+    );
+    Ok(proof.verify(expected_root)?.into())
+}
+
 #[cfg(test)]
 mod test {
-    use super::{Address, IterableFeeInfo};
-    use crate::{FeeAccount, FeeAmount, FeeInfo};
+    use jf_merkle_tree::{prelude::MerkleNode, MerkleTreeScheme, UniversalMerkleTreeScheme};
+
+    use super::{verify_account_proof, Address, FeeMerkleProof, IterableFeeInfo, U256};
+    use crate::{FeeAccount, FeeAccountProof, FeeAmount, FeeInfo, FeeMerkleTree};
 
     #[test]
     fn test_iterable_fee_info() {
@@ -464,4 +494,56 @@ mod test {
         let accounts = fees.accounts();
         assert_eq!(vec![FeeAccount::from(Address::default())], accounts);
     }
+
+    #[test]
+    fn test_fee_amount_conversions() {
+        // Conversions from and to `U256` cover the whole range.
+        let max = FeeAmount::from(U256::MAX);
+        assert_eq!(U256::from(max), U256::MAX);
+        assert_eq!(max.as_u64(), None);
+
+        let amt = FeeAmount::from(U256::from(u128::MAX));
+        assert_eq!(u128::try_from(amt).unwrap(), u128::MAX);
+
+        let overflow = FeeAmount::from(U256::from(u128::MAX) + U256::from(1));
+        let err = u128::try_from(overflow).unwrap_err();
+        assert!(err.to_string().contains("exceeds u128::MAX"), "{err}");
+        assert!(u128::try_from(max).is_err());
+    }
+
+    #[test]
+    fn test_verify_account_proof() {
+        let account = FeeAccount::from(Address::repeat_byte(1));
+        let other = FeeAccount::from(Address::repeat_byte(2));
+        let mut tree = FeeMerkleTree::new(20);
+        tree.update(account, FeeAmount::from(42)).unwrap();
+        let root = tree.commitment();
+
+        let (proof, _) = FeeAccountProof::prove(&tree, account.into()).unwrap();
+        assert_eq!(
+            verify_account_proof(account, &proof, &root).unwrap(),
+            FeeAmount::from(42)
+        );
+
+        // An account which is not in the tree has a zero balance.
+        let (absent, _) = FeeAccountProof::prove(&tree, other.into()).unwrap();
+        assert_eq!(
+            verify_account_proof(other, &absent, &root).unwrap(),
+            FeeAmount::from(0)
+        );
+
+        // A proof can't be used for a different account.
+        verify_account_proof(other, &proof, &root).unwrap_err();
+
+        // Claiming a different balance invalidates the proof.
+        let mut tampered = proof.clone();
+        let FeeMerkleProof::Presence(path) = &mut tampered.proof else {
+            panic!("expected presence proof");
+        };
+        let Some(MerkleNode::Leaf { elem, .. }) = path.proof.first_mut() else {
+            panic!("expected leaf node");
+        };
+        *elem = FeeAmount::from(1_000);
+        verify_account_proof(account, &tampered, &root).unwrap_err();
+    }
 }
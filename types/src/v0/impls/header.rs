@@ -10,7 +10,7 @@ use hotshot_types::{
     light_client::LightClientState,
     traits::{
         block_contents::{BlockHeader, BuilderFee},
-        node_implementation::{ConsensusTime, NodeType},
+        node_implementation::{ConsensusTime, NodeType, Versions},
         signature_key::BuilderSignatureKey,
         BlockPayload, ValidatedState as _,
     },
@@ -34,6 +34,7 @@ use super::{
 };
 use crate::{
     eth_signature_key::BuilderSignature,
+    payload_vid_commitment,
     v0::{
         header::{EitherOrVersion, VersionedHeader},
         impls::reward::{apply_rewards, find_validator_info, first_two_epochs},
@@ -42,7 +43,7 @@ use crate::{
     v0_1, v0_2, v0_3,
     v0_99::{self, ChainConfig, IterableFeeInfo, SolverAuctionResults},
     BlockMerkleCommitment, EpochVersion, FeeAccount, FeeAmount, FeeInfo, FeeMerkleCommitment,
-    Header, L1BlockInfo, L1Snapshot, Leaf2, NamespaceId, NsTable, SeqTypes, UpgradeType,
+    Header, L1BlockInfo, L1Snapshot, Leaf2, NamespaceId, NsTable, Payload, SeqTypes, UpgradeType,
 };
 
 impl v0_1::Header {
@@ -280,6 +281,26 @@ impl Header {
             },
         }
     }
+
+    /// Build the genesis header for an empty genesis payload.
+    ///
+    /// The namespace table and VID commitment of the empty payload are computed here, the latter
+    /// with [`payload_vid_commitment`] for the base version of `V`.
+    pub async fn genesis_from_state<V: Versions>(
+        node_state: &NodeState,
+        builder_commitment: BuilderCommitment,
+    ) -> Result<Self, <Payload as BlockPayload<SeqTypes>>::Error> {
+        let (_, ns_table) = Payload::empty();
+        let payload_commitment =
+            payload_vid_commitment::<V>(&[], &ValidatedState::default(), node_state).await?;
+        Ok(<Self as BlockHeader<SeqTypes>>::genesis(
+            node_state,
+            payload_commitment,
+            builder_commitment,
+            ns_table,
+        ))
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn create(
         chain_config: ChainConfig,
@@ -817,6 +838,57 @@ impl From<anyhow::Error> for InvalidBlockHeader {
     }
 }
 
+/// An invariant violated by a header relative to its parent.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum HeaderInvariantError {
+    #[error("height must increment by one: parent={parent}, child={child}")]
+    Height { parent: u64, child: u64 },
+    #[error("timestamp is decreasing: parent={parent}, child={child}")]
+    Timestamp { parent: u64, child: u64 },
+    #[error("l1_head is decreasing: parent={parent}, child={child}")]
+    L1Head { parent: u64, child: u64 },
+    #[error("l1_finalized is decreasing: parent={parent:?}, child={child:?}")]
+    L1Finalized {
+        parent: Option<L1BlockInfo>,
+        child: Option<L1BlockInfo>,
+    },
+}
+
+/// Check that `child` can succeed `parent` in a chain of headers.
+///
+/// The height must increment by one, and the timestamp, L1 head and L1 finalized block must not
+/// decrease.
+pub fn check_header_succession(
+    parent: &Header,
+    child: &Header,
+) -> Result<(), HeaderInvariantError> {
+    if child.height() != parent.height() + 1 {
+        return Err(HeaderInvariantError::Height {
+            parent: parent.height(),
+            child: child.height(),
+        });
+    }
+    if child.timestamp() < parent.timestamp() {
+        return Err(HeaderInvariantError::Timestamp {
+            parent: parent.timestamp(),
+            child: child.timestamp(),
+        });
+    }
+    if child.l1_head() < parent.l1_head() {
+        return Err(HeaderInvariantError::L1Head {
+            parent: parent.l1_head(),
+            child: child.l1_head(),
+        });
+    }
+    if child.l1_finalized() < parent.l1_finalized() {
+        return Err(HeaderInvariantError::L1Finalized {
+            parent: parent.l1_finalized(),
+            child: child.l1_finalized(),
+        });
+    }
+    Ok(())
+}
+
 impl BlockHeader<SeqTypes> for Header {
     type Error = InvalidBlockHeader;
 
@@ -1257,9 +1329,25 @@ mod test_headers {
     use alloy::{
         node_bindings::Anvil,
         primitives::{Address, U256},
+        providers::{Provider, ProviderBuilder},
+    };
+    use espresso_contract_deployer::{deploy_light_client_proxy, Contracts};
+    use hotshot_contract_adapter::{
+        light_client::expected_hotshot_commitment,
+        sol_types::{
+            lightclientmock::LightClient::StateHistoryCommitment, LightClientMock,
+            LightClientStateSol, StakeTableStateSol,
+        },
     };
     use hotshot_query_service::testing::mocks::MockVersions;
-    use hotshot_types::traits::signature_key::BuilderSignatureKey;
+    use hotshot_types::{
+        data::vid_commitment,
+        light_client::{hash_bytes_to_field, CircuitField},
+        traits::{
+            block_contents::GENESIS_VID_NUM_STORAGE_NODES, signature_key::BuilderSignatureKey,
+            EncodeBytes,
+        },
+    };
     use sequencer_utils::test_utils::setup_test;
     use v0_1::{BlockMerkleTree, FeeMerkleTree, L1Client};
     use vbs::{bincode_serializer::BincodeSerializer, version::StaticVersion, BinarySerializer};
@@ -1269,7 +1357,7 @@ mod test_headers {
         eth_signature_key::EthKeyPair,
         mock::MockStateCatchup,
         v0_1::{RewardInfo, RewardMerkleTree},
-        Leaf,
+        Leaf, ProposalValidationError,
     };
 
     #[derive(Debug, Default)]
@@ -1785,4 +1873,191 @@ mod test_headers {
             BincodeSerializer::<StaticVersion<0, 99>>::deserialize(&v99_bytes).unwrap();
         assert_eq!(v99_header, deserialized);
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_expected_hotshot_commitment() -> anyhow::Result<()> {
+        setup_test();
+
+        let provider = ProviderBuilder::new().on_anvil_with_wallet();
+        let mut contracts = Contracts::new();
+        let admin = provider.get_accounts().await?[0];
+        let lc_addr = deploy_light_client_proxy(
+            &provider,
+            &mut contracts,
+            true,
+            LightClientStateSol::dummy_genesis(),
+            StakeTableStateSol::dummy_genesis(),
+            admin,
+            None,
+        )
+        .await?;
+        let lc = LightClientMock::new(lc_addr, &provider);
+
+        let mut leaf = GenesisForTest::default().await.leaf;
+        *leaf.block_header_mut().height_mut() = 10;
+        let height = leaf.height();
+
+        // Build the commitment by hand: the Rescue hash of the serialized block Merkle root, read
+        // as a little-endian integer.
+        let mut root = vec![];
+        leaf.block_header()
+            .block_merkle_tree_root()
+            .serialize_compressed(&mut root)?;
+        let field: CircuitField = hash_bytes_to_field(&root)?;
+        let mut field_bytes = vec![];
+        field.serialize_compressed(&mut field_bytes)?;
+        let expected = U256::from_le_slice(&field_bytes);
+        assert_eq!(expected_hotshot_commitment(&leaf)?, expected);
+
+        // `getHotShotCommitment` returns the first commitment in the history whose height is
+        // greater than the requested one, so store the leaf's commitment followed by a later one.
+        lc.setStateHistory(vec![
+            StateHistoryCommitment {
+                l1BlockHeight: 1,
+                l1BlockTimestamp: 1,
+                hotShotBlockHeight: height,
+                hotShotBlockCommRoot: expected,
+            },
+            StateHistoryCommitment {
+                l1BlockHeight: 2,
+                l1BlockTimestamp: 2,
+                hotShotBlockHeight: height + 1,
+                hotShotBlockCommRoot: U256::ZERO,
+            },
+        ])
+        .send()
+        .await?
+        .watch()
+        .await?;
+
+        let on_chain = lc
+            .getHotShotCommitment(U256::from(height - 1))
+            .call()
+            .await?;
+        assert_eq!(on_chain.hotshotBlockHeight, height);
+        assert_eq!(on_chain.hotShotBlockCommRoot, expected);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_genesis_from_state() {
+        setup_test();
+
+        let node_state = NodeState::mock();
+        let (payload, ns_table) =
+            Payload::from_transactions([], &ValidatedState::default(), &node_state)
+                .await
+                .unwrap();
+        let builder_commitment = payload.builder_commitment(&ns_table);
+        let payload_commitment = vid_commitment::<MockVersions>(
+            &payload.encode(),
+            &ns_table.encode(),
+            GENESIS_VID_NUM_STORAGE_NODES,
+            <MockVersions as Versions>::Base::VERSION,
+        );
+        let expected = <Header as BlockHeader<SeqTypes>>::genesis(
+            &node_state,
+            payload_commitment,
+            builder_commitment.clone(),
+            ns_table,
+        );
+
+        assert_eq!(
+            Header::genesis_from_state::<MockVersions>(&node_state, builder_commitment)
+                .await
+                .unwrap(),
+            expected
+        );
+    }
+
+    async fn succession_test_headers() -> (Header, Header) {
+        let mut parent = Header::genesis_from_state::<MockVersions>(
+            &NodeState::mock(),
+            BuilderCommitment::from_bytes(b""),
+        )
+        .await
+        .unwrap();
+        *parent.timestamp_mut() = 10;
+        *parent.l1_head_mut() = 5;
+        *parent.l1_finalized_mut() = Some(l1_block(3));
+
+        let mut child = parent.clone();
+        *child.height_mut() += 1;
+        (parent, child)
+    }
+
+    #[tokio::test]
+    async fn test_check_header_succession() {
+        let (parent, child) = succession_test_headers().await;
+        check_header_succession(&parent, &child).unwrap();
+
+        // Everything may stay the same or advance, except the height which must increment by one.
+        let mut advanced = child.clone();
+        *advanced.timestamp_mut() += 1;
+        *advanced.l1_head_mut() += 1;
+        *advanced.l1_finalized_mut() = Some(l1_block(4));
+        check_header_succession(&parent, &advanced).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_check_header_succession_height() {
+        let (parent, mut child) = succession_test_headers().await;
+        for height in [parent.height(), parent.height() + 2] {
+            *child.height_mut() = height;
+            assert_eq!(
+                check_header_succession(&parent, &child).unwrap_err(),
+                HeaderInvariantError::Height {
+                    parent: parent.height(),
+                    child: height,
+                }
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_header_succession_timestamp() {
+        let (parent, mut child) = succession_test_headers().await;
+        *child.timestamp_mut() = 9;
+        assert_eq!(
+            check_header_succession(&parent, &child).unwrap_err(),
+            HeaderInvariantError::Timestamp {
+                parent: 10,
+                child: 9,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_header_succession_l1_head() {
+        let (parent, mut child) = succession_test_headers().await;
+        *child.l1_head_mut() = 4;
+        assert_eq!(
+            check_header_succession(&parent, &child).unwrap_err(),
+            HeaderInvariantError::L1Head {
+                parent: 5,
+                child: 4,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_header_succession_l1_finalized() {
+        let (parent, mut child) = succession_test_headers().await;
+        for finalized in [Some(l1_block(2)), None] {
+            *child.l1_finalized_mut() = finalized;
+            let err = check_header_succession(&parent, &child).unwrap_err();
+            assert_eq!(
+                err,
+                HeaderInvariantError::L1Finalized {
+                    parent: Some(l1_block(3)),
+                    child: finalized,
+                }
+            );
+            assert!(matches!(
+                ProposalValidationError::from(err),
+                ProposalValidationError::L1FinalizedDecrementing { .. }
+            ));
+        }
+    }
 }
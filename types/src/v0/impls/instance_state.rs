@@ -1,6 +1,7 @@
 use std::{collections::BTreeMap, sync::Arc};
 
 use alloy::primitives::Address;
+use anyhow::ensure;
 #[cfg(any(test, feature = "testing"))]
 use async_lock::RwLock;
 use async_trait::async_trait;
@@ -24,8 +25,8 @@ use super::{
     SeqTypes, TimeBasedUpgrade, UpgradeType, ViewBasedUpgrade,
 };
 use crate::v0::{
-    traits::StateCatchup, v0_99::ChainConfig, GenesisHeader, L1BlockInfo, L1Client, Timestamp,
-    Upgrade, UpgradeMode,
+    traits::StateCatchup, v0_99::ChainConfig, GenesisHeader, L1BlockInfo, L1Client, NamespaceId,
+    Timestamp, Upgrade, UpgradeMode,
 };
 #[cfg(any(test, feature = "testing"))]
 use crate::EpochCommittees;
@@ -63,6 +64,8 @@ pub struct NodeState {
     /// to use in functions such as genesis.
     /// (example: genesis returns V2 Header if version is 0.2)
     pub current_version: Version,
+    /// Namespaces which blocks may contain transactions for, if restricted by the genesis file.
+    pub allowed_namespaces: Option<Vec<NamespaceId>>,
 }
 
 #[async_trait]
@@ -122,6 +125,7 @@ impl NodeState {
             current_version,
             epoch_height: None,
             coordinator,
+            allowed_namespaces: None,
         }
     }
 
@@ -259,6 +263,22 @@ impl NodeState {
         self.epoch_height = Some(epoch_height);
         self
     }
+
+    pub fn with_allowed_namespaces(mut self, namespaces: Option<Vec<NamespaceId>>) -> Self {
+        self.allowed_namespaces = namespaces;
+        self
+    }
+
+    /// Check that transactions for `ns` may be included in a block.
+    ///
+    /// Validators reject proposals containing a namespace which is not allowed, so builders leave
+    /// out such transactions and the submit endpoint rejects them early.
+    pub fn check_namespace_allowed(&self, ns: NamespaceId) -> anyhow::Result<()> {
+        if let Some(allowed) = &self.allowed_namespaces {
+            ensure!(allowed.contains(&ns), "namespace {ns} is not allowed");
+        }
+        Ok(())
+    }
 }
 
 /// NewType to hold upgrades and some convenience behavior.
@@ -414,13 +434,13 @@ pub mod mock {
 
     #[async_trait]
     impl StateCatchup for MockStateCatchup {
-        async fn try_fetch_leaf(
+        async fn try_fetch_leaf_chain(
             &self,
             _retry: usize,
             _height: u64,
             _stake_table: HSStakeTable<SeqTypes>,
             _success_threshold: U256,
-        ) -> anyhow::Result<Leaf2> {
+        ) -> anyhow::Result<Vec<Leaf2>> {
             Err(anyhow::anyhow!("todo"))
         }
 
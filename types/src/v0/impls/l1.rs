@@ -15,20 +15,21 @@ use alloy::{
     rpc::{
         client::RpcClient,
         json_rpc::{RequestPacket, ResponsePacket},
-        types::Block,
+        types::{Block, Header},
     },
     transports::{http::Http, RpcError, TransportErrorKind},
 };
-use anyhow::Context;
+use anyhow::{ensure, Context};
+use async_broadcast::Sender;
 use async_trait::async_trait;
 use clap::Parser;
 use committable::{Commitment, Committable, RawCommitmentBuilder};
 use futures::{
     future::{Future, TryFuture, TryFutureExt},
-    stream::{self, StreamExt},
+    stream::{self, BoxStream, StreamExt},
 };
 use hotshot_contract_adapter::sol_types::FeeContract;
-use hotshot_types::traits::metrics::Metrics;
+use hotshot_types::traits::metrics::{Counter, Metrics};
 use lru::LruCache;
 use parking_lot::RwLock;
 use tokio::{
@@ -44,7 +45,7 @@ use super::{
     v0_1::{L1BlockInfoWithParent, SingleTransport, SingleTransportStatus, SwitchingTransport},
     L1BlockInfo, L1ClientMetrics, L1State, L1UpdateTask,
 };
-use crate::{FeeInfo, L1Client, L1ClientOptions, L1Event, L1Snapshot};
+use crate::{FeeInfo, L1Client, L1ClientOptions, L1Event, L1Reorg, L1Snapshot};
 
 impl PartialOrd for L1BlockInfo {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
@@ -70,9 +71,19 @@ impl From<&Block> for L1BlockInfo {
 
 impl From<&Block> for L1BlockInfoWithParent {
     fn from(block: &Block) -> Self {
+        (&block.header).into()
+    }
+}
+
+impl From<&Header> for L1BlockInfoWithParent {
+    fn from(header: &Header) -> Self {
         Self {
-            info: block.into(),
-            parent_hash: block.header.parent_hash,
+            info: L1BlockInfo {
+                number: header.number,
+                timestamp: U256::from(header.timestamp),
+                hash: header.hash,
+            },
+            parent_hash: header.parent_hash,
         }
     }
 }
@@ -131,8 +142,44 @@ impl L1ClientOptions {
         self
     }
 
+    /// Start building L1 client options, validating them when they are built.
+    pub fn builder() -> L1ClientOptionsBuilder {
+        Default::default()
+    }
+
+    /// Check that the options can be used by an L1 client.
+    ///
+    /// Zero block ranges and intervals are rejected, since they would make the client spin or hang.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.l1_events_max_block_range > 0,
+            "l1_events_max_block_range must be positive"
+        );
+        ensure!(
+            self.l1_events_channel_capacity > 0,
+            "l1_events_channel_capacity must be positive"
+        );
+        for (name, interval) in [
+            ("l1_retry_delay", self.l1_retry_delay),
+            ("l1_polling_interval", self.l1_polling_interval),
+            ("subscription_timeout", self.subscription_timeout),
+            ("subscription_max_backoff", self.subscription_max_backoff),
+            (
+                "stake_table_update_interval",
+                self.stake_table_update_interval,
+            ),
+        ] {
+            ensure!(!interval.is_zero(), "{name} must be positive");
+        }
+        if let Some(delay) = self.l1_rate_limit_delay {
+            ensure!(!delay.is_zero(), "l1_rate_limit_delay must be positive");
+        }
+        Ok(())
+    }
+
     /// Instantiate an `L1Client` for a given list of provider `Url`s.
     pub fn connect(self, urls: Vec<Url>) -> anyhow::Result<L1Client> {
+        self.validate().context("invalid L1 client options")?;
         // create custom transport
         let t = SwitchingTransport::new(self, urls)
             .with_context(|| "failed to create switching transport")?;
@@ -145,6 +192,70 @@ impl L1ClientOptions {
     }
 }
 
+/// Builder for [`L1ClientOptions`].
+///
+/// Options which are not set keep their default values. The options are validated by
+/// [`build`](Self::build).
+#[derive(Clone, Debug, Default)]
+pub struct L1ClientOptionsBuilder {
+    opt: L1ClientOptions,
+}
+
+macro_rules! l1_client_options_setters {
+    (optional $($field:ident: $ty:ty),+ $(,)?) => {
+        $(
+            #[doc = concat!("Set [`L1ClientOptions::", stringify!($field), "`].")]
+            pub fn $field(mut self, $field: $ty) -> Self {
+                self.opt.$field = Some($field);
+                self
+            }
+        )+
+    };
+    ($($field:ident: $ty:ty),+ $(,)?) => {
+        $(
+            #[doc = concat!("Set [`L1ClientOptions::", stringify!($field), "`].")]
+            pub fn $field(mut self, $field: $ty) -> Self {
+                self.opt.$field = $field;
+                self
+            }
+        )+
+    };
+}
+
+impl L1ClientOptionsBuilder {
+    l1_client_options_setters!(
+        l1_retry_delay: Duration,
+        l1_polling_interval: Duration,
+        l1_blocks_cache_size: NonZeroUsize,
+        l1_events_channel_capacity: usize,
+        l1_events_max_block_range: u64,
+        subscription_timeout: Duration,
+        subscription_max_backoff: Duration,
+        l1_frequent_failure_tolerance: Duration,
+        l1_consecutive_failure_tolerance: usize,
+        l1_failover_revert: Duration,
+        stake_table_update_interval: Duration,
+    );
+    l1_client_options_setters!(
+        optional l1_rate_limit_delay: Duration,
+        l1_ws_provider: Vec<Url>,
+        l1_finalized_safety_margin: u64,
+        l1_finalization_lag: u64,
+    );
+
+    /// Use the given metrics collector to publish metrics related to the L1 client.
+    pub fn metrics(mut self, metrics: &(impl Metrics + ?Sized)) -> Self {
+        self.opt = self.opt.with_metrics(metrics);
+        self
+    }
+
+    /// Validate and build the options.
+    pub fn build(self) -> anyhow::Result<L1ClientOptions> {
+        self.opt.validate()?;
+        Ok(self.opt)
+    }
+}
+
 impl L1ClientMetrics {
     fn new(metrics: &(impl Metrics + ?Sized), num_urls: usize) -> Self {
         // Create a counter family for the failures per URL
@@ -479,6 +590,7 @@ impl L1Client {
         let ws_urls = opt.l1_ws_provider.clone();
         let retry_delay = opt.l1_retry_delay;
         let subscription_timeout = opt.subscription_timeout;
+        let max_backoff = opt.subscription_max_backoff;
         let state = self.state.clone();
         let sender = self.sender.clone();
         let metrics = self.metrics().clone();
@@ -487,11 +599,12 @@ impl L1Client {
 
         let span = tracing::warn_span!("L1 client update");
 
-        async move {
-
-            for i in 0.. {
-                let ws;
-
+        let subscribe_rpc = rpc.clone();
+        let subscribe = move |i: u64| {
+            let rpc = subscribe_rpc.clone();
+            let ws_urls = ws_urls.clone();
+            let transport = transport.clone();
+            async move {
                 // Fetch current L1 head block for the first value of the stream to avoid having
                 // to wait for new L1 blocks until the update loop starts processing blocks.
                 let l1_head = loop {
@@ -502,146 +615,133 @@ impl L1Client {
                         },
                         Err(err) => {
                             tracing::info!("Failed to fetch L1 head block, will retry: err {err}");
-                        }
+                        },
                     }
                     sleep(retry_delay).await;
                 };
 
                 // Subscribe to new blocks.
-                let mut block_stream = {
-                    let res = match &ws_urls {
-                        Some(urls) => {
-                            // Use a new WebSockets host each time we retry in case there is a
-                            // problem with one of the hosts specifically.
-                            let provider = i % urls.len();
-                            let url = &urls[provider];
-                            ws = match ProviderBuilder::new().on_ws(WsConnect::new(url.clone())).await {
-                                Ok(ws) => ws,
-                                Err(err) => {
-                                    tracing::warn!(provider, "Failed to connect WebSockets provider: {err:#}");
-                                    sleep(retry_delay).await;
-                                    continue;
-                                }
-                            };
-                            ws.subscribe_blocks().await.map(|stream| {stream::once(async { l1_head.clone() }).chain(stream.into_stream()).boxed()})
-                        },
-                        None => {
-                           rpc
-                            .watch_blocks()
+                match ws_urls {
+                    Some(urls) => {
+                        // Use a new WebSockets host each time we retry in case there is a
+                        // problem with one of the hosts specifically.
+                        let provider = (i as usize) % urls.len();
+                        let url = &urls[provider];
+                        let ws = ProviderBuilder::new()
+                            .on_ws(WsConnect::new(url.clone()))
                             .await
-                            .map(|poller_builder| {
-                                // Configure it and get the stream
-                                let stream = poller_builder.with_poll_interval(polling_interval).into_stream();
-
-                                let rpc = rpc.clone();
-
-                                // For HTTP, we simulate a subscription by polling. The polling
-                                // stream provided by ethers only yields block hashes, so for each
-                                // one, we have to go fetch the block itself.
-                                stream::once(async { l1_head.clone() })
-                                 .chain(
-                                    stream.map(stream::iter).flatten().filter_map(move |hash| {
-                                        let rpc = rpc.clone();
-                                        async move {
-                                            match rpc.get_block(BlockId::hash(hash)).await {
-                                                Ok(Some(block)) => Some(block.header),
-                                                // If we can't fetch the block for some reason, we can
-                                                // just skip it.
-                                                Ok(None) => {
-                                                    tracing::warn!(%hash, "HTTP stream yielded a block hash that was not available");
-                                                    None
-                                                }
-                                                Err(err) => {
-                                                    tracing::warn!(%hash, "Error fetching block from HTTP stream: {err:#}");
-                                                    None
-                                                }
+                            .with_context(|| {
+                                format!("failed to connect WebSockets provider {provider}")
+                            })?;
+                        let stream = ws.subscribe_blocks().await?.into_stream();
+                        // The subscription is only served as long as the provider is alive, so
+                        // move it into the stream.
+                        Ok::<_, anyhow::Error>(
+                            stream::once(async move { l1_head })
+                                .chain(stream.map(move |head| {
+                                    let _ = &ws;
+                                    head
+                                }))
+                                .boxed(),
+                        )
+                    },
+                    None => {
+                        let poller_builder = rpc.watch_blocks().await?;
+                        // Configure it and get the stream
+                        let stream = poller_builder
+                            .with_poll_interval(polling_interval)
+                            .into_stream();
+
+                        // For HTTP, we simulate a subscription by polling. The polling
+                        // stream provided by ethers only yields block hashes, so for each
+                        // one, we have to go fetch the block itself.
+                        Ok(stream::once(async move { l1_head })
+                            .chain(
+                                stream.map(stream::iter).flatten().filter_map(move |hash| {
+                                    let rpc = rpc.clone();
+                                    async move {
+                                        match rpc.get_block(BlockId::hash(hash)).await {
+                                            Ok(Some(block)) => Some(block.header),
+                                            // If we can't fetch the block for some reason, we can
+                                            // just skip it.
+                                            Ok(None) => {
+                                                tracing::warn!(%hash, "HTTP stream yielded a block hash that was not available");
+                                                None
+                                            }
+                                            Err(err) => {
+                                                tracing::warn!(%hash, "Error fetching block from HTTP stream: {err:#}");
+                                                None
                                             }
                                         }
-                                    }))
-                                // Take until the transport is switched, so we will call `watch_blocks` instantly on it
-                            }.take_until(transport.wait_switch())
+                                    }
+                                }))
+                            // Take until the transport is switched, so we will call `watch_blocks` instantly on it
+                            .take_until(async move { transport.wait_switch().await })
                             .boxed())
-                        }
-                    };
-                    match res {
-                        Ok(stream) => stream,
+                    },
+                }
+            }
+        };
+
+        async move {
+            let mut block_stream = resubscribing_stream(
+                subscribe,
+                subscription_timeout,
+                retry_delay,
+                max_backoff,
+                metrics.reconnects.clone(),
+            );
+            while let Some(head) = block_stream.next().await {
+                let block = L1BlockInfoWithParent::from(&head);
+                let head = head.number;
+                tracing::debug!(head, "Received L1 block");
+
+                // A new block has been produced. This happens fairly rarely, so it is now ok to
+                // poll to see if a new block has been finalized.
+                let finalized = loop {
+                    match fetch_finalized_block_from_rpc(&rpc, opt.l1_finalization_lag).await {
+                        Ok(finalized) => break finalized,
                         Err(err) => {
-                            tracing::error!("Error subscribing to L1 blocks: {err:#}");
+                            tracing::warn!("Error getting finalized block: {err:#}");
                             sleep(retry_delay).await;
-                            continue;
-                        }
+                        },
                     }
                 };
 
-                tracing::info!("Established L1 block stream");
-                loop {
-                    // Wait for a block, timing out if we don't get one soon enough
-                    let block_timeout = tokio::time::timeout(subscription_timeout, block_stream.next()).await;
-                    match block_timeout {
-                        // We got a block
-                        Ok(Some(head)) => {
-                            let head = head.number;
-                            tracing::debug!(head, "Received L1 block");
-
-                            // A new block has been produced. This happens fairly rarely, so it is now ok to
-                            // poll to see if a new block has been finalized.
-                            let finalized = loop {
-                                match fetch_finalized_block_from_rpc(&rpc).await {
-                                    Ok(finalized) => break finalized,
-                                    Err(err) => {
-                                        tracing::warn!("Error getting finalized block: {err:#}");
-                                        sleep(retry_delay).await;
-                                    }
-                                }
-                            };
-
-                            // Update the state snapshot;
-                            let mut state = state.lock().await;
-                            if head > state.snapshot.head {
-                                tracing::debug!(head, old_head = state.snapshot.head, "L1 head updated");
-                                metrics.head.set(head as usize);
-                                state.snapshot.head = head;
-                                // Emit an event about the new L1 head. Ignore send errors; it just means no
-                                // one is listening to events right now.
-                                sender
-                                    .broadcast_direct(L1Event::NewHead { head })
-                                    .await
-                                    .ok();
-                            }
-                            if let Some(finalized) = finalized {
-                                if Some(finalized.info) > state.snapshot.finalized {
-                                    tracing::info!(
-                                        ?finalized,
-                                        old_finalized = ?state.snapshot.finalized,
-                                        "L1 finalized updated",
-                                    );
-                                    metrics.finalized.set(finalized.info.number as usize);
-                                    state.snapshot.finalized = Some(finalized.info);
-                                    state.put_finalized(finalized);
-                                    sender
-                                        .broadcast_direct(L1Event::NewFinalized { finalized })
-                                        .await
-                                        .ok();
-                                }
-                            }
-                            tracing::debug!("Updated L1 snapshot to {:?}", state.snapshot);
-                        }
-                        // The stream ended
-                        Ok(None) => {
-                            tracing::error!("L1 block stream ended unexpectedly, trying to re-establish block stream");
-                            break;
-                        }
-                        // We timed out waiting for a block
-                        Err(_) => {
-                            tracing::error!("No block received for {} seconds, trying to re-establish block stream", subscription_timeout.as_secs());
-                            break;
-                        }
+                // Update the state snapshot;
+                let mut state = state.lock().await;
+                check_reorg(&mut state, &sender, block).await;
+                if head > state.snapshot.head {
+                    tracing::debug!(head, old_head = state.snapshot.head, "L1 head updated");
+                    metrics.head.set(head as usize);
+                    state.snapshot.head = head;
+                    // Emit an event about the new L1 head. Ignore send errors; it just means no
+                    // one is listening to events right now.
+                    sender
+                        .broadcast_direct(L1Event::NewHead { head })
+                        .await
+                        .ok();
+                }
+                if let Some(finalized) = finalized {
+                    if Some(finalized.info) > state.snapshot.finalized {
+                        tracing::info!(
+                            ?finalized,
+                            old_finalized = ?state.snapshot.finalized,
+                            "L1 finalized updated",
+                        );
+                        metrics.finalized.set(finalized.info.number as usize);
+                        state.snapshot.finalized = Some(finalized.info);
+                        state.put_finalized(finalized);
+                        sender
+                            .broadcast_direct(L1Event::NewFinalized { finalized })
+                            .await
+                            .ok();
                     }
                 }
-
-                metrics.reconnects.add(1);
+                tracing::debug!("Updated L1 snapshot to {:?}", state.snapshot);
             }
-        }.instrument(span)
+        }
+        .instrument(span)
     }
 
     /// Get a snapshot from the l1.
@@ -649,6 +749,21 @@ impl L1Client {
         self.state.lock().await.snapshot
     }
 
+    /// Subscribe to reorgs of the L1 chain observed by the update task.
+    ///
+    /// Only reorgs observed after subscribing are yielded.
+    pub fn subscribe_reorgs(&self) -> BoxStream<'static, L1Reorg> {
+        self.receiver
+            .activate_cloned()
+            .filter_map(|event| async move {
+                match event {
+                    L1Event::Reorg(reorg) => Some(reorg),
+                    _ => None,
+                }
+            })
+            .boxed()
+    }
+
     /// Wait until the highest L1 block number reaches at least `number`.
     ///
     /// This function does not return any information about the block, since the block is not
@@ -861,7 +976,12 @@ impl L1Client {
                 // Don't hold state lock while fetching from network.
                 drop(state);
                 let block = loop {
-                    match fetch_finalized_block_from_rpc(&self.provider).await {
+                    match fetch_finalized_block_from_rpc(
+                        &self.provider,
+                        self.options().l1_finalization_lag,
+                    )
+                    .await
+                    {
                         Ok(Some(block)) => {
                             break block;
                         },
@@ -1059,9 +1179,54 @@ impl L1State {
             snapshot: Default::default(),
             finalized: LruCache::new(cache_size),
             last_finalized: None,
+            recent_heads: Default::default(),
+            recent_heads_capacity: cache_size,
         }
     }
 
+    /// Record a new L1 head, returning the reorg it reveals, if any.
+    ///
+    /// A reorg is detected when the new head's parent hash doesn't match the hash we saw for the
+    /// previous block, or when we already saw a different block at the new head's height.
+    fn observe_head(&mut self, head: L1BlockInfoWithParent) -> Option<L1Reorg> {
+        let number = head.info.number;
+        let parent_replaced = number > 0
+            && self
+                .recent_heads
+                .get(&(number - 1))
+                .is_some_and(|hash| *hash != head.parent_hash);
+        let head_replaced = self
+            .recent_heads
+            .get(&number)
+            .is_some_and(|hash| *hash != head.info.hash);
+        let from_block = if parent_replaced {
+            Some(number - 1)
+        } else if head_replaced {
+            Some(number)
+        } else {
+            None
+        };
+
+        let reorg = from_block.map(|from_block| {
+            let to_block = self
+                .recent_heads
+                .last_key_value()
+                .map_or(from_block, |(to_block, _)| *to_block);
+            // Forget about the replaced blocks.
+            self.recent_heads.split_off(&from_block);
+            L1Reorg {
+                from_block,
+                to_block,
+            }
+        });
+
+        self.recent_heads.insert(number, head.info.hash);
+        while self.recent_heads.len() > self.recent_heads_capacity.get() {
+            self.recent_heads.pop_first();
+        }
+        reorg
+    }
+
     fn put_finalized(&mut self, block: L1BlockInfoWithParent) {
         assert!(
             self.snapshot.finalized.is_some()
@@ -1086,10 +1251,31 @@ impl L1State {
     }
 }
 
+/// Record a new L1 head and notify subscribers if it reveals a reorg.
+async fn check_reorg(state: &mut L1State, sender: &Sender<L1Event>, head: L1BlockInfoWithParent) {
+    if let Some(reorg) = state.observe_head(head) {
+        tracing::warn!(?reorg, ?head, "L1 reorg detected");
+        // Ignore send errors; it just means no one is listening to events right now.
+        sender.broadcast_direct(L1Event::Reorg(reorg)).await.ok();
+    }
+}
+
 async fn fetch_finalized_block_from_rpc(
     rpc: &impl Provider,
+    finalization_lag: Option<u64>,
 ) -> anyhow::Result<Option<L1BlockInfoWithParent>> {
-    let Some(block) = rpc.get_block(BlockId::finalized()).await? else {
+    let id = match finalization_lag {
+        Some(lag) => {
+            let latest = rpc.get_block_number().await?;
+            let Some(number) = latest.checked_sub(lag) else {
+                tracing::warn!(latest, lag, "L1 chain is shorter than the finalization lag");
+                return Ok(None);
+            };
+            BlockId::number(number)
+        },
+        None => BlockId::finalized(),
+    };
+    let Some(block) = rpc.get_block(id).await? else {
         // This can happen in rare cases where the L1 chain is very young and has not finalized a
         // block yet. This is more common in testing and demo environments. In any case, we proceed
         // with a null L1 block rather than wait for the L1 to finalize a block, which can take a
@@ -1101,15 +1287,98 @@ async fn fetch_finalized_block_from_rpc(
     Ok(Some((&block).into()))
 }
 
+/// Turn a fallible subscription into a stream that never ends.
+///
+/// `subscribe` is called with the number of the attempt to establish a stream. Whenever it fails,
+/// or the stream it returns ends or does not yield an item within `timeout`, we subscribe again.
+/// Consecutive attempts are delayed with exponential backoff, starting at `min_delay` and capped at
+/// `max_delay`. The backoff is only reset by an item received at least `timeout` after the stream
+/// was established, so that a stream which drops right after yielding its first items, such as the
+/// current head it starts with, does not cause a reconnect loop without backoff.
+fn resubscribing_stream<T, F, Fut>(
+    subscribe: F,
+    timeout: Duration,
+    min_delay: Duration,
+    max_delay: Duration,
+    reconnects: Arc<dyn Counter>,
+) -> BoxStream<'static, T>
+where
+    T: Send + 'static,
+    F: FnMut(u64) -> Fut + Send + 'static,
+    Fut: Future<Output = anyhow::Result<BoxStream<'static, T>>> + Send,
+{
+    let state = (
+        subscribe,
+        None::<(BoxStream<'static, T>, Instant)>,
+        0u64,
+        min_delay,
+    );
+    stream::unfold(
+        state,
+        move |(mut subscribe, mut current, mut attempt, mut delay)| {
+            let reconnects = reconnects.clone();
+            async move {
+                loop {
+                    let (stream, established) = match &mut current {
+                        Some((stream, established)) => (stream, *established),
+                        None => match subscribe(attempt).await {
+                            Ok(stream) => {
+                                tracing::info!(attempt, "Established L1 block stream");
+                                let (stream, established) =
+                                    current.insert((stream, Instant::now()));
+                                (stream, *established)
+                            },
+                            Err(err) => {
+                                tracing::error!(attempt, "Error subscribing to L1 blocks: {err:#}");
+                                attempt += 1;
+                                sleep(delay).await;
+                                delay = min(delay * 2, max_delay);
+                                continue;
+                            },
+                        },
+                    };
+                    let next = tokio::time::timeout(timeout, stream.next()).await;
+                    match next {
+                        Ok(Some(item)) => {
+                            if established.elapsed() >= timeout {
+                                delay = min_delay;
+                            }
+                            return Some((item, (subscribe, current, attempt, delay)));
+                        },
+                        Ok(None) => {
+                            tracing::error!("L1 block stream ended unexpectedly");
+                        },
+                        Err(_) => {
+                            tracing::error!("No block received for {} seconds", timeout.as_secs());
+                        },
+                    }
+
+                    current = None;
+                    attempt += 1;
+                    reconnects.add(1);
+                    tracing::warn!(attempt, ?delay, "Re-establishing L1 block stream");
+                    sleep(delay).await;
+                    delay = min(delay * 2, max_delay);
+                }
+            }
+        },
+    )
+    .boxed()
+}
+
 #[cfg(test)]
 mod test {
-    use std::{ops::Add, time::Duration};
+    use std::{
+        ops::Add,
+        sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+        time::Duration,
+    };
 
     use alloy::{
         eips::BlockNumberOrTag,
         node_bindings::{Anvil, AnvilInstance},
         primitives::utils::parse_ether,
-        providers::layers::AnvilProvider,
+        providers::{ext::AnvilApi, layers::AnvilProvider},
     };
     use espresso_contract_deployer::{deploy_fee_contract_proxy, Contracts};
     use portpicker::pick_unused_port;
@@ -1243,6 +1512,72 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_options_builder() {
+        let opt = L1ClientOptions::builder()
+            .l1_events_max_block_range(5)
+            .l1_polling_interval(Duration::from_millis(500))
+            .l1_finalization_lag(2)
+            .build()
+            .unwrap();
+        assert_eq!(opt.l1_events_max_block_range, 5);
+        assert_eq!(opt.l1_polling_interval, Duration::from_millis(500));
+        assert_eq!(opt.l1_finalization_lag, Some(2));
+
+        let err = L1ClientOptions::builder()
+            .l1_events_max_block_range(0)
+            .build()
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("l1_events_max_block_range"),
+            "{err:#}"
+        );
+
+        L1ClientOptions::builder()
+            .l1_polling_interval(Duration::ZERO)
+            .build()
+            .unwrap_err();
+
+        // Options set directly are validated when connecting.
+        let opt = L1ClientOptions {
+            l1_events_max_block_range: 0,
+            ..Default::default()
+        };
+        opt.connect(vec!["http://localhost:1".parse().unwrap()])
+            .unwrap_err();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_finalization_lag() {
+        setup_test();
+
+        let anvil = Anvil::new().spawn();
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint_url());
+
+        // The chain is too short to have a finalized block yet.
+        assert_eq!(
+            fetch_finalized_block_from_rpc(&provider, Some(5))
+                .await
+                .unwrap(),
+            None
+        );
+
+        provider.anvil_mine(Some(10), None).await.unwrap();
+        let latest = provider.get_block_number().await.unwrap();
+        let finalized = fetch_finalized_block_from_rpc(&provider, Some(3))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(finalized.info.number, latest - 3);
+
+        let true_block = provider
+            .get_block(BlockId::number(latest - 3))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(finalized.info.hash, true_block.header.hash);
+    }
+
     async fn test_wait_for_finalized_block_helper(ws: bool) {
         setup_test();
 
@@ -1422,6 +1757,105 @@ mod test {
         test_wait_for_block_helper(false).await
     }
 
+    #[tokio::test]
+    async fn test_reorg_event() {
+        setup_test();
+
+        // A mock L1 chain over `blocks`, hash-chained by parent hash. `fork` distinguishes the
+        // hashes of competing chains.
+        fn mock_chain(
+            blocks: std::ops::Range<u64>,
+            parent_hash: B256,
+            fork: u8,
+        ) -> Vec<L1BlockInfoWithParent> {
+            let mut parent_hash = parent_hash;
+            blocks
+                .map(|number| {
+                    let mut hash = [fork; 32];
+                    hash[..8].copy_from_slice(&number.to_le_bytes());
+                    let block = L1BlockInfoWithParent {
+                        info: L1BlockInfo {
+                            number,
+                            timestamp: U256::from(number),
+                            hash: hash.into(),
+                        },
+                        parent_hash,
+                    };
+                    parent_hash = block.info.hash;
+                    block
+                })
+                .collect()
+        }
+
+        let l1_client = L1Client::new(vec!["http://localhost:1".parse().unwrap()]).unwrap();
+        let mut reorgs = l1_client.subscribe_reorgs();
+
+        let chain = mock_chain(0..5, B256::ZERO, 0);
+        for block in &chain {
+            check_reorg(
+                &mut *l1_client.state.lock().await,
+                &l1_client.sender,
+                *block,
+            )
+            .await;
+        }
+
+        // Rewrite block 3 and build on top of it.
+        let fork = mock_chain(3..6, chain[2].info.hash, 1);
+        for block in &fork {
+            check_reorg(
+                &mut *l1_client.state.lock().await,
+                &l1_client.sender,
+                *block,
+            )
+            .await;
+        }
+        let reorg = tokio::time::timeout(Duration::from_secs(1), reorgs.next())
+            .await
+            .expect("reorg event was not emitted")
+            .unwrap();
+        assert_eq!(
+            reorg,
+            L1Reorg {
+                from_block: 3,
+                to_block: 4
+            }
+        );
+
+        // A head whose parent is not the block we saw at the previous height also reveals a
+        // reorg, even if we never saw the replaced blocks themselves.
+        let orphan = mock_chain(6..7, B256::repeat_byte(2), 2);
+        check_reorg(
+            &mut *l1_client.state.lock().await,
+            &l1_client.sender,
+            orphan[0],
+        )
+        .await;
+        let reorg = tokio::time::timeout(Duration::from_secs(1), reorgs.next())
+            .await
+            .expect("reorg event was not emitted")
+            .unwrap();
+        assert_eq!(
+            reorg,
+            L1Reorg {
+                from_block: 5,
+                to_block: 5
+            }
+        );
+
+        // No further events for the extended chain.
+        let next = mock_chain(7..8, orphan[0].info.hash, 2);
+        check_reorg(
+            &mut *l1_client.state.lock().await,
+            &l1_client.sender,
+            next[0],
+        )
+        .await;
+        tokio::time::timeout(Duration::from_millis(100), reorgs.next())
+            .await
+            .unwrap_err();
+    }
+
     async fn test_reconnect_update_task_helper(ws: bool) {
         setup_test();
 
@@ -1488,6 +1922,92 @@ mod test {
         test_reconnect_update_task_helper(false).await
     }
 
+    #[derive(Clone, Debug, Default)]
+    struct TestCounter(Arc<AtomicUsize>);
+
+    impl Counter for TestCounter {
+        fn add(&self, amount: usize) {
+            self.0.fetch_add(amount, AtomicOrdering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resubscribing_stream() {
+        setup_test();
+
+        // The first subscription drops after two items, the second one stays open.
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let subscribe = {
+            let attempts = attempts.clone();
+            move |attempt: u64| {
+                attempts.fetch_add(1, AtomicOrdering::SeqCst);
+                async move {
+                    let stream = match attempt {
+                        0 => stream::iter([1, 2]).boxed(),
+                        _ => stream::iter([3, 4]).chain(stream::pending()).boxed(),
+                    };
+                    Ok(stream)
+                }
+            }
+        };
+        let reconnects = TestCounter::default();
+        let items = resubscribing_stream(
+            subscribe,
+            Duration::from_secs(5),
+            Duration::from_millis(10),
+            Duration::from_millis(100),
+            Arc::new(reconnects.clone()),
+        )
+        .take(4)
+        .collect::<Vec<_>>()
+        .await;
+
+        assert_eq!(items, [1, 2, 3, 4]);
+        assert_eq!(attempts.load(AtomicOrdering::SeqCst), 2);
+        assert_eq!(reconnects.0.load(AtomicOrdering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resubscribing_stream_backoff() {
+        setup_test();
+
+        // Every subscription yields a single item, like the current head a new L1 block stream
+        // starts with, and then drops right away.
+        let subscribed_at = Arc::new(Mutex::new(vec![]));
+        let subscribe = {
+            let subscribed_at = subscribed_at.clone();
+            move |attempt: u64| {
+                subscribed_at.try_lock().unwrap().push(Instant::now());
+                async move { Ok(stream::iter([attempt]).boxed()) }
+            }
+        };
+        let min_delay = Duration::from_millis(10);
+        let max_delay = Duration::from_millis(80);
+        let items = resubscribing_stream(
+            subscribe,
+            Duration::from_secs(5),
+            min_delay,
+            max_delay,
+            Arc::new(TestCounter::default()),
+        )
+        .take(6)
+        .collect::<Vec<_>>()
+        .await;
+        assert_eq!(items, [0, 1, 2, 3, 4, 5]);
+
+        // The items don't reset the backoff, so the delay between attempts keeps growing.
+        let subscribed_at = subscribed_at.try_lock().unwrap();
+        let mut expected = min_delay;
+        for attempts in subscribed_at.windows(2) {
+            let delay = attempts[1].duration_since(attempts[0]);
+            assert!(
+                delay >= expected,
+                "delay {delay:?} is shorter than {expected:?}"
+            );
+            expected = min(expected * 2, max_delay);
+        }
+    }
+
     // #[tokio::test]
     // async fn test_fetch_stake_table() -> anyhow::Result<()> {
     //     setup_test();
@@ -14,10 +14,13 @@ mod state;
 mod transaction;
 
 pub use auction::SolverAuctionResultsProvider;
-pub use fee_info::{retain_accounts, FeeError};
+pub use block::{namespace_transactions, payload_vid_commitment};
+pub use fee_info::{retain_accounts, verify_account_proof, FeeError};
+pub use header::{check_header_succession, HeaderInvariantError};
 #[cfg(any(test, feature = "testing"))]
 pub use instance_state::mock;
 pub use instance_state::{NodeState, UpgradeMap};
+pub use l1::L1ClientOptionsBuilder;
 pub use stake_table::*;
 pub use state::{
     get_l1_deposits, BuilderValidationError, ProposalValidationError, StateValidationError,
@@ -6,7 +6,9 @@ use std::{
 };
 
 use alloy::{
+    eips::BlockId,
     primitives::{Address, U256},
+    providers::Provider,
     rpc::types::Log,
 };
 use anyhow::{bail, Context};
@@ -40,13 +42,19 @@ use tracing::Instrument;
 use super::v0_3::DAMembers;
 use super::{
     traits::{MembershipPersistence, StateCatchup},
-    v0_3::{EventKey, StakeTableEvent, StakeTableFetcher, StakeTableUpdateTask, Validator},
+    v0_3::{
+        CachedValidators, EventKey, StakeTableEvent, StakeTableFetcher, StakeTableUpdateTask,
+        Validator, ValidatorRegistry,
+    },
     v0_99::ChainConfig,
-    Header, L1Client, Leaf2, PubKey, SeqTypes,
+    Header, L1BlockInfo, L1Client, Leaf2, PubKey, SeqTypes,
 };
 
 type Epoch = <SeqTypes as NodeType>::Epoch;
 
+/// Number of most recent epochs whose validators are kept in the `StakeTableFetcher` cache.
+const VALIDATORS_CACHE_EPOCHS: usize = 8;
+
 #[derive(Clone, PartialEq)]
 pub struct StakeTableEvents {
     registrations: Vec<(ValidatorRegistered, Log)>,
@@ -123,9 +131,22 @@ impl StakeTableEvents {
 pub fn validators_from_l1_events<I: Iterator<Item = StakeTableEvent>>(
     events: I,
 ) -> anyhow::Result<IndexMap<Address, Validator<BLSPubKey>>> {
-    let mut validators = IndexMap::new();
-    let mut bls_keys = HashSet::new();
-    let mut schnorr_keys = HashSet::new();
+    Ok(apply_l1_events(ValidatorRegistry::default(), events)?.validators)
+}
+
+/// Apply L1 stake table events to a registry extracted from previous events.
+///
+/// Applying the events following some L1 block to the registry extracted from all events up to
+/// that block yields the same registry as extracting it from all events at once.
+pub fn apply_l1_events<I: Iterator<Item = StakeTableEvent>>(
+    registry: ValidatorRegistry,
+    events: I,
+) -> anyhow::Result<ValidatorRegistry> {
+    let ValidatorRegistry {
+        mut validators,
+        used_bls_keys: mut bls_keys,
+        used_schnorr_keys: mut schnorr_keys,
+    } = registry;
     for event in events {
         tracing::debug!("Processing stake table event: {:?}", event);
         match event {
@@ -242,7 +263,11 @@ pub fn validators_from_l1_events<I: Iterator<Item = StakeTableEvent>>(
         }
     }
 
-    Ok(validators)
+    Ok(ValidatorRegistry {
+        validators,
+        used_bls_keys: bls_keys,
+        used_schnorr_keys: schnorr_keys,
+    })
 }
 
 /// Select active validators
@@ -350,6 +375,7 @@ impl StakeTableFetcher {
             l1_client,
             chain_config: Arc::new(Mutex::new(chain_config)),
             update_task: StakeTableUpdateTask(Mutex::new(None)).into(),
+            validators_cache: Default::default(),
         }
     }
 
@@ -371,8 +397,20 @@ impl StakeTableFetcher {
         let l1_retry = self.l1_client.options().l1_retry_delay;
         let update_delay = self.l1_client.options().stake_table_update_interval;
         let chain_config = self.chain_config.clone();
+        let mut reorgs = self.l1_client.subscribe_reorgs();
+
+        // Drop cached stake tables which may have been affected by an L1 reorg.
+        let reorg_fetcher = self.clone();
+        let watch_reorgs = async move {
+            while let Some(reorg) = reorgs.next().await {
+                tracing::warn!(?reorg, "L1 reorg, invalidating cached stake tables");
+                reorg_fetcher
+                    .invalidate_validators_cache(reorg.from_block)
+                    .await;
+            }
+        };
 
-        async move {
+        let update_stake_table = async move {
             // Get the stake table contract address from the chain config.
             // This may not contain a stake table address if we are on a pre-epoch version.
             // It keeps retrying until the chain config is upgraded
@@ -390,21 +428,47 @@ impl StakeTableFetcher {
             };
 
             // Begin the main polling loop
+            let mut prev_finalized: Option<L1BlockInfo> = None;
             loop {
                 let finalized_block = loop {
                     if let Some(block) = state.lock().await.last_finalized {
                         break block;
                     }
-                    tracing::debug!(
-                        "Finalized block not yet available. Retrying in {l1_retry:?}",
-                    );
+                    tracing::debug!("Finalized block not yet available. Retrying in {l1_retry:?}");
                     sleep(l1_retry).await;
                 };
 
-                tracing::debug!(
-                    "Attempting to fetch stake table at L1 block {finalized_block:?}",
+                // If the block we saw finalized last time has a different hash now, the L1
+                // reorged, so anything we cached for that block or later ones may be stale.
+                if let Some(prev) = prev_finalized {
+                    match self_clone
+                        .l1_client
+                        .get_block(BlockId::number(prev.number))
+                        .await
+                    {
+                        Ok(Some(block)) if block.header.hash != prev.hash => {
+                            tracing::warn!(
+                                ?prev,
+                                hash = %block.header.hash,
+                                "L1 finalized block was replaced, invalidating cached stake tables"
+                            );
+                            self_clone.invalidate_validators_cache(prev.number).await;
+                        },
+                        Ok(_) => {},
+                        Err(err) => {
+                            tracing::warn!(?prev, "failed to check L1 block for reorg: {err:#}");
+                        },
+                    }
+                }
+                prev_finalized = Some(
+                    self_clone
+                        .l1_client
+                        .wait_for_finalized_block(finalized_block)
+                        .await,
                 );
 
+                tracing::debug!("Attempting to fetch stake table at L1 block {finalized_block:?}");
+
                 // Retry stake table fetch until it succeeds
                 loop {
                     match self_clone
@@ -424,11 +488,13 @@ impl StakeTableFetcher {
                     }
                 }
 
-                tracing::debug!(
-                    "Waiting {update_delay:?} before next stake table update...",
-                );
+                tracing::debug!("Waiting {update_delay:?} before next stake table update...");
                 sleep(update_delay).await;
             }
+        };
+
+        async move {
+            futures::join!(watch_reorgs, update_stake_table);
         }
         .instrument(span)
     }
@@ -695,10 +761,46 @@ impl StakeTableFetcher {
         contract: Address,
         to_block: u64,
     ) -> anyhow::Result<IndexMap<Address, Validator<BLSPubKey>>> {
+        Ok(
+            Self::fetch_validator_registry(l1_client, contract, to_block)
+                .await?
+                .validators,
+        )
+    }
+
+    /// Get the validators and used keys from all events up to and including `to_block`.
+    ///
+    /// Only used by staking CLI which doesn't have persistence.
+    pub async fn fetch_validator_registry(
+        l1_client: L1Client,
+        contract: Address,
+        to_block: u64,
+    ) -> anyhow::Result<ValidatorRegistry> {
         let events = Self::fetch_events_from_contract(l1_client, contract, None, to_block).await?;
         let sorted = events.sort_events()?;
         // Process the sorted events and return the resulting stake table.
-        validators_from_l1_events(sorted.into_iter().map(|(_, e)| e))
+        apply_l1_events(
+            ValidatorRegistry::default(),
+            sorted.into_iter().map(|(_, e)| e),
+        )
+    }
+
+    /// Update a `registry` extracted from all events before `from_block` with the events from
+    /// `from_block` up to and including `to_block`.
+    ///
+    /// Only used by staking CLI which doesn't have persistence.
+    pub async fn update_validators(
+        l1_client: L1Client,
+        contract: Address,
+        registry: ValidatorRegistry,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<ValidatorRegistry> {
+        let events =
+            Self::fetch_events_from_contract(l1_client, contract, Some(from_block), to_block)
+                .await?;
+        let sorted = events.sort_events()?;
+        apply_l1_events(registry, sorted.into_iter().map(|(_, e)| e))
     }
 
     pub async fn fetch(
@@ -720,25 +822,89 @@ impl StakeTableFetcher {
             return None;
         };
 
-        let events = match self
-            .fetch_and_store_stake_table_events(address, l1_finalized_block_info.number())
+        self.fetch_validators(epoch, address, l1_finalized_block_info)
             .await
-            .map_err(GetStakeTablesError::L1ClientFetchError)
-        {
-            Ok(events) => events,
-            Err(e) => {
-                tracing::error!("failed to fetch stake table events {e:?}");
-                return None;
-            },
-        };
+    }
 
-        match active_validator_set_from_l1_events(events.into_iter().map(|(_, e)| e)) {
-            Ok(validators) => Some(validators),
-            Err(e) => {
-                tracing::error!("failed to construct stake table {e:?}");
-                None
+    /// Get the active validators for `epoch` from the stake table events of `contract` up to
+    /// `l1_block`.
+    ///
+    /// Served from memory if the validators of this epoch were already fetched at the same L1
+    /// block, otherwise queried from L1 and cached.
+    pub async fn fetch_validators(
+        &self,
+        epoch: Epoch,
+        contract: Address,
+        l1_block: L1BlockInfo,
+    ) -> Option<IndexMap<Address, Validator<BLSPubKey>>> {
+        self.cached_validators(epoch, contract, l1_block, || async {
+            let events = match self
+                .fetch_and_store_stake_table_events(contract, l1_block.number())
+                .await
+                .map_err(GetStakeTablesError::L1ClientFetchError)
+            {
+                Ok(events) => events,
+                Err(e) => {
+                    tracing::error!("failed to fetch stake table events {e:?}");
+                    return None;
+                },
+            };
+
+            match active_validator_set_from_l1_events(events.into_iter().map(|(_, e)| e)) {
+                Ok(validators) => Some(validators),
+                Err(e) => {
+                    tracing::error!("failed to construct stake table {e:?}");
+                    None
+                },
+            }
+        })
+        .await
+    }
+
+    /// Look up the validators of `epoch` in the cache, or get them from `fetch` and cache them.
+    ///
+    /// Only the validators of the most recent [`VALIDATORS_CACHE_EPOCHS`] epochs are kept.
+    async fn cached_validators<F, Fut>(
+        &self,
+        epoch: Epoch,
+        contract: Address,
+        l1_block: L1BlockInfo,
+        fetch: F,
+    ) -> Option<IndexMap<Address, Validator<BLSPubKey>>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Option<IndexMap<Address, Validator<BLSPubKey>>>>,
+    {
+        if let Some(cached) = self.validators_cache.lock().await.get(&epoch) {
+            if cached.contract == contract && cached.l1_block == l1_block {
+                tracing::debug!(%epoch, l1_block = l1_block.number, "using cached stake table");
+                return Some(cached.validators.clone());
+            }
+        }
+
+        let validators = fetch().await?;
+        let mut cache = self.validators_cache.lock().await;
+        cache.insert(
+            epoch,
+            CachedValidators {
+                contract,
+                l1_block,
+                validators: validators.clone(),
             },
+        );
+        while cache.len() > VALIDATORS_CACHE_EPOCHS {
+            cache.pop_first();
         }
+        Some(validators)
+    }
+
+    /// Drop cached validators derived from L1 blocks at or after `from_block`, e.g. because those
+    /// blocks were reorged.
+    pub async fn invalidate_validators_cache(&self, from_block: u64) {
+        self.validators_cache
+            .lock()
+            .await
+            .retain(|_, cached| cached.l1_block.number < from_block);
     }
 
     /// Retrieve and verify `ChainConfig`
@@ -1425,7 +1591,18 @@ pub mod testing {
 
 #[cfg(test)]
 mod tests {
-    use alloy::primitives::Address;
+    use std::time::Duration;
+
+    use alloy::{
+        node_bindings::Anvil,
+        primitives::{utils::parse_ether, Address, B256},
+        providers::ProviderBuilder,
+        sol_types::SolValue as _,
+    };
+    use ark_ec::CurveGroup as _;
+    use espresso_contract_deployer::{deploy_stake_table_proxy, deploy_token_proxy, Contracts};
+    use hotshot_contract_adapter::sol_types::{EspToken, G1PointSol};
+    use hotshot_types::{epoch_membership::EpochMembershipCoordinator, light_client::StateKeyPair};
     use sequencer_utils::test_utils::setup_test;
 
     use super::*;
@@ -1496,6 +1673,239 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_apply_l1_events() -> anyhow::Result<()> {
+        let val = TestValidator::random();
+        let other_val = TestValidator::random();
+        // The exited validator registers again with new keys.
+        let new_keys = TestValidator::random();
+        let delegator = Address::random();
+        let register = |val: &TestValidator, keys: &TestValidator| -> StakeTableEvent {
+            ValidatorRegistered {
+                account: val.account,
+                blsVk: keys.bls_vk.clone(),
+                schnorrVk: keys.schnorr_vk.clone(),
+                commission: val.commission,
+            }
+            .into()
+        };
+        let events: Vec<StakeTableEvent> = vec![
+            register(&val, &val),
+            Delegated {
+                delegator,
+                validator: val.account,
+                amount: U256::from(10),
+            }
+            .into(),
+            register(&other_val, &other_val),
+            Undelegated {
+                delegator,
+                validator: val.account,
+                amount: U256::from(3),
+            }
+            .into(),
+            Delegated {
+                delegator,
+                validator: other_val.account,
+                amount: U256::from(5),
+            }
+            .into(),
+            ValidatorExit {
+                validator: val.account,
+            }
+            .into(),
+            register(&val, &new_keys),
+            Delegated {
+                delegator,
+                validator: val.account,
+                amount: U256::from(7),
+            }
+            .into(),
+        ];
+
+        let full = apply_l1_events(ValidatorRegistry::default(), events.iter().cloned())?;
+        assert_eq!(full.validators.len(), 2);
+        assert_eq!(full.used_bls_keys.len(), 3);
+        for split in 0..=events.len() {
+            let (before, after) = events.split_at(split);
+            let cached = apply_l1_events(ValidatorRegistry::default(), before.iter().cloned())?;
+            let incremental = apply_l1_events(cached, after.iter().cloned())?;
+            assert_eq!(incremental, full, "split at {split}");
+        }
+
+        // The keys of the exited validator can't be registered again, whether the exit was applied
+        // incrementally or not.
+        let reuse = register(&TestValidator::random(), &val);
+        let mut all = events.clone();
+        all.push(reuse.clone());
+        assert!(validators_from_l1_events(all.into_iter()).is_err());
+        for split in 0..=events.len() {
+            let cached = apply_l1_events(
+                ValidatorRegistry::default(),
+                events[..split].iter().cloned(),
+            )?;
+            let after = events[split..].iter().cloned().chain([reuse.clone()]);
+            assert!(apply_l1_events(cached, after).is_err(), "split at {split}");
+        }
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fetch_validators_cached() -> anyhow::Result<()> {
+        setup_test();
+        let anvil = Anvil::new().spawn();
+        let wallet = anvil.wallet().unwrap();
+        let deployer = wallet.default_signer().address();
+        let provider = ProviderBuilder::new()
+            .wallet(wallet)
+            .on_http(anvil.endpoint_url());
+        let mut contracts = Contracts::new();
+
+        // A stake table with a single validator, that delegates to itself.
+        let token = deploy_token_proxy(&provider, &mut contracts, deployer, deployer).await?;
+        let contract = deploy_stake_table_proxy(
+            &provider,
+            &mut contracts,
+            token,
+            Address::random(), // fake LC address
+            U256::from(1),
+            deployer,
+        )
+        .await?;
+        let (bls_vk, bls_sk) = BLSPubKey::generated_from_seed_indexed([0; 32], 0);
+        let schnorr_vk = StateKeyPair::generate_from_seed_indexed([0; 32], 0).ver_key();
+        let bls_sig = BLSPubKey::sign(&bls_sk, &deployer.abi_encode())?;
+        let bls_sig: G1PointSol = bls_sig.sigma.into_affine().into();
+        let stake_table = StakeTable::new(contract, &provider);
+        stake_table
+            .registerValidator(
+                bls_vk.to_affine().into(),
+                schnorr_vk.to_affine().into(),
+                bls_sig.into(),
+                100,
+            )
+            .send()
+            .await?
+            .get_receipt()
+            .await?;
+        let stake = parse_ether("1")?;
+        EspToken::new(token, &provider)
+            .approve(contract, stake)
+            .send()
+            .await?
+            .get_receipt()
+            .await?;
+        stake_table
+            .delegate(deployer, stake)
+            .send()
+            .await?
+            .get_receipt()
+            .await?;
+
+        let fetcher = StakeTableFetcher::new(
+            Arc::new(crate::mock::MockStateCatchup::default()),
+            Arc::new(Mutex::new(crate::v0_1::NoStorage)),
+            L1Client::anvil(&anvil)?,
+            ChainConfig::default(),
+        );
+        let l1_block = L1BlockInfo {
+            number: provider.get_block_number().await?,
+            ..Default::default()
+        };
+        let validators = fetcher
+            .fetch_validators(EpochNumber::new(3), contract, l1_block)
+            .await
+            .unwrap();
+        assert_eq!(validators[&deployer].stake, stake);
+
+        // Only the most recent epochs are kept.
+        for epoch in 4..4 + VALIDATORS_CACHE_EPOCHS as u64 {
+            fetcher
+                .fetch_validators(EpochNumber::new(epoch), contract, l1_block)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            fetcher.validators_cache.lock().await.len(),
+            VALIDATORS_CACHE_EPOCHS
+        );
+        assert!(!fetcher
+            .validators_cache
+            .lock()
+            .await
+            .contains_key(&EpochNumber::new(3)));
+
+        // Without an L1, any L1 request would be retried forever, so from here on only cached
+        // stake tables can be fetched.
+        drop(anvil);
+        let epoch = EpochNumber::new(4);
+        for _ in 0..2 {
+            let fetched = tokio::time::timeout(
+                Duration::from_secs(1),
+                fetcher.fetch_validators(epoch, contract, l1_block),
+            )
+            .await?;
+            assert_eq!(fetched, Some(validators.clone()));
+        }
+
+        // A reorged block at the same height is a cache miss, and so is an invalidated entry.
+        let reorged = L1BlockInfo {
+            hash: B256::repeat_byte(1),
+            ..l1_block
+        };
+        assert!(tokio::time::timeout(
+            Duration::from_millis(100),
+            fetcher.fetch_validators(epoch, contract, reorged),
+        )
+        .await
+        .is_err());
+        fetcher.invalidate_validators_cache(l1_block.number).await;
+        assert!(fetcher.validators_cache.lock().await.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_epoch() -> anyhow::Result<()> {
+        setup_test();
+        let membership = Arc::new(RwLock::new(EpochCommittees::new_stake(
+            vec![],
+            vec![],
+            StakeTableFetcher::mock(),
+        )));
+        let coordinator = EpochMembershipCoordinator::new(membership, None, 100);
+        let epoch = EpochNumber::new(5);
+
+        let waiter = spawn({
+            let coordinator = coordinator.clone();
+            async move { coordinator.wait_for_epoch(epoch).await }
+        });
+        assert!(coordinator
+            .wait_for_epoch_timeout(epoch, Duration::from_millis(200))
+            .await
+            .is_err());
+        assert!(!waiter.is_finished());
+
+        // Loading the stake table releases the waiter.
+        let validator = Validator::mock();
+        let key = validator.stake_table_key;
+        coordinator
+            .update_membership(|membership| {
+                membership
+                    .update_stake_table(epoch, IndexMap::from([(validator.account, validator)]))
+            })
+            .await;
+        let epoch_membership = tokio::time::timeout(Duration::from_secs(1), waiter).await??;
+        assert_eq!(epoch_membership.epoch(), Some(epoch));
+        assert!(epoch_membership.has_stake(&key).await);
+        assert_eq!(epoch_membership.total_nodes().await, 1);
+
+        let epoch_membership = coordinator
+            .wait_for_epoch_timeout(epoch, Duration::from_millis(200))
+            .await?;
+        assert_eq!(epoch_membership.epoch(), Some(epoch));
+        Ok(())
+    }
+
     #[test]
     fn test_from_l1_events_failures() -> anyhow::Result<()> {
         let val = TestValidator::random();
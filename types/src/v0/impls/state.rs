@@ -39,11 +39,12 @@ use super::{
     MarketplaceVersion,
 };
 use crate::{
+    check_header_succession,
     traits::StateCatchup,
     v0_99::{ChainConfig, FullNetworkTx, IterableFeeInfo, ResolvableChainConfig},
-    BlockMerkleTree, Delta, FeeAccount, FeeAmount, FeeInfo, FeeMerkleTree, Header, Leaf2,
-    NsTableValidationError, PayloadByteLen, SeqTypes, UpgradeType, BLOCK_MERKLE_TREE_HEIGHT,
-    FEE_MERKLE_TREE_HEIGHT,
+    BlockMerkleTree, Delta, FeeAccount, FeeAmount, FeeInfo, FeeMerkleTree, Header,
+    HeaderInvariantError, Leaf2, NamespaceId, NsTableValidationError, PayloadByteLen, SeqTypes,
+    UpgradeType, BLOCK_MERKLE_TREE_HEIGHT, FEE_MERKLE_TREE_HEIGHT,
 };
 
 /// This enum is not used in code but functions as an index of
@@ -109,6 +110,8 @@ pub enum ProposalValidationError {
     },
     #[error("Invalid namespace table: {0}")]
     InvalidNsTable(NsTableValidationError),
+    #[error("Namespace {0} is not allowed")]
+    DisallowedNamespace(NamespaceId),
     #[error("Some fee amount or their sum total out of range")]
     SomeFeeAmountOutOfRange,
     #[error("Invalid timestamp: proposal={proposal_timestamp}, parent={parent_timestamp}")]
@@ -139,6 +142,26 @@ pub enum ProposalValidationError {
     RewardRootNotFound {},
 }
 
+impl From<HeaderInvariantError> for ProposalValidationError {
+    fn from(err: HeaderInvariantError) -> Self {
+        match err {
+            HeaderInvariantError::Height { parent, child } => Self::InvalidHeight {
+                parent_height: parent,
+                proposal_height: child,
+            },
+            HeaderInvariantError::Timestamp { parent, child } => Self::DecrementingTimestamp {
+                proposal_timestamp: child,
+                parent_timestamp: parent,
+            },
+            HeaderInvariantError::L1Head { .. } => Self::DecrementingL1Head,
+            HeaderInvariantError::L1Finalized { parent, child } => Self::L1FinalizedDecrementing {
+                parent: parent.map(|block| (block.number, block.timestamp.to::<u64>())),
+                proposed: child.map(|block| (block.number, block.timestamp.to::<u64>())),
+            },
+        }
+    }
+}
+
 impl StateDelta for Delta {}
 
 #[derive(Hash, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
@@ -368,14 +391,6 @@ impl<'a> Proposal<'a> {
     pub(crate) fn new(header: &'a Header, block_size: u32) -> Self {
         Self { header, block_size }
     }
-    /// The L1 head block number in the proposal must be non-decreasing relative
-    /// to the parent.
-    fn validate_l1_head(&self, parent_l1_head: u64) -> Result<(), ProposalValidationError> {
-        if self.header.l1_head() < parent_l1_head {
-            return Err(ProposalValidationError::DecrementingL1Head);
-        }
-        Ok(())
-    }
     /// The [`ChainConfig`] of proposal must be equal to the one stored in state.
     ///
     /// Equality is checked by comparing commitments.
@@ -393,21 +408,6 @@ impl<'a> Proposal<'a> {
         Ok(())
     }
 
-    /// The timestamp must be non-decreasing relative to parent.
-    fn validate_timestamp_non_dec(
-        &self,
-        parent_timestamp: u64,
-    ) -> Result<(), ProposalValidationError> {
-        if self.header.timestamp() < parent_timestamp {
-            return Err(ProposalValidationError::DecrementingTimestamp {
-                proposal_timestamp: self.header.timestamp(),
-                parent_timestamp,
-            });
-        }
-
-        Ok(())
-    }
-
     /// The timestamp must not drift too much from local system time.
     ///
     /// The tolerance is currently `12` seconds. This value may be moved to
@@ -452,6 +452,7 @@ pub(crate) struct ValidatedTransition<'a> {
     parent: &'a Header,
     proposal: Proposal<'a>,
     view_number: u64,
+    allowed_namespaces: Option<&'a [NamespaceId]>,
 }
 
 impl<'a> ValidatedTransition<'a> {
@@ -460,6 +461,7 @@ impl<'a> ValidatedTransition<'a> {
         parent: &'a Header,
         proposal: Proposal<'a>,
         view_number: u64,
+        allowed_namespaces: Option<&'a [NamespaceId]>,
     ) -> Self {
         let expected_chain_config = state
             .chain_config
@@ -471,57 +473,47 @@ impl<'a> ValidatedTransition<'a> {
             parent,
             proposal,
             view_number,
+            allowed_namespaces,
         }
     }
 
     /// Top level validation routine. Performs all validation units in
     /// the given order.
     /// ```ignore
+    /// self.validate_succession()?;
     /// self.validate_timestamp()?;
     /// self.validate_builder_fee()?;
-    /// self.validate_height()?;
     /// self.validate_chain_config()?;
     /// self.validate_block_size()?;
     /// self.validate_fee()?;
     /// self.validate_fee_merkle_tree()?;
     /// self.validate_block_merkle_tree()?;
-    /// self.validate_l1_finalized()?;
-    /// self.validate_l1_head()?;
+    /// self.validate_reward_merkle_tree()?;
     /// self.validate_namespace_table()?;
+    /// self.validate_allowed_namespaces()?;
     /// ```
     pub(crate) fn validate(self) -> Result<Self, ProposalValidationError> {
+        self.validate_succession()?;
         self.validate_timestamp()?;
         self.validate_builder_fee()?;
-        self.validate_height()?;
         self.validate_chain_config()?;
         self.validate_block_size()?;
         self.validate_fee()?;
         self.validate_fee_merkle_tree()?;
         self.validate_block_merkle_tree()?;
         self.validate_reward_merkle_tree()?;
-        self.validate_l1_finalized()?;
-        self.validate_l1_head()?;
         self.validate_namespace_table()?;
+        self.validate_allowed_namespaces()?;
 
         Ok(self)
     }
 
-    /// The proposal [Header::l1_finalized] must be `Some` and non-decreasing relative to parent.
-    fn validate_l1_finalized(&self) -> Result<(), ProposalValidationError> {
-        let proposed_finalized = self.proposal.header.l1_finalized();
-        let parent_finalized = self.parent.l1_finalized();
-
-        if proposed_finalized < parent_finalized {
-            // We are keeping the `Option` in the error b/c its the
-            // cleanest way to represent all the different error
-            // cases. The hash seems less useful and explodes the size
-            // of the error, so we strip it out.
-            return Err(ProposalValidationError::L1FinalizedDecrementing {
-                parent: parent_finalized.map(|block| (block.number, block.timestamp.to::<u64>())),
-                proposed: proposed_finalized
-                    .map(|block| (block.number, block.timestamp.to::<u64>())),
-            });
-        }
+    /// Validate that the proposal can succeed its parent, see [`check_header_succession`].
+    ///
+    /// The height must be `parent_height + 1`, and the timestamp, L1 head and L1 finalized block
+    /// must not decrease. Do this check first so we don't add unnecessary drift.
+    fn validate_succession(&self) -> Result<(), ProposalValidationError> {
+        check_header_succession(self.parent, self.proposal.header)?;
         Ok(())
     }
     /// Wait for our view of the L1 chain to catch up to the proposal.
@@ -562,11 +554,6 @@ impl<'a> ValidatedTransition<'a> {
         Ok(())
     }
 
-    /// Ensure that L1 Head on proposal is not decreasing.
-    fn validate_l1_head(&self) -> Result<(), ProposalValidationError> {
-        self.proposal.validate_l1_head(self.parent.l1_head())?;
-        Ok(())
-    }
     /// Validate basic numerical soundness and builder accounts by
     /// verifying signatures. Signatures are identified by index of fee `Vec`.
     fn validate_builder_fee(&self) -> Result<(), ProposalValidationError> {
@@ -612,25 +599,10 @@ impl<'a> ValidatedTransition<'a> {
         }
         Ok(())
     }
-    /// Validate that proposal height is `parent_height + 1`.
-    fn validate_height(&self) -> Result<(), ProposalValidationError> {
-        let parent_header = self.parent;
-        if self.proposal.header.height() != parent_header.height() + 1 {
-            return Err(ProposalValidationError::InvalidHeight {
-                parent_height: parent_header.height(),
-                proposal_height: self.proposal.header.height(),
-            });
-        }
-        Ok(())
-    }
-    /// Validate timestamp is not decreasing relative to parent and is
-    /// within a given tolerance of system time. Tolerance is
-    /// currently 12 seconds. This value may be moved to configuration
-    /// in the future. Do this check first so we don't add unnecessary drift.
+    /// Validate timestamp is within a given tolerance of system time.
+    /// Tolerance is currently 12 seconds. This value may be moved to
+    /// configuration in the future.
     fn validate_timestamp(&self) -> Result<(), ProposalValidationError> {
-        self.proposal
-            .validate_timestamp_non_dec(self.parent.timestamp())?;
-
         // Validate timestamp hasn't drifted too much from system time.
         let system_time: u64 = OffsetDateTime::now_utc().unix_timestamp() as u64;
         self.proposal.validate_timestamp_drift(system_time)?;
@@ -683,6 +655,21 @@ impl<'a> ValidatedTransition<'a> {
             .validate(&PayloadByteLen(self.proposal.block_size as usize))
             .map_err(ProposalValidationError::from)
     }
+    /// If the genesis restricts namespaces, every namespace in the proposal must be allowed.
+    fn validate_allowed_namespaces(&self) -> Result<(), ProposalValidationError> {
+        let Some(allowed) = self.allowed_namespaces else {
+            return Ok(());
+        };
+        let ns_table = self.proposal.header.ns_table();
+        for index in ns_table.iter() {
+            // The namespace table has already been validated.
+            let ns_id = ns_table.read_ns_id_unchecked(&index);
+            if !allowed.contains(&ns_id) {
+                return Err(ProposalValidationError::DisallowedNamespace(ns_id));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -1023,6 +1010,7 @@ impl HotShotState<SeqTypes> for ValidatedState {
             parent_leaf.block_header(),
             Proposal::new(proposed_header, payload_byte_len),
             view_number,
+            instance.allowed_namespaces.as_deref(),
         )
         .validate()?
         .wait_for_l1(&instance.l1_client)
@@ -1330,6 +1318,7 @@ mod test {
                 parent,
                 proposal,
                 view_number: 1,
+                allowed_namespaces: None,
             }
         }
     }
@@ -1406,19 +1395,22 @@ mod test {
 
         // Setup.
         let tx = Transaction::of_size(10);
-        let (header, block_size) = tx.into_mock_header().await;
+        let (mut parent, block_size) = tx.into_mock_header().await;
+        let mut header = parent.clone();
+        *header.height_mut() += 1;
 
         // Success Case
         let proposal = Proposal::new(&header, block_size);
-        // Note we are using the same header for parent and proposal,
-        // this may be OK depending on what we are testing.
-        ValidatedTransition::mock(NodeState::mock_v2(), &header, proposal)
-            .validate_l1_head()
+        ValidatedTransition::mock(NodeState::mock_v2(), &parent, proposal)
+            .validate_succession()
             .unwrap();
 
         // Error Case
+        *parent.l1_head_mut() = u64::MAX;
         let proposal = Proposal::new(&header, block_size);
-        let err = proposal.validate_l1_head(u64::MAX).unwrap_err();
+        let err = ValidatedTransition::mock(NodeState::mock_v2(), &parent, proposal)
+            .validate_succession()
+            .unwrap_err();
         assert_eq!(ProposalValidationError::DecrementingL1Head, err);
     }
 
@@ -1565,7 +1557,7 @@ mod test {
 
         let proposal = Proposal::new(&parent, block_size);
         let err = ValidatedTransition::mock(instance.clone(), &parent, proposal)
-            .validate_height()
+            .validate_succession()
             .unwrap_err();
 
         // Validation fails because the proposal is using same default.
@@ -1584,20 +1576,26 @@ mod test {
         let proposal = Proposal::new(&header, block_size);
 
         ValidatedTransition::mock(instance, &parent, proposal)
-            .validate_height()
+            .validate_succession()
             .unwrap();
     }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_validation_timestamp_non_dec() {
         initialize_logging();
+        let instance = NodeState::mock_v2();
         let tx = Transaction::of_size(10);
-        let (parent, block_size) = tx.into_mock_header().await;
+        let (mut parent, block_size) = tx.into_mock_header().await;
+        let mut header = parent.clone();
+        *header.height_mut() += 1;
 
         // Error case
-        let proposal = Proposal::new(&parent, block_size);
+        *parent.timestamp_mut() = u64::MAX;
+        let proposal = Proposal::new(&header, block_size);
         let proposal_timestamp = proposal.header.timestamp();
-        let err = proposal.validate_timestamp_non_dec(u64::MAX).unwrap_err();
+        let err = ValidatedTransition::mock(instance.clone(), &parent, proposal)
+            .validate_succession()
+            .unwrap_err();
 
         // Validation fails because the proposal is using same default.
         tracing::info!(%err, "task failed successfully");
@@ -1610,8 +1608,11 @@ mod test {
         );
 
         // Success case (genesis timestamp is `0`).
-        let proposal = Proposal::new(&parent, block_size);
-        proposal.validate_timestamp_non_dec(0).unwrap();
+        *parent.timestamp_mut() = 0;
+        let proposal = Proposal::new(&header, block_size);
+        ValidatedTransition::mock(instance, &parent, proposal)
+            .validate_succession()
+            .unwrap();
     }
 
     #[tokio::test(flavor = "multi_thread")]
@@ -1772,6 +1773,40 @@ mod test {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_validation_allowed_namespaces() {
+        initialize_logging();
+        // Setup.
+        let tx = Transaction::of_size(10);
+        let ns_id = tx.namespace();
+        let (header, block_size) = tx.into_mock_header().await;
+        let transition = |allowed_namespaces| ValidatedTransition {
+            allowed_namespaces,
+            ..ValidatedTransition::mock(
+                NodeState::mock_v2(),
+                &header,
+                Proposal::new(&header, block_size),
+            )
+        };
+
+        // Without an allowlist, any namespace is allowed.
+        transition(None).validate_allowed_namespaces().unwrap();
+
+        // Success case.
+        let allowed = [NamespaceId::from(0_u32), ns_id];
+        transition(Some(&allowed[..]))
+            .validate_allowed_namespaces()
+            .unwrap();
+
+        // Error case
+        let allowed = [NamespaceId::from(u32::from(ns_id) + 1)];
+        let err = transition(Some(&allowed[..]))
+            .validate_allowed_namespaces()
+            .unwrap_err();
+        tracing::info!(%err, "task failed successfully");
+        assert_eq!(ProposalValidationError::DisallowedNamespace(ns_id), err);
+    }
+
     #[test]
     fn test_charge_fee() {
         initialize_logging();
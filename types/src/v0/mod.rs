@@ -20,8 +20,10 @@ pub use header::Header;
 #[cfg(any(test, feature = "testing"))]
 pub use impls::mock;
 pub use impls::{
-    get_l1_deposits, retain_accounts, validators_from_l1_events, BuilderValidationError,
-    EpochCommittees, FeeError, ProposalValidationError, StateValidationError,
+    apply_l1_events, check_header_succession, get_l1_deposits, namespace_transactions,
+    payload_vid_commitment, retain_accounts, validators_from_l1_events, verify_account_proof,
+    BuilderValidationError, EpochCommittees, FeeError, HeaderInvariantError,
+    L1ClientOptionsBuilder, ProposalValidationError, StateValidationError,
 };
 pub use nsproof::*;
 pub use utils::*;
@@ -90,6 +92,7 @@ reexport_unchanged_types!(
     L1BlockInfo,
     L1Client,
     L1ClientOptions,
+    L1Reorg,
     L1Snapshot,
     NamespaceId,
     NsIndex,
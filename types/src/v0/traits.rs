@@ -19,7 +19,7 @@ use hotshot_types::{
     },
     drb::DrbResult,
     event::{HotShotAction, LeafInfo},
-    message::{convert_proposal, Proposal},
+    message::{convert_proposal, Proposal, UpgradeLock},
     simple_certificate::{
         LightClientStateUpdateCertificate, NextEpochQuorumCertificate2, QuorumCertificate,
         QuorumCertificate2, UpgradeCertificate,
@@ -30,7 +30,7 @@ use hotshot_types::{
         storage::Storage,
         ValidatedState as HotShotState,
     },
-    utils::genesis_epoch_from_version,
+    utils::{genesis_epoch_from_version, verify_leaf_chain},
 };
 use indexmap::IndexMap;
 use serde::{de::DeserializeOwned, Serialize};
@@ -42,20 +42,53 @@ use super::{
     v0_3::{EventKey, IndexedStake, StakeTableEvent, Validator},
 };
 use crate::{
-    v0::impls::ValidatedState, v0_99::ChainConfig, BlockMerkleTree, Event, FeeAccount,
-    FeeAccountProof, FeeMerkleCommitment, Leaf2, NetworkConfig, SeqTypes,
+    v0::impls::ValidatedState, v0_99::ChainConfig, BlockMerkleTree, EpochVersion, Event,
+    FeeAccount, FeeAccountProof, FeeMerkleCommitment, Leaf2, NetworkConfig, SeqTypes,
+    SequencerVersions,
 };
 
 #[async_trait]
 pub trait StateCatchup: Send + Sync {
+    /// Fetch the leaf at the given height together with the leaves which decide it, without
+    /// retrying on transient errors.
+    ///
+    /// The chain still has to be checked with [`verify_leaf_chain`]. `stake_table` and
+    /// `success_threshold` only let providers which collect responses from several peers skip the
+    /// ones that don't verify.
+    async fn try_fetch_leaf_chain(
+        &self,
+        retry: usize,
+        height: u64,
+        stake_table: HSStakeTable<SeqTypes>,
+        success_threshold: U256,
+    ) -> anyhow::Result<Vec<Leaf2>>;
+
     /// Fetch the leaf at the given height without retrying on transient errors.
+    ///
+    /// The leaf is only returned if it is decided by a chain of QCs signed by `stake_table`.
     async fn try_fetch_leaf(
         &self,
         retry: usize,
         height: u64,
         stake_table: HSStakeTable<SeqTypes>,
         success_threshold: U256,
-    ) -> anyhow::Result<Leaf2>;
+    ) -> anyhow::Result<Leaf2> {
+        let leaf_chain = self
+            .try_fetch_leaf_chain(retry, height, stake_table.clone(), success_threshold)
+            .await
+            .with_context(|| format!("failed to fetch leaf chain at height {height}"))?;
+
+        // Verify it, returning the leaf at the given height
+        verify_leaf_chain(
+            leaf_chain,
+            &stake_table,
+            success_threshold,
+            height,
+            &UpgradeLock::<SeqTypes, SequencerVersions<EpochVersion, EpochVersion>>::new(),
+        )
+        .await
+        .with_context(|| format!("failed to verify leaf chain at height {height}"))
+    }
 
     /// Fetch the leaf at the given height, retrying on transient errors.
     async fn fetch_leaf(
@@ -228,6 +261,18 @@ pub trait StateCatchup: Send + Sync {
 
 #[async_trait]
 impl<T: StateCatchup + ?Sized> StateCatchup for Arc<T> {
+    async fn try_fetch_leaf_chain(
+        &self,
+        retry: usize,
+        height: u64,
+        stake_table: HSStakeTable<SeqTypes>,
+        success_threshold: U256,
+    ) -> anyhow::Result<Vec<Leaf2>> {
+        (**self)
+            .try_fetch_leaf_chain(retry, height, stake_table, success_threshold)
+            .await
+    }
+
     async fn try_fetch_leaf(
         &self,
         retry: usize,
@@ -426,6 +471,15 @@ pub trait SequencerPersistence: Sized + Send + Sync + Clone + 'static {
     /// Save the orchestrator config to storage.
     async fn save_config(&self, cfg: &NetworkConfig) -> anyhow::Result<()>;
 
+    /// Load the version of the layout in which data was persisted.
+    ///
+    /// Returns `None` if no version was ever saved, either because the storage is empty or because
+    /// it was written before the layout was versioned.
+    async fn load_schema_version(&self) -> anyhow::Result<Option<u32>>;
+
+    /// Save the version of the layout in which data is persisted.
+    async fn save_schema_version(&self, version: u32) -> anyhow::Result<()>;
+
     /// Load the highest view saved with [`save_voted_view`](Self::save_voted_view).
     async fn load_latest_acted_view(&self) -> anyhow::Result<Option<ViewNumber>>;
 
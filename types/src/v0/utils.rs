@@ -18,13 +18,16 @@ use hotshot_types::{
     traits::node_implementation::NodeType,
 };
 use rand::Rng;
-use sequencer_utils::{impl_serde_from_string_or_integer, ser::FromStringOrInteger};
+use sequencer_utils::{
+    backoff::{Backoff, Retries},
+    impl_serde_from_string_or_integer,
+    ser::FromStringOrInteger,
+};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use time::{
     format_description::well_known::Rfc3339 as TimestampFormat, macros::time, Date, OffsetDateTime,
 };
-use tokio::time::sleep;
 
 pub fn upgrade_commitment_map<Types: NodeType>(
     map: CommitmentMap<Leaf<Types>>,
@@ -259,23 +262,22 @@ impl BackoffParams {
         mut state: S,
         f: impl for<'a> Fn(&'a mut S, usize) -> BoxFuture<'a, anyhow::Result<T>>,
     ) -> anyhow::Result<T> {
-        let mut delay = self.base;
-        for i in 0.. {
-            match f(&mut state, i).await {
+        let mut retries = Retries::new(self);
+        loop {
+            match f(&mut state, retries.count()).await {
                 Ok(res) => return Ok(res),
-                Err(err) if self.disable => {
+                Err(err) if !retries.should_retry() => {
                     return Err(err.context("Retryable operation failed; retries disabled"));
                 },
                 Err(err) => {
                     tracing::warn!(
-                        "Retryable operation failed, will retry after {delay:?}: {err:#}"
+                        "Retryable operation failed, will retry after {:?}: {err:#}",
+                        retries.delay()
                     );
-                    sleep(delay).await;
-                    delay = self.backoff(delay);
+                    retries.wait().await;
                 },
             }
         }
-        unreachable!()
     }
 
     #[must_use]
@@ -301,3 +303,17 @@ impl BackoffParams {
         min(delay, self.max)
     }
 }
+
+impl Backoff for BackoffParams {
+    fn should_retry(&self, _retries: usize) -> bool {
+        !self.disable
+    }
+
+    fn first_delay(&self) -> Duration {
+        self.base
+    }
+
+    fn next_delay(&self, delay: Duration) -> Duration {
+        self.backoff(delay)
+    }
+}
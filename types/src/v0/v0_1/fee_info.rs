@@ -9,6 +9,9 @@ use crate::FeeMerkleTree;
 
 // New Type for `U256` in order to implement `CanonicalSerialize` and
 // `CanonicalDeserialize`
+//
+// Conversions from and to `U256` are lossless, narrower integer types are converted with `TryFrom`
+// which fails instead of truncating large amounts.
 #[derive(
     Default,
     Hash,
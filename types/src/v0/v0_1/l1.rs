@@ -1,3 +1,10 @@
+use std::{
+    collections::BTreeMap,
+    num::NonZeroUsize,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
 use alloy::{
     network::Ethereum,
     primitives::{B256, U256},
@@ -15,11 +22,6 @@ use hotshot_types::traits::metrics::{Counter, Gauge, Metrics, NoMetrics};
 use lru::LruCache;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
-use std::{
-    num::NonZeroUsize,
-    sync::Arc,
-    time::{Duration, Instant},
-};
 use tokio::{
     sync::{Mutex, Notify},
     task::JoinHandle,
@@ -43,6 +45,17 @@ pub(crate) struct L1BlockInfoWithParent {
     pub(crate) parent_hash: B256,
 }
 
+/// A reorg of the L1 chain observed by the [`L1Client`].
+///
+/// The blocks in `from_block..=to_block` that the client had previously seen have been replaced.
+/// `from_block` is the lowest block the client knows to have changed; blocks older than the ones
+/// the client has observed as L1 heads may have changed too if the reorg was deeper than that.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct L1Reorg {
+    pub from_block: u64,
+    pub to_block: u64,
+}
+
 #[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct L1Snapshot {
     /// The relevant snapshot of the L1 includes a reference to the current head of the L1 chain.
@@ -118,6 +131,18 @@ pub struct L1ClientOptions {
     )]
     pub subscription_timeout: Duration,
 
+    /// Maximum delay between consecutive attempts to re-establish the L1 block stream.
+    ///
+    /// The delay starts at the L1 retry delay and doubles with each failed attempt, up to this
+    /// value.
+    #[clap(
+        long,
+        env = "ESPRESSO_SEQUENCER_L1_SUBSCRIPTION_MAX_BACKOFF",
+        default_value = "1m",
+        value_parser = parse_duration,
+    )]
+    pub subscription_max_backoff: Duration,
+
     /// Fail over to another provider if the current provider fails twice within this window.
     #[clap(
         long,
@@ -188,6 +213,14 @@ pub struct L1ClientOptions {
     #[clap(long, env = "ESPRESSO_SEQUENCER_L1_FINALIZED_SAFETY_MARGIN")]
     pub l1_finalized_safety_margin: Option<u64>,
 
+    /// Treat the block this many blocks behind the L1 head as finalized.
+    ///
+    /// If set, the L1 client does not query the L1 provider for its finalized block, but instead
+    /// considers `latest - L1_FINALIZATION_LAG` finalized. This is only meant for local testing
+    /// against L1s without real finality, such as anvil.
+    #[clap(long, env = "ESPRESSO_SEQUENCER_L1_FINALIZATION_LAG")]
+    pub l1_finalization_lag: Option<u64>,
+
     #[clap(skip = Arc::<Box<dyn Metrics>>::new(Box::new(NoMetrics)))]
     pub metrics: Arc<Box<dyn Metrics>>,
 }
@@ -229,12 +262,16 @@ pub(crate) struct L1State {
     pub(crate) snapshot: L1Snapshot,
     pub(crate) finalized: LruCache<u64, L1BlockInfoWithParent>,
     pub(crate) last_finalized: Option<u64>,
+    /// Hashes of the most recent L1 heads, used to detect reorgs.
+    pub(crate) recent_heads: BTreeMap<u64, B256>,
+    pub(crate) recent_heads_capacity: NonZeroUsize,
 }
 
 #[derive(Clone, Debug)]
 pub(crate) enum L1Event {
     NewHead { head: u64 },
     NewFinalized { finalized: L1BlockInfoWithParent },
+    Reorg(L1Reorg),
 }
 
 #[derive(Debug, Default)]
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+};
 
 use alloy::primitives::{Address, U256};
 use async_lock::Mutex;
@@ -14,7 +17,7 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use tokio::task::JoinHandle;
 
-use super::L1Client;
+use super::{L1BlockInfo, L1Client};
 use crate::{
     traits::{MembershipPersistence, StateCatchup},
     v0::ChainConfig,
@@ -49,6 +52,17 @@ pub struct Validator<KEY: SignatureKey> {
     pub delegators: HashMap<Address, U256>,
 }
 
+/// Validators extracted from stake table events, together with every key ever registered.
+///
+/// Keys of deregistered validators can't be registered again, so applying more events later
+/// requires the used keys as well as the current validators.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ValidatorRegistry {
+    pub validators: IndexMap<Address, Validator<BLSPubKey>>,
+    pub used_bls_keys: HashSet<BLSPubKey>,
+    pub used_schnorr_keys: HashSet<StateVerKey>,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, std::hash::Hash, Clone, Debug, PartialEq, Eq)]
 #[serde(bound(deserialize = ""))]
 pub struct Delegator {
@@ -76,6 +90,16 @@ pub struct StakeTableFetcher {
     /// Verifiable `ChainConfig` holding contract address
     pub(crate) chain_config: Arc<Mutex<ChainConfig>>,
     pub(crate) update_task: Arc<StakeTableUpdateTask>,
+    /// Active validators already fetched from L1, so unchanged epochs don't trigger L1 queries
+    pub(crate) validators_cache: Arc<Mutex<BTreeMap<EpochNumber, CachedValidators>>>,
+}
+
+/// Active validators of an epoch, as fetched from the stake table events up to an L1 block.
+#[derive(Clone, Debug)]
+pub(crate) struct CachedValidators {
+    pub(crate) contract: Address,
+    pub(crate) l1_block: L1BlockInfo,
+    pub(crate) validators: IndexMap<Address, Validator<BLSPubKey>>,
 }
 
 #[derive(Debug, Default)]
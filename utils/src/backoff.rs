@@ -0,0 +1,190 @@
+//! Retrying fallible operations with a backoff between attempts.
+
+use std::{fmt::Display, future::Future, time::Duration};
+
+use tokio::time::sleep;
+
+/// How often and how patiently to retry an operation, see [`retry_with_backoff`].
+pub trait Backoff {
+    /// Whether to retry after `retries` retries have been made already.
+    fn should_retry(&self, retries: usize) -> bool;
+
+    /// The delay before the first retry.
+    fn first_delay(&self) -> Duration;
+
+    /// The delay before the next retry, if the previous one was preceded by `delay`.
+    fn next_delay(&self, delay: Duration) -> Duration;
+}
+
+/// The state of a retry loop following a [`Backoff`].
+///
+/// This is the building block of [`retry_with_backoff`], for retry loops which need more control
+/// over each attempt, e.g. to pass it mutable state or to report why it gave up.
+#[derive(Clone, Copy, Debug)]
+pub struct Retries<'a, B: ?Sized> {
+    backoff: &'a B,
+    count: usize,
+    delay: Duration,
+}
+
+impl<'a, B: Backoff + ?Sized> Retries<'a, B> {
+    pub fn new(backoff: &'a B) -> Self {
+        Self {
+            backoff,
+            count: 0,
+            delay: backoff.first_delay(),
+        }
+    }
+
+    /// The number of retries made so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The delay before the next retry.
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Whether the backoff allows another retry.
+    pub fn should_retry(&self) -> bool {
+        self.backoff.should_retry(self.count)
+    }
+
+    /// Wait out the delay before the next retry.
+    pub async fn wait(&mut self) {
+        sleep(self.delay).await;
+        self.count += 1;
+        self.delay = self.backoff.next_delay(self.delay);
+    }
+}
+
+/// Run `op` until it succeeds, fails with an error that is not retryable, or `backoff` gives up.
+///
+/// The result of the last attempt is returned.
+pub async fn retry_with_backoff<B, F, Fut, T, E>(
+    backoff: &B,
+    is_retryable: impl Fn(&E) -> bool,
+    mut op: F,
+) -> Result<T, E>
+where
+    B: Backoff + ?Sized,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    let mut retries = Retries::new(backoff);
+    loop {
+        match op().await {
+            Err(err) if is_retryable(&err) && retries.should_retry() => {
+                tracing::warn!(
+                    retries = retries.count() + 1,
+                    "operation failed, retrying in {:?}: {err:#}",
+                    retries.delay()
+                );
+                retries.wait().await;
+            },
+            result => return result,
+        }
+    }
+}
+
+/// Exponential backoff which gives up after a fixed number of retries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoundedBackoff {
+    /// Maximum number of retries.
+    pub max_retries: usize,
+    /// Delay before the first retry.
+    pub base: Duration,
+    /// Factor by which the delay grows after each retry.
+    pub factor: u32,
+    /// Upper bound on the delay.
+    pub max: Duration,
+}
+
+impl Backoff for BoundedBackoff {
+    fn should_retry(&self, retries: usize) -> bool {
+        retries < self.max_retries
+    }
+
+    fn first_delay(&self) -> Duration {
+        self.base
+    }
+
+    fn next_delay(&self, delay: Duration) -> Duration {
+        (delay * self.factor).min(self.max)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// An operation which fails with `errors`, in order, and then succeeds with the number of the
+    /// successful attempt.
+    async fn op(attempts: &AtomicUsize, errors: &[&'static str]) -> Result<usize, &'static str> {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+        match errors.get(attempt) {
+            Some(err) => Err(err),
+            None => Ok(attempt + 1),
+        }
+    }
+
+    fn is_retryable(err: &&str) -> bool {
+        *err == "retryable"
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff() {
+        let backoff = BoundedBackoff {
+            max_retries: 3,
+            base: Duration::from_millis(1),
+            factor: 2,
+            max: Duration::from_millis(3),
+        };
+
+        let attempts = AtomicUsize::new(0);
+        let result = retry_with_backoff(&backoff, is_retryable, || {
+            op(&attempts, &["retryable", "retryable"])
+        })
+        .await;
+        assert_eq!(result, Ok(3));
+
+        // A non-retryable error aborts immediately.
+        let attempts = AtomicUsize::new(0);
+        let result = retry_with_backoff(&backoff, is_retryable, || {
+            op(&attempts, &["fatal", "retryable"])
+        })
+        .await;
+        assert_eq!(result, Err("fatal"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        // The backoff gives up after the maximum number of retries.
+        let attempts = AtomicUsize::new(0);
+        let result =
+            retry_with_backoff(&backoff, is_retryable, || op(&attempts, &["retryable"; 5])).await;
+        assert_eq!(result, Err("retryable"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_bounded_backoff() {
+        let backoff = BoundedBackoff {
+            max_retries: 2,
+            base: Duration::from_millis(10),
+            factor: 3,
+            max: Duration::from_millis(50),
+        };
+        assert!(backoff.should_retry(1));
+        assert!(!backoff.should_retry(2));
+
+        let delays = std::iter::successors(Some(backoff.first_delay()), |delay| {
+            Some(backoff.next_delay(*delay))
+        })
+        .take(4)
+        .collect::<Vec<_>>();
+        assert_eq!(delays, [10, 30, 50, 50].map(Duration::from_millis).to_vec());
+    }
+}
@@ -13,6 +13,7 @@ use committable::{Commitment, Committable};
 use tokio::time::sleep;
 use url::Url;
 
+pub mod backoff;
 pub mod logging;
 pub mod ser;
 pub mod test_utils;
@@ -1,30 +1,40 @@
 use clap::{Parser, ValueEnum};
-use hotshot::helpers::initialize_logging;
+use hotshot::helpers::span_events_from_env;
 use log_panics::BacktraceMode;
+use tracing::Subscriber;
+use tracing_subscriber::{fmt::MakeWriter, util::SubscriberInitExt, EnvFilter};
 
-/// Controls how backtraces are logged on panic.
+/// Format of the log output.
 ///
-/// The values here match the possible values of `RUST_LOG_FORMAT`, and their corresponding behavior
-/// on backtrace logging is:
+/// The format also controls how backtraces are logged on panic:
 /// * `full`: print a prettified dump of the stack trace and span trace to stdout, optimized for
 ///   human readability rather than machine parsing
 /// * `compact`: output the default panic message, with backtraces controlled by `RUST_BACKTRACE`
-/// * `json`: output the panic message and stack trace as a tracing event. This in turn works with
-///   the behavior of the tracing subscriber with `RUST_LOG_FORMAT=json` to output the event in a
-///   machine-parseable, JSON format.
-#[derive(Clone, Copy, Debug, Default, ValueEnum)]
-enum BacktraceLoggingMode {
+/// * `json`: output the panic message and stack trace as a tracing event, which in turn is logged
+///   as a JSON object like every other event, so that log aggregators can parse it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable output, one line per event.
     #[default]
     Full,
+    /// Like `full`, but shorter, e.g. without the names of the spans an event occurred in.
     Compact,
+    /// One JSON object per line.
     Json,
 }
 
 /// Logging configuration.
 #[derive(Clone, Debug, Default, Parser)]
 pub struct Config {
-    #[clap(long, env = "RUST_LOG_FORMAT")]
-    backtrace_mode: Option<BacktraceLoggingMode>,
+    /// Format of the log output.
+    #[clap(
+        long = "log-format",
+        alias = "backtrace-mode",
+        env = "RUST_LOG_FORMAT",
+        value_enum,
+        default_value_t
+    )]
+    pub log_format: LogFormat,
 }
 
 impl Config {
@@ -35,12 +45,93 @@ impl Config {
 
     /// Initialize logging and panic handlers based on this configuration.
     pub fn init(&self) {
-        initialize_logging();
+        // Logging may already have been initialized, e.g. by another test.
+        let _ = self.subscriber(std::io::stdout).try_init();
 
-        if let BacktraceLoggingMode::Json = self.backtrace_mode.unwrap_or_default() {
+        if self.log_format == LogFormat::Json {
             log_panics::Config::new()
                 .backtrace_mode(BacktraceMode::Resolved)
                 .install_panic_hook();
         }
     }
+
+    /// Build a subscriber writing events in the configured format to `writer`.
+    ///
+    /// Events are filtered according to `RUST_LOG`, and span events are logged according to
+    /// `RUST_LOG_SPAN_EVENTS`.
+    fn subscriber<W>(&self, writer: W) -> Box<dyn Subscriber + Send + Sync>
+    where
+        W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+    {
+        let builder = tracing_subscriber::fmt()
+            .with_env_filter(EnvFilter::from_default_env())
+            .with_span_events(span_events_from_env())
+            .with_writer(writer);
+        match self.log_format {
+            LogFormat::Full => Box::new(builder.finish()),
+            LogFormat::Compact => Box::new(builder.compact().finish()),
+            LogFormat::Json => Box::new(builder.json().finish()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{self, Write},
+        sync::{Arc, Mutex},
+    };
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn log_with(format: LogFormat) -> String {
+        let buffer = Buffer::default();
+        let writer = {
+            let buffer = buffer.clone();
+            move || buffer.clone()
+        };
+        let subscriber = Config { log_format: format }.subscriber(writer);
+        // Errors are logged regardless of `RUST_LOG`.
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::error!(answer = 42, "first");
+            tracing::error!("second");
+        });
+        let output = buffer.0.lock().unwrap().clone();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_json_format() {
+        let lines = log_with(LogFormat::Json)
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["level"], "ERROR");
+        assert_eq!(lines[0]["fields"]["message"], "first");
+        assert_eq!(lines[0]["fields"]["answer"], 42);
+        assert_eq!(lines[1]["fields"]["message"], "second");
+
+        // The human-readable formats are not JSON.
+        for format in [LogFormat::Full, LogFormat::Compact] {
+            let output = log_with(format);
+            assert_eq!(output.lines().count(), 2);
+            assert!(output
+                .lines()
+                .all(|line| serde_json::from_str::<serde_json::Value>(line).is_err()));
+        }
+    }
 }